@@ -1,36 +1,152 @@
-use egui::{Color32, Layout, ScrollArea, Stroke};
+use chrono::{DateTime, Local};
+use egui::{Color32, Layout, RichText, ScrollArea, Stroke};
 use egui_commonmark::CommonMarkCache;
 use egui_modal::Modal;
 use egui_theme_switch::global_theme_switch;
 use flowync::{CompactFlower, error::Compact};
 use ollama_rs::{Ollama, models::LocalModel};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use tokio::runtime;
 
 use crate::{
     assign_if_some,
-    ollama::OllamaClient,
-    prompt::{Prompt, PromptState},
-    view::{View, ViewMainPanel},
+    backend::{Backend, BackendKind},
+    card::{self, PromptCard},
+    clippings::{self, Clipping},
+    config::{self, ConfigFile, ConfigWatcher, EnvOverrides, Theme},
+    content::{self, ContentType},
+    editor, fonts,
+    group::{self, PromptGroup, PromptListRow},
+    hardware::Hardware,
+    http,
+    icons::{self, Icon, IconButton},
+    image_gen::StableDiffusionClient,
+    library::{self, LibraryImport, LibraryImportMode, PromptLibrary},
+    mail,
+    maintenance::PRUNED_HISTORY_LEN,
+    markdown,
+    migration::{self, Migration},
+    model_check::{self, MissingModels, ModelOnboarding},
+    ollama::{self, OllamaBackend, OllamaClient, PullProgress},
+    openai::OpenAiBackend,
+    patch::{self, Patch, PatchKind, PatchPreview},
+    prompt::{self, Prompt, PromptKind},
+    queue::{self, Priority, QueuedJobs},
+    recovery::{self, StorageRecovery},
+    script::{self, ImportStrategy, Script, ScriptImport},
+    settings::{EndpointTls, FontSettings, Settings},
+    shared::{self, SharedSource},
+    snippet::{Snippet, SnippetPolicy},
+    spell::SpellChecker,
+    store::{self, Store},
+    sync::{self, RemoteLibrary, SyncBackend, SyncMerge},
+    timeline::{self, TimelineFilter},
+    tokens,
+    undo::{self, Removal, UndoStack},
+    view::{PromptForm, View, ViewMainPanel},
+    watch,
+    window::{self, WindowGeometry},
 };
 
 pub const TITLE: &str = "Reprompt";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The quick-jump index is only shown for libraries that are tedious to scroll through
+const QUICK_JUMP_MIN_PROMPTS: usize = 20;
+const TYPE_AHEAD_TIMEOUT_MS: u64 = 1000;
+pub const PROMPT_SEARCH_ID: &str = "prompt_search";
+
+/// Upgrades of the persisted state, the index of a migration being the version it upgrades from.
+///
+/// Renamed fields are better handled with `#[serde(alias)]`, migrations fill in or convert the data
+/// that changed meaning.
+const MIGRATIONS: &[Migration<App>] = &[
+    Migration {
+        description: "start versioning the persisted state",
+        apply: |_| {},
+    },
+    Migration {
+        description: "record the last usage and the reply count of the prompts",
+        apply: |app| {
+            app.prompts
+                .iter_mut()
+                .for_each(Prompt::record_usage_from_history);
+        },
+    },
+];
+const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Files that can be imported or dropped onto the window
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    PromptCard,
+    Script,
+    Document,
+}
+
+impl FileKind {
+    fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            card::EXTENSION => Some(Self::PromptCard),
+            "json" => Some(Self::Script),
+            extension if watch::DOCUMENT_EXTENSIONS.contains(&extension) => Some(Self::Document),
+            _ => None,
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SortMode {
     HistoryCount,
     LastUsage,
     #[default]
     InsertionOrder,
+    Alphabetical,
+}
+
+impl SortMode {
+    const ALL: [SortMode; 4] = [
+        SortMode::InsertionOrder,
+        SortMode::LastUsage,
+        SortMode::HistoryCount,
+        SortMode::Alphabetical,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::InsertionOrder => "manual",
+            SortMode::LastUsage => "last used",
+            SortMode::HistoryCount => "most replies",
+            SortMode::Alphabetical => "alphabetical",
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 pub struct App {
+    /// States saved before versioning was introduced deserialize as version 0
+    #[serde(default)]
+    schema_version: u32,
     prompts: Vec<Prompt>,
+    /// Groups of the prompt list, in the order they are listed
+    groups: Vec<PromptGroup>,
+    /// Passages clipped from the responses of all prompts, the oldest first
+    clippings: Vec<Clipping>,
     view: View,
     ollama_models: OllamaModels,
     ui_scale: f32,
+    window: WindowGeometry,
+    sync: SyncState,
     #[serde(skip)]
     tokio_runtime: runtime::Runtime,
     #[serde(skip)]
@@ -38,14 +154,68 @@ pub struct App {
     #[serde(skip)]
     commonmark_cache: CommonMarkCache,
     sort_mode: SortMode,
+    settings: Settings,
+    #[serde(skip)]
+    stable_diffusion_client: StableDiffusionClient,
+    #[serde(skip)]
+    type_ahead: TypeAhead,
+    #[serde(skip)]
+    scroll_to_prompt: Option<usize>,
+    #[serde(skip)]
+    visible_prompt_rows: Range<usize>,
+    /// Set when the stored state failed to load, to offer the recovery options on startup
+    #[serde(skip)]
+    storage_recovery: Option<StorageRecovery>,
+    /// Why the database couldn't be opened, shown on startup
+    #[serde(skip)]
+    store_error: Option<String>,
+    #[serde(skip)]
+    timeline: TimelineFilter,
+    /// Queued generations don't start while paused, the running ones finish
+    #[serde(skip)]
+    queue_paused: bool,
+    /// Lists only the prompts with a running generation
+    #[serde(skip)]
+    generating_only: bool,
+    /// Lists only the prompts whose title or content contains it
+    #[serde(skip)]
+    prompt_search: String,
+    /// Lists only the prompts with this tag
+    #[serde(skip)]
+    tag_filter: Option<String>,
+    /// Lists only the clippings of the prompt with this title
+    #[serde(skip)]
+    clippings_prompt: Option<String>,
+    #[serde(skip)]
+    config_watcher: Option<ConfigWatcher>,
+    #[serde(skip)]
+    env_overrides: EnvOverrides,
+    /// Fonts installed in the context, applied again when the settings change
+    #[serde(skip)]
+    applied_fonts: Option<FontSettings>,
+    #[serde(skip)]
+    shared: SharedPrompts,
+    #[serde(skip)]
+    spell_check: SpellCheck,
+    #[serde(skip)]
+    undo: UndoStack,
+    /// Holds the prompts and their history, none when the database couldn't be opened and the
+    /// prompts are kept in the app state instead
+    #[serde(skip)]
+    store: Option<Store>,
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             prompts: Vec::new(),
+            groups: Vec::new(),
+            clippings: Vec::new(),
             view: Default::default(),
             ui_scale: 1.2,
+            window: Default::default(),
+            sync: Default::default(),
             tokio_runtime: tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
                 .build()
@@ -54,6 +224,29 @@ impl Default for App {
             ollama_models: Default::default(),
             commonmark_cache: CommonMarkCache::default(),
             sort_mode: SortMode::InsertionOrder,
+            settings: Settings::default(),
+            stable_diffusion_client: StableDiffusionClient::new(
+                Settings::default().stable_diffusion_url,
+                reqwest::Client::new(),
+            ),
+            type_ahead: Default::default(),
+            scroll_to_prompt: None,
+            visible_prompt_rows: 0..0,
+            storage_recovery: None,
+            store_error: None,
+            timeline: Default::default(),
+            queue_paused: false,
+            generating_only: false,
+            prompt_search: String::new(),
+            tag_filter: None,
+            clippings_prompt: None,
+            config_watcher: None,
+            env_overrides: Default::default(),
+            applied_fonts: None,
+            shared: Default::default(),
+            spell_check: Default::default(),
+            undo: Default::default(),
+            store: None,
         }
     }
 }
@@ -66,6 +259,23 @@ struct OllamaModels {
     available: Vec<LocalModel>,
     #[serde(skip)]
     load_flower: LoadLocalModelsFlower,
+    #[serde(skip)]
+    warm_up_flower: WarmUpModelFlower,
+    /// When each model was last loaded by a request, by model name
+    #[serde(skip)]
+    used_at: HashMap<String, Instant>,
+    #[serde(skip)]
+    pull_flower: PullModelFlower,
+    /// Context window of each model, by model name, none when Ollama doesn't report it
+    #[serde(skip)]
+    context_windows: HashMap<String, Option<u64>>,
+    #[serde(skip)]
+    context_window_flower: ContextWindowFlower,
+    /// Missing models last offered to be pulled or remapped, not offered again until they change
+    #[serde(skip)]
+    reported_missing: Vec<String>,
+    /// Whether the recommended models were offered, which happens once when the server has none
+    onboarding_offered: bool,
 }
 
 impl Default for OllamaModels {
@@ -74,40 +284,406 @@ impl Default for OllamaModels {
             selected: Default::default(),
             available: Default::default(),
             load_flower: LoadLocalModelsFlower::new(1),
+            warm_up_flower: WarmUpModelFlower::new(2),
+            used_at: HashMap::new(),
+            pull_flower: PullModelFlower::new(1),
+            context_windows: HashMap::new(),
+            context_window_flower: ContextWindowFlower::new(1),
+            reported_missing: Vec::new(),
+            onboarding_offered: false,
+        }
+    }
+}
+
+impl OllamaModels {
+    /// Whether the model was used recently enough to still be loaded by Ollama
+    fn is_warm(&self, name: &str) -> bool {
+        self.used_at
+            .get(name)
+            .is_some_and(|used_at| used_at.elapsed() < Duration::from_secs(ollama::KEEP_ALIVE_SECS))
+    }
+
+    fn mark_used(&mut self, name: &str) {
+        self.used_at.insert(name.to_string(), Instant::now());
+    }
+
+    fn selected_context_window(&self) -> Option<u64> {
+        let selected = self.selected.as_ref()?;
+
+        self.context_windows.get(&selected.name).copied().flatten()
+    }
+}
+
+/// Accumulates characters typed over the prompt list to select a prompt by its title
+#[derive(Default)]
+struct TypeAhead {
+    query: String,
+    typed_at: Option<Instant>,
+}
+
+impl TypeAhead {
+    fn is_active(&self) -> bool {
+        self.typed_at.is_some_and(|typed_at| {
+            typed_at.elapsed() <= Duration::from_millis(TYPE_AHEAD_TIMEOUT_MS)
+        })
+    }
+
+    fn push(&mut self, text: &str) -> &str {
+        if !self.is_active() {
+            self.query.clear();
         }
+
+        self.query.push_str(&text.to_lowercase());
+        self.typed_at = Some(Instant::now());
+
+        &self.query
     }
 }
 
 type LoadLocalModelsFlower =
     CompactFlower<Vec<LocalModel>, (Vec<LocalModel>, Option<LocalModel>), anyhow::Error>;
+type WarmUpModelFlower = CompactFlower<(), String, String>;
+type ContextWindowFlower = CompactFlower<(), (String, Option<u64>), (String, String)>;
+type PullModelFlower = CompactFlower<PullProgress, String, String>;
+type SharedPromptsFlower = CompactFlower<(), Vec<PromptCard>, String>;
+type SyncFlower = CompactFlower<(), SyncStep, String>;
+type SpellCheckerFlower = CompactFlower<(), SpellChecker, String>;
+
+enum SyncStep {
+    Fetched(RemoteLibrary),
+    Pushed,
+}
+
+/// Sync of the library with other machines
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct SyncState {
+    /// Synced prompts removed here since the last sync, by their sync identifier
+    removed: HashSet<String>,
+    last_synced_at: Option<DateTime<Local>>,
+    /// Merge waiting for its conflicts to be resolved before it is pushed
+    #[serde(skip)]
+    pending: Option<SyncMerge>,
+    /// Fingerprints of the prompts being pushed, recorded as synced once the push succeeds
+    #[serde(skip)]
+    pushed: HashMap<String, u64>,
+    #[serde(skip)]
+    flower: SyncFlower,
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        Self {
+            removed: HashSet::new(),
+            last_synced_at: None,
+            pending: None,
+            pushed: HashMap::new(),
+            flower: SyncFlower::new(1),
+        }
+    }
+}
+
+impl SyncState {
+    /// Keeps a removed prompt from being brought back by the next sync
+    fn forget(&mut self, prompt: &Prompt) {
+        if prompt.synced_fingerprint.is_some() {
+            self.removed.insert(prompt.sync_id.clone());
+        }
+    }
+
+    /// Syncs a prompt brought back after its removal again
+    fn remember(&mut self, prompt: &Prompt) {
+        self.removed.remove(&prompt.sync_id);
+    }
+}
+
+/// Read-only prompts of the shared source, read again periodically
+struct SharedPrompts {
+    cards: Vec<PromptCard>,
+    /// Source the cards were read from, set once it is read
+    source: Option<SharedSource>,
+    fetched_at: Option<Instant>,
+    /// Why the last refresh failed, the previously read cards staying listed
+    error: Option<String>,
+    flower: SharedPromptsFlower,
+}
+
+impl Default for SharedPrompts {
+    fn default() -> Self {
+        Self {
+            cards: Vec::new(),
+            source: None,
+            fetched_at: None,
+            error: None,
+            flower: SharedPromptsFlower::new(1),
+        }
+    }
+}
+
+/// Dictionary of the spell check, loaded in the background whenever the settings change
+struct SpellCheck {
+    checker: Option<SpellChecker>,
+    /// Dictionary setting the checker was loaded for, set once loading starts
+    loaded_for: Option<String>,
+    flower: SpellCheckerFlower,
+}
+
+impl Default for SpellCheck {
+    fn default() -> Self {
+        Self {
+            checker: None,
+            loaded_for: None,
+            flower: SpellCheckerFlower::new(1),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum AppAction {
-    GeneratePromptResponse { idx: usize, input: String },
-    RegeneratePromptResponse { idx: usize, history_idx: usize },
-    StopPromptGeneration(usize),
+    GeneratePromptResponse {
+        idx: usize,
+        input: String,
+        /// Answers instead of the selected model, e.g. a smaller one when the selected one stalled
+        model: Option<LocalModel>,
+    },
+    RegeneratePromptResponse {
+        idx: usize,
+        history_idx: usize,
+    },
+    /// Aborts the generation of a prompt, keeping the partial output
+    CancelGeneration {
+        idx: usize,
+    },
     CloseDialog,
     OpenAddPromptDialog,
     CancelPromptModification,
     CreatePrompt,
     OpenRemovePromptDialog(usize),
     RemovePrompt(usize),
+    /// Restores the latest removed prompt or response
+    Undo,
     OpenEditPromptDialog(usize),
-    OpenRemovePromptHistoryDialog { idx: usize, history_idx: usize },
-    RemovePromptHistory { idx: usize, history_idx: usize },
+    OpenRemovePromptHistoryDialog {
+        idx: usize,
+        history_idx: usize,
+    },
+    RemovePromptHistory {
+        idx: usize,
+        history_idx: usize,
+    },
     EditPrompt,
     SelectPrompt(usize),
     SelectOllamaModel(LocalModel),
     ReloadOllamaModels,
+    ShowMissingModels(MissingModels),
+    OpenModelOnboarding,
+    PullModel(String),
+    RemapMissingModels,
     SetUIScale(f32),
-    ShowErrorDialog { title: String, message: String },
+    ShowErrorDialog {
+        title: String,
+        message: String,
+    },
+    ExportPromptScript(usize),
+    /// Saves the history to a Markdown or HTML file
+    ExportPromptHistory(usize),
+    ExportPromptCard(usize),
+    /// Imports prompt cards and scripts, attaches documents to the input of the selected prompt
+    ImportFiles(Vec<PathBuf>),
+    ReplayPromptScript(usize),
+    OpenSettingsDialog,
+    SaveSettings,
+    /// Writes the settings of the dialog to a profile file
+    ExportSettingsProfile,
+    /// Loads a profile file into the settings dialog, to review before saving
+    ImportSettingsProfile,
+    SynthesizePromptResponse {
+        idx: usize,
+        history_idx: usize,
+    },
+    OpenQuickOpenDialog,
+    /// The quick open listing the commands along with the prompts
+    OpenCommandPalette,
+    QuickOpenPrompt(usize),
+    /// Reverse search over the past inputs, starting with the ones of the prompt
+    OpenInputSearch(usize),
+    /// Replaces the input of the prompt with a past one
+    RecallInput {
+        idx: usize,
+        input: String,
+    },
+    ToggleTheme,
+    /// Merges the library with the synced one and pushes the result
+    Sync,
+    OpenSyncConflictsDialog,
+    ResolveSyncConflicts,
+    SelectSharedPrompt(usize),
+    /// Copies a shared prompt into the library, where it can be used and edited
+    DuplicateSharedPrompt(usize),
+    /// Moves the focus to the input of the selected prompt, e.g. from a search box
+    FocusInput,
+    AvoidBannedPhrases(usize),
+    /// Starts a new conversation of the prompt
+    NewChatSession(usize),
+    DeleteChatSession {
+        idx: usize,
+        created_at: DateTime<Local>,
+    },
+    /// Names the active conversation of the prompt from its first exchange
+    GenerateChatTitle(usize),
+    CritiquePromptResponse(usize),
+    /// Asks the model to continue the latest response of the prompt, e.g. when it was cut off
+    ContinuePromptResponse(usize),
+    /// Reads the next page of older responses of the prompt from the database
+    LoadOlderHistory(usize),
+    /// Asks for a folder whose new documents the prompt processes
+    PickWatchedFolder(usize),
+    /// Asks for a local mailbox whose messages the prompt summarizes into a digest
+    OpenMailbox {
+        idx: usize,
+        maildir: bool,
+    },
+    SaveMailDigest(usize),
+    /// Fetches the feeds of the prompt to summarize their new items into a briefing
+    GenerateBriefing(usize),
+    StopWatchingFolder(usize),
+    ScorePromptResponses {
+        idx: usize,
+        history_idx: Option<usize>,
+    },
+    SwitchToSmallerModel {
+        idx: usize,
+        model: LocalModel,
+    },
+    OpenMaintenanceDialog,
+    ImportScript,
+    ResolveScriptImport(ImportStrategy),
+    OpenExportPromptsDialog,
+    ExportPrompts {
+        include_history: bool,
+    },
+    ImportPrompts,
+    ResolvePromptsImport(LibraryImportMode),
+    ArchivePrompt {
+        idx: usize,
+        archived: bool,
+    },
+    SetPromptDisabled {
+        idx: usize,
+        disabled: bool,
+    },
+    SetPromptLocked {
+        idx: usize,
+        locked: bool,
+    },
+    /// Moves the prompt into the group, out of any group when it's empty
+    SetPromptGroup {
+        idx: usize,
+        group: String,
+    },
+    ToggleGroupCollapsed(usize),
+    MoveGroup {
+        from: usize,
+        to: usize,
+    },
+    RemoveGroup(usize),
+    /// Merges the prompt with the sync identifier `remove` into the one with `keep`
+    MergePrompts {
+        keep: String,
+        remove: String,
+    },
+    PrunePromptHistory(usize),
+    SelectEnsembleCandidate {
+        idx: usize,
+        history_idx: usize,
+        candidate_idx: usize,
+    },
+    RestoreStateBackup,
+    DiscardRecoveredPrompts,
+    /// Patches a file picked by the user with the diff in a response, after a preview
+    ApplyDiff {
+        idx: usize,
+        history_idx: usize,
+    },
+    ConfirmApplyPatch,
+    OpenInEditor {
+        idx: usize,
+        history_idx: usize,
+    },
+    /// Asks for confirmation before running a snippet of a response allowed by the settings
+    OpenRunSnippetDialog {
+        idx: usize,
+        history_idx: usize,
+        snippet: Snippet,
+    },
+    RunSnippet,
+    ShowTimeline,
+    ShowClippings,
+    /// Saves a passage of a history entry to the clippings
+    ClipQuote {
+        idx: usize,
+        created_at: DateTime<Local>,
+        model: String,
+        quote: String,
+    },
+    /// Removes a clipping, by the time it was clipped at
+    RemoveClipping(DateTime<Local>),
+    ExportClippings,
+    /// Stops all running generations and drops the queued ones
+    CancelAllGenerations,
+    SetQueuePaused(bool),
+    SetQueuePriority {
+        idx: usize,
+        priority: Priority,
+    },
+    /// Selects a prompt and scrolls its history to the response
+    ShowHistoryEntry {
+        idx: usize,
+        created_at: DateTime<Local>,
+    },
+    /// Stops flagging the word as misspelled
+    AddSpellingWord(String),
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.ui(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if !self.save_prompts() {
+            eframe::set_value(storage, eframe::APP_KEY, self);
+            return;
+        }
+
+        // The app state only keeps the UI state once the prompts are in the database
+        let prompts = std::mem::take(&mut self.prompts);
+
+        eframe::set_value(storage, eframe::APP_KEY, self);
+        self.prompts = prompts;
+    }
+}
+
+impl App {
+    /// Renders a frame and handles the resulting action.
+    ///
+    /// It only needs the egui context, so that the app can be driven headlessly, e.g. by UI tests.
+    pub fn ui(&mut self, ctx: &egui::Context) {
         let mut action = None;
 
         assign_if_some!(action, self.handle_keyboard_input(ctx));
+        assign_if_some!(action, Self::handle_dropped_files(ctx));
+        assign_if_some!(action, self.poll_config_file(ctx));
+        assign_if_some!(action, self.apply_fonts(ctx));
+        icons::set_show_labels(ctx, self.settings.icon_labels);
+        self.window.update(ctx);
+        self.refresh_shared_prompts(ctx);
+        self.load_spell_checker();
+        self.fetch_context_window();
+        self.poll_folder_watches(ctx);
+        self.show_drop_overlay(ctx);
+        let undo_period = self.undo_period();
+        assign_if_some!(action, undo::show_toast(ctx, &mut self.undo, undo_period));
 
         let add_prompt_modal = View::create_modify_prompt_modal(
             ctx,
@@ -124,6 +700,30 @@ impl eframe::App for App {
         let remove_prompt_history_modal =
             Modal::new(ctx, "remove_prompt_history_modal").with_close_on_outside_click(true);
         let error_modal = Modal::new(ctx, "error_modal").with_close_on_outside_click(true);
+        let settings_modal = Modal::new(ctx, "settings_modal").with_close_on_outside_click(true);
+        let quick_open_modal =
+            Modal::new(ctx, "quick_open_modal").with_close_on_outside_click(true);
+        let maintenance_modal =
+            Modal::new(ctx, "maintenance_modal").with_close_on_outside_click(true);
+        let import_modal = Modal::new(ctx, "import_modal").with_close_on_outside_click(true);
+        let recovery_modal = Modal::new(ctx, "recovery_modal");
+        let apply_patch_modal =
+            Modal::new(ctx, "apply_patch_modal").with_close_on_outside_click(true);
+        let run_snippet_modal =
+            Modal::new(ctx, "run_snippet_modal").with_close_on_outside_click(true);
+        let models_modal = Modal::new(ctx, "models_modal").with_close_on_outside_click(true);
+        let export_modal = Modal::new(ctx, "export_modal").with_close_on_outside_click(true);
+        let input_search_modal =
+            Modal::new(ctx, "input_search_modal").with_close_on_outside_click(true);
+
+        if let Some(recovery) = self.storage_recovery.take() {
+            recovery_modal.open();
+            self.view.open_recovery_modal(recovery);
+        }
+
+        if let Some(message) = self.store_error.take() {
+            self.show_error(&error_modal, "Database Error".to_string(), message);
+        }
 
         assign_if_some!(
             action,
@@ -134,6 +734,16 @@ impl eframe::App for App {
                 &edit_prompt_modal,
                 &remove_prompt_history_modal,
                 &error_modal,
+                &settings_modal,
+                &quick_open_modal,
+                &maintenance_modal,
+                &import_modal,
+                &recovery_modal,
+                &apply_patch_modal,
+                &run_snippet_modal,
+                &models_modal,
+                &export_modal,
+                &input_search_modal,
             )
         );
 
@@ -145,34 +755,389 @@ impl eframe::App for App {
             &edit_prompt_modal,
             &remove_prompt_history_modal,
             &error_modal,
+            &settings_modal,
+            &quick_open_modal,
+            &maintenance_modal,
+            &import_modal,
+            &recovery_modal,
+            &apply_patch_modal,
+            &run_snippet_modal,
+            &models_modal,
+            &export_modal,
+            &input_search_modal,
         );
     }
 
-    fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+    /// An app holding the given prompts with the default settings, not persisted anywhere
+    pub fn with_prompts(prompts: Vec<Prompt>) -> Self {
+        Self {
+            prompts,
+            ..Default::default()
+        }
     }
-}
 
-impl App {
     pub fn from_eframe_context(cc: &eframe::CreationContext<'_>) -> Self {
         eframe::storage_dir(TITLE);
+        egui_extras::install_image_loaders(&cc.egui_ctx);
 
-        let mut app: Self = match cc.storage {
-            Some(storage) => eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+        let mut app = match cc
+            .storage
+            .and_then(|storage| storage.get_string(eframe::APP_KEY))
+        {
+            Some(serialized) => {
+                Self::load(&serialized).unwrap_or_else(|e| Self::recover(&serialized, e))
+            }
             None => Default::default(),
         };
 
+        app.open_store();
         app.set_scale(&cc.egui_ctx, app.ui_scale);
+        if let Err(e) = app.settings.load_secrets() {
+            log::warn!("Failed to read the secrets from the keyring: {e}");
+        }
+
+        if let Some(path) = config::path() {
+            if path.exists()
+                && let Err(e) = ConfigFile::load(&path)
+                    .and_then(|config| app.apply_config_file(&cc.egui_ctx, &config))
+            {
+                log::warn!("Failed to apply the config file: {e}");
+            }
+
+            app.config_watcher = Some(ConfigWatcher::new(path));
+        }
+
+        app.env_overrides = EnvOverrides::from_env();
+        app.env_overrides.apply(&mut app.settings);
+
+        if let Err(e) = app.apply_settings() {
+            log::warn!("Failed to apply the settings, using the defaults: {e}");
+        }
+
+        app.tokio_runtime.spawn_blocking(tokens::warm_up);
 
         app
     }
 
+    /// Puts the values of the config file over the current settings
+    fn apply_config_file(
+        &mut self,
+        ctx: &egui::Context,
+        config: &ConfigFile,
+    ) -> anyhow::Result<()> {
+        self.settings = config.apply(&self.settings)?;
+        self.env_overrides.apply(&mut self.settings);
+
+        if let Some(theme) = config.theme {
+            Self::set_theme(ctx, theme);
+        }
+
+        Ok(())
+    }
+
+    fn set_theme(ctx: &egui::Context, theme: Theme) {
+        ctx.set_theme(match theme {
+            Theme::System => egui::ThemePreference::System,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::Dark => egui::ThemePreference::Dark,
+        });
+    }
+
+    /// Applies the config file again once it changed, so that dotfile edits take effect right away
+    fn poll_config_file(&mut self, ctx: &egui::Context) -> Option<AppAction> {
+        let watcher = self.config_watcher.as_mut()?;
+
+        ctx.request_repaint_after(config::CHECK_INTERVAL);
+
+        let result = watcher
+            .poll()?
+            .and_then(|config| self.apply_config_file(ctx, &config))
+            .and_then(|()| self.apply_settings());
+
+        result.err().map(|e| AppAction::ShowErrorDialog {
+            title: "Config file".to_string(),
+            message: format!("Failed to reload the config file.\n\nError: {e}"),
+        })
+    }
+
+    /// Installs the fonts of the settings once they changed
+    fn apply_fonts(&mut self, ctx: &egui::Context) -> Option<AppAction> {
+        if self.applied_fonts.as_ref() == Some(&self.settings.fonts) {
+            return None;
+        }
+
+        self.applied_fonts = Some(self.settings.fonts.clone());
+
+        fonts::apply(ctx, &self.settings.fonts)
+            .err()
+            .map(|e| AppAction::ShowErrorDialog {
+                title: "Font Error".to_string(),
+                message: format!("Failed to load the fonts.\n\nError: {e}"),
+            })
+    }
+
+    /// Reads the persisted state and migrates it to the current schema version.
+    ///
+    /// A state that loads successfully becomes the latest backup.
+    fn load(serialized: &str) -> anyhow::Result<Self> {
+        let mut app = migration::parse::<Self>(serialized)?;
+
+        let version = app.schema_version;
+        app.schema_version = migration::run(&mut app, version, MIGRATIONS)?;
+
+        if let Err(e) = recovery::write_backup(&Self::backups_dir(), serialized) {
+            log::warn!("Failed to back up the stored state: {e}");
+        }
+
+        Ok(app)
+    }
+
+    /// Opens the database of the prompts and reads them from it.
+    ///
+    /// Prompts in the app state take precedence, as they are only there when it was saved before
+    /// the database existed or when the database failed to be written, and they are moved into the
+    /// database on the next save. Without a database they stay in the app state.
+    fn open_store(&mut self) {
+        let Some(dir) = eframe::storage_dir(TITLE) else {
+            self.store_error = Some(
+                "No data directory was found for the database, the prompts are kept with the app state instead.".to_string(),
+            );
+            return;
+        };
+        let path = dir.join(store::FILE_NAME);
+        let result = Store::open(&path).and_then(|store| {
+            if self.prompts.is_empty() {
+                self.prompts = store
+                    .load()?
+                    .into_iter()
+                    .map(Prompt::from_stored)
+                    .collect::<anyhow::Result<_>>()?;
+            }
+
+            Ok(store)
+        });
+
+        match result {
+            Ok(store) => self.store = Some(store),
+            Err(e) => {
+                log::error!("Failed to open the database {}: {e}", path.display());
+                self.store_error = Some(format!(
+                    "Failed to open the database {}, the prompts are kept with the app state instead.\n\nError: {e}",
+                    path.display()
+                ));
+            }
+        }
+    }
+
+    /// Writes the prompts to the database, returns whether they were written
+    fn save_prompts(&mut self) -> bool {
+        // The prompts are stored by their identifier
+        sync::assign_ids(&mut self.prompts);
+
+        let Some(store) = &mut self.store else {
+            return false;
+        };
+
+        self.prompts
+            .iter_mut()
+            .map(Prompt::to_record)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .and_then(|records| store.save(&records))
+            .inspect_err(|e| {
+                log::error!("Failed to write the prompts to the database, keeping them in the app state: {e}");
+            })
+            .is_ok()
+    }
+
+    /// Reads the next page of older responses of the prompt from the database
+    fn load_older_history(&mut self, idx: usize) -> anyhow::Result<()> {
+        if let Some(store) = &self.store
+            && let Some(prompt) = self.prompts.get_mut(idx)
+            && let Some(unloaded) = prompt.unloaded_history()
+        {
+            prompt.load_older_history(store.history_page(&prompt.sync_id, Some(unloaded.before))?);
+        }
+
+        Ok(())
+    }
+
+    fn load_all_history(&mut self) -> anyhow::Result<()> {
+        (0..self.prompts.len()).try_for_each(|idx| self.load_full_history(idx))
+    }
+
+    /// Reads all the responses of the prompt left in the database, before its whole history is used
+    fn load_full_history(&mut self, idx: usize) -> anyhow::Result<()> {
+        if let Some(store) = &self.store
+            && let Some(prompt) = self.prompts.get_mut(idx)
+        {
+            while let Some(unloaded) = prompt.unloaded_history() {
+                prompt.load_history(store.history_page(&prompt.sync_id, Some(unloaded.before))?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts with the prompts that could still be read, keeping a copy of the unreadable state
+    fn recover(serialized: &str, error: anyhow::Error) -> Self {
+        log::error!("{error}, trying to recover the prompts");
+
+        let corrupted_copy = recovery::keep_corrupted(&Self::backups_dir(), serialized)
+            .inspect_err(|e| log::error!("Failed to keep a copy of the stored state: {e}"))
+            .ok();
+        let prompts: Vec<Prompt> = recovery::recover_prompts(serialized);
+
+        Self {
+            storage_recovery: Some(StorageRecovery {
+                error: error.to_string(),
+                corrupted_copy,
+                recovered_prompts: prompts.len(),
+                latest_backup: recovery::latest_backup(&Self::backups_dir()),
+            }),
+            prompts,
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the persisted part of the state with the latest backup
+    fn restore_latest_backup(&mut self, ctx: &egui::Context) -> anyhow::Result<()> {
+        let path = recovery::latest_backup(&Self::backups_dir())
+            .ok_or_else(|| anyhow::anyhow!("There is no backup to restore"))?;
+        let restored = Self::load(&std::fs::read_to_string(path)?)?;
+
+        // Backups taken since the prompts are in the database only hold the UI state
+        if !restored.prompts.is_empty() {
+            self.prompts = restored.prompts;
+        }
+
+        self.ollama_models.selected = restored.ollama_models.selected;
+        self.sort_mode = restored.sort_mode;
+        self.settings = restored.settings;
+        self.env_overrides.apply(&mut self.settings);
+        self.set_scale(ctx, restored.ui_scale);
+
+        if let Err(e) = self.settings.load_secrets() {
+            log::warn!("Failed to read the secrets from the keyring: {e}");
+        }
+
+        self.apply_settings()
+    }
+
+    fn handle_dropped_files(ctx: &egui::Context) -> Option<AppAction> {
+        let paths: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .collect()
+        });
+
+        (!paths.is_empty()).then_some(AppAction::ImportFiles(paths))
+    }
+
+    /// Queues the documents added to the watched folders, which are checked every few seconds
+    fn poll_folder_watches(&mut self, ctx: &egui::Context) {
+        if self.prompts.iter().any(Prompt::is_watching_folder) {
+            ctx.request_repaint_after(config::CHECK_INTERVAL);
+        }
+
+        self.prompts.iter_mut().for_each(Prompt::poll_folder_watch);
+    }
+
+    /// Tells what dropping the files dragged over the window will do
+    fn show_drop_overlay(&self, ctx: &egui::Context) {
+        let kinds: Vec<Option<FileKind>> = ctx.input(|i| {
+            i.raw
+                .hovered_files
+                .iter()
+                .map(|file| file.path.as_deref().and_then(FileKind::of))
+                .collect()
+        });
+
+        if kinds.is_empty() {
+            return;
+        }
+
+        let count = |kind: Option<FileKind>| kinds.iter().filter(|k| **k == kind).count();
+        let prompts = count(Some(FileKind::PromptCard)) + count(Some(FileKind::Script));
+        let documents = count(Some(FileKind::Document));
+        let unsupported = count(None);
+        let mut lines = Vec::new();
+
+        if prompts > 0 {
+            lines.push(format!("📥 Import {prompts} prompt(s)"));
+        }
+
+        if documents > 0 {
+            lines.push(match self.view.main_panel {
+                ViewMainPanel::Prompt(_) => {
+                    format!("📎 Attach {documents} document(s) to the input")
+                }
+                ViewMainPanel::Welcome
+                | ViewMainPanel::SharedPrompt(_)
+                | ViewMainPanel::Timeline
+                | ViewMainPanel::Clippings => "Select a prompt to attach documents to".to_string(),
+            });
+        }
+
+        if unsupported > 0 {
+            lines.push(format!("{unsupported} unsupported file(s) will be ignored"));
+        }
+
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("drop_overlay"),
+        ));
+        let screen_rect = ctx.screen_rect();
+
+        painter.rect_filled(screen_rect, 0.0, Color32::from_black_alpha(192));
+        painter.text(
+            screen_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            lines.join("\n"),
+            egui::FontId::proportional(20.0),
+            Color32::WHITE,
+        );
+    }
+
     fn handle_keyboard_input(&self, ctx: &egui::Context) -> Option<AppAction> {
         let mut action = None;
+        // Text fields undo their own edits
+        let is_editing = ctx.memory(|memory| memory.focused().is_some());
 
         ctx.input(|i| {
             if i.modifiers.ctrl {
-                if i.key_pressed(egui::Key::Equals) || i.key_pressed(egui::Key::Plus) {
+                if i.key_pressed(egui::Key::P) && !self.view.is_modal_shown() {
+                    // Ctrl+P: Quick open a prompt by its title
+                    action = Some(AppAction::OpenQuickOpenDialog);
+                } else if i.key_pressed(egui::Key::K) && !self.view.is_modal_shown() {
+                    // Ctrl+K: Search the prompts and the actions
+                    action = Some(AppAction::OpenCommandPalette);
+                } else if i.key_pressed(egui::Key::N) && !self.view.is_modal_shown() {
+                    // Ctrl+N: Add a prompt
+                    action = Some(AppAction::OpenAddPromptDialog);
+                } else if i.key_pressed(egui::Key::L) && !self.view.is_modal_shown() {
+                    // Ctrl+L: Focus the input, e.g. leaving a search box
+                    action = Some(AppAction::FocusInput);
+                } else if i.key_pressed(egui::Key::R)
+                    && !self.view.is_modal_shown()
+                    && let ViewMainPanel::Prompt(idx) = self.view.main_panel
+                {
+                    // Ctrl+R: Search the past inputs to recall one
+                    action = Some(AppAction::OpenInputSearch(idx));
+                } else if i.key_pressed(egui::Key::Z)
+                    && !i.modifiers.shift
+                    && !is_editing
+                    && !self.view.is_modal_shown()
+                {
+                    // Ctrl+Z: Restore the latest removed prompt or response
+                    action = Some(AppAction::Undo);
+                } else if i.key_pressed(egui::Key::Tab) && !self.view.is_modal_shown() {
+                    // Ctrl+Tab/Ctrl+Shift+Tab: Navigate between prompts, from any text field
+                    action = self
+                        .adjacent_prompt(i.modifiers.shift)
+                        .map(AppAction::SelectPrompt);
+                } else if i.key_pressed(egui::Key::Equals) || i.key_pressed(egui::Key::Plus) {
                     // Ctrl+Plus: Increase scale by 0.1, clamped to max 2.5
                     let new_scale = (self.ui_scale + 0.1).clamp(1.0, 2.5);
                     if new_scale != self.ui_scale {
@@ -187,61 +1152,217 @@ impl App {
                 }
             } else if i.key_pressed(egui::Key::Tab) && !self.view.is_modal_shown() {
                 // Tab/Shift-Tab: Navigate between prompts
-                let prompt_indices = self.sort_prompt_indices();
-
-                if let ViewMainPanel::Prompt(current_idx) = self.view.main_panel {
-                    if !self.prompts.is_empty()
-                        && let Some(pos) = prompt_indices.iter().position(|&idx| idx == current_idx)
-                    {
-                        let next_idx = if i.modifiers.shift {
-                            // Shift-Tab: Previous prompt
-                            if pos > 0 {
-                                prompt_indices[pos - 1]
-                            } else {
-                                prompt_indices[prompt_indices.len() - 1]
-                            }
-                        } else {
-                            // Tab: Next prompt
-                            if pos < prompt_indices.len() - 1 {
-                                prompt_indices[pos + 1]
-                            } else {
-                                prompt_indices[0]
-                            }
-                        };
-
-                        action = Some(AppAction::SelectPrompt(next_idx));
-                    }
-                } else if !self.prompts.is_empty()
-                    && let Some(&first_idx) = prompt_indices.first()
-                {
-                    action = Some(AppAction::SelectPrompt(first_idx));
-                }
+                action = self
+                    .adjacent_prompt(i.modifiers.shift)
+                    .map(AppAction::SelectPrompt);
             }
         });
         action
     }
 
+    fn save_settings(&mut self, settings: Settings) -> anyhow::Result<()> {
+        let mut previous = std::mem::replace(&mut self.settings, settings);
+        let stored = self.settings.store_secrets(&mut previous).map_err(|e| {
+            anyhow::anyhow!(
+                "the header values are kept for this session only, storing them in the OS keyring failed: {e}"
+            )
+        });
+
+        self.apply_settings()?;
+        self.load_local_models();
+
+        stored
+    }
+
+    /// Rebuilds the provider clients from the settings
+    fn apply_settings(&mut self) -> anyhow::Result<()> {
+        let backend: Arc<dyn Backend> = match self.settings.backend {
+            BackendKind::Ollama => Arc::new(OllamaBackend::new(ollama::connect(
+                &self.settings.ollama_url,
+                http::client(
+                    &self.settings,
+                    &self.settings.ollama_tls,
+                    &self.settings.ollama_headers,
+                )?,
+            )?)),
+            BackendKind::OpenAi => Arc::new(OpenAiBackend::new(
+                http::client(
+                    &self.settings,
+                    &self.settings.openai_tls,
+                    &self.settings.openai_headers,
+                )?,
+                &self.settings.openai_url,
+                &self.settings.openai_api_key,
+            )?),
+        };
+
+        self.ollama_client = self
+            .ollama_client
+            .with_backend(backend)
+            .with_demo(
+                self.settings
+                    .demo_provider
+                    .then(|| Duration::from_millis(self.settings.demo_token_delay_ms)),
+            )
+            .with_retries(self.settings.generation_retries)
+            .with_output_limit(
+                self.settings.max_output_tokens,
+                if self.settings.auto_continue {
+                    self.settings.auto_continue_max
+                } else {
+                    0
+                },
+            );
+        self.stable_diffusion_client = StableDiffusionClient::new(
+            self.settings.stable_diffusion_url.clone(),
+            http::client(
+                &self.settings,
+                &self.settings.stable_diffusion_tls,
+                &self.settings.stable_diffusion_headers,
+            )?,
+        );
+
+        Ok(())
+    }
+
     fn set_scale(&mut self, ctx: &egui::Context, value: f32) {
         self.ui_scale = value;
         ctx.set_zoom_factor(self.ui_scale);
     }
 
     fn remove_prompt(&mut self, idx: usize) {
-        self.prompts.remove(idx);
-    }
+        // Its responses are deleted from the database on the next save, but can still be restored
+        if let Err(e) = self.load_full_history(idx) {
+            log::warn!("Failed to read the older responses of the removed prompt: {e}");
+        }
 
-    fn add_prompt(&mut self, title: String, content: String) {
-        let id = self.prompts.len();
-        let prompt = Prompt::new(title, content, id);
+        let removed = self.prompts.remove(idx);
 
-        self.prompts.push(prompt);
+        self.sync.forget(&removed);
+        self.undo.push(
+            Removal::Prompt {
+                idx,
+                prompt: Box::new(removed),
+            },
+            self.undo_period(),
+        );
     }
 
-    fn edit_prompt(&mut self, idx: usize, title: String, content: String) {
+    fn remove_history(&mut self, idx: usize, history_idx: usize) {
+        // The entry is restored into its prompt by its identifier, which the indices aren't
+        sync::assign_ids(&mut self.prompts);
+
+        let Some(prompt) = self.prompts.get_mut(idx) else {
+            return;
+        };
+
+        if let Some(response) = prompt.remove_history(history_idx) {
+            let removal = Removal::History {
+                sync_id: prompt.sync_id.clone(),
+                title: prompt.title.clone(),
+                response: Box::new(response),
+            };
+
+            self.undo.push(removal, self.undo_period());
+        }
+    }
+
+    /// Restores the latest removal that hasn't expired yet
+    fn undo_removal(&mut self) {
+        match self.undo.pop() {
+            Some(Removal::Prompt { idx, prompt }) => {
+                let idx = idx.min(self.prompts.len());
+
+                self.sync.remember(&prompt);
+                self.prompts.insert(idx, *prompt);
+                self.view.select_prompt(idx);
+            }
+            Some(Removal::History {
+                sync_id, response, ..
+            }) => {
+                match self
+                    .prompts
+                    .iter()
+                    .position(|prompt| prompt.sync_id == sync_id)
+                {
+                    Some(idx) => {
+                        self.prompts[idx].insert_history(*response);
+                        self.view.select_prompt(idx);
+                    }
+                    // Its prompt was removed for good in the meantime
+                    None => response.discard(),
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn undo_period(&self) -> Duration {
+        Duration::from_secs(self.settings.undo_secs)
+    }
+
+    fn add_prompt(&mut self, form: &PromptForm) {
+        let id = self.prompts.len();
+        let mut prompt = Prompt::new(form.title.clone(), form.content.clone(), id);
+
+        form.apply(&mut prompt);
+        self.prompts.push(prompt);
+    }
+
+    fn edit_prompt(&mut self, idx: usize, form: &PromptForm) {
         if let Some(prompt) = self.prompts.get_mut(idx) {
-            prompt.title = title;
-            prompt.content = content;
+            form.apply(prompt);
+        }
+    }
+
+    /// Moves the history of the `remove` prompt into the `keep` one and removes it from the library
+    /// Analyzes the library, the prompts being given a sync identifier first for the report to
+    /// refer to them by
+    fn open_maintenance_report(&mut self) {
+        sync::assign_ids(&mut self.prompts);
+        self.view.open_maintenance_modal(&self.prompts);
+    }
+
+    fn merge_prompts(&mut self, keep: &str, remove: &str) -> anyhow::Result<()> {
+        let position = |sync_id: &str| {
+            self.prompts
+                .iter()
+                .position(|prompt| prompt.sync_id == sync_id)
+        };
+        let (Some(keep), Some(remove)) = (position(keep), position(remove)) else {
+            anyhow::bail!("The prompts to merge no longer exist.");
+        };
+
+        anyhow::ensure!(keep != remove, "The prompts to merge no longer exist.");
+        anyhow::ensure!(
+            !self.prompts[keep].state.is_generating()
+                && !self.prompts[remove].state.is_generating(),
+            "Wait for the responses to finish generating before merging the prompts."
+        );
+        anyhow::ensure!(
+            !self.prompts[remove].locked,
+            "\"{}\" is locked, unlock it before merging it into another prompt.",
+            self.prompts[remove].title
+        );
+
+        self.load_full_history(remove)?;
+
+        let removed = self.prompts.remove(remove);
+        let keep = if keep > remove { keep - 1 } else { keep };
+
+        self.sync.forget(&removed);
+
+        self.prompts[keep].merge_history(removed);
+
+        if let ViewMainPanel::Prompt(selected) = self.view.main_panel {
+            match selected.cmp(&remove) {
+                std::cmp::Ordering::Equal => self.view.select_prompt(keep),
+                std::cmp::Ordering::Greater => self.view.select_prompt(selected - 1),
+                std::cmp::Ordering::Less => {}
+            }
         }
+
+        Ok(())
     }
 
     fn get_prompt_mut(&mut self, idx: usize) -> Option<&mut Prompt> {
@@ -258,37 +1379,52 @@ impl App {
         edit_prompt_modal: &Modal,
         remove_prompt_history_modal: &Modal,
         error_modal: &Modal,
+        settings_modal: &Modal,
+        quick_open_modal: &Modal,
+        maintenance_modal: &Modal,
+        import_modal: &Modal,
+        recovery_modal: &Modal,
+        apply_patch_modal: &Modal,
+        run_snippet_modal: &Modal,
+        models_modal: &Modal,
+        export_modal: &Modal,
+        input_search_modal: &Modal,
     ) {
         if let Some(action) = action {
             match action {
-                AppAction::StopPromptGeneration(idx) => {
+                AppAction::CancelGeneration { idx } => {
                     if let Some(prompt) = self.prompts.get_mut(idx) {
-                        self.ollama_client.cancel_generation();
-                        prompt.state = PromptState::Idle;
+                        prompt.cancel_generation();
                     }
                 }
-                AppAction::GeneratePromptResponse { idx, input } => {
-                    if let Some(selected_model) = &self.ollama_models.selected
-                        && let Some(prompt) = self.prompts.get_mut(idx)
-                    {
-                        prompt.generate_response(
-                            input,
-                            selected_model,
-                            &self.tokio_runtime,
-                            &self.ollama_client,
+                AppAction::GeneratePromptResponse { idx, input, model } => {
+                    if let Err(e) = self.generate_prompt_response(idx, input, model) {
+                        self.show_error(
+                            error_modal,
+                            "Response Generation Error".to_string(),
+                            e.to_string(),
                         );
                     }
                 }
                 AppAction::RegeneratePromptResponse { idx, history_idx } => {
-                    if let Some(selected_model) = &self.ollama_models.selected
-                        && let Some(prompt) = self.prompts.get_mut(idx)
+                    if let Some(input) = self
+                        .prompts
+                        .get_mut(idx)
+                        .and_then(|prompt| prompt.regenerate_input(history_idx))
                     {
-                        prompt.regenerate_response(
-                            history_idx,
-                            selected_model,
-                            &self.tokio_runtime,
-                            &self.ollama_client,
-                        );
+                        let result = self.generate_prompt_response(idx, input, None);
+
+                        if let Some(prompt) = self.prompts.get_mut(idx) {
+                            prompt.clear_pending_regeneration();
+                        }
+
+                        if let Err(e) = result {
+                            self.show_error(
+                                error_modal,
+                                "Response Generation Error".to_string(),
+                                e.to_string(),
+                            );
+                        }
                     }
                 }
                 AppAction::CloseDialog => {
@@ -304,106 +1440,1793 @@ impl App {
                     self.view.close_modal();
                 }
                 AppAction::CreatePrompt => {
-                    if let Some((title, content)) = self.view.get_add_prompt_modal_data() {
+                    if let Some(form) = self.view.get_add_prompt_modal_data().cloned() {
                         let new_prompt_idx = self.prompts.len();
-                        self.add_prompt(title.clone(), content.clone());
+                        self.add_prompt(&form);
+
+                        add_prompt_modal.close();
+                        self.view.close_modal();
+                        self.view.select_prompt(new_prompt_idx);
+                    }
+                }
+                AppAction::OpenRemovePromptDialog(idx) => {
+                    if self.prompts.get(idx).is_some_and(|prompt| !prompt.locked) {
+                        remove_prompt_modal.open();
+                        self.view.open_remove_prompt_modal(idx);
+                    }
+                }
+                AppAction::RemovePrompt(idx) => {
+                    self.view.close_modal();
+                    self.remove_prompt(idx);
+                }
+                AppAction::OpenEditPromptDialog(idx) => {
+                    if let Some(prompt) = self.prompts.get(idx).filter(|prompt| !prompt.locked) {
+                        edit_prompt_modal.open();
+                        self.view.open_edit_prompt_modal(idx, prompt);
+                    }
+                }
+                AppAction::OpenRemovePromptHistoryDialog { idx, history_idx } => {
+                    remove_prompt_history_modal.open();
+                    self.view.open_remove_prompt_history_modal(idx, history_idx);
+                }
+                AppAction::RemovePromptHistory { idx, history_idx } => {
+                    self.view.close_modal();
+
+                    // A generation started since the dialog opened would shift the entry to remove
+                    if self
+                        .prompts
+                        .get(idx)
+                        .is_some_and(|prompt| prompt.state.is_generating())
+                    {
+                        self.show_error(
+                            error_modal,
+                            "Response Removal Error".to_string(),
+                            "The prompt is generating a response, remove the entry once it has finished.".to_string(),
+                        );
+                    } else {
+                        self.remove_history(idx, history_idx);
+                    }
+                }
+                AppAction::Undo => self.undo_removal(),
+                AppAction::EditPrompt => {
+                    if let Some((idx, form)) = self
+                        .view
+                        .get_edit_prompt_modal_data()
+                        .map(|(idx, form)| (idx, form.clone()))
+                    {
+                        let previous_content = self
+                            .prompts
+                            .get(idx)
+                            .map(|prompt| prompt.content.clone())
+                            .filter(|content| *content != form.content);
+
+                        self.edit_prompt(idx, &form);
+
+                        if let Some(previous_content) = previous_content
+                            && let Some(prompt) = self.prompts.get_mut(idx)
+                        {
+                            prompt.offer_benchmark(previous_content);
+                        }
+
+                        edit_prompt_modal.close();
+                        self.view.close_modal();
+                        self.view.select_prompt(idx);
+                    }
+                }
+                AppAction::SelectPrompt(idx) => {
+                    self.view.select_prompt(idx);
+                    self.scroll_to_prompt = Some(idx);
+
+                    if self.settings.warm_up_on_select
+                        && self
+                            .prompts
+                            .get(idx)
+                            .is_some_and(|prompt| prompt.kind == PromptKind::Text)
+                    {
+                        self.warm_up_selected_model();
+                    }
+                }
+                AppAction::AvoidBannedPhrases(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && let Some(selected_model) = &self.ollama_models.selected
+                    {
+                        prompt.regenerate_avoiding_banned_phrases(
+                            selected_model,
+                            &self.tokio_runtime,
+                            &self.ollama_client,
+                        );
+                    }
+                }
+                AppAction::NewChatSession(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && !prompt.state.is_generating()
+                    {
+                        prompt.new_chat_session();
+                    }
+                }
+                AppAction::DeleteChatSession { idx, created_at } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && !prompt.state.is_generating()
+                    {
+                        prompt.delete_chat_session(created_at);
+                    }
+                }
+                AppAction::GenerateChatTitle(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && let Some(selected_model) = &self.ollama_models.selected
+                    {
+                        // A title doesn't need the larger model
+                        let model = ollama::smaller_model(
+                            &self.ollama_models.available,
+                            &selected_model.name,
+                        )
+                        .unwrap_or(selected_model);
+
+                        prompt.generate_chat_title(model, &self.tokio_runtime, &self.ollama_client);
+                    }
+                }
+                AppAction::CritiquePromptResponse(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && let Some(selected_model) = &self.ollama_models.selected
+                    {
+                        prompt.critique_response(
+                            selected_model,
+                            &self.tokio_runtime,
+                            &self.ollama_client,
+                        );
+                    }
+                }
+                AppAction::ContinuePromptResponse(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx)
+                        && let Some(selected_model) = &self.ollama_models.selected
+                    {
+                        prompt.continue_response(
+                            selected_model,
+                            &self.tokio_runtime,
+                            &self.ollama_client,
+                        );
+                    }
+                }
+                AppAction::LoadOlderHistory(idx) => {
+                    if let Err(e) = self.load_older_history(idx) {
+                        self.show_error(
+                            error_modal,
+                            "History Error".to_string(),
+                            format!("Failed to load the older responses.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::PickWatchedFolder(idx) => {
+                    if let Some(dir) = rfd::FileDialog::new()
+                        .set_title("Watch folder")
+                        .pick_folder()
+                        && let Some(prompt) = self.prompts.get_mut(idx)
+                        && let Err(e) = prompt.watch_folder(dir)
+                    {
+                        self.show_error(
+                            error_modal,
+                            "Watch Folder Error".to_string(),
+                            format!("Failed to watch the folder.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::OpenMailbox { idx, maildir } => {
+                    if let Err(e) = self.open_mailbox(idx, maildir) {
+                        self.show_error(
+                            error_modal,
+                            "Mail Digest Error".to_string(),
+                            format!("Failed to read the mailbox.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::SaveMailDigest(idx) => {
+                    if let Err(e) = self.save_mail_digest(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Mail Digest Error".to_string(),
+                            format!("Failed to save the digest.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::GenerateBriefing(idx) => {
+                    if let Err(e) = self.generate_briefing(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Briefing Error".to_string(),
+                            format!("Failed to fetch the feeds.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::StopWatchingFolder(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.stop_watching_folder();
+                    }
+                }
+                AppAction::OpenQuickOpenDialog => {
+                    quick_open_modal.open();
+                    self.view.open_quick_open_modal(false);
+                }
+                AppAction::OpenCommandPalette => {
+                    quick_open_modal.open();
+                    self.view.open_quick_open_modal(true);
+                }
+                AppAction::ToggleTheme => {
+                    Self::set_theme(
+                        ctx,
+                        if ctx.style().visuals.dark_mode {
+                            Theme::Light
+                        } else {
+                            Theme::Dark
+                        },
+                    );
+                }
+                AppAction::Sync => {
+                    if let Err(e) = self.start_sync() {
+                        self.show_error(error_modal, "Sync Error".to_string(), e.to_string());
+                    }
+                }
+                AppAction::OpenSyncConflictsDialog => {
+                    if let Some(merge) = self.sync.pending.take() {
+                        import_modal.open();
+                        self.view.open_sync_conflicts_modal(merge);
+                    }
+                }
+                AppAction::ResolveSyncConflicts => {
+                    if let Some(merge) = self.view.take_sync_conflicts_modal_data() {
+                        import_modal.close();
+                        sync::resolve(&mut self.prompts, merge.conflicts);
+
+                        if let Err(e) = self.push_sync(merge.version) {
+                            self.show_error(error_modal, "Sync Error".to_string(), e.to_string());
+                        }
+                    }
+                }
+                AppAction::SelectSharedPrompt(idx) => {
+                    self.view.main_panel = ViewMainPanel::SharedPrompt(idx);
+                }
+                AppAction::DuplicateSharedPrompt(idx) => {
+                    if let Some(card) = self.shared.cards.get(idx).cloned() {
+                        let id = self.prompts.len();
+
+                        self.prompts.push(card.into_prompt(id));
+                        self.view.select_prompt(id);
+                        self.scroll_to_prompt = Some(id);
+                    }
+                }
+                AppAction::FocusInput => {
+                    ctx.memory_mut(|memory| {
+                        memory.request_focus(egui::Id::new(prompt::PROMPT_INPUT_ID));
+                    });
+                }
+                AppAction::OpenInputSearch(idx) => {
+                    input_search_modal.open();
+                    self.view.open_input_search_modal(idx);
+                }
+                AppAction::RecallInput { idx, input } => {
+                    input_search_modal.close();
+                    self.view.close_modal();
+
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.recall_input(input);
+                        ctx.memory_mut(|memory| {
+                            memory.request_focus(egui::Id::new(prompt::PROMPT_INPUT_ID));
+                        });
+                    }
+                }
+                AppAction::QuickOpenPrompt(idx) => {
+                    quick_open_modal.close();
+                    self.view.close_modal();
+                    self.view.select_prompt(idx);
+                    self.scroll_to_prompt = Some(idx);
+                }
+                AppAction::SelectOllamaModel(local_model) => {
+                    self.ollama_models.selected = Some(local_model);
+                }
+                AppAction::SetUIScale(scale) => {
+                    let clamped_scale = scale.clamp(1.0, 2.5);
+
+                    self.set_scale(ctx, clamped_scale);
+                }
+                AppAction::ShowErrorDialog { title, message } => {
+                    self.show_error(error_modal, title, message);
+                }
+                AppAction::ReloadOllamaModels => {
+                    self.load_local_models();
+                }
+                AppAction::ShowMissingModels(missing) => {
+                    if !missing.models.is_empty() {
+                        models_modal.open();
+                        self.view.open_missing_models_modal(missing);
+                    } else if self.view.get_missing_models_modal_data().is_some() {
+                        models_modal.close();
+                        self.view.close_modal();
+                    }
+                }
+                AppAction::OpenModelOnboarding => {
+                    self.ollama_models.onboarding_offered = true;
+                    models_modal.open();
+                    self.view
+                        .open_model_onboarding_modal(ModelOnboarding::new(Hardware::detect()));
+                }
+                AppAction::PullModel(name) => {
+                    if let Some(missing) = self.view.get_missing_models_modal_data_mut() {
+                        missing.pulling = Some(name.clone());
+                        missing.progress = None;
+                        missing.pull_error = None;
+                    }
+
+                    if let Some(onboarding) = self.view.get_model_onboarding_modal_data_mut() {
+                        onboarding.pulling = Some(name.clone());
+                        onboarding.progress = None;
+                        onboarding.pull_error = None;
+                    }
+
+                    self.pull_model(name);
+                }
+                AppAction::RemapMissingModels => {
+                    if let Some(missing) = self.view.get_missing_models_modal_data().cloned() {
+                        models_modal.close();
+                        self.view.close_modal();
+                        self.remap_missing_models(&missing);
+                    }
+                }
+                AppAction::ExportPromptScript(idx) => {
+                    if let Err(e) = self.export_prompt_script(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the script.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ExportPromptHistory(idx) => {
+                    if let Err(e) = self.export_prompt_history(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the history.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ExportPromptCard(idx) => {
+                    if let Err(e) = self.export_prompt_card(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the prompt card.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ApplyDiff { idx, history_idx } => {
+                    match self.preview_patch(idx, history_idx) {
+                        Ok(Some(preview)) => {
+                            apply_patch_modal.open();
+                            self.view.open_apply_patch_modal(preview);
+                        }
+                        Ok(None) => {}
+                        Err(e) => self.show_error(
+                            error_modal,
+                            "Apply Diff Error".to_string(),
+                            format!("Failed to apply the diff.\n\nError: {e}"),
+                        ),
+                    }
+                }
+                AppAction::ConfirmApplyPatch => {
+                    if let Some(preview) = self.view.get_apply_patch_modal_data().cloned() {
+                        apply_patch_modal.close();
+                        self.view.close_modal();
+
+                        if let Err(e) = patch::write_with_backup(&preview) {
+                            self.show_error(
+                                error_modal,
+                                "Apply Diff Error".to_string(),
+                                format!(
+                                    "Failed to write {}.\n\nError: {e}",
+                                    preview.path.display()
+                                ),
+                            );
+                        }
+                    }
+                }
+                AppAction::OpenInEditor { idx, history_idx } => {
+                    if let Err(e) = self.open_in_editor(idx, history_idx) {
+                        self.show_error(
+                            error_modal,
+                            "Editor Error".to_string(),
+                            format!("Failed to open the response in the editor.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::OpenRunSnippetDialog {
+                    idx,
+                    history_idx,
+                    snippet,
+                } => {
+                    let policy = SnippetPolicy::from_settings(&self.settings);
+
+                    match policy.check(&snippet) {
+                        Ok(()) => {
+                            let command_line = policy.command_line(&snippet);
+
+                            run_snippet_modal.open();
+                            self.view.open_run_snippet_modal(
+                                idx,
+                                history_idx,
+                                snippet,
+                                command_line,
+                            );
+                        }
+                        Err(e) => self.show_error(
+                            error_modal,
+                            "Snippet Error".to_string(),
+                            format!("The snippet may not run.\n\nError: {e}"),
+                        ),
+                    }
+                }
+                AppAction::RunSnippet => {
+                    if let Some((idx, history_idx, snippet)) = self
+                        .view
+                        .get_run_snippet_modal_data()
+                        .map(|(idx, history_idx, snippet)| (idx, history_idx, snippet.clone()))
+                    {
+                        run_snippet_modal.close();
+                        self.view.close_modal();
+
+                        if let Some(prompt) = self.prompts.get_mut(idx) {
+                            prompt.run_snippet(
+                                history_idx,
+                                snippet,
+                                SnippetPolicy::from_settings(&self.settings),
+                                &self.tokio_runtime,
+                            );
+                        }
+                    }
+                }
+                AppAction::CancelAllGenerations => {
+                    self.ollama_client.cancel_generation();
+
+                    for prompt in &mut self.prompts {
+                        prompt.clear_queue();
+                        prompt.cancel_generation();
+                    }
+                }
+                AppAction::SetQueuePaused(paused) => {
+                    self.queue_paused = paused;
+                }
+                AppAction::SetQueuePriority { idx, priority } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.set_queue_priority(priority);
+                    }
+                }
+                AppAction::ShowTimeline => {
+                    self.view.main_panel = ViewMainPanel::Timeline;
+                }
+                AppAction::ShowClippings => {
+                    self.view.main_panel = ViewMainPanel::Clippings;
+                }
+                AppAction::ClipQuote {
+                    idx,
+                    created_at,
+                    model,
+                    quote,
+                } => {
+                    if let Some(prompt) = self.prompts.get(idx) {
+                        self.clippings.push(Clipping {
+                            quote,
+                            prompt_title: prompt.title.clone(),
+                            model,
+                            response_created_at: created_at,
+                            clipped_at: Local::now(),
+                        });
+                    }
+                }
+                AppAction::RemoveClipping(clipped_at) => {
+                    self.clippings
+                        .retain(|clipping| clipping.clipped_at != clipped_at);
+                }
+                AppAction::ExportClippings => {
+                    if let Err(e) = self.export_clippings() {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the clippings.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ShowHistoryEntry { idx, created_at } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.jump_to_response(created_at);
+                        self.view.select_prompt(idx);
+                    }
+                }
+                AppAction::AddSpellingWord(word) => {
+                    if !self.settings.spell_check_words.contains(&word) {
+                        self.settings.spell_check_words.push(word);
+                    }
+                }
+                AppAction::ImportFiles(paths) => {
+                    for path in paths {
+                        match self.import_file(&path) {
+                            Ok(Some(import)) => {
+                                import_modal.open();
+                                self.view.open_import_script_modal(import);
+                            }
+                            Ok(None) => {}
+                            Err(e) => self.show_error(
+                                error_modal,
+                                "Import Error".to_string(),
+                                format!("Failed to import {}.\n\nError: {e}", path.display()),
+                            ),
+                        }
+                    }
+                }
+                AppAction::OpenSettingsDialog => {
+                    settings_modal.open();
+                    self.view.open_settings_modal(&self.settings);
+                }
+                AppAction::SaveSettings => {
+                    let settings = self.view.get_settings_modal_data().cloned();
+
+                    self.view.close_modal();
+
+                    if let Some(settings) = settings
+                        && let Err(e) = self.save_settings(settings)
+                    {
+                        self.show_error(
+                            error_modal,
+                            "Settings Error".to_string(),
+                            format!("Failed to save the settings.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ExportSettingsProfile => {
+                    if let Err(e) = self.export_settings_profile(ctx) {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the settings profile.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ImportSettingsProfile => {
+                    if let Err(e) = self.import_settings_profile(ctx) {
+                        self.show_error(
+                            error_modal,
+                            "Import Error".to_string(),
+                            format!("Failed to import the settings profile.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ScorePromptResponses { idx, history_idx } => {
+                    if let Err(e) = self.score_prompt_responses(idx, history_idx) {
+                        self.show_error(error_modal, "Judge Error".to_string(), e.to_string());
+                    }
+                }
+                AppAction::SwitchToSmallerModel { idx, model } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.switch_model(model);
+                    }
+                }
+                AppAction::RestoreStateBackup => {
+                    recovery_modal.close();
+                    self.view.close_modal();
+
+                    if let Err(e) = self.restore_latest_backup(ctx) {
+                        self.show_error(error_modal, "Restore Error".to_string(), e.to_string());
+                    }
+                }
+                AppAction::DiscardRecoveredPrompts => {
+                    recovery_modal.close();
+                    self.view.close_modal();
+                    self.prompts.clear();
+                }
+                AppAction::OpenMaintenanceDialog => {
+                    maintenance_modal.open();
+                    self.open_maintenance_report();
+                }
+                AppAction::ImportScript => match self.import_script() {
+                    Ok(Some(import)) => {
+                        import_modal.open();
+                        self.view.open_import_script_modal(import);
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.show_error(
+                        error_modal,
+                        "Import Error".to_string(),
+                        format!("Failed to import the script.\n\nError: {e}"),
+                    ),
+                },
+                AppAction::ResolveScriptImport(strategy) => {
+                    if let Some(import) = self.view.get_import_script_modal_data().cloned() {
+                        import_modal.close();
+                        self.view.close_modal();
+                        self.resolve_script_import(import, strategy);
+                    }
+                }
+                AppAction::OpenExportPromptsDialog => {
+                    export_modal.open();
+                    self.view.open_export_prompts_modal();
+                }
+                AppAction::ExportPrompts { include_history } => {
+                    export_modal.close();
+                    self.view.close_modal();
+
+                    if let Err(e) = self.export_prompts(include_history) {
+                        self.show_error(
+                            error_modal,
+                            "Export Error".to_string(),
+                            format!("Failed to export the prompts.\n\nError: {e}"),
+                        );
+                    }
+                }
+                AppAction::ImportPrompts => match self.import_prompts() {
+                    Ok(Some(import)) => {
+                        import_modal.open();
+                        self.view.open_import_prompts_modal(import);
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.show_error(
+                        error_modal,
+                        "Import Error".to_string(),
+                        format!("Failed to import the prompts.\n\nError: {e}"),
+                    ),
+                },
+                AppAction::ResolvePromptsImport(mode) => {
+                    if let Some(import) = self.view.take_import_prompts_modal_data() {
+                        import_modal.close();
+
+                        if let Err(e) = self.resolve_prompts_import(import, mode) {
+                            self.show_error(
+                                error_modal,
+                                "Import Error".to_string(),
+                                format!("Failed to import the prompts.\n\nError: {e}"),
+                            );
+                        }
+                    }
+                }
+                AppAction::ArchivePrompt { idx, archived } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.archived = archived;
+                    }
+
+                    self.open_maintenance_report();
+                }
+                AppAction::SetPromptDisabled { idx, disabled } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.disabled = disabled;
+                    }
+
+                    if disabled
+                        && matches!(self.view.main_panel, ViewMainPanel::Prompt(selected) if selected == idx)
+                    {
+                        self.view.main_panel = ViewMainPanel::Welcome;
+                    }
+                }
+                AppAction::SetPromptLocked { idx, locked } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.locked = locked;
+                    }
+                }
+                AppAction::SetPromptGroup { idx, group } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.group = group;
+                    }
+                }
+                AppAction::ToggleGroupCollapsed(idx) => {
+                    if let Some(group) = self.groups.get_mut(idx) {
+                        group.collapsed = !group.collapsed;
+                    }
+                }
+                AppAction::MoveGroup { from, to } => {
+                    group::move_group(&mut self.groups, from, to);
+                }
+                AppAction::RemoveGroup(idx) => {
+                    group::remove_group(&mut self.groups, &mut self.prompts, idx);
+                }
+                AppAction::MergePrompts { keep, remove } => {
+                    if let Err(e) = self.merge_prompts(&keep, &remove) {
+                        maintenance_modal.close();
+                        self.show_error(error_modal, "Merge Error".to_string(), e.to_string());
+                    } else {
+                        self.open_maintenance_report();
+                    }
+                }
+                AppAction::PrunePromptHistory(idx) => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.prune_history(PRUNED_HISTORY_LEN);
+                    }
+
+                    self.open_maintenance_report();
+                }
+                AppAction::SelectEnsembleCandidate {
+                    idx,
+                    history_idx,
+                    candidate_idx,
+                } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.select_candidate(history_idx, candidate_idx);
+                    }
+                }
+                AppAction::SynthesizePromptResponse { idx, history_idx } => {
+                    if let Some(prompt) = self.prompts.get_mut(idx) {
+                        prompt.synthesize_speech(
+                            history_idx,
+                            Self::audio_dir(),
+                            &self.settings.tts_command,
+                            &self.tokio_runtime,
+                        );
+                    }
+                }
+                AppAction::ReplayPromptScript(idx) => {
+                    if let Err(e) = self.replay_prompt_script(idx) {
+                        self.show_error(
+                            error_modal,
+                            "Import Error".to_string(),
+                            format!("Failed to import the script.\n\nError: {e}"),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn score_prompt_responses(
+        &mut self,
+        idx: usize,
+        history_idx: Option<usize>,
+    ) -> anyhow::Result<()> {
+        let Some(prompt) = self.prompts.get_mut(idx) else {
+            return Ok(());
+        };
+
+        let judge = self
+            .ollama_models
+            .available
+            .iter()
+            .find(|model| model.name == prompt.judge_model)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "The judge model \"{}\" is not available.",
+                    prompt.judge_model
+                )
+            })?;
+
+        prompt.score_responses(history_idx, judge, &self.tokio_runtime, &self.ollama_client);
+
+        Ok(())
+    }
+
+    fn generate_prompt_response(
+        &mut self,
+        idx: usize,
+        input: String,
+        model: Option<LocalModel>,
+    ) -> anyhow::Result<()> {
+        let Some(prompt) = self.prompts.get_mut(idx) else {
+            return Ok(());
+        };
+        let selected_model = model.as_ref().or(self.ollama_models.selected.as_ref());
+
+        match prompt.kind {
+            PromptKind::Text if prompt.debate.enabled => {
+                let models = prompt
+                    .debate
+                    .resolve(&self.ollama_models.available)
+                    .map_err(|e| anyhow::anyhow!("Failed to start the debate: {e}"))?;
+
+                prompt.generate_debate(input, models, &self.tokio_runtime, &self.ollama_client);
+            }
+            PromptKind::Text if prompt.compare.enabled => {
+                let models = prompt
+                    .compare
+                    .resolve(&self.ollama_models.available)
+                    .map_err(|e| anyhow::anyhow!("Failed to start the comparison: {e}"))?;
+
+                for model in &models {
+                    self.ollama_models.mark_used(&model.name);
+                }
+
+                prompt.generate_comparison(input, models, &self.tokio_runtime, &self.ollama_client);
+            }
+            PromptKind::Text if prompt.ensemble.enabled => {
+                let judge = prompt
+                    .ensemble
+                    .resolve_judge(&self.ollama_models.available)
+                    .map_err(|e| anyhow::anyhow!("Failed to start the ensemble: {e}"))?;
+
+                if let Some(selected_model) = selected_model {
+                    prompt.generate_ensemble(
+                        input,
+                        selected_model,
+                        judge,
+                        &self.tokio_runtime,
+                        &self.ollama_client,
+                    );
+                }
+            }
+            PromptKind::Text => {
+                if let Some(selected_model) = selected_model {
+                    prompt.generate_response(
+                        input,
+                        selected_model,
+                        &self.tokio_runtime,
+                        &self.ollama_client,
+                    );
+
+                    let name = selected_model.name.clone();
+                    self.ollama_models.mark_used(&name);
+                }
+            }
+            PromptKind::Image => {
+                prompt.generate_images(
+                    input,
+                    Self::images_dir(),
+                    &self.tokio_runtime,
+                    &self.stable_diffusion_client,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Backups of the persisted app state are kept next to it
+    fn backups_dir() -> PathBuf {
+        eframe::storage_dir(TITLE)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("backups")
+    }
+
+    /// Generated images are stored next to the persisted app state
+    fn images_dir() -> PathBuf {
+        eframe::storage_dir(TITLE)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("images")
+    }
+
+    /// Synthesized speech is cached next to the persisted app state as well
+    fn audio_dir() -> PathBuf {
+        eframe::storage_dir(TITLE)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("audio")
+    }
+
+    fn show_error(&mut self, error_modal: &Modal, title: String, message: String) {
+        error_modal.open();
+        self.view.open_error_modal(title, message);
+    }
+
+    fn export_prompt_script(&mut self, idx: usize) -> anyhow::Result<()> {
+        self.load_full_history(idx)?;
+
+        if let Some(prompt) = self.prompts.get(idx)
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Export script")
+                .set_file_name(format!("{}.json", prompt.title))
+                .add_filter("Reprompt script", &["json"])
+                .save_file()
+        {
+            prompt.to_script().save(&path)?;
+        }
+
+        Ok(())
+    }
+
+    fn generate_briefing(&mut self, idx: usize) -> anyhow::Result<()> {
+        let client = http::client(&self.settings, &EndpointTls::default(), &[])?;
+
+        if let Some(prompt) = self.prompts.get_mut(idx) {
+            prompt.fetch_feeds(client, &self.tokio_runtime);
+        }
+
+        Ok(())
+    }
+
+    fn open_mailbox(&mut self, idx: usize, maildir: bool) -> anyhow::Result<()> {
+        let dialog = rfd::FileDialog::new().set_title("Open mailbox");
+        let path = if maildir {
+            dialog.pick_folder()
+        } else {
+            dialog.pick_file()
+        };
+
+        if let Some(path) = path
+            && let Some(prompt) = self.prompts.get_mut(idx)
+        {
+            let messages = mail::read_mailbox(&path)?;
+
+            anyhow::ensure!(
+                !messages.is_empty(),
+                "There are no messages in {}.",
+                path.display()
+            );
+
+            prompt.open_mail_digest(path, messages);
+        }
+
+        Ok(())
+    }
+
+    fn save_mail_digest(&self, idx: usize) -> anyhow::Result<()> {
+        if let Some(document) = self.prompts.get(idx).and_then(Prompt::mail_digest_document)
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Save digest")
+                .set_file_name("digest.md")
+                .add_filter("Markdown", &["md"])
+                .save_file()
+        {
+            std::fs::write(path, document)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the listed clippings to a markdown file
+    fn export_clippings(&self) -> anyhow::Result<()> {
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title("Export clippings")
+            .set_file_name("clippings.md")
+            .add_filter("Markdown", &["md"])
+            .save_file()
+        {
+            let clippings = clippings::filtered(&self.clippings, self.clippings_prompt.as_deref());
+
+            std::fs::write(&path, clippings::to_markdown(clippings.into_iter()))
+                .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the history as HTML when the file is named so, as Markdown otherwise
+    fn export_prompt_history(&mut self, idx: usize) -> anyhow::Result<()> {
+        self.load_full_history(idx)?;
+
+        if let Some(prompt) = self.prompts.get(idx)
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Export history")
+                .set_file_name(format!("{}.md", prompt.title))
+                .add_filter("Markdown", &["md"])
+                .add_filter("HTML", &["html", "htm"])
+                .save_file()
+        {
+            let transcript = prompt.to_saved_transcript();
+            let is_html = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    matches!(extension.to_lowercase().as_str(), "html" | "htm")
+                });
+            let text = if is_html {
+                markdown::to_html_document(&prompt.title, &transcript)
+            } else {
+                transcript
+            };
+
+            std::fs::write(&path, text)
+                .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    fn export_prompt_card(&self, idx: usize) -> anyhow::Result<()> {
+        if let Some(prompt) = self.prompts.get(idx)
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Export prompt card")
+                .set_file_name(format!("{}.{}", prompt.title, card::EXTENSION))
+                .add_filter("Reprompt prompt card", &[card::EXTENSION])
+                .save_file()
+        {
+            PromptCard::from_prompt(prompt).save(&path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Opens a response in the external editor, only the code when it is a single code block
+    fn open_in_editor(&self, idx: usize, history_idx: usize) -> anyhow::Result<()> {
+        let Some(output) = self
+            .prompts
+            .get(idx)
+            .and_then(|prompt| prompt.response_output(history_idx))
+        else {
+            return Ok(());
+        };
+
+        let (text, extension) = match content::detect(output) {
+            ContentType::Code { language, code } => {
+                (code, editor::extension_of(&language).to_string())
+            }
+            ContentType::Json(_) => (content::strip_fence(output).to_string(), "json".to_string()),
+            ContentType::Csv(_) => (content::strip_fence(output).to_string(), "csv".to_string()),
+            ContentType::Diff(diff) => (diff, "diff".to_string()),
+            ContentType::Markdown => (output.to_string(), "md".to_string()),
+        };
+
+        editor::open(&self.settings.editor_command, &text, &extension)?;
+
+        Ok(())
+    }
+
+    /// Applies the diff of a response to a file picked by the user, without writing it yet
+    fn preview_patch(
+        &self,
+        idx: usize,
+        history_idx: usize,
+    ) -> anyhow::Result<Option<PatchPreview>> {
+        let Some(output) = self
+            .prompts
+            .get(idx)
+            .and_then(|prompt| prompt.response_output(history_idx))
+        else {
+            return Ok(None);
+        };
+
+        let ContentType::Diff(diff) = content::detect(output) else {
+            anyhow::bail!("the response is not a diff");
+        };
+        let patch = Patch::parse(&diff)?;
+
+        let mut dialog = rfd::FileDialog::new().set_title("Apply diff to file");
+
+        if let Some(name) = patch
+            .target
+            .as_deref()
+            .and_then(|target| Path::new(target).file_name())
+        {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+
+        // A new file is saved where the user picks, the diff applying to no content
+        let (path, original) = if patch.kind == PatchKind::Create {
+            let Some(path) = dialog.save_file() else {
+                return Ok(None);
+            };
+
+            (path, String::new())
+        } else {
+            let Some(path) = dialog.pick_file() else {
+                return Ok(None);
+            };
+            let original = std::fs::read_to_string(&path)?;
+
+            (path, original)
+        };
+        let patched = patch.apply(&original)?;
+
+        Ok(Some(PatchPreview {
+            path,
+            kind: patch.kind,
+            diff,
+            patched,
+        }))
+    }
+
+    /// Adds the prompt of a card, selecting its recommended model when it is available
+    fn import_prompt_card(&mut self, path: &Path) -> anyhow::Result<()> {
+        let card = PromptCard::load(path)?;
+        let recommended_model = card.recommended_model.clone().and_then(|name| {
+            self.ollama_models
+                .available
+                .iter()
+                .find(|model| model.name == name)
+                .cloned()
+        });
+
+        let id = self.prompts.len();
+        self.prompts.push(card.into_prompt(id));
+        self.view.select_prompt(id);
+
+        if recommended_model.is_some() {
+            self.ollama_models.selected = recommended_model;
+        }
+
+        Ok(())
+    }
+
+    fn import_script(&mut self) -> anyhow::Result<Option<ScriptImport>> {
+        match rfd::FileDialog::new()
+            .set_title("Import")
+            .add_filter("Reprompt prompt card or script", &[card::EXTENSION, "json"])
+            .pick_file()
+        {
+            Some(path) => self.import_file(&path),
+            None => Ok(None),
+        }
+    }
+
+    /// Imports a prompt card or a script, or attaches a document to the input of the selected prompt.
+    ///
+    /// Returns the script when a prompt with the same content already exists.
+    fn import_file(&mut self, path: &Path) -> anyhow::Result<Option<ScriptImport>> {
+        match FileKind::of(path) {
+            Some(FileKind::PromptCard) => self.import_prompt_card(path).map(|()| None),
+            Some(FileKind::Script) => self.load_script_import(path),
+            Some(FileKind::Document) => self.attach_document(path).map(|()| None),
+            None => anyhow::bail!("Only prompt cards, scripts and text documents are supported."),
+        }
+    }
+
+    fn attach_document(&mut self, path: &Path) -> anyhow::Result<()> {
+        let ViewMainPanel::Prompt(idx) = self.view.main_panel else {
+            anyhow::bail!("Select a prompt to attach the document to its input.");
+        };
+        let text = std::fs::read_to_string(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        if let Some(prompt) = self.prompts.get_mut(idx) {
+            prompt.attach_to_input(&name, &text);
+        }
+
+        Ok(())
+    }
+
+    /// Imports a script as a new prompt, or returns it when a prompt with the same content exists
+    fn load_script_import(&mut self, path: &Path) -> anyhow::Result<Option<ScriptImport>> {
+        let script = Script::load(path)?;
+        let content_hash = script::content_hash(&script.content);
+
+        let Some(existing) = self
+            .prompts
+            .iter()
+            .position(|prompt| script::content_hash(&prompt.content) == content_hash)
+        else {
+            self.resolve_script_import(
+                ScriptImport {
+                    script,
+                    existing: None,
+                    duplicate_steps: 0,
+                },
+                ImportStrategy::KeepBoth,
+            );
+
+            return Ok(None);
+        };
+
+        let hashes = self.prompts[existing].response_hashes();
+        let duplicate_steps = script
+            .steps
+            .iter()
+            .filter(|step| hashes.contains(&script::exchange_hash(&step.input, &step.output)))
+            .count();
+
+        Ok(Some(ScriptImport {
+            script,
+            existing: Some(existing),
+            duplicate_steps,
+        }))
+    }
+
+    fn resolve_script_import(&mut self, import: ScriptImport, strategy: ImportStrategy) {
+        let ScriptImport {
+            script, existing, ..
+        } = import;
+
+        match (strategy, existing) {
+            (ImportStrategy::Merge, Some(idx)) if idx < self.prompts.len() => {
+                self.prompts[idx].import_steps(script.steps);
+                self.view.select_prompt(idx);
+            }
+            _ => {
+                let id = self.prompts.len();
+                let mut prompt = Prompt::new(script.title, script.content, id);
+
+                prompt.import_steps(script.steps);
+                self.prompts.push(prompt);
+                self.view.select_prompt(id);
+            }
+        }
+    }
+
+    fn export_settings_profile(&self, ctx: &egui::Context) -> anyhow::Result<()> {
+        if let Some(settings) = self.view.get_settings_modal_data()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Export settings profile")
+                .set_file_name("reprompt-profile.toml")
+                .add_filter("TOML", &["toml"])
+                .save_file()
+        {
+            let theme = match ctx.options(|options| options.theme_preference) {
+                egui::ThemePreference::System => Theme::System,
+                egui::ThemePreference::Light => Theme::Light,
+                egui::ThemePreference::Dark => Theme::Dark,
+            };
+
+            config::save_profile(&path, settings, theme)?;
+        }
+
+        Ok(())
+    }
+
+    /// Puts the settings of a profile into the settings dialog and applies its theme.
+    ///
+    /// The values of the headers it names are read from the OS keyring, as they are never exported.
+    fn import_settings_profile(&mut self, ctx: &egui::Context) -> anyhow::Result<()> {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import settings profile")
+            .add_filter("Settings profile", &["toml"])
+            .pick_file()
+        else {
+            return Ok(());
+        };
+
+        let profile = ConfigFile::load(&path)?;
+
+        if let Some(settings) = self.view.get_settings_modal_data_mut() {
+            *settings = profile.apply(settings)?;
+            settings.load_secrets()?;
+        }
+
+        if let Some(theme) = profile.theme {
+            Self::set_theme(ctx, theme);
+        }
+
+        Ok(())
+    }
+
+    fn export_prompts(&mut self, include_history: bool) -> anyhow::Result<()> {
+        if include_history {
+            self.load_all_history()?;
+        }
+
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title("Export prompts")
+            .set_file_name("prompts.json")
+            .add_filter("JSON", &["json"])
+            .add_filter("TOML", &["toml"])
+            .save_file()
+        {
+            PromptLibrary::new(&self.prompts, include_history)?.save(&path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads exported prompts, asking whether to merge or replace unless the library is empty
+    fn import_prompts(&mut self) -> anyhow::Result<Option<LibraryImport>> {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import prompts")
+            .add_filter("Exported prompts", &["json", "toml"])
+            .pick_file()
+        else {
+            return Ok(None);
+        };
+
+        let import = LibraryImport::new(PromptLibrary::load(&path)?, &self.prompts);
+
+        if self.prompts.is_empty() {
+            self.resolve_prompts_import(import, LibraryImportMode::Replace)?;
+
+            return Ok(None);
+        }
+
+        Ok(Some(import))
+    }
+
+    fn resolve_prompts_import(
+        &mut self,
+        import: LibraryImport,
+        mode: LibraryImportMode,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self
+                .prompts
+                .iter()
+                .any(|prompt| prompt.state.is_generating()),
+            "Wait for the responses to finish generating before importing prompts."
+        );
+
+        match mode {
+            LibraryImportMode::Replace => {
+                self.prompts = import.library.prompts;
+                self.view.main_panel = ViewMainPanel::Welcome;
+            }
+            LibraryImportMode::Merge => {
+                for prompt in import.library.prompts {
+                    match library::find_same_content(&self.prompts, &prompt) {
+                        Some(idx) => self.prompts[idx].merge_history(prompt),
+                        None => self.prompts.push(prompt),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn replay_prompt_script(&mut self, idx: usize) -> anyhow::Result<()> {
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title("Replay script")
+            .add_filter("Reprompt script", &["json"])
+            .pick_file()
+        {
+            let script = Script::load(&path)?;
+
+            if let Some(prompt) = self.get_prompt_mut(idx) {
+                prompt.start_replay(script);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn load_local_models(&self) {
+        let handle = self.ollama_models.load_flower.handle();
+        let ollama_client = self.ollama_client.clone();
+        let current_selected = self.ollama_models.selected.clone();
+        let default_model = self.env_overrides.default_model.clone();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match ollama_client.list_models().await {
+                Ok(response) => {
+                    let env_selected = default_model.and_then(|name| {
+                        let model = response.iter().find(|model| model.name == name).cloned();
+
+                        if model.is_none() {
+                            log::warn!(
+                                "The model {name} set by {} is not available",
+                                config::DEFAULT_MODEL_VAR
+                            );
+                        }
+
+                        model
+                    });
+                    let maybe_selected = env_selected.or_else(|| {
+                        response.first().and_then(|default| match current_selected {
+                            Some(selected)
+                                if !response
+                                    .iter()
+                                    .map(|m| &m.name)
+                                    .cloned()
+                                    .collect::<Vec<String>>()
+                                    .contains(&selected.name) =>
+                            {
+                                Some(default.clone())
+                            }
+                            None => Some(default.clone()),
+                            _ => None,
+                        })
+                    });
+
+                    handle.success((response, maybe_selected))
+                }
+                Err(e) => handle.error(e),
+            }
+        });
+    }
+
+    fn pull_model(&self, name: String) {
+        let handle = self.ollama_models.pull_flower.handle();
+        let ollama_client = self.ollama_client.clone();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match ollama_client
+                .pull_model(&name, |progress| handle.send(progress))
+                .await
+            {
+                Ok(()) => handle.success(name),
+                Err(e) => handle.error(format!("Failed to pull {name}: {e}")),
+            }
+        });
+    }
+
+    fn poll_pull_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+
+        self.ollama_models
+            .pull_flower
+            .extract(|progress| {
+                if let Some(missing) = self.view.get_missing_models_modal_data_mut() {
+                    missing.progress = Some(progress.clone());
+                }
+
+                if let Some(onboarding) = self.view.get_model_onboarding_modal_data_mut() {
+                    onboarding.progress = Some(progress);
+                }
+            })
+            .finalize(|result| {
+                let error = match result {
+                    Ok(_) => {
+                        // Listing the models again updates the missing ones
+                        action = Some(AppAction::ReloadOllamaModels);
+                        None
+                    }
+                    Err(Compact::Suppose(e) | Compact::Panicked(e)) => Some(e),
+                };
+
+                if let Some(missing) = self.view.get_missing_models_modal_data_mut() {
+                    missing.pulling = None;
+                    missing.progress = None;
+                    missing.pull_error = error.clone();
+                }
+
+                if let Some(onboarding) = self.view.get_model_onboarding_modal_data_mut() {
+                    onboarding.pulling = None;
+                    onboarding.progress = None;
+                    onboarding.pull_error = error;
+                }
+            });
+
+        action
+    }
+
+    /// Replaces the missing models with the picked ones in the selection and the prompt settings
+    fn remap_missing_models(&mut self, missing: &MissingModels) {
+        for model in missing
+            .models
+            .iter()
+            .filter(|model| !model.replacement.is_empty())
+        {
+            if model.selected
+                && let Some(replacement) = self
+                    .ollama_models
+                    .available
+                    .iter()
+                    .find(|available| available.name == model.replacement)
+            {
+                self.ollama_models.selected = Some(replacement.clone());
+            }
+
+            for idx in &model.prompts {
+                if let Some(prompt) = self.prompts.get_mut(*idx) {
+                    prompt.remap_model(&model.name, &model.replacement);
+                }
+            }
+        }
+    }
+
+    /// Loads the selected model in the background unless it is still loaded from a recent request
+    fn warm_up_selected_model(&self) {
+        let Some(model) = self.ollama_models.selected.clone() else {
+            return;
+        };
+
+        if self.ollama_models.is_warm(&model.name) || self.ollama_models.warm_up_flower.is_active()
+        {
+            return;
+        }
+
+        let handle = self.ollama_models.warm_up_flower.handle();
+        let ollama_client = self.ollama_client.clone();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match ollama_client.warm_up(&model).await {
+                Ok(()) => handle.success(model.name),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Asks Ollama for the context window of the selected model, once per model
+    fn fetch_context_window(&self) {
+        let Some(model) = self.ollama_models.selected.clone() else {
+            return;
+        };
+
+        if self.ollama_models.context_windows.contains_key(&model.name)
+            || self.ollama_models.context_window_flower.is_active()
+        {
+            return;
+        }
+
+        let handle = self.ollama_models.context_window_flower.handle();
+        let ollama_client = self.ollama_client.clone();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match ollama_client.context_window(&model).await {
+                Ok(context_window) => handle.success((model.name, context_window)),
+                Err(e) => handle.error((model.name, e.to_string())),
+            }
+        });
+    }
+
+    /// Reads the shared prompts when the source changed or once the refresh interval passed
+    fn refresh_shared_prompts(&mut self, ctx: &egui::Context) {
+        let source = SharedSource::parse(&self.settings.shared_source);
+
+        if source != self.shared.source {
+            self.shared = SharedPrompts {
+                source: source.clone(),
+                ..Default::default()
+            };
+        }
+
+        let Some(source) = source else {
+            return;
+        };
+        let interval = shared::refresh_interval(self.settings.shared_refresh_mins);
+
+        ctx.request_repaint_after(interval);
+
+        if self.shared.flower.is_active()
+            || self
+                .shared
+                .fetched_at
+                .is_some_and(|fetched_at| fetched_at.elapsed() < interval)
+        {
+            return;
+        }
+
+        self.shared.fetched_at = Some(Instant::now());
+
+        let client = match http::client(&self.settings, &EndpointTls::default(), &[]) {
+            Ok(client) => client,
+            Err(e) => {
+                self.shared.error = Some(e.to_string());
+                return;
+            }
+        };
+        let handle = self.shared.flower.handle();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match source.fetch(&client).await {
+                Ok(cards) => handle.success(cards),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Loads the dictionary of the settings once it changed, the previous one checking the input
+    /// until then
+    fn load_spell_checker(&mut self) {
+        let dictionary = self.settings.spell_check_dictionary.trim().to_string();
+
+        if !self.settings.spell_check
+            || self.spell_check.loaded_for.as_ref() == Some(&dictionary)
+            || self.spell_check.flower.is_active()
+        {
+            return;
+        }
+
+        self.spell_check.loaded_for = Some(dictionary.clone());
+
+        let handle = self.spell_check.flower.handle();
+
+        self.tokio_runtime.spawn_blocking(move || {
+            handle.activate();
+
+            let path = if dictionary.is_empty() {
+                SpellChecker::find_dictionary()
+            } else {
+                Some(PathBuf::from(dictionary))
+            };
+            let loaded = path
+                .ok_or_else(|| anyhow::anyhow!("no Hunspell dictionary is installed"))
+                .and_then(|path| SpellChecker::load(&path));
+
+            match loaded {
+                Ok(checker) => handle.success(checker),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    fn poll_spell_check_flower(&mut self) {
+        self.spell_check
+            .flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok(checker) => self.spell_check.checker = Some(checker),
+                // The input is left unchecked rather than interrupting with a dialog
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    log::warn!("Failed to load the spell check dictionary: {e}");
+                    self.spell_check.checker = None;
+                }
+            });
+    }
+
+    fn sync_backend(&self) -> anyhow::Result<(SyncBackend, reqwest::Client)> {
+        let backend = SyncBackend::parse(&self.settings.sync_target).ok_or_else(|| {
+            anyhow::anyhow!("Set a folder or a WebDAV URL to sync with in the settings.")
+        })?;
+        let client = http::client(
+            &self.settings,
+            &EndpointTls::default(),
+            &self.settings.sync_headers,
+        )?;
+
+        Ok((backend, client))
+    }
+
+    /// Fetches the synced library, merged into the local one once it arrives
+    fn start_sync(&mut self) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.sync.flower.is_active(), "A sync is already running.");
+
+        let (backend, client) = self.sync_backend()?;
+        let handle = self.sync.flower.handle();
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match backend.fetch(&client).await {
+                Ok(remote) => handle.success(SyncStep::Fetched(remote)),
+                Err(e) => handle.error(format!("Failed to fetch the synced library: {e}")),
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Merges the fetched library, asking which version to keep of the prompts changed on both
+    /// machines before pushing the result
+    fn merge_synced(&mut self, remote: RemoteLibrary) -> anyhow::Result<Option<AppAction>> {
+        anyhow::ensure!(
+            !self
+                .prompts
+                .iter()
+                .any(|prompt| prompt.state.is_generating()),
+            "Wait for the responses to finish generating before syncing."
+        );
+
+        let selected = match self.view.main_panel {
+            ViewMainPanel::Prompt(idx) => {
+                self.prompts.get(idx).map(|prompt| prompt.sync_id.clone())
+            }
+            _ => None,
+        };
+        let merge = sync::merge(&mut self.prompts, remote, &self.sync.removed);
+
+        // Prompts removed by the sync shift the indices
+        match selected.and_then(|sync_id| {
+            self.prompts
+                .iter()
+                .position(|prompt| prompt.sync_id == sync_id)
+        }) {
+            Some(idx) => self.view.select_prompt(idx),
+            None if matches!(self.view.main_panel, ViewMainPanel::Prompt(_)) => {
+                self.view.main_panel = ViewMainPanel::Welcome;
+            }
+            None => {}
+        }
+
+        if merge.conflicts.is_empty() {
+            self.push_sync(merge.version)?;
+
+            return Ok(None);
+        }
+
+        self.sync.pending = Some(merge);
+
+        Ok(Some(AppAction::OpenSyncConflictsDialog))
+    }
+
+    fn push_sync(&mut self, version: Option<String>) -> anyhow::Result<()> {
+        let (backend, client) = self.sync_backend()?;
+
+        // The synced library holds the whole history
+        self.load_all_history()?;
+
+        let library = PromptLibrary::new(&self.prompts, true)?;
+        let handle = self.sync.flower.handle();
+
+        self.sync.pushed = sync::fingerprints(&self.prompts);
+
+        self.tokio_runtime.spawn(async move {
+            handle.activate();
+
+            match backend.push(&client, &library, version).await {
+                Ok(()) => handle.success(SyncStep::Pushed),
+                Err(e) => handle.error(format!("Failed to push the synced library: {e}")),
+            }
+        });
 
-                        add_prompt_modal.close();
-                        self.view.close_modal();
-                        self.view.select_prompt(new_prompt_idx);
-                    }
-                }
-                AppAction::OpenRemovePromptDialog(idx) => {
-                    remove_prompt_modal.open();
-                    self.view.open_remove_prompt_modal(idx);
-                }
-                AppAction::RemovePrompt(idx) => {
-                    self.view.close_modal();
-                    self.remove_prompt(idx);
-                }
-                AppAction::OpenEditPromptDialog(idx) => {
-                    if let Some(prompt) = self.prompts.get(idx) {
-                        edit_prompt_modal.open();
-                        self.view.open_edit_prompt_modal(idx, prompt);
-                    }
-                }
-                AppAction::OpenRemovePromptHistoryDialog { idx, history_idx } => {
-                    remove_prompt_history_modal.open();
-                    self.view.open_remove_prompt_history_modal(idx, history_idx);
-                }
-                AppAction::RemovePromptHistory { idx, history_idx } => {
-                    self.view.close_modal();
+        Ok(())
+    }
 
-                    if let Some(prompt) = self.get_prompt_mut(idx) {
-                        prompt.remove_history(history_idx);
-                    }
-                }
-                AppAction::EditPrompt => {
-                    if let Some((idx, title, content)) = self.view.get_edit_prompt_modal_data() {
-                        self.edit_prompt(idx, title.clone(), content.clone());
+    fn poll_sync_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+        let mut fetched = None;
 
-                        edit_prompt_modal.close();
-                        self.view.close_modal();
-                        self.view.select_prompt(idx);
-                    }
-                }
-                AppAction::SelectPrompt(idx) => {
-                    self.view.select_prompt(idx);
-                }
-                AppAction::SelectOllamaModel(local_model) => {
-                    self.ollama_models.selected = Some(local_model);
-                }
-                AppAction::SetUIScale(scale) => {
-                    let clamped_scale = scale.clamp(1.0, 2.5);
+        self.sync
+            .flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok(SyncStep::Fetched(remote)) => fetched = Some(remote),
+                Ok(SyncStep::Pushed) => {
+                    let pushed = std::mem::take(&mut self.sync.pushed);
 
-                    self.set_scale(ctx, clamped_scale);
+                    sync::mark_synced(&mut self.prompts, &pushed);
+                    self.sync.removed.clear();
+                    self.sync.last_synced_at = Some(Local::now());
                 }
-                AppAction::ShowErrorDialog { title, message } => {
-                    error_modal.open();
-                    self.view.open_error_modal(title, message);
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Sync Error".to_string(),
+                        message: e,
+                    });
                 }
-                AppAction::ReloadOllamaModels => {
-                    self.load_local_models();
+            });
+
+        if let Some(remote) = fetched {
+            match self.merge_synced(remote) {
+                Ok(merge_action) => assign_if_some!(action, merge_action),
+                Err(e) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Sync Error".to_string(),
+                        message: e.to_string(),
+                    });
                 }
             }
         }
+
+        action
     }
 
-    pub fn load_local_models(&self) {
-        let handle = self.ollama_models.load_flower.handle();
-        let ollama_client = self.ollama_client.clone();
-        let current_selected = self.ollama_models.selected.clone();
+    fn poll_shared_flower(&mut self) {
+        self.shared
+            .flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok(cards) => {
+                    self.shared.cards = cards;
+                    self.shared.error = None;
+                }
+                // Refreshed in the background, so the error is shown in the list rather than a dialog
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    log::warn!("Failed to read the shared prompts: {e}");
+                    self.shared.error = Some(e);
+                }
+            });
+    }
 
-        self.tokio_runtime.spawn(async move {
-            handle.activate();
+    fn poll_warm_up_flower(&mut self) {
+        let mut warmed_up = None;
 
-            match ollama_client.list_models().await {
-                Ok(response) => {
-                    let maybe_selected =
-                        response.first().and_then(|default| match current_selected {
-                            Some(selected)
-                                if !response
-                                    .iter()
-                                    .map(|m| &m.name)
-                                    .cloned()
-                                    .collect::<Vec<String>>()
-                                    .contains(&selected.name) =>
-                            {
-                                Some(default.clone())
-                            }
-                            None => Some(default.clone()),
-                            _ => None,
-                        });
+        self.ollama_models
+            .warm_up_flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok(name) => warmed_up = Some(name),
+                // The model is loaded by the next request anyway
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    log::warn!("Failed to warm up the model: {e}")
+                }
+            });
 
-                    handle.success((response, maybe_selected))
+        if let Some(name) = warmed_up {
+            self.ollama_models.mark_used(&name);
+        }
+    }
+
+    fn poll_context_window_flower(&mut self) {
+        let context_windows = &mut self.ollama_models.context_windows;
+
+        self.ollama_models
+            .context_window_flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok((name, context_window)) => {
+                    context_windows.insert(name, context_window);
                 }
-                Err(e) => handle.error(e),
-            }
-        });
+                // The input is counted without a limit rather than asking again every frame
+                Err(Compact::Suppose((name, e))) => {
+                    log::warn!("Failed to read the context window of {name}: {e}");
+                    context_windows.insert(name, None);
+                }
+                Err(Compact::Panicked(e)) => {
+                    log::warn!("Failed to read the context window: {e}");
+                }
+            });
     }
 
     fn poll_load_flower(&mut self) -> Option<AppAction> {
         let mut action = None;
+        let mut loaded = false;
+        let previous_selected = self.ollama_models.selected.clone();
 
         self.ollama_models
             .load_flower
@@ -412,7 +3235,12 @@ impl App {
             })
             .finalize(|result| match result {
                 Ok((models, maybe_selected)) => {
+                    loaded = true;
                     self.ollama_models.available = models;
+                    // A pulled model may replace one of the same name
+                    self.ollama_models.context_windows.clear();
+                    // Only the startup selection is overridden, reloads keep the user's choice
+                    self.env_overrides.default_model = None;
                     if let Some(selected) = maybe_selected {
                         self.ollama_models.selected = Some(selected);
                     }
@@ -431,9 +3259,34 @@ impl App {
                 }
             });
 
+        if loaded {
+            let missing = model_check::find(
+                previous_selected.as_ref(),
+                &self.prompts,
+                &self.ollama_models.available,
+                self.ollama_models.selected.as_ref(),
+            );
+            let names: Vec<String> = missing.names().into_iter().map(str::to_string).collect();
+
+            // An open dialog is refreshed, e.g. once a model was pulled
+            if names != self.ollama_models.reported_missing
+                || self.view.get_missing_models_modal_data().is_some()
+            {
+                self.ollama_models.reported_missing = names;
+                action = Some(AppAction::ShowMissingModels(missing));
+            } else if self.ollama_models.available.is_empty()
+                && !self.ollama_models.onboarding_offered
+                // Only Ollama pulls the recommended models
+                && self.settings.backend == BackendKind::Ollama
+            {
+                action = Some(AppAction::OpenModelOnboarding);
+            }
+        }
+
         action
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn show(
         &mut self,
         ctx: &egui::Context,
@@ -442,6 +3295,16 @@ impl App {
         edit_prompt_modal: &Modal,
         remove_prompt_history_modal: &Modal,
         error_modal: &Modal,
+        settings_modal: &Modal,
+        quick_open_modal: &Modal,
+        maintenance_modal: &Modal,
+        import_modal: &Modal,
+        recovery_modal: &Modal,
+        apply_patch_modal: &Modal,
+        run_snippet_modal: &Modal,
+        models_modal: &Modal,
+        export_modal: &Modal,
+        input_search_modal: &Modal,
     ) -> Option<AppAction> {
         let mut action = None;
 
@@ -453,9 +3316,22 @@ impl App {
                 remove_prompt_modal,
                 edit_prompt_modal,
                 error_modal,
+                settings_modal,
+                quick_open_modal,
+                maintenance_modal,
+                import_modal,
+                recovery_modal,
+                apply_patch_modal,
+                run_snippet_modal,
+                models_modal,
+                export_modal,
+                input_search_modal,
             )
         );
 
+        assign_if_some!(action, self.show_status_bar(ctx));
+        assign_if_some!(action, self.dispatch_queue());
+
         assign_if_some!(
             action,
             self.show_main_panel(ctx, remove_prompt_history_modal)
@@ -465,9 +3341,202 @@ impl App {
             assign_if_some!(action, self.poll_load_flower());
         }
 
+        if self.ollama_models.pull_flower.is_active() {
+            assign_if_some!(action, self.poll_pull_flower());
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if self.ollama_models.warm_up_flower.is_active() {
+            self.poll_warm_up_flower();
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if self.ollama_models.context_window_flower.is_active() {
+            self.poll_context_window_flower();
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if self.shared.flower.is_active() {
+            self.poll_shared_flower();
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if self.sync.flower.is_active() {
+            assign_if_some!(action, self.poll_sync_flower());
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        if self.spell_check.flower.is_active() {
+            self.poll_spell_check_flower();
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        action
+    }
+
+    /// The prompts with queued generations, in the order they start
+    fn queued_jobs(&self) -> Vec<QueuedJobs> {
+        let mut jobs: Vec<QueuedJobs> = self
+            .prompts
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, prompt)| {
+                Some(QueuedJobs {
+                    idx,
+                    priority: prompt.queued_priority()?,
+                    count: prompt.queued_count(),
+                })
+            })
+            .collect();
+
+        queue::schedule(&mut jobs);
+
+        jobs
+    }
+
+    /// Starts the next queued generation of the top priority, lower priorities waiting for it
+    fn dispatch_queue(&mut self) -> Option<AppAction> {
+        if self.queue_paused {
+            return None;
+        }
+
+        let jobs = self.queued_jobs();
+        let top_priority = queue::top_priority(&jobs)?;
+
+        jobs.iter()
+            .take_while(|job| job.priority == top_priority)
+            .find_map(|job| {
+                let (input, model) = self.prompts.get_mut(job.idx)?.take_queued_input()?;
+
+                Some(AppAction::GeneratePromptResponse {
+                    idx: job.idx,
+                    input,
+                    model,
+                })
+            })
+    }
+
+    /// Whether the queued generations of a prompt have to wait, for the queue to be resumed or for
+    /// the ones of a higher priority
+    fn is_queue_held(&self, idx: usize) -> bool {
+        self.queue_paused
+            || self.prompts.get(idx).and_then(Prompt::queued_priority)
+                < queue::top_priority(&self.queued_jobs())
+    }
+
+    /// Shows the running and queued generations, with controls to pause the queue and cancel all
+    fn show_status_bar(&self, ctx: &egui::Context) -> Option<AppAction> {
+        let mut action = None;
+
+        let generating = self
+            .prompts
+            .iter()
+            .filter(|prompt| prompt.state.is_generating())
+            .count();
+        let jobs = self.queued_jobs();
+        let queued: usize = jobs.iter().map(|job| job.count).sum();
+
+        if generating == 0 && queued == 0 && !self.queue_paused {
+            return None;
+        }
+
+        if queued > 0 {
+            // Queued generations of the prompts that are not shown start on the next frames
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if generating > 0 {
+                    ui.spinner();
+                }
+
+                ui.label(format!("{generating} generating"));
+
+                ui.add_enabled_ui(!jobs.is_empty(), |ui| {
+                    icons::menu_button(
+                        ui,
+                        IconButton::new(Icon::Menu)
+                            .text(format!("{queued} queued"))
+                            .frame(true),
+                        |ui| {
+                            egui::Grid::new("queue_panel").striped(true).show(ui, |ui| {
+                                for job in &jobs {
+                                    ui.label(&self.prompts[job.idx].title);
+                                    ui.label(format!("{} generations", job.count));
+
+                                    egui::ComboBox::from_id_salt(("queue_priority", job.idx))
+                                        .selected_text(job.priority.label())
+                                        .show_ui(ui, |ui| {
+                                            for priority in Priority::ALL {
+                                                if ui
+                                                    .selectable_label(
+                                                        job.priority == priority,
+                                                        priority.label(),
+                                                    )
+                                                    .clicked()
+                                                {
+                                                    action = Some(AppAction::SetQueuePriority {
+                                                        idx: job.idx,
+                                                        priority,
+                                                    });
+                                                }
+                                            }
+                                        });
+                                    ui.end_row();
+                                }
+                            });
+                        },
+                    )
+                    .response
+                    .on_hover_text("Queued generations in the order they start, by priority");
+                });
+
+                if self.queue_paused {
+                    ui.label(RichText::new("Queue paused").color(ui.style().visuals.warn_fg_color));
+                }
+
+                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .add_enabled(
+                            generating > 0 || queued > 0,
+                            IconButton::new(Icon::Stop).text("Cancel all").frame(true),
+                        )
+                        .on_hover_text("Stop all running generations and drop the queued ones")
+                        .clicked()
+                    {
+                        action = Some(AppAction::CancelAllGenerations);
+                    }
+
+                    let (icon, label, hint) = if self.queue_paused {
+                        (
+                            Icon::Play,
+                            "Resume queue",
+                            "Start the queued generations again",
+                        )
+                    } else {
+                        (
+                            Icon::Pause,
+                            "Pause queue",
+                            "Don't start queued generations, the running ones finish",
+                        )
+                    };
+
+                    if ui
+                        .add(IconButton::new(icon).text(label).frame(true))
+                        .on_hover_text(hint)
+                        .clicked()
+                    {
+                        action = Some(AppAction::SetQueuePaused(!self.queue_paused));
+                    }
+                });
+            });
+        });
+
         action
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn show_left_panel(
         &mut self,
         ctx: &egui::Context,
@@ -475,14 +3544,29 @@ impl App {
         remove_prompt_modal: &Modal,
         edit_prompt_modal: &Modal,
         error_modal: &Modal,
+        settings_modal: &Modal,
+        quick_open_modal: &Modal,
+        maintenance_modal: &Modal,
+        import_modal: &Modal,
+        recovery_modal: &Modal,
+        apply_patch_modal: &Modal,
+        run_snippet_modal: &Modal,
+        models_modal: &Modal,
+        export_modal: &Modal,
+        input_search_modal: &Modal,
     ) -> Option<AppAction> {
         let (max_width, min_width) = Self::get_left_panel_width(ctx);
         let mut action = None;
 
-        egui::SidePanel::left("left_panel_prompts")
+        let panel = egui::SidePanel::left("left_panel_prompts")
             .resizable(true)
             .max_width(max_width)
             .min_width(min_width)
+            .default_width(
+                self.window
+                    .left_panel_width
+                    .unwrap_or(window::DEFAULT_LEFT_PANEL_WIDTH),
+            )
             .show(ctx, |ui| {
                 ui.add_space(20.0);
 
@@ -492,16 +3576,39 @@ impl App {
                     assign_if_some!(action, self.show_left_panel_model_selector(ui));
 
                     self.show_left_panel_sort_mode_selector(ui);
+
+                    self.show_left_panel_generating_filter(ui);
+                });
+
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    self.show_left_panel_tag_filter(ui);
+
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.prompt_search)
+                            .id(egui::Id::new(PROMPT_SEARCH_ID))
+                            .hint_text("🔍 Search prompts")
+                            .desired_width(f32::INFINITY),
+                    );
                 });
 
+                assign_if_some!(action, self.show_left_panel_shared_prompts(ui));
+
                 ui.add_space(6.0);
 
                 ui.separator();
 
-                assign_if_some!(
-                    action,
-                    self.show_left_panel_prompts(ui, remove_prompt_modal, edit_prompt_modal)
-                );
+                assign_if_some!(action, self.handle_left_panel_type_ahead(ui));
+
+                ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                    assign_if_some!(action, self.show_left_panel_quick_jump(ui));
+
+                    assign_if_some!(
+                        action,
+                        self.show_left_panel_prompts(ui, remove_prompt_modal, edit_prompt_modal)
+                    );
+                });
 
                 ui.with_layout(Layout::bottom_up(egui::Align::Min), |ui| {
                     ui.add_space(12.0);
@@ -511,6 +3618,85 @@ impl App {
 
                         ui.add_space(12.0);
 
+                        if ui
+                            .add(IconButton::new(Icon::Settings))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Settings")
+                            .clicked()
+                        {
+                            action = Some(AppAction::OpenSettingsDialog);
+                        }
+
+                        ui.add_space(12.0);
+
+                        if ui
+                            .add(IconButton::new(Icon::Maintenance))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Library maintenance")
+                            .clicked()
+                        {
+                            action = Some(AppAction::OpenMaintenanceDialog);
+                        }
+
+                        ui.add_space(12.0);
+
+                        if ui
+                            .add(IconButton::new(Icon::Clock))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Timeline of the responses of all prompts")
+                            .clicked()
+                        {
+                            action = Some(AppAction::ShowTimeline);
+                        }
+
+                        ui.add_space(12.0);
+
+                        if ui
+                            .add(IconButton::new(Icon::Cut))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Passages clipped from the responses")
+                            .clicked()
+                        {
+                            action = Some(AppAction::ShowClippings);
+                        }
+
+                        ui.add_space(12.0);
+
+                        if ui
+                            .add(IconButton::new(Icon::Import))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text(
+                                "Import a prompt card, or a script as a prompt with its history",
+                            )
+                            .clicked()
+                        {
+                            action = Some(AppAction::ImportScript);
+                        }
+
+                        ui.add_space(12.0);
+
+                        icons::menu_button(ui, IconButton::new(Icon::Library), |ui| {
+                            if ui.button("Export prompts…").clicked() {
+                                ui.close_menu();
+                                action = Some(AppAction::OpenExportPromptsDialog);
+                            }
+
+                            if ui.button("Import prompts…").clicked() {
+                                ui.close_menu();
+                                action = Some(AppAction::ImportPrompts);
+                            }
+                        })
+                        .response
+                        .on_hover_text("Export or import all prompts");
+
+                        if !self.settings.sync_target.trim().is_empty() {
+                            ui.add_space(12.0);
+
+                            assign_if_some!(action, self.show_left_panel_sync_button(ui));
+                        }
+
+                        ui.add_space(12.0);
+
                         // UI Scale control
                         ui.horizontal(|ui| {
                             let mut scale = self.ui_scale;
@@ -531,28 +3717,138 @@ impl App {
                         });
                     });
 
-                    ui.add_space(12.0);
+                    ui.add_space(12.0);
+
+                    // Version label (slightly smaller than default)
+                    ui.label(egui::RichText::new(format!("v{VERSION}")).size(12.0));
+                });
+
+                if add_prompt_modal.was_outside_clicked()
+                    || error_modal.was_outside_clicked()
+                    || settings_modal.was_outside_clicked()
+                    || quick_open_modal.was_outside_clicked()
+                    || maintenance_modal.was_outside_clicked()
+                    || import_modal.was_outside_clicked()
+                    || apply_patch_modal.was_outside_clicked()
+                    || run_snippet_modal.was_outside_clicked()
+                    || models_modal.was_outside_clicked()
+                    || export_modal.was_outside_clicked()
+                    || input_search_modal.was_outside_clicked()
+                {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                add_prompt_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_add_prompt_modal(ui, add_prompt_modal, &self.groups)
+                    );
+                });
+
+                error_modal.show(|ui| {
+                    assign_if_some!(action, self.view.show_error_modal(ui, error_modal));
+                });
+
+                settings_modal.show(|ui| {
+                    assign_if_some!(action, self.view.show_settings_modal(ui, settings_modal));
+                });
+
+                import_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_import_script_modal(ui, import_modal, &self.prompts)
+                    );
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_import_prompts_modal(ui, import_modal, &self.prompts)
+                    );
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_sync_conflicts_modal(ui, import_modal, &self.prompts)
+                    );
+                });
+
+                export_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_export_prompts_modal(ui, export_modal, &self.prompts)
+                    );
+                });
+
+                recovery_modal.show(|ui| {
+                    assign_if_some!(action, self.view.show_recovery_modal(ui, recovery_modal));
+                });
 
-                    // Version label (slightly smaller than default)
-                    ui.label(egui::RichText::new(format!("v{VERSION}")).size(12.0));
+                run_snippet_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view.show_run_snippet_modal(ui, run_snippet_modal)
+                    );
                 });
 
-                if add_prompt_modal.was_outside_clicked() || error_modal.was_outside_clicked() {
-                    action = Some(AppAction::CloseDialog);
-                }
+                models_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view.show_missing_models_modal(
+                            ui,
+                            models_modal,
+                            &self.prompts,
+                            &self.ollama_models.available,
+                        )
+                    );
+                    assign_if_some!(
+                        action,
+                        self.view.show_model_onboarding_modal(
+                            ui,
+                            models_modal,
+                            &self.ollama_models.available,
+                        )
+                    );
+                });
 
-                add_prompt_modal.show(|ui| {
+                apply_patch_modal.show(|ui| {
                     assign_if_some!(
                         action,
-                        self.view.show_add_prompt_modal(ui, add_prompt_modal)
+                        self.view.show_apply_patch_modal(ui, apply_patch_modal)
                     );
                 });
 
-                error_modal.show(|ui| {
-                    assign_if_some!(action, self.view.show_error_modal(ui, error_modal));
+                maintenance_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_maintenance_modal(ui, maintenance_modal, &self.prompts)
+                    );
+                });
+
+                quick_open_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view.show_quick_open_modal(
+                            ui,
+                            quick_open_modal,
+                            &self.prompts,
+                            &self.ollama_models.available,
+                        )
+                    );
+                });
+
+                input_search_modal.show(|ui| {
+                    assign_if_some!(
+                        action,
+                        self.view
+                            .show_input_search_modal(ui, input_search_modal, &self.prompts)
+                    );
                 });
             });
 
+        self.window.left_panel_width = Some(panel.response.rect.width());
+
         action
     }
 
@@ -560,7 +3856,7 @@ impl App {
         let mut action = None;
 
         if ui
-            .add(egui::Button::new("➕ Add Prompt").stroke(Stroke::NONE))
+            .add(IconButton::new(Icon::Add).text("Add Prompt").frame(true))
             .on_hover_cursor(egui::CursorIcon::PointingHand)
             .on_hover_text("Create new prompt")
             .clicked()
@@ -586,16 +3882,21 @@ impl App {
                             }
                         }
                     });
+
+                if self.ollama_models.warm_up_flower.is_active() {
+                    ui.spinner().on_hover_text("Warming up");
+                } else if self.ollama_models.is_warm(&selected.name) {
+                    ui.label("🔥")
+                        .on_hover_text("Warm: the model was loaded recently");
+                } else {
+                    ui.label(egui::RichText::new("❄").weak())
+                        .on_hover_text("Cold: the first request loads the model");
+                }
             }
 
             // Add reload button next to the model selector
             if ui
-                .add(
-                    egui::Button::new("🔄")
-                        .fill(Color32::TRANSPARENT)
-                        .small()
-                        .stroke(Stroke::NONE),
-                )
+                .add(IconButton::new(Icon::Reload))
                 .on_hover_cursor(egui::CursorIcon::PointingHand)
                 .on_hover_text("Reload models")
                 .clicked()
@@ -612,71 +3913,236 @@ impl App {
             ui.label("Sort by:");
 
             egui::ComboBox::from_id_salt("sort_mode_selector")
-                .selected_text(match self.sort_mode {
-                    SortMode::InsertionOrder => "insertion",
-                    SortMode::HistoryCount => "history count",
-                    SortMode::LastUsage => "last usage",
-                })
+                .selected_text(self.sort_mode.label())
                 .show_ui(ui, |ui| {
-                    if ui
-                        .selectable_label(
-                            matches!(self.sort_mode, SortMode::InsertionOrder),
-                            "insertion (default)",
-                        )
-                        .clicked()
-                    {
-                        self.sort_mode = SortMode::InsertionOrder;
-                    }
-                    if ui
-                        .selectable_label(
-                            matches!(self.sort_mode, SortMode::HistoryCount),
-                            "history count",
-                        )
-                        .clicked()
-                    {
-                        self.sort_mode = SortMode::HistoryCount;
+                    for mode in SortMode::ALL {
+                        ui.selectable_value(&mut self.sort_mode, mode, mode.label());
                     }
-                    if ui
-                        .selectable_label(
-                            matches!(self.sort_mode, SortMode::LastUsage),
-                            "last usage",
-                        )
-                        .clicked()
-                    {
-                        self.sort_mode = SortMode::LastUsage;
+                });
+        });
+    }
+
+    /// Shows how many prompts are generating, clicking it lists only them
+    fn show_left_panel_generating_filter(&mut self, ui: &mut egui::Ui) {
+        let generating = self
+            .prompts
+            .iter()
+            .filter(|prompt| prompt.state.is_generating())
+            .count();
+
+        if generating == 0 && !self.generating_only {
+            return;
+        }
+
+        if ui
+            .add(
+                IconButton::new(Icon::Reload)
+                    .text(generating.to_string())
+                    .selected(self.generating_only),
+            )
+            .on_hover_text(if self.generating_only {
+                "Show all prompts"
+            } else {
+                "Only show the prompts that are generating"
+            })
+            .clicked()
+        {
+            self.generating_only = !self.generating_only;
+        }
+    }
+
+    /// Lists only the prompts with the picked tag, shown once a prompt is tagged
+    fn show_left_panel_tag_filter(&mut self, ui: &mut egui::Ui) {
+        let tags: BTreeSet<&String> = self
+            .prompts
+            .iter()
+            .filter(|prompt| !prompt.archived)
+            .flat_map(|prompt| &prompt.tags)
+            .collect();
+
+        if tags.is_empty() && self.tag_filter.is_none() {
+            return;
+        }
+
+        let selected_text = match &self.tag_filter {
+            Some(tag) => format!("#{tag}"),
+            None => "All tags".to_string(),
+        };
+
+        egui::ComboBox::from_id_salt("tag_filter_selector")
+            .selected_text(selected_text)
+            .width(100.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.tag_filter, None, "All tags");
+
+                for tag in tags {
+                    ui.selectable_value(&mut self.tag_filter, Some(tag.clone()), format!("#{tag}"));
+                }
+            })
+            .response
+            .on_hover_text("Only list the prompts with a tag");
+    }
+
+    /// Selects the first prompt whose title starts with the text typed while hovering the list
+    fn handle_left_panel_type_ahead(&mut self, ui: &mut egui::Ui) -> Option<AppAction> {
+        let mut action = None;
+
+        let is_searching = ui.memory(|m| m.has_focus(egui::Id::new(PROMPT_SEARCH_ID)));
+
+        if !self.view.is_modal_shown() && ui.ui_contains_pointer() && !is_searching {
+            // Take the text events so that they don't end up in the focused prompt input
+            let typed = ui.input_mut(|i| {
+                let mut typed = String::new();
+
+                i.events.retain(|event| match event {
+                    egui::Event::Text(text) => {
+                        typed.push_str(text);
+                        false
                     }
+                    _ => true,
                 });
+
+                typed
+            });
+
+            if !typed.is_empty() {
+                let query = self.type_ahead.push(&typed).to_owned();
+
+                if let Some(idx) = self
+                    .selectable_prompt_indices()
+                    .into_iter()
+                    .find(|&idx| self.prompts[idx].title.to_lowercase().starts_with(&query))
+                {
+                    action = Some(AppAction::SelectPrompt(idx));
+                }
+            }
+        }
+
+        if self.type_ahead.is_active() {
+            ui.label(egui::RichText::new(format!("Jump to: {}", self.type_ahead.query)).weak());
+            ui.ctx()
+                .request_repaint_after(Duration::from_millis(TYPE_AHEAD_TIMEOUT_MS));
+        }
+
+        action
+    }
+
+    /// Shows a column of title initials that jump to the first prompt starting with each one
+    fn show_left_panel_quick_jump(&self, ui: &mut egui::Ui) -> Option<AppAction> {
+        let mut action = None;
+
+        if self.prompts.len() < QUICK_JUMP_MIN_PROMPTS {
+            return action;
+        }
+
+        let mut initials: Vec<(char, usize)> = Vec::new();
+
+        for idx in self.selectable_prompt_indices() {
+            if let Some(initial) = Self::prompt_initial(&self.prompts[idx].title)
+                && !initials.iter().any(|(c, _)| *c == initial)
+            {
+                initials.push((initial, idx));
+            }
+        }
+
+        initials.sort_by_key(|(initial, _)| *initial);
+
+        ui.vertical(|ui| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+
+            for (initial, idx) in initials {
+                if ui
+                    .add(
+                        egui::Button::new(egui::RichText::new(initial).small())
+                            .fill(Color32::TRANSPARENT)
+                            .small()
+                            .stroke(Stroke::NONE),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                {
+                    action = Some(AppAction::SelectPrompt(idx));
+                }
+            }
         });
+
+        action
+    }
+
+    /// Titles are indexed by their first letter, titles starting with a digit are grouped under `#`
+    fn prompt_initial(title: &str) -> Option<char> {
+        title.chars().find(|c| c.is_alphanumeric()).map(|c| {
+            if c.is_numeric() {
+                '#'
+            } else {
+                c.to_uppercase().next().unwrap_or(c)
+            }
+        })
+    }
+
+    /// The listed prompts that can be selected, the disabled ones left out
+    fn selectable_prompt_indices(&self) -> Vec<usize> {
+        let mut prompt_indices = self.sort_prompt_indices();
+        prompt_indices.retain(|&idx| !self.prompts[idx].disabled);
+
+        prompt_indices
+    }
+
+    /// The prompt after or before the selected one in the list, wrapping around, or the first one
+    /// when none is selected
+    fn adjacent_prompt(&self, backwards: bool) -> Option<usize> {
+        let prompt_indices = self.selectable_prompt_indices();
+
+        let ViewMainPanel::Prompt(current_idx) = self.view.main_panel else {
+            return prompt_indices.first().copied();
+        };
+        let pos = prompt_indices.iter().position(|&idx| idx == current_idx)?;
+
+        let next_pos = if backwards {
+            pos.checked_sub(1).unwrap_or(prompt_indices.len() - 1)
+        } else {
+            (pos + 1) % prompt_indices.len()
+        };
+
+        prompt_indices.get(next_pos).copied()
     }
 
     /// Sorts prompt indices based on the current sort mode
     fn sort_prompt_indices(&self) -> Vec<usize> {
-        let mut prompt_indices = (0..self.prompts.len()).collect::<Vec<usize>>();
+        let mut prompt_indices = (0..self.prompts.len())
+            .filter(|&idx| !self.prompts[idx].archived)
+            .filter(|&idx| !self.generating_only || self.prompts[idx].state.is_generating())
+            .filter(|&idx| {
+                self.tag_filter
+                    .as_ref()
+                    .is_none_or(|tag| self.prompts[idx].tags.contains(tag))
+            })
+            .filter(|&idx| {
+                let prompt = &self.prompts[idx];
+
+                self.prompt_search.trim().is_empty()
+                    || content::contains_query(&prompt.title, &self.prompt_search)
+                    || content::contains_query(&prompt.content, &self.prompt_search)
+                    || prompt
+                        .tags
+                        .iter()
+                        .any(|tag| content::contains_query(tag, &self.prompt_search))
+            })
+            .collect::<Vec<usize>>();
 
         match self.sort_mode {
             SortMode::HistoryCount => {
-                prompt_indices.sort_by(|&a, &b| {
-                    // First sort by history count (descending)
-                    let count_a = self.prompts[a].history_count();
-                    let count_b = self.prompts[b].history_count();
-
-                    count_b.cmp(&count_a)
-                });
+                // Most replies first
+                prompt_indices
+                    .sort_by_key(|&idx| std::cmp::Reverse(self.prompts[idx].reply_count()));
             }
             SortMode::LastUsage => {
-                prompt_indices.sort_by(|&a, &b| {
-                    // Sort by last usage time (descending)
-                    let last_used_a = self.prompts[a].get_last_used_time();
-                    let last_used_b = self.prompts[b].get_last_used_time();
-
-                    // Handle cases where one or both might be None
-                    match (last_used_a, last_used_b) {
-                        (Some(time_a), Some(time_b)) => time_b.cmp(&time_a), // More recent first
-                        (Some(_), None) => std::cmp::Ordering::Less,         // a is more recent
-                        (None, Some(_)) => std::cmp::Ordering::Greater,      // b is more recent
-                        (None, None) => std::cmp::Ordering::Equal,           // both are equal
-                    }
-                });
+                // Most recently used first, the never used ones last
+                prompt_indices
+                    .sort_by_key(|&idx| std::cmp::Reverse(self.prompts[idx].last_used_at()));
+            }
+            SortMode::Alphabetical => {
+                prompt_indices.sort_by_cached_key(|&idx| self.prompts[idx].title.to_lowercase());
             }
             SortMode::InsertionOrder => {
                 // No sorting - maintain insertion order
@@ -686,6 +4152,206 @@ impl App {
         prompt_indices
     }
 
+    fn show_left_panel_sync_button(&self, ui: &mut egui::Ui) -> Option<AppAction> {
+        if self.sync.flower.is_active() {
+            ui.spinner().on_hover_text("Syncing");
+
+            return None;
+        }
+
+        let last_synced = match self.sync.last_synced_at {
+            Some(synced_at) => format!("last synced at {}", synced_at.format("%Y-%m-%d %H:%M")),
+            None => "never synced".to_string(),
+        };
+
+        ui.add(IconButton::new(Icon::Sync))
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(format!(
+                "Sync the prompts with other machines, {last_synced}"
+            ))
+            .clicked()
+            .then_some(AppAction::Sync)
+    }
+
+    /// The read-only prompts of the shared source, when one is set
+    fn show_left_panel_shared_prompts(&self, ui: &mut egui::Ui) -> Option<AppAction> {
+        let mut action = None;
+
+        self.shared.source.as_ref()?;
+
+        ui.add_space(6.0);
+
+        egui::CollapsingHeader::new(format!("Shared prompts ({})", self.shared.cards.len()))
+            .id_salt("shared_prompts")
+            .default_open(true)
+            .show(ui, |ui| {
+                if self.shared.flower.is_active() && self.shared.cards.is_empty() {
+                    ui.spinner();
+                }
+
+                if let Some(error) = &self.shared.error {
+                    ui.label(
+                        RichText::new("⚠ Failed to refresh")
+                            .small()
+                            .color(ui.visuals().warn_fg_color),
+                    )
+                    .on_hover_text(error);
+                }
+
+                ScrollArea::vertical()
+                    .id_salt("shared_prompts_scroll")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        for (idx, card) in self.shared.cards.iter().enumerate() {
+                            let selected = matches!(
+                                self.view.main_panel,
+                                ViewMainPanel::SharedPrompt(shown) if shown == idx
+                            );
+                            let text = if card.appearance.icon.is_empty() {
+                                card.title.clone()
+                            } else {
+                                format!("{} {}", card.appearance.icon, card.title)
+                            };
+
+                            if ui
+                                .selectable_label(selected, text)
+                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                .clicked()
+                            {
+                                action = Some(AppAction::SelectSharedPrompt(idx));
+                            }
+                        }
+                    });
+            });
+
+        action
+    }
+
+    /// Header of a group in the prompt list, collapsing it when clicked and dragged to reorder it
+    fn show_left_panel_group_header(&self, ui: &mut egui::Ui, idx: usize) -> Option<AppAction> {
+        let mut action = None;
+        let group = &self.groups[idx];
+        let count = self
+            .prompts
+            .iter()
+            .filter(|prompt| !prompt.archived && prompt.group == group.name)
+            .count();
+        let arrow = if group.collapsed {
+            Icon::Next
+        } else {
+            Icon::Expand
+        };
+
+        let header = ui
+            .dnd_drag_source(
+                egui::Id::new(("dragged_group", idx)),
+                PromptListRow::Group(idx),
+                |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(arrow);
+
+                        let header = ui.add(
+                            egui::Label::new(RichText::new(&group.name).strong())
+                                .truncate()
+                                .sense(egui::Sense::click()),
+                        );
+
+                        ui.label(RichText::new(count.to_string()).weak());
+
+                        header
+                    })
+                    .inner
+                },
+            )
+            .inner;
+
+        header.context_menu(|ui| {
+            if ui
+                .add_enabled(idx > 0, egui::Button::new("Move up"))
+                .clicked()
+            {
+                action = Some(AppAction::MoveGroup {
+                    from: idx,
+                    to: idx - 1,
+                });
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(idx + 1 < self.groups.len(), egui::Button::new("Move down"))
+                .clicked()
+            {
+                action = Some(AppAction::MoveGroup {
+                    from: idx,
+                    to: idx + 1,
+                });
+                ui.close_menu();
+            }
+
+            if ui
+                .button("Remove group")
+                .on_hover_text("Keep its prompts, listed without a group")
+                .clicked()
+            {
+                action = Some(AppAction::RemoveGroup(idx));
+                ui.close_menu();
+            }
+        });
+
+        if header
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text("Drop prompts here to group them, drag the header to reorder the groups")
+            .clicked()
+        {
+            action = Some(AppAction::ToggleGroupCollapsed(idx));
+        }
+
+        action
+    }
+
+    /// Highlights the row a prompt or a group header is dragged over, and tells what dropping it
+    /// there does: a prompt joins the group of the row, a group takes the place of the other one
+    fn drop_on_prompt_row(
+        &self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        target: PromptListRow,
+    ) -> Option<AppAction> {
+        let dragged = response.dnd_hover_payload::<PromptListRow>()?;
+
+        let action = match (*dragged, target) {
+            (PromptListRow::Prompt(idx), PromptListRow::Group(group_idx)) => {
+                AppAction::SetPromptGroup {
+                    idx,
+                    group: self.groups[group_idx].name.clone(),
+                }
+            }
+            (PromptListRow::Prompt(idx), PromptListRow::Prompt(other))
+                if self.prompts[idx].group != self.prompts[other].group =>
+            {
+                AppAction::SetPromptGroup {
+                    idx,
+                    group: self.prompts[other].group.clone(),
+                }
+            }
+            (PromptListRow::Group(from), PromptListRow::Group(to)) if from != to => {
+                AppAction::MoveGroup { from, to }
+            }
+            _ => return None,
+        };
+
+        ui.painter().rect_stroke(
+            response.rect,
+            6.0,
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+
+        response
+            .dnd_release_payload::<PromptListRow>()
+            .map(|_| action)
+    }
+
     fn show_left_panel_prompts(
         &mut self,
         ui: &mut egui::Ui,
@@ -694,38 +4360,105 @@ impl App {
     ) -> Option<AppAction> {
         let mut action = None;
 
-        ScrollArea::vertical().show(ui, |ui| {
-            // Sort prompts based on current sort mode
-            let prompt_indices = self.sort_prompt_indices();
+        group::add_missing(&mut self.groups, &self.prompts);
 
-            for &idx in &prompt_indices {
-                let prompt = &self.prompts[idx];
-                let selected = self.view.is_prompt_selected(idx);
+        // A prompt selected from elsewhere, e.g. the quick open, is shown in its collapsed group
+        if let Some(idx) = self.scroll_to_prompt
+            && let Some(prompt) = self.prompts.get(idx)
+            && let Some(group) = self
+                .groups
+                .iter_mut()
+                .find(|group| group.name == prompt.group)
+        {
+            group.collapsed = false;
+        }
 
-                ui.add_space(6.0);
+        // Sort prompts based on current sort mode
+        let prompt_indices = self.sort_prompt_indices();
+        let filtered = self.generating_only
+            || self.tag_filter.is_some()
+            || !self.prompt_search.trim().is_empty();
+        let rows = group::rows(&self.groups, &self.prompts, &prompt_indices, filtered);
+        let row_height = Prompt::left_panel_card_height(ui) + 6.0;
+        let mut scroll_area = ScrollArea::vertical();
+
+        // Only the visible rows are laid out, so jump directly to the offset of a row off screen
+        if let Some(pos) = self.scroll_to_prompt.and_then(|idx| {
+            rows.iter()
+                .position(|&row| row == PromptListRow::Prompt(idx))
+        }) && !self.visible_prompt_rows.contains(&pos)
+        {
+            scroll_area = scroll_area
+                .vertical_scroll_offset(pos as f32 * (row_height + ui.spacing().item_spacing.y));
+        }
+
+        // Keeps the spinners of the prompts generating in the background going
+        if prompt_indices
+            .iter()
+            .any(|&idx| self.prompts[idx].state.is_generating())
+        {
+            ui.ctx().request_repaint();
+        }
 
-                assign_if_some!(action, prompt.show_left_panel(ui, selected, idx));
+        scroll_area.show_rows(ui, row_height, rows.len(), |ui, visible| {
+            self.visible_prompt_rows = visible.clone();
 
-                if remove_prompt_modal.was_outside_clicked()
-                    || edit_prompt_modal.was_outside_clicked()
+            for &row in &rows[visible] {
+                let response = ui.allocate_ui_with_layout(
+                    egui::vec2(ui.available_width(), row_height),
+                    Layout::top_down(egui::Align::Min),
+                    |ui| {
+                        ui.set_min_height(row_height);
+                        ui.add_space(6.0);
+
+                        match row {
+                            PromptListRow::Group(group_idx) => {
+                                self.show_left_panel_group_header(ui, group_idx)
+                            }
+                            PromptListRow::Prompt(idx) => {
+                                let prompt = &self.prompts[idx];
+                                let selected = self.view.is_prompt_selected(idx);
+
+                                ui.dnd_drag_source(
+                                    egui::Id::new(("dragged_prompt", idx)),
+                                    row,
+                                    |ui| prompt.show_left_panel(ui, selected, idx),
+                                )
+                                .inner
+                            }
+                        }
+                    },
+                );
+
+                assign_if_some!(action, response.inner);
+                assign_if_some!(action, self.drop_on_prompt_row(ui, &response.response, row));
+
+                if let PromptListRow::Prompt(idx) = row
+                    && self.scroll_to_prompt == Some(idx)
                 {
-                    action = Some(AppAction::CloseDialog);
+                    response.response.scroll_to_me(None);
+                    self.scroll_to_prompt = None;
                 }
             }
+        });
 
-            remove_prompt_modal.show(|ui| {
-                assign_if_some!(
-                    action,
-                    self.view.show_remove_prompt_modal(ui, remove_prompt_modal)
-                );
-            });
+        if remove_prompt_modal.was_outside_clicked() || edit_prompt_modal.was_outside_clicked() {
+            action = Some(AppAction::CloseDialog);
+        }
 
-            edit_prompt_modal.show(|ui| {
-                assign_if_some!(
-                    action,
-                    self.view.show_edit_prompt_modal(ui, edit_prompt_modal)
-                )
-            });
+        remove_prompt_modal.show(|ui| {
+            assign_if_some!(
+                action,
+                self.view.show_remove_prompt_modal(ui, remove_prompt_modal)
+            );
+        });
+
+        edit_prompt_modal.show(|ui| {
+            assign_if_some!(
+                action,
+                self.view
+                    .show_edit_prompt_modal(ui, edit_prompt_modal, &self.groups)
+            )
         });
 
         action
@@ -737,12 +4470,27 @@ impl App {
         remove_prompt_history_modal: &Modal,
     ) -> Option<AppAction> {
         let mut action = None;
+        let hold_queue = match self.view.main_panel {
+            ViewMainPanel::Prompt(idx) => self.is_queue_held(idx),
+            _ => self.queue_paused,
+        };
 
         let Self {
             commonmark_cache,
             prompts,
+            settings,
+            ollama_models,
+            timeline,
+            shared,
+            clippings,
+            clippings_prompt,
+            spell_check,
             ..
         } = self;
+        let spell_checker = spell_check
+            .checker
+            .as_ref()
+            .filter(|_| settings.spell_check);
 
         egui::CentralPanel::default().show(ctx, |ui| match self.view.main_panel {
             ViewMainPanel::Welcome => {
@@ -750,15 +4498,46 @@ impl App {
 
                 ui.with_layout(Layout::top_down(egui::Align::Center), |ui| {
                     ui.label("Welcome to the Reprompt app! Please select a model and add prompts to get started.");
+                    ui.add_space(12.0);
+
+                    if ui
+                        .add(IconButton::new(Icon::Download).text("Recommended models").frame(true))
+                        .on_hover_text("Pull a model that suits the memory of this machine")
+                        .clicked()
+                    {
+                        action = Some(AppAction::OpenModelOnboarding);
+                    }
                 });
             }
+            ViewMainPanel::Timeline => {
+                assign_if_some!(action, timeline::show(ui, timeline, prompts));
+            }
+            ViewMainPanel::Clippings => {
+                assign_if_some!(action, clippings::show(ui, clippings, clippings_prompt, prompts));
+            }
+            ViewMainPanel::SharedPrompt(idx) => match shared.cards.get(idx) {
+                Some(card) => {
+                    assign_if_some!(action, View::show_shared_prompt(ui, card, idx));
+                }
+                None => {
+                    ui.add_space(40.0);
+                    ui.vertical_centered(|ui| {
+                        ui.label(RichText::new("The shared prompts are not loaded yet").weak());
+                    });
+                }
+            },
             ViewMainPanel::Prompt(idx) => {
                 if let Some(prompt) = prompts.get_mut(idx) {
                     assign_if_some!(action, prompt.show_main_panel(
                         ui,
                         self.view.is_modal_shown(),
+                        hold_queue,
                         idx,
                         commonmark_cache,
+                        settings,
+                        &ollama_models.available,
+                        ollama_models.selected_context_window(),
+                        spell_checker,
                     ));
 
                     if remove_prompt_history_modal.was_outside_clicked() {
@@ -770,7 +4549,12 @@ impl App {
                     });
 
 
-                    if prompt.state.is_generating() {
+                    if prompt.state.is_generating()
+                        || prompt.is_replaying()
+                        || prompt.is_speaking()
+                        || prompt.is_scoring()
+                        || prompt.is_running_snippet()
+                    {
                         ctx.request_repaint();
                     }
                 }
@@ -0,0 +1,107 @@
+use std::{future::Future, pin::Pin};
+
+use ollama_rs::{generation::chat::ChatMessage, models::LocalModel};
+use tokio_stream::Stream;
+
+use crate::{
+    compose::ComposedPrompt,
+    history::{ModelMetrics, StopReason},
+    ollama::PullProgress,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The pieces of an answer as they are generated
+pub type TextStream = Pin<Box<dyn Stream<Item = anyhow::Result<Chunk>> + Send>>;
+
+/// A piece of a streamed answer
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub text: String,
+    /// Sent with the last piece by the servers that report them
+    pub metrics: Option<ModelMetrics>,
+    /// Why the answer ended, sent with the last piece
+    pub stop_reason: Option<StopReason>,
+}
+
+impl Chunk {
+    pub fn text(text: String) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+/// Options of a generation, left to the server when unset
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerationOptions {
+    /// The same seed gives the same answer where supported
+    pub seed: Option<i32>,
+    /// Tokens of the answer at most
+    pub max_tokens: Option<u32>,
+}
+
+/// Kind of server the text models answer from, picked in the settings
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BackendKind {
+    #[default]
+    Ollama,
+    /// A server with the OpenAI chat completions API, e.g. LM Studio, vLLM, the llama.cpp server
+    /// or OpenAI itself
+    OpenAi,
+}
+
+impl BackendKind {
+    pub const ALL: [Self; 2] = [Self::Ollama, Self::OpenAi];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ollama => "Ollama",
+            Self::OpenAi => "OpenAI-compatible",
+        }
+    }
+}
+
+/// A server the text models answer from.
+///
+/// The generations stream their answer, the caller accumulating the pieces and cancelling the
+/// stream by dropping it.
+pub trait Backend: Send + Sync {
+    fn list_models(&self) -> BoxFuture<'_, anyhow::Result<Vec<LocalModel>>>;
+
+    /// Answers a single request
+    fn complete(
+        &self,
+        model: String,
+        prompt: ComposedPrompt,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>>;
+
+    /// Answers the last message of a conversation, the previous ones being its context
+    fn chat(
+        &self,
+        model: String,
+        messages: Vec<ChatMessage>,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>>;
+
+    /// Loads the model into memory, for servers that load them on demand
+    fn warm_up(&self, _model: String) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Tokens the model attends to, none when the server doesn't report them
+    fn context_window(&self, _model: String) -> BoxFuture<'_, anyhow::Result<Option<u64>>> {
+        Box::pin(async { Ok(None) })
+    }
+
+    /// Downloads the model, for servers with a model library
+    fn pull_model<'a>(
+        &'a self,
+        name: String,
+        _on_progress: &'a (dyn Fn(PullProgress) + Send + Sync),
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move { anyhow::bail!("{name} can't be pulled from this server") })
+    }
+}
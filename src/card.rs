@@ -0,0 +1,105 @@
+use std::{fs, path::Path};
+
+use crate::{
+    compose::{FewShotExample, PromptTemplate},
+    debate::DebateSettings,
+    ensemble::EnsembleSettings,
+    prompt::{Prompt, PromptAppearance, PromptKind},
+};
+
+/// File extension of exported prompt cards
+pub const EXTENSION: &str = "reprompt";
+
+const CARD_VERSION: u32 = 1;
+
+/// A single prompt with its settings, but without its history, to share as a self-contained file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PromptCard {
+    pub version: u32,
+    pub title: String,
+    pub content: String,
+    pub kind: PromptKind,
+    pub appearance: PromptAppearance,
+    pub template: PromptTemplate,
+    pub examples: Vec<FewShotExample>,
+    pub banned_phrases: Vec<String>,
+    pub regenerate_on_banned_phrases: bool,
+    pub critique_and_revise: bool,
+    pub debate: DebateSettings,
+    pub ensemble: EnsembleSettings,
+    pub judge_rubric: String,
+    pub judge_model: String,
+    pub tags: Vec<String>,
+    /// Model the prompt was last used with
+    pub recommended_model: Option<String>,
+}
+
+impl PromptCard {
+    pub fn from_prompt(prompt: &Prompt) -> Self {
+        Self {
+            version: CARD_VERSION,
+            title: prompt.title.clone(),
+            content: prompt.content.clone(),
+            kind: prompt.kind,
+            appearance: prompt.appearance.clone(),
+            template: prompt.template.clone(),
+            examples: prompt.examples.clone(),
+            banned_phrases: prompt.banned_phrases.clone(),
+            regenerate_on_banned_phrases: prompt.regenerate_on_banned_phrases,
+            critique_and_revise: prompt.critique_and_revise,
+            debate: prompt.debate.clone(),
+            ensemble: prompt.ensemble.clone(),
+            judge_rubric: prompt.judge_rubric.clone(),
+            judge_model: prompt.judge_model.clone(),
+            tags: prompt.tags.clone(),
+            recommended_model: prompt.last_model_name().map(str::to_string),
+        }
+    }
+
+    pub fn into_prompt(self, id: usize) -> Prompt {
+        let mut prompt = Prompt::new(String::new(), String::new(), id);
+
+        self.apply_to(&mut prompt);
+
+        prompt
+    }
+
+    /// Replaces the settings of the prompt with the ones of the card, keeping its history
+    pub fn apply_to(self, prompt: &mut Prompt) {
+        prompt.title = self.title;
+        prompt.content = self.content;
+        prompt.kind = self.kind;
+        prompt.appearance = self.appearance;
+        prompt.template = self.template;
+        prompt.examples = self.examples;
+        prompt.banned_phrases = self.banned_phrases;
+        prompt.regenerate_on_banned_phrases = self.regenerate_on_banned_phrases;
+        prompt.critique_and_revise = self.critique_and_revise;
+        prompt.debate = self.debate;
+        prompt.ensemble = self.ensemble;
+        prompt.judge_rubric = self.judge_rubric;
+        prompt.judge_model = self.judge_model;
+        prompt.tags = self.tags;
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let card: Self = serde_json::from_str(&json)?;
+
+        anyhow::ensure!(
+            card.version <= CARD_VERSION,
+            "the prompt card was exported by a newer version of the app"
+        );
+
+        Ok(card)
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Local};
+
+use crate::compose::{ComposedPrompt, Exchange};
+
+/// Title of a conversation until it's named
+pub const UNTITLED: &str = "New conversation";
+
+/// Characters of a generated title at most
+const TITLE_MAX_CHARS: usize = 60;
+
+/// A named conversation of a prompt, its responses referring to it by its creation time
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ChatSession {
+    pub created_at: DateTime<Local>,
+    pub title: String,
+    /// The title was generated or written, so it's not generated again
+    pub titled: bool,
+    /// Hidden from the list of conversations, but kept with its responses
+    pub archived: bool,
+}
+
+impl Default for ChatSession {
+    fn default() -> Self {
+        Self {
+            created_at: Local::now(),
+            title: UNTITLED.to_string(),
+            titled: false,
+            archived: false,
+        }
+    }
+}
+
+/// Asks for a short title of a conversation from its first exchange
+pub fn title_prompt(exchange: &Exchange) -> ComposedPrompt {
+    ComposedPrompt {
+        system: None,
+        prompt: format!(
+            "Here is the start of a conversation.\n\n\
+             User:\n{}\n\n\
+             Assistant:\n{}\n\n\
+             Reply with a title of at most six words for this conversation, without quotes or \
+             punctuation at the end, and nothing else.",
+            exchange.input, exchange.output
+        ),
+    }
+}
+
+/// The title a model replied with, its first line without quotes, markdown or a final period,
+/// none when it's empty
+pub fn clean_title(output: &str) -> Option<String> {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    let line = line.strip_prefix("Title:").unwrap_or(line);
+    let title = line
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '*' | '#' | '`'))
+        .trim_end_matches('.');
+
+    (!title.is_empty()).then(|| title.chars().take(TITLE_MAX_CHARS).collect())
+}
@@ -0,0 +1,94 @@
+use std::{cell::RefCell, error::Error};
+
+use arboard::Clipboard;
+
+/// Programs taking the clipboard content on stdin and serving it from the background, so that it
+/// outlives the app on Linux, where the content of the clipboard is gone with the app owning it
+#[cfg(target_os = "linux")]
+const CLIPBOARD_DAEMONS: [(&str, &[&str]); 3] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+thread_local! {
+    /// Kept open so that the copied content stays available while the app runs
+    static CLIPBOARD: RefCell<Option<Clipboard>> = const { RefCell::new(None) };
+}
+
+/// Copies the text, handed over to `wl-copy`, `xclip` or `xsel` on Linux when one is installed
+pub fn set_text(text: &str) -> Result<(), Box<dyn Error>> {
+    #[cfg(target_os = "linux")]
+    if hand_over(text) {
+        return Ok(());
+    }
+
+    with_clipboard(|clipboard| clipboard.set_text(text))
+}
+
+/// Copies the HTML with its text alternative, which on Linux only lasts as long as the app
+/// unless a clipboard manager takes it over
+pub fn set_html(html: &str, text: &str) -> Result<(), Box<dyn Error>> {
+    with_clipboard(|clipboard| clipboard.set_html(html, Some(text)))
+}
+
+fn with_clipboard(
+    set: impl FnOnce(&mut Clipboard) -> Result<(), arboard::Error>,
+) -> Result<(), Box<dyn Error>> {
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        if clipboard.is_none() {
+            *clipboard = Some(Clipboard::new()?);
+        }
+
+        if let Some(clipboard) = clipboard.as_mut() {
+            set(clipboard)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Passes the text to the first clipboard daemon that runs, `wl-copy` only under Wayland
+#[cfg(target_os = "linux")]
+fn hand_over(text: &str) -> bool {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+    CLIPBOARD_DAEMONS
+        .iter()
+        .filter(|(program, _)| wayland || *program != "wl-copy")
+        .any(|(program, args)| match run_daemon(program, args, text) {
+            Ok(()) => true,
+            Err(e) => {
+                log::debug!("Failed to hand the clipboard over to {program}: {e}");
+                false
+            }
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn run_daemon(program: &str, args: &[&str], text: &str) -> std::io::Result<()> {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Closing stdin lets the daemon fork into the background and the started process exit
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("exited with {status}")))
+    }
+}
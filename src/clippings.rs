@@ -0,0 +1,188 @@
+use chrono::{DateTime, Local};
+use egui::{Label, RichText, ScrollArea};
+
+use crate::{
+    app::AppAction,
+    icons::{Icon, IconButton},
+    prompt::Prompt,
+};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// A passage clipped from a response, linking back to the history entry it was taken from
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Clipping {
+    pub quote: String,
+    /// Title of the prompt when the passage was clipped
+    pub prompt_title: String,
+    pub model: String,
+    /// The history entry the passage was clipped from, by its creation time
+    pub response_created_at: DateTime<Local>,
+    pub clipped_at: DateTime<Local>,
+}
+
+impl Default for Clipping {
+    fn default() -> Self {
+        Self {
+            quote: String::new(),
+            prompt_title: String::new(),
+            model: String::new(),
+            response_created_at: Local::now(),
+            clipped_at: Local::now(),
+        }
+    }
+}
+
+impl Clipping {
+    /// The prompt whose history still holds the entry, even if it was renamed since
+    fn find_prompt(&self, prompts: &[Prompt]) -> Option<usize> {
+        prompts.iter().position(|prompt| {
+            prompt
+                .responses()
+                .any(|response| response.created_at == self.response_created_at)
+        })
+    }
+}
+
+/// The clippings of one prompt, by its title, or all of them, the newest first
+pub fn filtered<'a>(clippings: &'a [Clipping], prompt_title: Option<&str>) -> Vec<&'a Clipping> {
+    clippings
+        .iter()
+        .rev()
+        .filter(|clipping| prompt_title.is_none_or(|title| clipping.prompt_title == title))
+        .collect()
+}
+
+/// Formats the clippings as markdown quotes, each followed by where it was clipped from
+pub fn to_markdown<'a>(clippings: impl Iterator<Item = &'a Clipping>) -> String {
+    let mut markdown = "# Clippings\n".to_string();
+
+    for clipping in clippings {
+        let quote = clipping
+            .quote
+            .trim()
+            .lines()
+            .map(|line| format!("> {line}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        markdown.push_str(&format!(
+            "\n{quote}\n\n— *{}*, response of {} by {}\n",
+            clipping.prompt_title,
+            clipping.response_created_at.format(TIME_FORMAT),
+            clipping.model
+        ));
+    }
+
+    markdown
+}
+
+/// Shows the clippings, of all prompts or of the picked one, with a link to their entry
+pub fn show(
+    ui: &mut egui::Ui,
+    clippings: &[Clipping],
+    prompt_filter: &mut Option<String>,
+    prompts: &[Prompt],
+) -> Option<AppAction> {
+    let mut action = None;
+
+    let mut titles: Vec<&str> = clippings
+        .iter()
+        .map(|clipping| clipping.prompt_title.as_str())
+        .collect();
+    titles.sort_unstable();
+    titles.dedup();
+
+    let shown = filtered(clippings, prompt_filter.as_deref());
+
+    ui.add_space(12.0);
+    ui.heading("✂ Clippings");
+    ui.add_space(6.0);
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("clippings_prompt")
+            .selected_text(prompt_filter.as_deref().unwrap_or("All prompts"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(prompt_filter, None, "All prompts");
+
+                for title in titles {
+                    ui.selectable_value(prompt_filter, Some(title.to_string()), title);
+                }
+            });
+
+        if ui
+            .add_enabled(
+                !shown.is_empty(),
+                IconButton::new(Icon::Save).text("Export…").frame(true),
+            )
+            .on_hover_text("Save the listed clippings to a markdown file")
+            .clicked()
+        {
+            action = Some(AppAction::ExportClippings);
+        }
+    });
+
+    ui.add_space(6.0);
+    ui.label(RichText::new(format!("{} clippings", shown.len())).weak());
+    ui.separator();
+
+    if shown.is_empty() {
+        ui.label(
+            RichText::new("Select a passage of a response with the quote button and clip it to collect it here")
+                .weak(),
+        );
+    }
+
+    ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+        for clipping in shown {
+            ui.add_space(6.0);
+            ui.label(RichText::new(clipping.quote.trim()).italics());
+
+            ui.horizontal(|ui| {
+                match clipping.find_prompt(prompts) {
+                    Some(idx) => {
+                        if ui
+                            .link(&clipping.prompt_title)
+                            .on_hover_text("Show the response in its prompt")
+                            .clicked()
+                        {
+                            action = Some(AppAction::ShowHistoryEntry {
+                                idx,
+                                created_at: clipping.response_created_at,
+                            });
+                        }
+                    }
+                    None => {
+                        ui.label(&clipping.prompt_title)
+                            .on_hover_text("The response was removed from the history");
+                    }
+                }
+
+                ui.add(
+                    Label::new(
+                        RichText::new(format!(
+                            "{} · {}",
+                            clipping.response_created_at.format(TIME_FORMAT),
+                            clipping.model
+                        ))
+                        .weak(),
+                    )
+                    .truncate(),
+                );
+
+                if ui
+                    .add(IconButton::new(Icon::Delete).small())
+                    .on_hover_text("Remove the clipping")
+                    .clicked()
+                {
+                    action = Some(AppAction::RemoveClipping(clipping.clipped_at));
+                }
+            });
+
+            ui.separator();
+        }
+    });
+
+    action
+}
@@ -0,0 +1,91 @@
+use ollama_rs::models::LocalModel;
+
+use crate::history::GenerationStats;
+
+/// Mode where several models answer the same input, shown side by side
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CompareSettings {
+    pub enabled: bool,
+    pub models: Vec<String>,
+}
+
+/// The answer of one of the compared models, kept on the history entry
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ComparedAnswer {
+    pub model: String,
+    pub output: String,
+    /// Why the model failed to answer, the other models answering regardless
+    pub error: Option<String>,
+    pub generation: Option<GenerationStats>,
+}
+
+impl ComparedAnswer {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.error.is_some() || self.generation.is_some()
+    }
+}
+
+impl CompareSettings {
+    pub const MIN_MODELS: usize = 2;
+    /// More columns than that are too narrow to read
+    pub const MAX_MODELS: usize = 4;
+
+    pub fn resolve(&self, available: &[LocalModel]) -> anyhow::Result<Vec<LocalModel>> {
+        anyhow::ensure!(
+            self.models.len() >= Self::MIN_MODELS,
+            "select at least {} models to compare",
+            Self::MIN_MODELS
+        );
+
+        self.models
+            .iter()
+            .map(|name| {
+                available
+                    .iter()
+                    .find(|model| &model.name == name)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("model \"{name}\" is not available"))
+            })
+            .collect()
+    }
+
+    /// Adds or removes the model, up to the maximum number of compared models
+    pub fn toggle_model(&mut self, name: &str) {
+        if let Some(position) = self.models.iter().position(|model| model == name) {
+            self.models.remove(position);
+        } else if self.models.len() < Self::MAX_MODELS {
+            self.models.push(name.to_string());
+        }
+    }
+}
+
+/// Names the compared models in the history entry, e.g. "llama3.2 | qwen2.5"
+pub fn label(models: &[LocalModel]) -> String {
+    models
+        .iter()
+        .map(|model| model.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// All answers one after the other, the output of the grouped history entry for copying and
+/// searching
+pub fn answers_overview(answers: &[ComparedAnswer]) -> String {
+    answers
+        .iter()
+        .map(|answer| match &answer.error {
+            Some(error) => format!("### {}\n\n*Failed: {error}*", answer.model),
+            None => format!("### {}\n\n{}", answer.model, answer.output),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use ollama_rs::generation::chat::ChatMessage;
+
+/// How the content of a prompt and the user input are combined into a request
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum PromptTemplate {
+    /// `{content}:\n{input}`
+    #[default]
+    ContentFirst,
+    /// `{input}\n\n{content}`
+    InputFirst,
+    /// The content is sent as the system message and the input as the prompt
+    ContentAsSystem,
+    /// A custom layout with `{content}` and `{input}` placeholders
+    Custom(String),
+}
+
+/// An input/output pair included as a demonstration in every request of a prompt
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(default)]
+pub struct FewShotExample {
+    pub input: String,
+    pub output: String,
+}
+
+/// Returns the banned phrases found in `text`, ignoring case
+pub fn find_banned_phrases<'a>(text: &str, phrases: &'a [String]) -> Vec<&'a str> {
+    let text = text.to_lowercase();
+
+    phrases
+        .iter()
+        .map(|phrase| phrase.trim())
+        .filter(|phrase| !phrase.is_empty() && text.contains(&phrase.to_lowercase()))
+        .collect()
+}
+
+/// Names of the `{{name}}` placeholders of a prompt content, in order of first appearance
+pub fn variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let name = rest[..end].trim();
+
+        if is_variable_name(name) && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+
+        rest = &rest[end + 2..];
+    }
+
+    names
+}
+
+/// Replaces the `{{name}}` placeholders with their values, leaving the unknown ones as they are
+pub fn fill_variables(content: &str, values: &BTreeMap<String, String>) -> String {
+    let mut filled = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start + 2..];
+
+        let placeholder = rest
+            .find("}}")
+            .and_then(|end| Some((end, values.get(rest[..end].trim())?)));
+
+        match placeholder {
+            Some((end, value)) => {
+                filled.push_str(value);
+                rest = &rest[end + 2..];
+            }
+            None => filled.push_str("{{"),
+        }
+    }
+
+    filled.push_str(rest);
+
+    filled
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Instruction appended to the input when a response is regenerated because of banned phrases
+pub fn banned_phrases_instruction(phrases: &[&str]) -> String {
+    format!(
+        "Do not use any of the following words or phrases in your answer: {}.",
+        phrases
+            .iter()
+            .map(|phrase| format!("\"{phrase}\""))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// A previous input of a conversation with the answer the model gave to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    pub input: String,
+    pub output: String,
+}
+
+/// The final request sent to the model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposedPrompt {
+    pub system: Option<String>,
+    pub prompt: String,
+}
+
+impl ComposedPrompt {
+    /// Asks the model to review its own answer to this request and reply with an improved one
+    pub fn critique(&self, answer: &str) -> Self {
+        Self {
+            system: self.system.clone(),
+            prompt: format!(
+                "Here is a request and a draft answer to it.\n\n\
+                 Request:\n{}\n\n\
+                 Draft answer:\n{}\n\n\
+                 Critically review the draft for mistakes, omissions and unclear parts, \
+                 then reply with the improved final answer only.",
+                self.prompt, answer
+            ),
+        }
+    }
+
+    /// Chat messages continuing the conversation this request started, the follow-up inputs being
+    /// sent as they are since the first one already carries the prompt content
+    pub fn continue_conversation(self, exchanges: &[Exchange], input: &str) -> Vec<ChatMessage> {
+        let mut messages: Vec<ChatMessage> =
+            self.system.map(ChatMessage::system).into_iter().collect();
+
+        for (idx, exchange) in exchanges.iter().enumerate() {
+            let request = if idx == 0 {
+                self.prompt.clone()
+            } else {
+                exchange.input.clone()
+            };
+
+            messages.push(ChatMessage::user(request));
+            messages.push(ChatMessage::assistant(exchange.output.clone()));
+        }
+
+        messages.push(ChatMessage::user(input.to_string()));
+
+        messages
+    }
+
+    /// Chat messages asking the model to go on with its answer to this request, or to the last
+    /// message of the conversation, which was cut off
+    pub fn continue_answer(
+        self,
+        conversation: Option<Vec<ChatMessage>>,
+        answer: &str,
+    ) -> Vec<ChatMessage> {
+        let mut messages = conversation.unwrap_or_else(|| {
+            self.system
+                .map(ChatMessage::system)
+                .into_iter()
+                .chain([ChatMessage::user(self.prompt)])
+                .collect()
+        });
+
+        messages.push(ChatMessage::assistant(answer.to_string()));
+        messages.push(ChatMessage::user(CONTINUE_INSTRUCTION.to_string()));
+
+        messages
+    }
+
+    /// Embeds a style guide into the system message, or ahead of the prompt when there is none
+    pub fn with_style_guide(mut self, style_guide: &str) -> Self {
+        let style_guide = format!("Follow this style guide:\n\n{}", style_guide.trim());
+
+        match self.system.as_mut() {
+            Some(system) => {
+                system.push_str("\n\n");
+                system.push_str(&style_guide);
+            }
+            None => self.prompt = format!("{style_guide}\n\n{}", self.prompt),
+        }
+
+        self
+    }
+}
+
+/// Asks the model to go on with an answer that was cut off, e.g. by a length limit
+pub const CONTINUE_INSTRUCTION: &str = "Continue your answer exactly where it stopped, without \
+     repeating anything or commenting on it.";
+
+impl PromptTemplate {
+    pub const DEFAULT_CUSTOM: &str = "{content}\n\n{input}";
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ContentFirst => "content first",
+            Self::InputFirst => "input first",
+            Self::ContentAsSystem => "content as system",
+            Self::Custom(_) => "custom",
+        }
+    }
+
+    pub fn compose(
+        &self,
+        content: &str,
+        examples: &[FewShotExample],
+        input: &str,
+    ) -> ComposedPrompt {
+        let input = &Self::with_examples(examples, input);
+
+        match self {
+            Self::ContentFirst => ComposedPrompt {
+                system: None,
+                prompt: format!("{content}:\n{input}"),
+            },
+            Self::InputFirst => ComposedPrompt {
+                system: None,
+                prompt: format!("{input}\n\n{content}"),
+            },
+            Self::ContentAsSystem => ComposedPrompt {
+                system: Some(content.to_string()),
+                prompt: input.to_string(),
+            },
+            Self::Custom(template) => ComposedPrompt {
+                system: None,
+                prompt: template
+                    .replace("{content}", content)
+                    .replace("{input}", input),
+            },
+        }
+    }
+
+    /// Prefixes the input with the examples, leaving the output of the last pair for the model
+    fn with_examples(examples: &[FewShotExample], input: &str) -> String {
+        let examples = examples
+            .iter()
+            .filter(|example| !example.input.is_empty() || !example.output.is_empty())
+            .collect::<Vec<_>>();
+
+        if examples.is_empty() {
+            return input.to_string();
+        }
+
+        let mut composed = String::new();
+
+        for example in examples {
+            composed.push_str(&format!(
+                "Input: {}\nOutput: {}\n\n",
+                example.input, example.output
+            ));
+        }
+
+        composed.push_str(&format!("Input: {input}\nOutput:"));
+
+        composed
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::settings::Settings;
+
+pub const FILE_NAME: &str = "reprompt.toml";
+
+/// Ollama URL taking precedence over the stored settings and the config file
+pub const OLLAMA_HOST_VAR: &str = "REPROMPT_OLLAMA_HOST";
+/// Model selected once the models are listed at startup
+pub const DEFAULT_MODEL_VAR: &str = "REPROMPT_DEFAULT_MODEL";
+
+/// How often the config file is checked for changes
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Location of the config file, e.g. `$XDG_CONFIG_HOME/reprompt/reprompt.toml` on Linux
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("reprompt").join(FILE_NAME))
+}
+
+/// Color theme set by the config file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+/// Settings read from `reprompt.toml`, taking precedence over the stored ones.
+///
+/// The file uses the keys of [`Settings`], the missing ones keep their stored value, so that it
+/// may only pin what a dotfile setup needs. `${NAME}` in a string value is replaced with the
+/// environment variable `NAME`, e.g. for the header values that are not written down.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    table: toml::Table,
+    pub theme: Option<Theme>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+
+        Self::parse(&text).map_err(|e| anyhow::anyhow!("invalid {}: {e}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut table: toml::Table = toml::from_str(text)?;
+
+        for (_, value) in table.iter_mut() {
+            expand_env(value)?;
+        }
+
+        let theme = table
+            .remove("theme")
+            .map(toml::Value::try_into)
+            .transpose()?;
+
+        Ok(Self { table, theme })
+    }
+
+    /// The settings with the values of the file over them
+    pub fn apply(&self, settings: &Settings) -> anyhow::Result<Settings> {
+        let mut table = toml::Table::try_from(settings)?;
+        merge(&mut table, &self.table);
+
+        let mut merged: Settings = table.try_into()?;
+
+        // Secrets are not serialized, the stored ones are kept unless the file sets them
+        if !self.table.contains_key("openai_api_key") {
+            merged.openai_api_key = settings.openai_api_key.clone();
+        }
+        if !self.table.contains_key("ollama_headers") {
+            merged.ollama_headers = settings.ollama_headers.clone();
+        }
+        if !self.table.contains_key("openai_headers") {
+            merged.openai_headers = settings.openai_headers.clone();
+        }
+        if !self.table.contains_key("stable_diffusion_headers") {
+            merged.stable_diffusion_headers = settings.stable_diffusion_headers.clone();
+        }
+        if !self.table.contains_key("sync_headers") {
+            merged.sync_headers = settings.sync_headers.clone();
+        }
+
+        Ok(merged)
+    }
+}
+
+/// Writes the settings and the theme as a profile to share, in the format of the config file so
+/// that it may be imported or used as one. The prompts and the header values are left out.
+pub fn save_profile(path: &Path, settings: &Settings, theme: Theme) -> anyhow::Result<()> {
+    let mut table = toml::Table::try_from(settings)?;
+    table.insert("theme".to_string(), toml::Value::try_from(theme)?);
+
+    fs::write(path, toml::to_string_pretty(&table)?)?;
+
+    Ok(())
+}
+
+/// Settings given by environment variables at startup, for scripted and containerized runs
+#[derive(Debug, Clone, Default)]
+pub struct EnvOverrides {
+    pub ollama_host: Option<String>,
+    pub default_model: Option<String>,
+}
+
+impl EnvOverrides {
+    pub fn from_env() -> Self {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        Self {
+            ollama_host: var(OLLAMA_HOST_VAR),
+            default_model: var(DEFAULT_MODEL_VAR),
+        }
+    }
+
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(host) = &self.ollama_host {
+            // Like `OLLAMA_HOST`, the scheme may be left out
+            settings.ollama_url = if host.contains("://") {
+                host.clone()
+            } else {
+                format!("http://{host}")
+            };
+        }
+    }
+}
+
+/// Reloads the config file when it changes
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+}
+
+impl ConfigWatcher {
+    /// Watches the file loaded at startup, or that may be created later
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            modified: modified_at(&path),
+            path,
+            checked_at: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file once it changed since the last call, checked at most every few seconds.
+    ///
+    /// A removed file isn't reported, the settings it applied stay in place.
+    pub fn poll(&mut self) -> Option<anyhow::Result<ConfigFile>> {
+        if self.checked_at.elapsed() < CHECK_INTERVAL {
+            return None;
+        }
+
+        self.checked_at = Instant::now();
+
+        let modified = modified_at(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+        modified.map(|_| ConfigFile::load(&self.path))
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Overwrites the values of `target` with the ones of `source`, merging the nested tables
+fn merge(target: &mut toml::Table, source: &toml::Table) {
+    for (key, value) in source {
+        match (target.get_mut(key), value) {
+            (Some(toml::Value::Table(target)), toml::Value::Table(source)) => merge(target, source),
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Replaces the `${NAME}` references of the string values with the environment variables
+fn expand_env(value: &mut toml::Value) -> anyhow::Result<()> {
+    match value {
+        toml::Value::String(text) => {
+            let mut from = 0;
+
+            while let Some(start) = text[from..].find("${").map(|start| from + start) {
+                let Some(len) = text[start..].find('}') else {
+                    break;
+                };
+                let name = &text[start + 2..start + len];
+                let var = env::var(name).map_err(|_| {
+                    anyhow::anyhow!(
+                        "the environment variable {name} referenced by the config is not set"
+                    )
+                })?;
+
+                // The value isn't expanded again
+                from = start + var.len();
+                text.replace_range(start..=start + len, &var);
+            }
+        }
+        toml::Value::Array(values) => values.iter_mut().try_for_each(expand_env)?,
+        toml::Value::Table(table) => table
+            .iter_mut()
+            .try_for_each(|(_, value)| expand_env(value))?,
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+};
+
+use chrono::{DateTime, Local};
+
+/// What a response predominantly consists of, for outputs better read in a specialized viewer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentType {
+    Markdown,
+    Json(serde_json::Value),
+    Csv(Vec<Vec<String>>),
+    Diff(String),
+    Code { language: String, code: String },
+}
+
+impl ContentType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Json(_) => "JSON tree",
+            Self::Csv(_) => "Table",
+            Self::Diff(_) => "Diff",
+            Self::Code { .. } => "Code",
+        }
+    }
+}
+
+/// Less than this share of consistent lines is not considered a table or a diff
+const MIN_LINE_SHARE: f32 = 0.8;
+
+/// Detects the content type of an output, looking inside it when it is a single fenced code block
+pub fn detect(output: &str) -> ContentType {
+    let (language, body) = match unfence(output) {
+        Some((language, body)) => (language, body),
+        None => ("", output.trim()),
+    };
+
+    if let Ok(value @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) =
+        serde_json::from_str(body)
+    {
+        return ContentType::Json(value);
+    }
+
+    if language == "diff" || is_diff(body) {
+        return ContentType::Diff(body.to_string());
+    }
+
+    if language == "csv" || is_csv(body) {
+        return ContentType::Csv(body.lines().map(parse_csv_line).collect());
+    }
+
+    if !language.is_empty() {
+        return ContentType::Code {
+            language: language.to_string(),
+            code: body.to_string(),
+        };
+    }
+
+    ContentType::Markdown
+}
+
+/// Returns the output without the fence around it, when it is a single fenced code block
+pub fn strip_fence(output: &str) -> &str {
+    unfence(output).map_or(output, |(_, body)| body)
+}
+
+/// Whether the text contains the query, ignoring case
+pub fn contains_query(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(&query.trim().to_lowercase())
+}
+
+/// Outputs with the matches of the history search highlighted, by the creation time of their
+/// response, so that they are only highlighted again once the query or the output changes
+#[derive(Default)]
+pub struct HighlightCache {
+    query: String,
+    /// Hash of the output and the output highlighted
+    outputs: HashMap<DateTime<Local>, (u64, String)>,
+}
+
+impl HighlightCache {
+    pub fn get(&mut self, created_at: DateTime<Local>, output: &str, query: &str) -> &str {
+        if self.query != query {
+            self.query = query.to_string();
+            self.outputs.clear();
+        }
+
+        let mut hasher = DefaultHasher::new();
+        output.hash(&mut hasher);
+        let output_hash = hasher.finish();
+
+        let (cached_hash, highlighted) = self
+            .outputs
+            .entry(created_at)
+            .or_insert_with(|| (output_hash, highlight_matches(output, query)));
+
+        if *cached_hash != output_hash {
+            *cached_hash = output_hash;
+            *highlighted = highlight_matches(output, query);
+        }
+
+        highlighted
+    }
+}
+
+/// Makes the matches of the query bold, leaving the code, the link destinations and the URLs
+/// untouched, as the emphasis markers would break them
+pub fn highlight_matches(markdown: &str, query: &str) -> String {
+    let query = query.trim().to_lowercase();
+
+    if query.is_empty() {
+        return markdown.to_string();
+    }
+
+    let mut highlighted = String::with_capacity(markdown.len());
+    let mut in_fence = false;
+
+    for line in markdown.split_inclusive('\n') {
+        let is_fence = line.trim_start().starts_with("```");
+
+        if is_fence {
+            in_fence = !in_fence;
+        }
+
+        if in_fence || is_fence {
+            highlighted.push_str(line);
+            continue;
+        }
+
+        // Odd parts are inside code spans
+        for (idx, part) in line.split('`').enumerate() {
+            if idx > 0 {
+                highlighted.push('`');
+            }
+
+            if idx % 2 == 0 {
+                let mut last = 0;
+
+                for link in link_ranges(part) {
+                    highlighted.push_str(&bold_matches(&part[last..link.start], &query));
+                    highlighted.push_str(&part[link.clone()]);
+                    last = link.end;
+                }
+
+                highlighted.push_str(&bold_matches(&part[last..], &query));
+            } else {
+                highlighted.push_str(part);
+            }
+        }
+    }
+
+    highlighted
+}
+
+/// Byte ranges of the link destinations, autolinks, reference definitions and bare URLs of a line
+fn link_ranges(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+
+    // A reference definition, `[label]: destination`, whose label the links refer to
+    if line.trim_start().starts_with('[') && line.contains("]:") {
+        ranges.push(0..line.len());
+        return ranges;
+    }
+
+    let mut idx = 0;
+
+    while let Some(rest) = line.get(idx..).filter(|rest| !rest.is_empty()) {
+        let is_word_start = line[..idx]
+            .chars()
+            .next_back()
+            .is_none_or(|previous| previous.is_whitespace() || previous == '(');
+        let end = if rest.starts_with("](") {
+            rest.find(')').map(|end| end + 1)
+        } else if let Some(label) = rest.strip_prefix("][") {
+            // The label of a full reference link
+            label.find(']').map(|end| end + 3)
+        } else if rest.starts_with('<') {
+            rest.find('>')
+                .filter(|&end| !rest[..end].contains(char::is_whitespace))
+                .map(|end| end + 1)
+        } else if is_word_start
+            && ["http://", "https://", "www."]
+                .iter()
+                .any(|prefix| rest.starts_with(prefix))
+        {
+            Some(rest.find(char::is_whitespace).unwrap_or(rest.len()))
+        } else {
+            None
+        };
+
+        match end {
+            Some(end) => {
+                ranges.push(idx..idx + end);
+                idx += end;
+            }
+            None => idx += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    ranges
+}
+
+fn bold_matches(text: &str, query: &str) -> String {
+    let lowercase = text.to_lowercase();
+
+    // Offsets only carry over when lowercasing kept the byte lengths
+    if lowercase.len() != text.len() {
+        return text.to_string();
+    }
+
+    let mut bold = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (start, _) in lowercase.match_indices(query) {
+        let end = start + query.len();
+
+        bold.push_str(&text[last..start]);
+        bold.push_str("**");
+        bold.push_str(&text[start..end]);
+        bold.push_str("**");
+        last = end;
+    }
+
+    bold.push_str(&text[last..]);
+
+    bold
+}
+
+/// Returns the language and the body of an output made of a single fenced code block
+fn unfence(output: &str) -> Option<(&str, &str)> {
+    let output = output.trim();
+    let rest = output.strip_prefix("```")?;
+    let (language, body) = rest.split_once('\n')?;
+    let body = body.trim_end().strip_suffix("```")?;
+
+    if body.contains("\n```") {
+        return None;
+    }
+
+    Some((language.trim(), body.trim_end()))
+}
+
+fn is_diff(body: &str) -> bool {
+    let lines: Vec<&str> = body.lines().collect();
+    let has_hunk = lines.iter().any(|line| line.starts_with("@@"));
+    let diff_lines = lines
+        .iter()
+        .filter(|line| {
+            line.is_empty()
+                || line.starts_with(['+', '-', ' ', '@'])
+                || line.starts_with("diff ")
+                || line.starts_with("index ")
+        })
+        .count();
+
+    has_hunk && diff_lines as f32 >= lines.len() as f32 * MIN_LINE_SHARE
+}
+
+fn is_csv(body: &str) -> bool {
+    let lines: Vec<&str> = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+
+    let Some(columns) = lines.first().map(|line| parse_csv_line(line).len()) else {
+        return false;
+    };
+
+    let consistent = lines
+        .iter()
+        .filter(|line| parse_csv_line(line).len() == columns)
+        .count();
+
+    lines.len() >= 2 && columns >= 2 && consistent as f32 >= lines.len() as f32 * MIN_LINE_SHARE
+}
+
+/// Splits a CSV line on commas outside of double quotes, unescaping doubled quotes
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+
+    fields.push(field.trim().to_string());
+
+    fields
+}
@@ -0,0 +1,90 @@
+use ollama_rs::models::LocalModel;
+
+use crate::compose::ComposedPrompt;
+
+/// Experimental mode where two models answer the same input and a third one synthesizes the result
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DebateSettings {
+    pub enabled: bool,
+    pub first_model: String,
+    pub second_model: String,
+    /// Empty when the first model synthesizes the final answer
+    pub synthesizer_model: String,
+}
+
+/// The answer of one of the debating models, kept on the history entry
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct DebateAnswer {
+    pub model: String,
+    pub output: String,
+}
+
+/// The models taking part in a debate, resolved against the locally available ones
+pub struct DebateModels {
+    pub first: LocalModel,
+    pub second: LocalModel,
+    pub synthesizer: LocalModel,
+}
+
+impl DebateSettings {
+    pub fn resolve(&self, available: &[LocalModel]) -> anyhow::Result<DebateModels> {
+        let find = |name: &str| {
+            available
+                .iter()
+                .find(|model| model.name == name)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("model \"{name}\" is not available"))
+        };
+
+        anyhow::ensure!(
+            !self.first_model.is_empty() && !self.second_model.is_empty(),
+            "select two models to debate"
+        );
+
+        let first = find(&self.first_model)?;
+        let second = find(&self.second_model)?;
+        let synthesizer = if self.synthesizer_model.is_empty() {
+            first.clone()
+        } else {
+            find(&self.synthesizer_model)?
+        };
+
+        Ok(DebateModels {
+            first,
+            second,
+            synthesizer,
+        })
+    }
+}
+
+impl DebateModels {
+    pub fn label(&self) -> String {
+        format!(
+            "{} vs {} → {}",
+            self.first.name, self.second.name, self.synthesizer.name
+        )
+    }
+}
+
+/// Asks the synthesizer to merge the answers of the debating models into a final one
+pub fn synthesis_prompt(request: &ComposedPrompt, answers: &[DebateAnswer]) -> ComposedPrompt {
+    let answers = answers
+        .iter()
+        .enumerate()
+        .map(|(idx, answer)| format!("Answer {} ({}):\n{}", idx + 1, answer.model, answer.output))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    ComposedPrompt {
+        system: request.system.clone(),
+        prompt: format!(
+            "Several assistants answered the following request.\n\n\
+             Request:\n{}\n\n\
+             {answers}\n\n\
+             Compare the answers, resolve their disagreements and reply with the best \
+             final answer only.",
+            request.prompt
+        ),
+    }
+}
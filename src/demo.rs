@@ -0,0 +1,48 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use ollama_rs::models::LocalModel;
+
+/// Name of the built-in model answering with canned responses, for demos without Ollama
+pub const MODEL_NAME: &str = "demo";
+
+const RESPONSES: [&str; 3] = [
+    "Here is a short overview:\n\n\
+     1. **Start small**: write the simplest version that works.\n\
+     2. **Measure**: find out where the time actually goes.\n\
+     3. **Iterate**: improve one thing at a time and keep what helps.\n\n\
+     Most of the gains usually come from the first two steps.",
+    "Sure! A minimal example in Rust:\n\n\
+     ```rust\n\
+     fn greet(name: &str) -> String {\n    format!(\"Hello, {name}!\")\n}\n\
+     ```\n\n\
+     Call it with `greet(\"world\")` to get `Hello, world!`.",
+    "Good question. There are two common approaches:\n\n\
+     - *Eager*: compute everything up front, simple but slower to start.\n\
+     - *Lazy*: compute on demand, faster to start but harder to reason about.\n\n\
+     > Pick the eager one unless the start-up time really matters.",
+];
+
+pub fn model() -> LocalModel {
+    LocalModel {
+        name: MODEL_NAME.to_string(),
+        modified_at: String::new(),
+        size: 0,
+    }
+}
+
+pub fn is_demo(model: &LocalModel) -> bool {
+    model.name == MODEL_NAME
+}
+
+/// Picks the canned response for a prompt, the same prompt always getting the same one
+pub fn response(prompt: &str) -> &'static str {
+    let mut hasher = DefaultHasher::new();
+    prompt.trim().hash(&mut hasher);
+
+    RESPONSES[(hasher.finish() % RESPONSES.len() as u64) as usize]
+}
+
+/// Splits a response into the tokens it is streamed as, keeping the whitespace
+pub fn tokens(response: &str) -> impl Iterator<Item = &str> {
+    response.split_inclusive(char::is_whitespace)
+}
@@ -0,0 +1,81 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+};
+
+use chrono::Local;
+
+/// Opens a file with the default application, when neither a command nor `$VISUAL`/`$EDITOR` is set
+#[cfg(target_os = "macos")]
+const FALLBACK_COMMAND: &str = "open -t";
+#[cfg(target_os = "windows")]
+const FALLBACK_COMMAND: &str = "notepad";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const FALLBACK_COMMAND: &str = "xdg-open";
+
+/// File extension matching the language of a fenced code block, for the editor to highlight it
+pub fn extension_of(language: &str) -> &str {
+    match language.to_lowercase().as_str() {
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" | "js" => "js",
+        "typescript" | "ts" => "ts",
+        "shell" | "bash" | "sh" | "zsh" => "sh",
+        "markdown" => "md",
+        "c++" | "cpp" => "cpp",
+        "csharp" | "c#" => "cs",
+        "golang" | "go" => "go",
+        "ruby" => "rb",
+        "kotlin" => "kt",
+        "yaml" => "yml",
+        _ if !language.is_empty() && language.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            language
+        }
+        _ => "txt",
+    }
+}
+
+/// Writes the text to a temporary file and opens it in the external editor, without waiting for it.
+///
+/// The command is split on whitespace, `{file}` is replaced with the file path, which is appended
+/// when the command has no placeholder. An empty command falls back to `$VISUAL`, then `$EDITOR`.
+pub fn open(command: &str, text: &str, extension: &str) -> anyhow::Result<PathBuf> {
+    let path = env::temp_dir().join(format!(
+        "reprompt-{}.{extension}",
+        Local::now().format("%Y%m%d-%H%M%S%.f")
+    ));
+
+    fs::write(&path, text)?;
+
+    let command = Some(command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_COMMAND.to_string());
+
+    let file = path.display().to_string();
+    let mut parts: Vec<String> = command
+        .split_whitespace()
+        .map(|part| part.replace("{file}", &file))
+        .collect();
+
+    if !command.contains("{file}") {
+        parts.push(file);
+    }
+
+    let mut child = Command::new(&parts[0])
+        .args(&parts[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("failed to run `{}`: {e}", parts[0]))?;
+
+    // Reaps the editor once it is closed
+    thread::spawn(move || child.wait());
+
+    Ok(path)
+}
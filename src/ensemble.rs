@@ -0,0 +1,94 @@
+use ollama_rs::models::LocalModel;
+
+use crate::compose::ComposedPrompt;
+
+/// Samples the same request several times with different seeds, optionally letting a judge pick the best
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct EnsembleSettings {
+    pub enabled: bool,
+    pub samples: usize,
+    /// Empty when all candidates are displayed instead of judged
+    pub judge_model: String,
+}
+
+impl Default for EnsembleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            samples: 3,
+            judge_model: String::new(),
+        }
+    }
+}
+
+/// One of the sampled answers, kept on the history entry
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EnsembleCandidate {
+    pub seed: i32,
+    pub output: String,
+}
+
+impl EnsembleSettings {
+    pub const SAMPLES_RANGE: std::ops::RangeInclusive<usize> = 2..=8;
+
+    pub fn resolve_judge(&self, available: &[LocalModel]) -> anyhow::Result<Option<LocalModel>> {
+        if self.judge_model.is_empty() {
+            return Ok(None);
+        }
+
+        available
+            .iter()
+            .find(|model| model.name == self.judge_model)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("model \"{}\" is not available", self.judge_model))
+    }
+}
+
+/// Shows every candidate when no judge picked one of them
+pub fn candidates_overview(candidates: &[EnsembleCandidate]) -> String {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, candidate)| {
+            format!(
+                "### Candidate {} (seed {})\n\n{}",
+                idx + 1,
+                candidate.seed,
+                candidate.output
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Asks the judge to pick the best candidate by its number
+pub fn judge_prompt(request: &ComposedPrompt, candidates: &[EnsembleCandidate]) -> ComposedPrompt {
+    let candidates = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, candidate)| format!("Candidate {}:\n{}", idx + 1, candidate.output))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    ComposedPrompt {
+        system: request.system.clone(),
+        prompt: format!(
+            "Several candidate answers were given to the following request.\n\n\
+             Request:\n{}\n\n\
+             {candidates}\n\n\
+             Reply with the number of the best candidate only.",
+            request.prompt
+        ),
+    }
+}
+
+/// Extracts the index of the candidate chosen by the judge from its reply
+pub fn parse_judge_choice(reply: &str, candidates: usize) -> Option<usize> {
+    reply
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse::<usize>().ok())
+        .find(|number| (1..=candidates).contains(number))
+        .map(|number| number - 1)
+}
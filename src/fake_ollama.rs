@@ -0,0 +1,310 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ollama_rs::models::LocalModel;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// Reply of the fake server to a generate or chat request
+#[derive(Clone, Debug)]
+pub enum FakeReply {
+    /// Streams the tokens, waiting `delay` before each of them
+    Stream {
+        tokens: Vec<String>,
+        delay: Duration,
+    },
+    /// Answers with the HTTP error status and the message as the body
+    Error { status: u16, message: String },
+}
+
+impl FakeReply {
+    pub fn stream(tokens: &[&str], delay: Duration) -> Self {
+        Self::Stream {
+            tokens: tokens.iter().map(ToString::to_string).collect(),
+            delay,
+        }
+    }
+}
+
+struct FakeState {
+    /// Path the API is served under, empty when at the root
+    prefix: String,
+    models: Vec<LocalModel>,
+    /// Replies given in order, the last one being repeated
+    replies: VecDeque<FakeReply>,
+    requests: Vec<serde_json::Value>,
+}
+
+impl FakeState {
+    fn next_reply(&mut self) -> FakeReply {
+        let reply = if self.replies.len() > 1 {
+            self.replies.pop_front()
+        } else {
+            self.replies.front().cloned()
+        };
+
+        reply.unwrap_or_else(|| FakeReply::stream(&[], Duration::ZERO))
+    }
+}
+
+/// Local HTTP server speaking the part of the Ollama protocol the app uses, for headless tests.
+///
+/// It lists the given models on `/api/tags` and answers `/api/generate` and `/api/chat` with the
+/// given replies, streaming them as newline-delimited JSON like Ollama does, the last chunk
+/// counting the tokens. The server stops when dropped.
+pub struct FakeOllama {
+    addr: SocketAddr,
+    state: Arc<Mutex<FakeState>>,
+    task: JoinHandle<()>,
+}
+
+impl FakeOllama {
+    pub async fn start(models: Vec<LocalModel>, replies: Vec<FakeReply>) -> io::Result<Self> {
+        Self::start_under("", models, replies).await
+    }
+
+    /// Serves the API under `prefix`, like a reverse proxy forwarding e.g. `/ollama` to Ollama
+    pub async fn start_under(
+        prefix: &str,
+        models: Vec<LocalModel>,
+        replies: Vec<FakeReply>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(FakeState {
+            prefix: prefix.trim_end_matches('/').to_string(),
+            models,
+            replies: replies.into(),
+            requests: Vec::new(),
+        }));
+
+        let task = tokio::spawn({
+            let state = state.clone();
+
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, state.clone()));
+                }
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    /// A model entry as listed by `/api/tags`
+    pub fn model(name: &str, size: u64) -> LocalModel {
+        LocalModel {
+            name: name.to_string(),
+            modified_at: "2024-01-01T00:00:00Z".to_string(),
+            size,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, lock(&self.state).prefix)
+    }
+
+    /// Bodies of the generate and chat requests received so far
+    pub fn generate_requests(&self) -> Vec<serde_json::Value> {
+        lock(&self.state).requests.clone()
+    }
+}
+
+impl Drop for FakeOllama {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Answers the requests of a kept-alive connection until the client closes it
+async fn serve(stream: TcpStream, state: Arc<Mutex<FakeState>>) {
+    let mut stream = BufReader::new(stream);
+
+    while let Ok(Some((path, body))) = read_request(&mut stream).await {
+        let prefix = lock(&state).prefix.clone();
+        let path = path.strip_prefix(&prefix).unwrap_or_default();
+        let result = match path {
+            "/api/tags" => {
+                let models = lock(&state).models.clone();
+                let body = serde_json::json!({ "models": models }).to_string();
+
+                write_response(stream.get_mut(), 200, &body).await
+            }
+            "/api/generate" | "/api/chat" => {
+                let is_chat = path == "/api/chat";
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                let is_streaming = request["stream"].as_bool().unwrap_or(true);
+                let model = request["model"].as_str().unwrap_or_default().to_string();
+                let reply = {
+                    let mut state = lock(&state);
+                    state.requests.push(request);
+                    state.next_reply()
+                };
+
+                write_generation(stream.get_mut(), &model, reply, is_streaming, is_chat).await
+            }
+            _ => write_response(stream.get_mut(), 404, "404 page not found").await,
+        };
+
+        if result.is_err() {
+            break;
+        }
+    }
+}
+
+fn lock(state: &Mutex<FakeState>) -> std::sync::MutexGuard<'_, FakeState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads the path and the body of the next request, `None` once the connection is closed
+async fn read_request(stream: &mut BufReader<TcpStream>) -> io::Result<Option<(String, Vec<u8>)>> {
+    let mut request_line = String::new();
+
+    if stream.read_line(&mut request_line).await? == 0 {
+        return Ok(None);
+    }
+
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    let mut content_length = 0;
+
+    loop {
+        let mut header = String::new();
+
+        if stream.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or_default();
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await?;
+
+    Ok(Some((path, body)))
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &str) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        reason(status),
+        body.len()
+    );
+
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.flush().await
+}
+
+async fn write_generation(
+    stream: &mut TcpStream,
+    model: &str,
+    reply: FakeReply,
+    is_streaming: bool,
+    is_chat: bool,
+) -> io::Result<()> {
+    let (tokens, delay) = match reply {
+        FakeReply::Stream { tokens, delay } => (tokens, delay),
+        FakeReply::Error { status, message } => {
+            let body = serde_json::json!({ "error": message }).to_string();
+
+            return write_response(stream, status, &body).await;
+        }
+    };
+
+    if !is_streaming {
+        let body = generation_chunk(model, &tokens.concat(), Some(tokens.len()), is_chat);
+
+        return write_response(stream, 200, &body).await;
+    }
+
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n",
+        )
+        .await?;
+
+    for token in &tokens {
+        tokio::time::sleep(delay).await;
+        write_chunk(stream, &generation_chunk(model, token, None, is_chat)).await?;
+    }
+
+    write_chunk(
+        stream,
+        &generation_chunk(model, "", Some(tokens.len()), is_chat),
+    )
+    .await?;
+    stream.write_all(b"0\r\n\r\n").await?;
+    stream.flush().await
+}
+
+async fn write_chunk(stream: &mut TcpStream, line: &str) -> io::Result<()> {
+    let line = format!("{line}\n");
+
+    stream
+        .write_all(format!("{:x}\r\n{line}\r\n", line.len()).as_bytes())
+        .await?;
+    stream.flush().await
+}
+
+/// A chunk of the answer, the last one with the count of the tokens streamed
+fn generation_chunk(
+    model: &str,
+    response: &str,
+    eval_count: Option<usize>,
+    is_chat: bool,
+) -> String {
+    let mut chunk = serde_json::json!({
+        "model": model,
+        "created_at": "2024-01-01T00:00:00Z",
+        "done": eval_count.is_some(),
+    });
+
+    if is_chat {
+        chunk["message"] = serde_json::json!({ "role": "assistant", "content": response });
+    } else {
+        chunk["response"] = serde_json::json!(response);
+    }
+
+    if let Some(eval_count) = eval_count {
+        for duration in [
+            "total_duration",
+            "load_duration",
+            "prompt_eval_duration",
+            "eval_duration",
+        ] {
+            chunk[duration] = serde_json::json!(0);
+        }
+
+        chunk["prompt_eval_count"] = serde_json::json!(0);
+        chunk["eval_count"] = serde_json::json!(eval_count);
+    }
+
+    chunk.to_string()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
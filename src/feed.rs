@@ -0,0 +1,326 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use chrono::{DateTime, FixedOffset, Local};
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::mail;
+
+/// New items of a feed taken into a briefing at most, the newest ones
+pub const ITEMS_PER_FEED: usize = 10;
+
+/// An RSS or Atom feed a prompt makes briefings of
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct FeedSubscription {
+    pub url: String,
+    /// Title of the feed as it was last fetched, the URL until then
+    pub title: String,
+    /// Identifiers of the items of the last fetch, so that only the ones published since are new
+    seen: BTreeSet<String>,
+}
+
+impl FeedSubscription {
+    pub fn new(url: String) -> Self {
+        Self {
+            title: url.clone(),
+            url,
+            ..Default::default()
+        }
+    }
+
+    /// Takes the items of the fetched feed that weren't seen before, the newest first
+    pub fn take_new_items(&mut self, feed: Feed) -> Vec<FeedItem> {
+        if !feed.title.is_empty() {
+            self.title = feed.title;
+        }
+
+        let seen = std::mem::replace(
+            &mut self.seen,
+            feed.items.iter().map(|item| item.id.clone()).collect(),
+        );
+        let mut items: Vec<FeedItem> = feed
+            .items
+            .into_iter()
+            .filter(|item| !seen.contains(&item.id))
+            .collect();
+
+        items.sort_by_key(|item| std::cmp::Reverse(item.published));
+        items.truncate(ITEMS_PER_FEED);
+
+        items
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Feed {
+    pub title: String,
+    pub items: Vec<FeedItem>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeedItem {
+    /// The guid or id of the item, its link when it has none
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub published: Option<DateTime<FixedOffset>>,
+    /// The description or content, without HTML
+    pub summary: String,
+}
+
+impl FeedItem {
+    /// The item as an input, headed by its title, source and link
+    pub fn to_input(&self, feed_title: &str) -> String {
+        format!(
+            "Title: {}\nSource: {feed_title}\nLink: {}\n\n{}\n",
+            self.title, self.link, self.summary
+        )
+    }
+}
+
+/// Reads an RSS 2.0, RSS 1.0 or Atom feed
+pub fn parse_feed(xml: &str) -> anyhow::Result<Feed> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = Document::parse_with_options(xml, options)?;
+    let root = document.root_element();
+
+    match root.tag_name().name() {
+        "rss" => {
+            let channel = child(root, "channel")
+                .ok_or_else(|| anyhow::anyhow!("the RSS feed has no channel"))?;
+
+            Ok(Feed {
+                title: child_text(channel, "title"),
+                items: children(channel, "item").map(rss_item).collect(),
+            })
+        }
+        // RSS 1.0 lists the items next to the channel
+        "RDF" => Ok(Feed {
+            title: child(root, "channel")
+                .map_or_else(String::new, |channel| child_text(channel, "title")),
+            items: children(root, "item").map(rss_item).collect(),
+        }),
+        "feed" => Ok(Feed {
+            title: child_text(root, "title"),
+            items: children(root, "entry").map(atom_entry).collect(),
+        }),
+        name => anyhow::bail!("<{name}> is neither an RSS nor an Atom feed"),
+    }
+}
+
+/// Downloads and reads the feed at the URL
+pub async fn fetch_feed(client: &reqwest::Client, url: &str) -> anyhow::Result<Feed> {
+    let text = client
+        .get(url.trim())
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    parse_feed(&text)
+}
+
+fn rss_item(item: Node) -> FeedItem {
+    let link = child_text(item, "link");
+    let id = Some(child_text(item, "guid"))
+        .filter(|guid| !guid.is_empty())
+        .or_else(|| item.attribute("about").map(str::to_string))
+        .unwrap_or_else(|| link.clone());
+    // The full content if there's one, the description otherwise
+    let summary = Some(child_text(item, "encoded"))
+        .filter(|content| !content.is_empty())
+        .unwrap_or_else(|| child_text(item, "description"));
+
+    FeedItem {
+        id,
+        title: child_text(item, "title"),
+        link,
+        published: parse_date(&child_text(item, "pubDate"))
+            .or_else(|| parse_date(&child_text(item, "date"))),
+        summary: plain_text(&summary),
+    }
+}
+
+fn atom_entry(entry: Node) -> FeedItem {
+    let links: Vec<Node> = children(entry, "link").collect();
+    let link = links
+        .iter()
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .or(links.first())
+        .and_then(|link| link.attribute("href"))
+        .unwrap_or_default()
+        .to_string();
+    let summary = Some(child_text(entry, "summary"))
+        .filter(|summary| !summary.is_empty())
+        .unwrap_or_else(|| child_text(entry, "content"));
+
+    FeedItem {
+        id: Some(child_text(entry, "id"))
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| link.clone()),
+        title: child_text(entry, "title"),
+        link,
+        published: parse_date(&child_text(entry, "published"))
+            .or_else(|| parse_date(&child_text(entry, "updated"))),
+        summary: plain_text(&summary),
+    }
+}
+
+/// The child elements with the given name, whatever their namespace
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// The text of the first child element with the given name, empty when there's none
+fn child_text(node: Node, name: &'static str) -> String {
+    child(node, name).map_or_else(String::new, |child| {
+        child
+            .descendants()
+            .filter(Node::is_text)
+            .filter_map(|node| node.text())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    })
+}
+
+/// RSS 2.0 dates follow RFC 2822, the others RFC 3339
+fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+}
+
+/// The text of an HTML summary on as few lines as it needs
+fn plain_text(html: &str) -> String {
+    mail::strip_tags(html)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// New items of the feeds summarized one by one with a prompt, put together into a briefing once
+/// they are all summarized
+#[derive(Default)]
+pub struct Briefing {
+    /// New items still to summarize, with the title of their feed
+    queue: VecDeque<(String, FeedItem)>,
+    /// Summarized items with the creation time of their response, none until it's added
+    summaries: Vec<(String, FeedItem, Option<DateTime<Local>>)>,
+    /// Feeds that failed to be fetched, with the error
+    pub errors: Vec<(String, String)>,
+}
+
+impl Briefing {
+    pub fn new(items: Vec<(String, FeedItem)>, errors: Vec<(String, String)>) -> Self {
+        Self {
+            queue: items.into(),
+            errors,
+            ..Default::default()
+        }
+    }
+
+    pub fn has_items(&self) -> bool {
+        !self.queue.is_empty() || !self.summaries.is_empty()
+    }
+
+    /// Items still to summarize
+    pub fn queued_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn summarized_len(&self) -> usize {
+        self.summaries.len()
+    }
+
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Takes the next item to summarize as an input
+    pub fn next_input(&mut self) -> Option<String> {
+        let (feed_title, item) = self.queue.pop_front()?;
+        let input = item.to_input(&feed_title);
+
+        self.summaries.push((feed_title, item, None));
+
+        Some(input)
+    }
+
+    /// Records the response the last item taken is summarized in
+    pub fn record_response(&mut self, created_at: DateTime<Local>) {
+        if let Some((_, _, response @ None)) = self.summaries.last_mut() {
+            *response = Some(created_at);
+        }
+    }
+
+    /// Whether all the items are summarized, the last one taken having started generating
+    pub fn is_done(&self) -> bool {
+        self.queue.is_empty()
+            && self
+                .summaries
+                .last()
+                .is_none_or(|(_, _, created_at)| created_at.is_some())
+    }
+
+    /// The briefing in markdown, the summaries grouped by feed and headed by a link to their item,
+    /// with the outputs of the responses by their creation time
+    pub fn document<'a>(&self, output: impl Fn(DateTime<Local>) -> Option<&'a str>) -> String {
+        let mut document = format!("# Briefing of {}\n", Local::now().format("%Y-%m-%d"));
+        let mut feed_titles: Vec<&str> = Vec::new();
+
+        for (feed_title, _, _) in &self.summaries {
+            if !feed_titles.contains(&feed_title.as_str()) {
+                feed_titles.push(feed_title);
+            }
+        }
+
+        for feed_title in feed_titles {
+            document.push_str(&format!("\n## {feed_title}\n"));
+
+            for (_, item, created_at) in self
+                .summaries
+                .iter()
+                .filter(|(title, _, _)| title == feed_title)
+            {
+                let title = if item.title.is_empty() {
+                    "(untitled)"
+                } else {
+                    &item.title
+                };
+                let summary = created_at
+                    .and_then(&output)
+                    .filter(|output| !output.trim().is_empty())
+                    .unwrap_or("*No summary was generated.*");
+
+                if item.link.is_empty() {
+                    document.push_str(&format!("\n### {title}\n"));
+                } else {
+                    document.push_str(&format!("\n### [{title}]({})\n", item.link));
+                }
+
+                if let Some(published) = item.published {
+                    document.push_str(&format!("\n*{}*\n", published.format("%Y-%m-%d %H:%M")));
+                }
+
+                document.push_str(&format!("\n{}\n", summary.trim()));
+            }
+        }
+
+        document
+    }
+}
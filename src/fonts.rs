@@ -0,0 +1,147 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use egui::{FontData, FontDefinitions, FontFamily, TextStyle};
+
+use crate::settings::FontSettings;
+
+const UI_FONT: &str = "ui_font";
+const CODE_FONT: &str = "code_font";
+
+/// Sizes of the bundled egui styles, the other styles are scaled along with the body
+const DEFAULT_BODY_SIZE: f32 = 12.5;
+const DEFAULT_SMALL_SIZE: f32 = 9.0;
+const DEFAULT_HEADING_SIZE: f32 = 18.0;
+
+/// How deep the font directories are searched for an installed font
+const FONT_DIR_DEPTH: usize = 4;
+
+/// Installs the fonts, placed before the bundled ones so that these cover the glyphs they lack
+pub fn apply(ctx: &egui::Context, settings: &FontSettings) -> anyhow::Result<()> {
+    let mut fonts = FontDefinitions::default();
+
+    for (name, font, family) in [
+        (UI_FONT, &settings.ui_font, FontFamily::Proportional),
+        (CODE_FONT, &settings.code_font, FontFamily::Monospace),
+    ] {
+        if font.trim().is_empty() {
+            continue;
+        }
+
+        fonts.font_data.insert(
+            name.to_string(),
+            Arc::new(FontData::from_owned(load(font)?)),
+        );
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .insert(0, name.to_string());
+    }
+
+    ctx.set_fonts(fonts);
+
+    let scale = settings.ui_size / DEFAULT_BODY_SIZE;
+
+    ctx.all_styles_mut(|style| {
+        for (text_style, font_id) in style.text_styles.iter_mut() {
+            font_id.size = match text_style {
+                TextStyle::Small => DEFAULT_SMALL_SIZE * scale,
+                TextStyle::Heading => DEFAULT_HEADING_SIZE * scale,
+                TextStyle::Monospace => settings.code_size,
+                _ => settings.ui_size,
+            };
+        }
+    });
+
+    Ok(())
+}
+
+/// Reads a font file, or the file of an installed font found by its name
+fn load(font: &str) -> anyhow::Result<Vec<u8>> {
+    let font = font.trim();
+    let path = Path::new(font);
+
+    let path = if path.is_file() {
+        path.to_path_buf()
+    } else {
+        find_installed(font).ok_or_else(|| {
+            anyhow::anyhow!("\"{font}\" is neither a font file nor an installed font")
+        })?
+    };
+
+    fs::read(&path).map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))
+}
+
+/// Directories the system and the user install fonts to
+fn font_dirs() -> Vec<PathBuf> {
+    let mut font_dirs: Vec<PathBuf> = dirs::font_dir().into_iter().collect();
+
+    font_dirs.extend(
+        [
+            "/usr/share/fonts",
+            "/usr/local/share/fonts",
+            "/Library/Fonts",
+            "/System/Library/Fonts",
+            "C:\\Windows\\Fonts",
+        ]
+        .map(PathBuf::from),
+    );
+
+    font_dirs
+}
+
+/// Finds the TrueType or OpenType file named after a font, e.g. `DejaVuSans.ttf` or
+/// `DejaVuSans-Regular.ttf` for "DejaVu Sans"
+fn find_installed(name: &str) -> Option<PathBuf> {
+    let name = normalize(name);
+
+    font_dirs()
+        .iter()
+        .find_map(|dir| find_in(dir, &name, FONT_DIR_DEPTH))
+}
+
+fn find_in(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+
+    for path in fs::read_dir(dir).ok()?.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            subdirs.push(path);
+            continue;
+        }
+
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("ttf") || extension.eq_ignore_ascii_case("otf")
+            });
+        let stem = path
+            .file_stem()
+            .map(|stem| normalize(&stem.to_string_lossy()))
+            .unwrap_or_default();
+
+        if is_font && (stem == name || stem == format!("{name}regular")) {
+            return Some(path);
+        }
+    }
+
+    if depth == 0 {
+        return None;
+    }
+
+    subdirs
+        .iter()
+        .find_map(|subdir| find_in(subdir, name, depth - 1))
+}
+
+/// Lowercase letters and digits of a font name, so that spaces and dashes don't matter
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
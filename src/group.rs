@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use crate::prompt::Prompt;
+
+/// A collapsible section of the prompt list, the prompts refer to it by name
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PromptGroup {
+    pub name: String,
+    pub collapsed: bool,
+}
+
+impl PromptGroup {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            collapsed: false,
+        }
+    }
+}
+
+/// A row of the prompt list, which is virtualized so headers and prompts share the same height
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptListRow {
+    /// Index of the group
+    Group(usize),
+    /// Index of the prompt
+    Prompt(usize),
+}
+
+/// Adds the groups the prompts were assigned to without being listed yet, e.g. after an import or
+/// a sync, after the others
+pub fn add_missing(groups: &mut Vec<PromptGroup>, prompts: &[Prompt]) {
+    let mut known: HashSet<String> = groups.iter().map(|group| group.name.clone()).collect();
+
+    for prompt in prompts {
+        if !prompt.group.is_empty() && known.insert(prompt.group.clone()) {
+            groups.push(PromptGroup::new(&prompt.group));
+        }
+    }
+}
+
+/// Lays out the listed prompts, in their order, with the ungrouped ones first and the others
+/// under the header of their group. When `filtered`, e.g. while searching, the groups without a
+/// listed prompt are left out and the collapsed ones are expanded to show the matches.
+pub fn rows(
+    groups: &[PromptGroup],
+    prompts: &[Prompt],
+    indices: &[usize],
+    filtered: bool,
+) -> Vec<PromptListRow> {
+    let mut rows: Vec<PromptListRow> = indices
+        .iter()
+        .filter(|&&idx| prompts[idx].group.is_empty())
+        .map(|&idx| PromptListRow::Prompt(idx))
+        .collect();
+
+    for (group_idx, group) in groups.iter().enumerate() {
+        let members: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|&idx| prompts[idx].group == group.name)
+            .collect();
+
+        if filtered && members.is_empty() {
+            continue;
+        }
+
+        rows.push(PromptListRow::Group(group_idx));
+
+        if !group.collapsed || filtered {
+            rows.extend(members.into_iter().map(PromptListRow::Prompt));
+        }
+    }
+
+    rows
+}
+
+/// Moves the group to the position of another one, shifting the groups in between
+pub fn move_group(groups: &mut Vec<PromptGroup>, from: usize, to: usize) {
+    if from < groups.len() && to < groups.len() {
+        let group = groups.remove(from);
+
+        groups.insert(to, group);
+    }
+}
+
+/// Removes the group, its prompts stay in the list without a group
+pub fn remove_group(groups: &mut Vec<PromptGroup>, prompts: &mut [Prompt], idx: usize) {
+    if idx >= groups.len() {
+        return;
+    }
+
+    let group = groups.remove(idx);
+
+    for prompt in prompts
+        .iter_mut()
+        .filter(|prompt| prompt.group == group.name)
+    {
+        prompt.group.clear();
+    }
+}
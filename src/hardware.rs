@@ -0,0 +1,174 @@
+use std::process::Command;
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+/// Share of the RAM a model may take when it runs on the CPU, the rest is left to the system
+const RAM_SHARE_FOR_MODELS: f64 = 0.6;
+
+/// Memory of the machine, `None` when it couldn't be detected
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hardware {
+    pub ram_bytes: Option<u64>,
+    /// Memory of the largest NVIDIA GPU, Apple silicon shares the RAM with its GPU instead
+    pub vram_bytes: Option<u64>,
+}
+
+impl Hardware {
+    /// Reads the memory of the machine, running `nvidia-smi` for the GPU when it is installed
+    pub fn detect() -> Self {
+        Self {
+            ram_bytes: detect_ram(),
+            vram_bytes: detect_vram(),
+        }
+    }
+
+    /// The memory as told to the user, e.g. "16 GB of RAM and a GPU with 8 GB"
+    pub fn describe(&self) -> Option<String> {
+        let gb = |bytes: u64| (bytes as f64 / GB as f64).round();
+
+        match (self.ram_bytes, self.vram_bytes) {
+            (Some(ram), Some(vram)) => Some(format!(
+                "{} GB of RAM and a GPU with {} GB",
+                gb(ram),
+                gb(vram)
+            )),
+            (Some(ram), None) => Some(format!("{} GB of RAM", gb(ram))),
+            (None, Some(vram)) => Some(format!("a GPU with {} GB", gb(vram))),
+            (None, None) => None,
+        }
+    }
+
+    /// Memory a model can be loaded into, the GPU memory when it has more than the share of the RAM
+    pub fn model_memory_gb(&self) -> Option<f64> {
+        let ram = self
+            .ram_bytes
+            .map(|bytes| bytes as f64 / GB as f64 * RAM_SHARE_FOR_MODELS);
+        let vram = self.vram_bytes.map(|bytes| bytes as f64 / GB as f64);
+
+        match (ram, vram) {
+            (Some(ram), Some(vram)) => Some(ram.max(vram)),
+            (ram, vram) => ram.or(vram),
+        }
+    }
+}
+
+/// A model of the Ollama library suggested to newcomers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecommendedModel {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub download_gb: f64,
+    /// Memory it needs to answer at a usable speed
+    pub memory_gb: f64,
+}
+
+/// From the smallest to the largest
+pub const RECOMMENDED_MODELS: [RecommendedModel; 6] = [
+    RecommendedModel {
+        name: "llama3.2:1b",
+        description: "Fast and light, for short and simple tasks",
+        download_gb: 1.3,
+        memory_gb: 2.0,
+    },
+    RecommendedModel {
+        name: "llama3.2:3b",
+        description: "A good default for laptops",
+        download_gb: 2.0,
+        memory_gb: 4.0,
+    },
+    RecommendedModel {
+        name: "qwen2.5:7b",
+        description: "Capable all-rounder, good at code and other languages",
+        download_gb: 4.7,
+        memory_gb: 8.0,
+    },
+    RecommendedModel {
+        name: "qwen2.5:14b",
+        description: "Stronger reasoning, needs a GPU or plenty of RAM",
+        download_gb: 9.0,
+        memory_gb: 14.0,
+    },
+    RecommendedModel {
+        name: "qwen2.5:32b",
+        description: "Close to the hosted models, for workstations",
+        download_gb: 20.0,
+        memory_gb: 24.0,
+    },
+    RecommendedModel {
+        name: "llama3.3:70b",
+        description: "The largest one, for multi-GPU machines",
+        download_gb: 43.0,
+        memory_gb: 48.0,
+    },
+];
+
+/// The models that fit into the memory, with the largest of them recommended first
+pub fn recommend(hardware: &Hardware) -> Vec<RecommendedModel> {
+    let Some(memory_gb) = hardware.model_memory_gb() else {
+        // Without knowing the memory, the smaller models are the safe bet
+        return RECOMMENDED_MODELS[..2].iter().rev().copied().collect();
+    };
+
+    RECOMMENDED_MODELS
+        .iter()
+        .rev()
+        .filter(|model| model.memory_gb <= memory_gb)
+        .copied()
+        .collect()
+}
+
+fn detect_ram() -> Option<u64> {
+    if cfg!(target_os = "linux") {
+        let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let kb = meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+
+        Some(kb * 1024)
+    } else if cfg!(target_os = "macos") {
+        run(Command::new("sysctl").args(["-n", "hw.memsize"]))?
+            .trim()
+            .parse()
+            .ok()
+    } else if cfg!(target_os = "windows") {
+        run(Command::new("powershell").args([
+            "-NoProfile",
+            "-Command",
+            "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory",
+        ]))?
+        .trim()
+        .parse()
+        .ok()
+    } else {
+        None
+    }
+}
+
+fn detect_vram() -> Option<u64> {
+    let output = run(Command::new("nvidia-smi")
+        .args(["--query-gpu=memory.total", "--format=csv,noheader,nounits"]))?;
+
+    // In MiB, one line per GPU
+    output
+        .lines()
+        .filter_map(|line| line.trim().parse::<u64>().ok())
+        .max()
+        .map(|mib| mib * 1024 * 1024)
+}
+
+fn run(command: &mut Command) -> Option<String> {
+    let output = command
+        .output()
+        .inspect_err(|e| log::debug!("Failed to run {command:?}: {e}"))
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,485 @@
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::{
+    compare::ComparedAnswer, debate::DebateAnswer, ensemble::EnsembleCandidate, judge::JudgeScore,
+    script, snippet::SnippetRun,
+};
+
+/// Average silent reading speed, to estimate how long the responses take to read
+const WORDS_PER_MINUTE: usize = 230;
+
+/// One exchange of a prompt history, the newest response being the first in the history
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PromptResponse {
+    pub input: String,
+    pub output: String,
+    pub local_model_name: String,
+    #[serde(skip)]
+    pub requested_at: Instant,
+    pub created_at: DateTime<Local>,
+    /// Synthesized speech of the output, cached on disk
+    pub audio: Option<PathBuf>,
+    /// The first pass of a response revised after self-critique
+    pub draft: Option<String>,
+    /// Answers of the debating models the output was synthesized from
+    pub debate: Vec<DebateAnswer>,
+    /// Answers of the compared models, shown side by side, the output holding all of them
+    pub comparison: Vec<ComparedAnswer>,
+    /// Answers sampled with different seeds, the output being the chosen one or all of them
+    pub candidates: Vec<EnsembleCandidate>,
+    pub selected_candidate: Option<usize>,
+    pub score: Option<JudgeScore>,
+    /// Entry this one was regenerated from or asked about, by its creation time
+    pub parent: Option<DateTime<Local>>,
+    /// Outputs of the code snippets of the response that were run, the latest last
+    pub snippet_runs: Vec<SnippetRun>,
+    /// Starred inputs are re-run as a benchmark when the prompt content changes
+    pub starred: bool,
+    /// The generation was cancelled, the output being what was received until then
+    pub cancelled: bool,
+    /// Values the `{{name}}` placeholders of the content were filled with
+    pub variables: BTreeMap<String, String>,
+    /// Speed of the generation, when the output was streamed
+    pub generation: Option<GenerationStats>,
+    /// Token counts and durations the server reported at the end of the generation
+    pub metrics: Option<ModelMetrics>,
+    /// Why the generation ended, when it is known
+    pub stop_reason: Option<StopReason>,
+    /// Kept in memory only, the exchange is never saved
+    #[serde(skip)]
+    pub incognito: bool,
+    /// Outputs of the earlier generations of the entry, the oldest first and the output being
+    /// the latest version
+    pub versions: Vec<String>,
+    /// Older version shown instead of the output while flipping through the versions
+    #[serde(skip)]
+    pub shown_version: Option<usize>,
+    /// Highlighted passages of the output with a comment, for reviewing it
+    pub annotations: Vec<Annotation>,
+    /// Name of the document of the watched folder the response processed
+    pub source_file: Option<String>,
+    /// Conversation the response belongs to, by its creation time
+    pub chat_session: Option<DateTime<Local>>,
+}
+
+impl Default for PromptResponse {
+    fn default() -> Self {
+        Self {
+            input: Default::default(),
+            output: Default::default(),
+            local_model_name: "unknown_model".to_owned(),
+            requested_at: Instant::now(),
+            created_at: Local::now(),
+            audio: None,
+            draft: None,
+            debate: Vec::new(),
+            comparison: Vec::new(),
+            candidates: Vec::new(),
+            selected_candidate: None,
+            score: None,
+            parent: None,
+            snippet_runs: Vec::new(),
+            starred: false,
+            cancelled: false,
+            variables: BTreeMap::new(),
+            generation: None,
+            metrics: None,
+            stop_reason: None,
+            incognito: false,
+            versions: Vec::new(),
+            shown_version: None,
+            annotations: Vec::new(),
+            source_file: None,
+            chat_session: None,
+        }
+    }
+}
+
+impl PromptResponse {
+    pub fn new(input: String, output: String, local_model_name: String) -> Self {
+        Self {
+            input,
+            output,
+            local_model_name,
+            ..Default::default()
+        }
+    }
+
+    pub fn word_count(&self) -> usize {
+        word_count(&self.output)
+    }
+
+    /// Takes over the output of a regeneration, keeping the previous output as an older version
+    pub fn push_version(&mut self, regenerated: PromptResponse) {
+        let mut versions = std::mem::take(&mut self.versions);
+        versions.push(std::mem::take(&mut self.output));
+
+        *self = PromptResponse {
+            input: std::mem::take(&mut self.input),
+            created_at: self.created_at,
+            parent: self.parent,
+            starred: self.starred,
+            variables: std::mem::take(&mut self.variables),
+            versions,
+            shown_version: None,
+            // Kept for the passages the new version still contains
+            annotations: std::mem::take(&mut self.annotations),
+            chat_session: self.chat_session,
+            ..regenerated
+        };
+    }
+
+    /// Why the generation ended, the responses cancelled before the reasons were recorded included
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+            .clone()
+            .or_else(|| self.cancelled.then_some(StopReason::Cancelled))
+    }
+
+    /// Deletes the files of a response that is removed for good
+    pub fn discard(self) {
+        if let Some(audio) = self.audio {
+            let _ = std::fs::remove_file(audio);
+        }
+    }
+
+    pub fn version_count(&self) -> usize {
+        self.versions.len() + 1
+    }
+
+    /// Whether it's the plain answer of a single model, shown in its latest version, which the
+    /// model can be asked to continue
+    pub fn can_continue(&self) -> bool {
+        !self.output.is_empty()
+            && self.debate.is_empty()
+            && self.comparison.is_empty()
+            && self.candidates.is_empty()
+            && self.shown_version.is_none()
+    }
+
+    /// The output of the version being looked at, the latest one unless flipped back
+    pub fn shown_output(&self) -> &str {
+        self.shown_version
+            .and_then(|version| self.versions.get(version))
+            .unwrap_or(&self.output)
+    }
+
+    /// The input and the output in markdown, like an entry of a transcript
+    pub fn to_markdown(&self) -> String {
+        format!(
+            "**Q:** {}\n\n**A:**\n\n{}\n",
+            self.input.trim(),
+            self.output.trim()
+        )
+    }
+}
+
+/// Color of a highlighted passage, telling the kinds of comments apart
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HighlightColor {
+    #[default]
+    Yellow,
+    Green,
+    Blue,
+    Red,
+}
+
+impl HighlightColor {
+    pub const ALL: [Self; 4] = [Self::Yellow, Self::Green, Self::Blue, Self::Red];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Red => "red",
+        }
+    }
+}
+
+/// A highlighted passage of an output with a comment
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Annotation {
+    /// Position of the passage in the output, in characters
+    pub start: usize,
+    pub end: usize,
+    /// The passage itself, to find it again in an output that changed
+    pub passage: String,
+    pub comment: String,
+    pub color: HighlightColor,
+}
+
+impl Annotation {
+    /// Byte range of the passage in the output, at its position or else at its first occurrence,
+    /// none when the output no longer contains it
+    pub fn byte_range(&self, output: &str) -> Option<Range<usize>> {
+        let byte_at = |chars: usize| {
+            output
+                .char_indices()
+                .map(|(idx, _)| idx)
+                .chain(std::iter::once(output.len()))
+                .nth(chars)
+        };
+
+        if let (Some(start), Some(end)) = (byte_at(self.start), byte_at(self.end))
+            && output.get(start..end) == Some(self.passage.as_str())
+        {
+            return Some(start..end);
+        }
+
+        output
+            .find(&self.passage)
+            .filter(|_| !self.passage.is_empty())
+            .map(|start| start..start + self.passage.len())
+    }
+}
+
+/// Non-overlapping byte ranges of the annotations found in the output, in order, with the index
+/// of their annotation. A passage overlapping an earlier one is only highlighted past it.
+pub fn annotated_ranges(output: &str, annotations: &[Annotation]) -> Vec<(Range<usize>, usize)> {
+    let mut ranges: Vec<(Range<usize>, usize)> = annotations
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, annotation)| Some((annotation.byte_range(output)?, idx)))
+        .collect();
+
+    ranges.sort_by_key(|(range, _)| range.start);
+
+    let mut end = 0;
+
+    ranges
+        .into_iter()
+        .filter_map(|(range, idx)| {
+            let start = range.start.max(end);
+
+            (start < range.end).then(|| {
+                end = range.end;
+                (start..range.end, idx)
+            })
+        })
+        .collect()
+}
+
+/// Speed of a streamed generation
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct GenerationStats {
+    /// Streamed chunks, Ollama streams one token per chunk
+    pub tokens: usize,
+    /// From the request to the last token
+    pub elapsed_secs: f64,
+    /// From the first token to the last, without the model loading and the prompt evaluation
+    pub streaming_secs: f64,
+}
+
+impl GenerationStats {
+    pub fn tokens_per_sec(&self) -> Option<f64> {
+        (self.tokens > 1 && self.streaming_secs > 0.0)
+            .then(|| (self.tokens - 1) as f64 / self.streaming_secs)
+    }
+
+    /// E.g. "12.4 tok/s · 3.1 s · 450 tokens"
+    pub fn summary(&self) -> String {
+        let timing = format!("{:.1} s · {} tokens", self.elapsed_secs, self.tokens);
+
+        match self.tokens_per_sec() {
+            Some(tokens_per_sec) => format!("{tokens_per_sec:.1} tok/s · {timing}"),
+            None => timing,
+        }
+    }
+}
+
+/// Why a generation ended
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    /// The model ended the answer
+    Stop,
+    /// The answer reached a stop sequence, told apart from the natural end by some servers only
+    StopSequence,
+    /// The answer was cut off by the length limit
+    Length,
+    Cancelled,
+    /// The stream failed after part of the answer arrived, with the error
+    Error(String),
+}
+
+impl StopReason {
+    /// E.g. "✂ length limit"
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Self::Stop => "✔ stopped",
+            Self::StopSequence => "⛔ stop sequence",
+            Self::Length => "✂ length limit",
+            Self::Cancelled => "⏹ cancelled",
+            Self::Error(_) => "⚠ error",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::Stop => "The model ended the answer".to_string(),
+            Self::StopSequence => "The answer reached a stop sequence".to_string(),
+            Self::Length => "The answer was cut off by the length limit".to_string(),
+            Self::Cancelled => "The generation was cancelled, the output is partial".to_string(),
+            Self::Error(e) => format!("The generation failed, the output is partial.\n\n{e}"),
+        }
+    }
+}
+
+/// Token counts and durations reported by the server once a generation is done, the durations
+/// in nanoseconds
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct ModelMetrics {
+    /// Tokens of the prompt that were evaluated, the ones cached from an earlier request aside
+    pub prompt_eval_count: u64,
+    /// Tokens of the output
+    pub eval_count: u64,
+    /// From the request to the last token, loading the model included
+    pub total_duration: u64,
+    pub load_duration: u64,
+    pub prompt_eval_duration: u64,
+    pub eval_duration: u64,
+}
+
+impl ModelMetrics {
+    /// Adds up the work of an answer generated in several requests
+    pub fn add(&mut self, other: &ModelMetrics) {
+        self.prompt_eval_count += other.prompt_eval_count;
+        self.eval_count += other.eval_count;
+        self.total_duration += other.total_duration;
+        self.load_duration += other.load_duration;
+        self.prompt_eval_duration += other.prompt_eval_duration;
+        self.eval_duration += other.eval_duration;
+    }
+
+    /// Output tokens per second, as measured by the server
+    pub fn eval_rate(&self) -> Option<f64> {
+        (self.eval_duration > 0).then(|| self.eval_count as f64 / secs(self.eval_duration))
+    }
+}
+
+/// Seconds of a duration in nanoseconds
+pub fn secs(nanos: u64) -> f64 {
+    Duration::from_nanos(nanos).as_secs_f64()
+}
+
+/// Measures a generation while its chunks stream in
+#[derive(Debug)]
+pub struct GenerationTimer {
+    started_at: Instant,
+    first_chunk_at: Option<Instant>,
+    chunks: usize,
+}
+
+impl Default for GenerationTimer {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            first_chunk_at: None,
+            chunks: 0,
+        }
+    }
+}
+
+impl GenerationTimer {
+    pub fn chunk(&mut self) {
+        self.first_chunk_at.get_or_insert_with(Instant::now);
+        self.chunks += 1;
+    }
+
+    pub fn finish(&self) -> GenerationStats {
+        GenerationStats {
+            tokens: self.chunks,
+            elapsed_secs: self.started_at.elapsed().as_secs_f64(),
+            streaming_secs: self
+                .first_chunk_at
+                .map_or(0.0, |first_chunk_at| first_chunk_at.elapsed().as_secs_f64()),
+        }
+    }
+}
+
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Estimated time to read the given number of words, e.g. "3 min read"
+pub fn reading_time(words: usize) -> String {
+    if words < WORDS_PER_MINUTE {
+        "< 1 min read".to_string()
+    } else {
+        format!("{} min read", words.div_ceil(WORDS_PER_MINUTE))
+    }
+}
+
+/// Serializes the history without the incognito exchanges, so that they are never saved
+pub fn serialize_saved<S: serde::Serializer>(
+    history: &VecDeque<PromptResponse>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(history.iter().filter(|response| !response.incognito))
+}
+
+/// Hashes of the exchanges in the history
+pub fn exchange_hashes(history: &VecDeque<PromptResponse>) -> HashSet<u64> {
+    history
+        .iter()
+        .map(|response| script::exchange_hash(&response.input, &response.output))
+        .collect()
+}
+
+/// Adds responses not in the history yet, keeping the newest response first
+pub fn extend(
+    history: &mut VecDeque<PromptResponse>,
+    responses: impl IntoIterator<Item = PromptResponse>,
+) {
+    let mut hashes = exchange_hashes(history);
+
+    history.extend(responses.into_iter().filter(|response| {
+        hashes.insert(script::exchange_hash(&response.input, &response.output))
+    }));
+    history
+        .make_contiguous()
+        .sort_by_key(|response| std::cmp::Reverse(response.created_at));
+}
+
+/// Formats the responses as a markdown transcript, in the order they are given
+pub fn transcript<'a>(title: &str, responses: impl Iterator<Item = &'a PromptResponse>) -> String {
+    let mut transcript = format!("# {title}\n");
+
+    for response in responses {
+        transcript.push_str(&format!(
+            "\n**Q:** {}\n\n**A** ({}, {}):\n\n{}\n\n---\n",
+            response.input.trim(),
+            response.local_model_name,
+            response.created_at.format("%Y-%m-%d %H:%M"),
+            response.output.trim()
+        ));
+    }
+
+    transcript
+}
+
+/// Counts the responses generated each day
+pub fn activity_by_day<'a>(
+    responses: impl Iterator<Item = &'a PromptResponse>,
+) -> BTreeMap<NaiveDate, usize> {
+    let mut activity = BTreeMap::new();
+
+    for response in responses {
+        *activity
+            .entry(response.created_at.date_naive())
+            .or_default() += 1;
+    }
+
+    activity
+}
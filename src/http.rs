@@ -0,0 +1,68 @@
+use std::fs;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+
+use crate::settings::{EndpointTls, ExtraHeader, Settings};
+
+/// Builds the HTTP client of a provider endpoint.
+///
+/// Without an explicit proxy the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables
+/// are respected.
+pub fn client(
+    settings: &Settings,
+    tls: &EndpointTls,
+    headers: &[ExtraHeader],
+) -> anyhow::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .danger_accept_invalid_certs(tls.accept_invalid_certs)
+        .default_headers(header_map(headers)?);
+
+    let ca_bundle = tls.ca_bundle.trim();
+
+    if !ca_bundle.is_empty() {
+        let pem = fs::read(ca_bundle)
+            .map_err(|e| anyhow::anyhow!("failed to read the CA bundle \"{ca_bundle}\": {e}"))?;
+
+        for certificate in reqwest::Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    let proxy_url = settings.proxy_url.trim();
+
+    if !proxy_url.is_empty() {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| anyhow::anyhow!("invalid proxy URL \"{proxy_url}\": {e}"))?;
+
+        builder = builder.proxy(proxy);
+    }
+
+    Ok(builder.build()?)
+}
+
+fn header_map(headers: &[ExtraHeader]) -> anyhow::Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+
+    for header in headers
+        .iter()
+        .filter(|header| !header.name.trim().is_empty())
+    {
+        let name = HeaderName::from_bytes(header.name.trim().as_bytes())
+            .map_err(|e| anyhow::anyhow!("invalid header name \"{}\": {e}", header.name))?;
+        let value = match header.value.trim().strip_prefix("Basic ") {
+            // Credentials given in plain text, the encoded form never contains a colon
+            Some(credentials) if name == AUTHORIZATION && credentials.contains(':') => {
+                format!("Basic {}", STANDARD.encode(credentials))
+            }
+            _ => header.value.trim().to_string(),
+        };
+        let mut value = HeaderValue::from_str(&value)
+            .map_err(|e| anyhow::anyhow!("invalid value of the header \"{name}\": {e}"))?;
+
+        value.set_sensitive(true);
+        map.append(name, value);
+    }
+
+    Ok(map)
+}
@@ -0,0 +1,619 @@
+use std::f32::consts::TAU;
+
+use egui::{
+    Color32, Id, InnerResponse, Pos2, Rect, Response, Sense, Shape, Stroke, StrokeKind, TextStyle,
+    TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText, WidgetType, menu::BarState, pos2, vec2,
+};
+
+/// Stroke width of the icons, relative to their size
+const STROKE_WIDTH: f32 = 0.1;
+
+/// Icons drawn as vector shapes, so that they scale with the text and take the colors of the theme
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Icon {
+    Add,
+    Remove,
+    Edit,
+    Reload,
+    Model,
+    Settings,
+    Stop,
+    Play,
+    Pause,
+    SkipForward,
+    Continue,
+    Previous,
+    Next,
+    Expand,
+    Star,
+    Starred,
+    Save,
+    Document,
+    Card,
+    Import,
+    Download,
+    Copy,
+    Quote,
+    Highlight,
+    Speak,
+    Judge,
+    Patch,
+    Clock,
+    Cut,
+    Library,
+    Maintenance,
+    Sync,
+    Accept,
+    Back,
+    Incognito,
+    Folder,
+    Mail,
+    Feed,
+    Menu,
+    Archive,
+    Merge,
+    Restore,
+    Delete,
+}
+
+impl Icon {
+    /// Name of the icon, read by screen readers and shown in accessibility mode
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Add => "Add",
+            Self::Remove => "Remove",
+            Self::Edit => "Edit",
+            Self::Reload => "Reload",
+            Self::Model => "Model",
+            Self::Settings => "Settings",
+            Self::Stop => "Stop",
+            Self::Play => "Play",
+            Self::Pause => "Pause",
+            Self::SkipForward => "Skip forward",
+            Self::Continue => "Continue",
+            Self::Previous => "Previous",
+            Self::Next => "Next",
+            Self::Expand => "Expand",
+            Self::Star => "Star",
+            Self::Starred => "Starred",
+            Self::Save => "Save",
+            Self::Document => "Document",
+            Self::Card => "Card",
+            Self::Import => "Import",
+            Self::Download => "Download",
+            Self::Copy => "Copy",
+            Self::Quote => "Quote",
+            Self::Highlight => "Highlight",
+            Self::Speak => "Speak",
+            Self::Judge => "Judge",
+            Self::Patch => "Patch",
+            Self::Clock => "Clock",
+            Self::Cut => "Cut",
+            Self::Library => "Library",
+            Self::Maintenance => "Maintenance",
+            Self::Sync => "Sync",
+            Self::Accept => "Accept",
+            Self::Back => "Back",
+            Self::Incognito => "Incognito",
+            Self::Folder => "Folder",
+            Self::Mail => "Mail",
+            Self::Feed => "Feed",
+            Self::Menu => "Menu",
+            Self::Archive => "Archive",
+            Self::Merge => "Merge",
+            Self::Restore => "Restore",
+            Self::Delete => "Delete",
+        }
+    }
+
+    /// Paints the icon into the square rectangle
+    pub fn paint(self, ui: &Ui, rect: Rect, color: Color32) {
+        let size = rect.width();
+        let stroke = Stroke::new((size * STROKE_WIDTH).max(1.0), color);
+        // Position relative to the rectangle, from 0.0 to 1.0 on both axes
+        let at = |x: f32, y: f32| rect.min + vec2(x, y) * size;
+        let painter = ui.painter();
+        let line = |points: &[(f32, f32)]| {
+            painter.add(Shape::line(
+                points.iter().map(|&(x, y)| at(x, y)).collect(),
+                stroke,
+            ));
+        };
+        let outline = |points: &[(f32, f32)]| {
+            painter.add(Shape::closed_line(
+                points.iter().map(|&(x, y)| at(x, y)).collect(),
+                stroke,
+            ));
+        };
+        // Convex polygons only
+        let fill = |points: &[(f32, f32)]| {
+            painter.add(Shape::convex_polygon(
+                points.iter().map(|&(x, y)| at(x, y)).collect(),
+                color,
+                Stroke::NONE,
+            ));
+        };
+        let frame = |min: (f32, f32), max: (f32, f32)| {
+            painter.rect_stroke(
+                Rect::from_min_max(at(min.0, min.1), at(max.0, max.1)),
+                size * 0.06,
+                stroke,
+                StrokeKind::Middle,
+            );
+        };
+        let block = |min: (f32, f32), max: (f32, f32)| {
+            painter.rect_filled(
+                Rect::from_min_max(at(min.0, min.1), at(max.0, max.1)),
+                0.0,
+                color,
+            );
+        };
+
+        match self {
+            Self::Add => {
+                painter.line_segment([at(0.5, 0.15), at(0.5, 0.85)], stroke);
+                painter.line_segment([at(0.15, 0.5), at(0.85, 0.5)], stroke);
+            }
+            Self::Remove => {
+                painter.line_segment([at(0.2, 0.2), at(0.8, 0.8)], stroke);
+                painter.line_segment([at(0.8, 0.2), at(0.2, 0.8)], stroke);
+            }
+            Self::Edit => {
+                let tip = at(0.15, 0.85);
+                let along = vec2(1.0, -1.0).normalized() * size;
+                let across = vec2(1.0, 1.0).normalized() * size * 0.12;
+                let neck = tip + along * 0.22;
+                let end = tip + along * 0.95;
+
+                painter.add(Shape::closed_line(
+                    vec![
+                        tip,
+                        neck + across,
+                        end + across,
+                        end - across,
+                        neck - across,
+                    ],
+                    stroke,
+                ));
+                painter.line_segment([neck + across, neck - across], stroke);
+            }
+            Self::Reload => {
+                let center = rect.center();
+                let radius = size * 0.32;
+                let end = TAU * 0.85;
+
+                let tangent = Vec2::angled(end + TAU / 4.0) * size;
+                let radial = Vec2::angled(end) * size;
+                let tip = center + Vec2::angled(end) * radius;
+
+                painter.add(Shape::line(arc(center, radius, TAU * 0.1, end), stroke));
+                painter.add(Shape::convex_polygon(
+                    vec![
+                        tip + tangent * 0.2,
+                        tip + radial * 0.14,
+                        tip - radial * 0.14,
+                    ],
+                    color,
+                    Stroke::NONE,
+                ));
+            }
+            Self::Model => {
+                frame((0.12, 0.18), (0.88, 0.66));
+                line(&[(0.5, 0.66), (0.5, 0.82)]);
+                line(&[(0.3, 0.82), (0.7, 0.82)]);
+            }
+            Self::Settings => {
+                let center = rect.center();
+                let teeth = Stroke::new(size * 0.14, color);
+
+                painter.circle_stroke(center, size * 0.22, stroke);
+
+                for tooth in 0..8 {
+                    let direction = Vec2::angled(TAU * tooth as f32 / 8.0) * size;
+                    painter.line_segment(
+                        [center + direction * 0.28, center + direction * 0.42],
+                        teeth,
+                    );
+                }
+            }
+            Self::Stop => block((0.25, 0.25), (0.75, 0.75)),
+            Self::Play => fill(&[(0.28, 0.2), (0.8, 0.5), (0.28, 0.8)]),
+            Self::Pause => {
+                block((0.28, 0.2), (0.42, 0.8));
+                block((0.58, 0.2), (0.72, 0.8));
+            }
+            Self::SkipForward => {
+                fill(&[(0.2, 0.2), (0.62, 0.5), (0.2, 0.8)]);
+                block((0.66, 0.2), (0.78, 0.8));
+            }
+            Self::Continue => {
+                fill(&[(0.12, 0.22), (0.5, 0.5), (0.12, 0.78)]);
+                fill(&[(0.5, 0.22), (0.88, 0.5), (0.5, 0.78)]);
+            }
+            Self::Previous => line(&[(0.62, 0.2), (0.34, 0.5), (0.62, 0.8)]),
+            Self::Next => line(&[(0.38, 0.2), (0.66, 0.5), (0.38, 0.8)]),
+            Self::Expand => line(&[(0.2, 0.38), (0.5, 0.66), (0.8, 0.38)]),
+            Self::Star | Self::Starred => {
+                let center = rect.center();
+                let points: Vec<Pos2> = (0..10)
+                    .map(|point| {
+                        let radius = if point % 2 == 0 { 0.44 } else { 0.18 };
+                        center + Vec2::angled(TAU * (point as f32 / 10.0 - 0.25)) * radius * size
+                    })
+                    .collect();
+
+                if self == Self::Starred {
+                    // The star isn't convex, so it's filled one branch at a time
+                    for (point, next) in points.iter().zip(points.iter().cycle().skip(1)) {
+                        painter.add(Shape::convex_polygon(
+                            vec![center, *point, *next],
+                            color,
+                            Stroke::NONE,
+                        ));
+                    }
+                } else {
+                    painter.add(Shape::closed_line(points, stroke));
+                }
+            }
+            Self::Save => {
+                frame((0.15, 0.15), (0.85, 0.85));
+                frame((0.32, 0.15), (0.68, 0.38));
+                frame((0.3, 0.56), (0.7, 0.85));
+            }
+            Self::Document => {
+                outline(&[
+                    (0.22, 0.1),
+                    (0.6, 0.1),
+                    (0.78, 0.28),
+                    (0.78, 0.9),
+                    (0.22, 0.9),
+                ]);
+                line(&[(0.6, 0.1), (0.6, 0.28), (0.78, 0.28)]);
+                line(&[(0.34, 0.5), (0.66, 0.5)]);
+                line(&[(0.34, 0.7), (0.66, 0.7)]);
+            }
+            Self::Card => {
+                frame((0.22, 0.1), (0.78, 0.9));
+                painter.circle_stroke(rect.center(), size * 0.12, stroke);
+            }
+            Self::Import => {
+                line(&[(0.12, 0.55), (0.12, 0.85), (0.88, 0.85), (0.88, 0.55)]);
+                line(&[(0.5, 0.1), (0.5, 0.62)]);
+                line(&[(0.32, 0.44), (0.5, 0.62), (0.68, 0.44)]);
+            }
+            Self::Download => {
+                line(&[(0.5, 0.12), (0.5, 0.72)]);
+                line(&[(0.25, 0.48), (0.5, 0.72), (0.75, 0.48)]);
+                line(&[(0.2, 0.88), (0.8, 0.88)]);
+            }
+            Self::Copy => {
+                frame((0.32, 0.32), (0.85, 0.85));
+                line(&[(0.15, 0.68), (0.15, 0.15), (0.68, 0.15)]);
+            }
+            Self::Quote => {
+                for x in [0.3, 0.68] {
+                    painter.circle_filled(at(x, 0.62), size * 0.12, color);
+                    line(&[(x - 0.1, 0.6), (x + 0.08, 0.2)]);
+                }
+            }
+            Self::Highlight => {
+                line(&[(0.15, 0.22), (0.85, 0.22)]);
+                painter.rect_filled(
+                    Rect::from_min_max(at(0.1, 0.38), at(0.9, 0.62)),
+                    0.0,
+                    color.gamma_multiply(0.5),
+                );
+                line(&[(0.15, 0.5), (0.85, 0.5)]);
+                line(&[(0.15, 0.78), (0.85, 0.78)]);
+            }
+            Self::Speak => {
+                let center = at(0.5, 0.5);
+
+                block((0.1, 0.38), (0.3, 0.62));
+                fill(&[(0.28, 0.38), (0.5, 0.18), (0.5, 0.82), (0.28, 0.62)]);
+
+                for radius in [0.2, 0.36] {
+                    painter.add(Shape::line(
+                        arc(center, radius * size, -TAU / 8.0, TAU / 8.0),
+                        stroke,
+                    ));
+                }
+            }
+            Self::Judge => {
+                line(&[(0.5, 0.15), (0.5, 0.85)]);
+                line(&[(0.3, 0.85), (0.7, 0.85)]);
+                line(&[(0.15, 0.25), (0.85, 0.25)]);
+
+                for x in [0.15, 0.85] {
+                    line(&[(x - 0.12, 0.55), (x, 0.25), (x + 0.12, 0.55)]);
+                    painter.add(Shape::line(
+                        arc(at(x, 0.55), size * 0.12, 0.0, TAU / 2.0),
+                        stroke,
+                    ));
+                }
+            }
+            Self::Patch => {
+                let center = rect.center();
+                let along = vec2(1.0, -1.0).normalized() * size;
+                let across = vec2(1.0, 1.0).normalized() * size;
+
+                painter.add(Shape::closed_line(
+                    vec![
+                        center + along * 0.42 + across * 0.16,
+                        center + along * 0.42 - across * 0.16,
+                        center - along * 0.42 - across * 0.16,
+                        center - along * 0.42 + across * 0.16,
+                    ],
+                    stroke,
+                ));
+                painter.circle_filled(center, size * 0.06, color);
+            }
+            Self::Clock => {
+                painter.circle_stroke(rect.center(), size * 0.4, stroke);
+                line(&[(0.5, 0.25), (0.5, 0.5), (0.68, 0.5)]);
+            }
+            Self::Cut => {
+                for x in [0.3, 0.7] {
+                    painter.circle_stroke(at(x, 0.75), size * 0.13, stroke);
+                }
+                line(&[(0.38, 0.64), (0.72, 0.12)]);
+                line(&[(0.62, 0.64), (0.28, 0.12)]);
+            }
+            Self::Library => {
+                frame((0.12, 0.2), (0.32, 0.85));
+                frame((0.36, 0.2), (0.56, 0.85));
+                outline(&[(0.62, 0.26), (0.78, 0.2), (0.92, 0.8), (0.76, 0.86)]);
+            }
+            Self::Maintenance => {
+                line(&[(0.78, 0.08), (0.52, 0.52)]);
+                outline(&[(0.36, 0.46), (0.68, 0.6), (0.52, 0.92), (0.12, 0.76)]);
+            }
+            Self::Sync => {
+                line(&[(0.32, 0.85), (0.32, 0.15)]);
+                line(&[(0.16, 0.32), (0.32, 0.15), (0.48, 0.32)]);
+                line(&[(0.68, 0.15), (0.68, 0.85)]);
+                line(&[(0.52, 0.68), (0.68, 0.85), (0.84, 0.68)]);
+            }
+            Self::Accept => line(&[(0.15, 0.5), (0.4, 0.75), (0.85, 0.22)]),
+            Self::Back => {
+                line(&[(0.15, 0.5), (0.85, 0.5)]);
+                line(&[(0.4, 0.25), (0.15, 0.5), (0.4, 0.75)]);
+            }
+            Self::Incognito => {
+                for x in [0.3, 0.7] {
+                    painter.circle_filled(at(x, 0.55), size * 0.17, color);
+                }
+                line(&[(0.45, 0.5), (0.55, 0.5)]);
+                line(&[(0.13, 0.5), (0.05, 0.32)]);
+                line(&[(0.87, 0.5), (0.95, 0.32)]);
+            }
+            Self::Folder => outline(&[
+                (0.1, 0.2),
+                (0.4, 0.2),
+                (0.5, 0.32),
+                (0.9, 0.32),
+                (0.9, 0.82),
+                (0.1, 0.82),
+            ]),
+            Self::Mail => {
+                frame((0.1, 0.22), (0.9, 0.78));
+                line(&[(0.1, 0.22), (0.5, 0.55), (0.9, 0.22)]);
+            }
+            Self::Feed => {
+                let center = at(0.2, 0.8);
+
+                painter.circle_filled(center, size * 0.08, color);
+
+                for radius in [0.32, 0.58] {
+                    painter.add(Shape::line(
+                        arc(center, radius * size, -TAU / 4.0, 0.0),
+                        stroke,
+                    ));
+                }
+            }
+            Self::Menu => {
+                for y in [0.25, 0.5, 0.75] {
+                    line(&[(0.15, y), (0.85, y)]);
+                }
+            }
+            Self::Archive => {
+                frame((0.1, 0.18), (0.9, 0.38));
+                line(&[(0.16, 0.38), (0.16, 0.85), (0.84, 0.85), (0.84, 0.38)]);
+                line(&[(0.4, 0.56), (0.6, 0.56)]);
+            }
+            Self::Merge => {
+                line(&[(0.25, 0.12), (0.5, 0.5), (0.5, 0.88)]);
+                line(&[(0.75, 0.12), (0.5, 0.5)]);
+            }
+            Self::Restore => {
+                line(&[(0.2, 0.3), (0.6, 0.3)]);
+                painter.add(Shape::line(
+                    arc(at(0.6, 0.52), size * 0.22, -TAU / 4.0, TAU / 4.0),
+                    stroke,
+                ));
+                line(&[(0.6, 0.74), (0.35, 0.74)]);
+                line(&[(0.35, 0.15), (0.2, 0.3), (0.35, 0.45)]);
+            }
+            Self::Delete => {
+                line(&[(0.15, 0.22), (0.85, 0.22)]);
+                line(&[(0.4, 0.22), (0.4, 0.12), (0.6, 0.12), (0.6, 0.22)]);
+                line(&[(0.22, 0.22), (0.3, 0.88), (0.7, 0.88), (0.78, 0.22)]);
+                line(&[(0.42, 0.38), (0.44, 0.74)]);
+                line(&[(0.58, 0.38), (0.56, 0.74)]);
+            }
+        }
+    }
+
+    fn size(ui: &Ui) -> f32 {
+        ui.text_style_height(&TextStyle::Button)
+    }
+}
+
+/// Points along the arc of a circle, clockwise from the start angle to the end one
+fn arc(center: Pos2, radius: f32, start: f32, end: f32) -> Vec<Pos2> {
+    (0..=24)
+        .map(|step| center + Vec2::angled(start + (end - start) * step as f32 / 24.0) * radius)
+        .collect()
+}
+
+/// The icon alone, sized like the text next to it
+impl Widget for Icon {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(Vec2::splat(Self::size(ui)), Sense::hover());
+        response
+            .widget_info(|| WidgetInfo::labeled(WidgetType::Label, ui.is_enabled(), self.label()));
+
+        if ui.is_rect_visible(rect) {
+            self.paint(ui, rect, ui.visuals().text_color());
+        }
+
+        if show_labels(ui.ctx()) {
+            ui.label(self.label());
+        }
+
+        response
+    }
+}
+
+/// A button showing an icon, and its text next to it when it has one or in accessibility mode
+pub struct IconButton {
+    icon: Icon,
+    text: Option<String>,
+    frame: bool,
+    selected: bool,
+    text_style: TextStyle,
+}
+
+impl IconButton {
+    pub fn new(icon: Icon) -> Self {
+        Self {
+            icon,
+            text: None,
+            frame: false,
+            selected: false,
+            text_style: TextStyle::Button,
+        }
+    }
+
+    /// Text always shown next to the icon, rather than its name in accessibility mode
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Fills the background even when the button isn't hovered
+    pub fn frame(mut self, frame: bool) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// Highlights the button like a selected label, for toggles
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Sizes the icon and the text like small text
+    pub fn small(mut self) -> Self {
+        self.text_style = TextStyle::Small;
+        self
+    }
+}
+
+impl Widget for IconButton {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let show_text = self.text.is_some() || show_labels(ui.ctx());
+        let text = self.text.unwrap_or_else(|| self.icon.label().to_string());
+
+        let icon_size = ui.text_style_height(&self.text_style);
+        let padding = if self.frame {
+            ui.spacing().button_padding
+        } else {
+            vec2(ui.spacing().button_padding.x, 0.0)
+        };
+        let galley = show_text.then(|| {
+            WidgetText::from(text.as_str()).into_galley(
+                ui,
+                Some(TextWrapMode::Extend),
+                f32::INFINITY,
+                self.text_style.clone(),
+            )
+        });
+        let text_width = galley
+            .as_ref()
+            .map_or(0.0, |galley| ui.spacing().icon_spacing + galley.size().x);
+
+        let (rect, response) = ui.allocate_exact_size(
+            vec2(icon_size + text_width, icon_size) + padding * 2.0,
+            Sense::click(),
+        );
+        response.widget_info(|| {
+            WidgetInfo::selected(WidgetType::Button, ui.is_enabled(), self.selected, &text)
+        });
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact_selectable(&response, self.selected);
+            let color = if self.selected {
+                ui.visuals().selection.stroke.color
+            } else {
+                visuals.text_color()
+            };
+
+            if self.selected {
+                ui.painter().rect_filled(
+                    rect.expand(visuals.expansion),
+                    visuals.corner_radius,
+                    ui.visuals().selection.bg_fill,
+                );
+            } else if self.frame || response.hovered() || response.has_focus() {
+                ui.painter().rect_filled(
+                    rect.expand(visuals.expansion),
+                    visuals.corner_radius,
+                    visuals.weak_bg_fill,
+                );
+            }
+
+            let icon_rect = Rect::from_min_size(rect.min + padding, Vec2::splat(icon_size));
+            self.icon.paint(ui, icon_rect, color);
+
+            if let Some(galley) = galley {
+                let pos = pos2(
+                    icon_rect.max.x + ui.spacing().icon_spacing,
+                    rect.center().y - galley.size().y / 2.0,
+                );
+                ui.painter().galley(pos, galley, color);
+            }
+        }
+
+        response
+    }
+}
+
+/// A menu opened by an icon button, like [`Ui::menu_button`] for a text one
+pub fn menu_button<R>(
+    ui: &mut Ui,
+    button: IconButton,
+    add_contents: impl FnOnce(&mut Ui) -> R,
+) -> InnerResponse<Option<R>> {
+    let bar_id = ui.id();
+    let mut bar_state = BarState::load(ui.ctx(), bar_id);
+    let response = ui.add(button);
+    let inner = bar_state.bar_menu(&response, add_contents);
+
+    bar_state.store(ui.ctx(), bar_id);
+
+    InnerResponse::new(inner.map(|inner| inner.inner), response)
+}
+
+fn labels_id() -> Id {
+    Id::new("icon_labels")
+}
+
+/// Whether the names of the icons are shown next to them, for accessibility
+pub fn set_show_labels(ctx: &egui::Context, show: bool) {
+    ctx.data_mut(|data| data.insert_temp(labels_id(), show));
+}
+
+fn show_labels(ctx: &egui::Context) -> bool {
+    ctx.data(|data| data.get_temp(labels_id()).unwrap_or_default())
+}
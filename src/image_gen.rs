@@ -0,0 +1,62 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Local;
+
+/// Client for the txt2img API of a local Stable Diffusion WebUI (AUTOMATIC1111, Forge, SD.Next)
+#[derive(Clone)]
+pub struct StableDiffusionClient {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(serde::Serialize)]
+struct Txt2ImgRequest<'a> {
+    prompt: &'a str,
+}
+
+#[derive(serde::Deserialize)]
+struct Txt2ImgResponse {
+    images: Vec<String>,
+}
+
+impl StableDiffusionClient {
+    pub fn new(url: String, client: reqwest::Client) -> Self {
+        Self { client, url }
+    }
+
+    /// Generates images for the prompt and stores them as PNG files in `dir`
+    pub async fn generate_images(&self, prompt: &str, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/sdapi/v1/txt2img",
+                self.url.trim_end_matches('/')
+            ))
+            .json(&Txt2ImgRequest { prompt })
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Txt2ImgResponse>()
+            .await?;
+
+        fs::create_dir_all(dir)?;
+
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S%3f");
+
+        response
+            .images
+            .iter()
+            .enumerate()
+            .map(|(i, image)| {
+                let path = dir.join(format!("{timestamp}-{i}.png"));
+                fs::write(&path, STANDARD.decode(image)?)?;
+
+                Ok(path)
+            })
+            .collect()
+    }
+}
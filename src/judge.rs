@@ -0,0 +1,64 @@
+use chrono::{DateTime, Local};
+
+use crate::compose::ComposedPrompt;
+
+pub const DEFAULT_RUBRIC: &str =
+    "Rate the response for correctness, completeness and clarity with respect to the request.";
+
+/// A score given to a response by a judge model
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct JudgeScore {
+    /// From 1 to 10
+    pub score: u8,
+    pub rationale: String,
+    pub model: String,
+    pub scored_at: DateTime<Local>,
+}
+
+/// Asks the judge to score a response against the rubric in a parseable format
+pub fn scoring_prompt(input: &str, output: &str, rubric: &str) -> ComposedPrompt {
+    let rubric = match rubric.trim() {
+        "" => DEFAULT_RUBRIC,
+        rubric => rubric,
+    };
+
+    ComposedPrompt {
+        system: Some(format!(
+            "You are a strict judge. {rubric}\n\n\
+             Reply in exactly this format:\n\
+             Score: <integer from 1 to 10>\n\
+             Rationale: <one short paragraph>"
+        )),
+        prompt: format!("Request:\n{input}\n\nResponse:\n{output}"),
+    }
+}
+
+/// Reads the score and the rationale from the reply of the judge
+pub fn parse_score(reply: &str, model: &str) -> anyhow::Result<JudgeScore> {
+    let field = |name: &str| {
+        reply.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .trim_matches('*')
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().trim_matches('*').trim().to_string())
+        })
+    };
+
+    let score = field("score")
+        .and_then(|score| {
+            score
+                .split(|c: char| !c.is_ascii_digit())
+                .find(|number| !number.is_empty())
+                .and_then(|number| number.parse::<u8>().ok())
+        })
+        .filter(|score| (1..=10).contains(score))
+        .ok_or_else(|| anyhow::anyhow!("the judge did not reply with a score: {reply}"))?;
+
+    Ok(JudgeScore {
+        score,
+        rationale: field("rationale").unwrap_or_default(),
+        model: model.to_string(),
+        scored_at: Local::now(),
+    })
+}
@@ -1,16 +1,121 @@
-pub mod app;
+//! Reprompt keeps reusable prompts and their history for local Ollama models.
+//!
+//! The core modules manage prompts, their history and the provider clients without depending on
+//! egui, the desktop app is built on top of them behind the `gui` feature:
+//!
+//! - [`compose`], [`compare`], [`debate`], [`ensemble`] and [`judge`] build the requests sent to the
+//!   models
+//! - [`content`] detects outputs better read as a JSON tree, a table, a diff or code, and
+//!   [`patch`] applies the diffs to files, [`editor`] opens responses in an external editor and
+//!   [`snippet`] runs their shell and Python snippets
+//! - [`markdown`] converts the responses to plain text or HTML for copying
+//! - [`spell`] checks the spelling of the input against a Hunspell dictionary
+//! - [`prompt`] holds a prompt with its settings and history, and [`history`] and [`script`] the
+//!   exchanges of a prompt and record or replay them, [`chat`] the conversations of its
+//!   conversation mode
+//! - [`ollama`], [`image_gen`] and [`tts`] talk to the providers, configured by [`settings`] and
+//!   the [`config`] file, and [`demo`] answers with canned responses. The text models answer
+//!   from a [`backend`], Ollama or an [`openai`] compatible server
+//! - [`hardware`] detects the memory of the machine and recommends the models that fit into it,
+//!   and [`tokens`] estimates whether a request still fits into the context window of the model
+//! - [`queue`] orders the queued generations by priority and [`typewriter`] smooths out the
+//!   streamed text
+//! - [`watch`] finds the documents added to a folder, [`mail`] reads the messages of a local
+//!   mailbox and [`feed`] the new items of RSS and Atom feeds, for a prompt to process them
+//! - [`migration`] and [`recovery`] read the persisted state, and [`store`] keeps the prompts and
+//!   their history in a SQLite database
+//!
+//! The `test-support` feature adds a fake Ollama server to test against without a running Ollama.
+
+pub mod backend;
+pub mod chat;
+pub mod compare;
+pub mod compose;
+pub mod config;
+pub mod content;
+pub mod debate;
+pub mod demo;
+pub mod editor;
+pub mod ensemble;
+#[cfg(feature = "test-support")]
+pub mod fake_ollama;
+pub mod feed;
+pub mod hardware;
+pub mod history;
+pub mod http;
+pub mod image_gen;
+pub mod judge;
+pub mod mail;
+pub mod markdown;
+pub mod migration;
 pub mod ollama;
+pub mod openai;
+pub mod patch;
 pub mod prompt;
+pub mod queue;
+pub mod recovery;
+pub mod script;
+pub mod secrets;
+pub mod settings;
+pub mod snippet;
+pub mod spell;
+pub mod store;
+pub mod style_guide;
+pub mod tokens;
+pub mod tts;
+pub mod typewriter;
+pub mod watch;
+
+#[cfg(feature = "gui")]
+pub mod app;
+#[cfg(feature = "gui")]
+pub mod card;
+#[cfg(feature = "gui")]
+mod clipboard;
+#[cfg(feature = "gui")]
+pub mod clippings;
+#[cfg(feature = "gui")]
+pub mod fonts;
+#[cfg(feature = "gui")]
+pub mod group;
+#[cfg(feature = "gui")]
+pub mod icons;
+#[cfg(feature = "gui")]
+pub mod library;
+#[cfg(feature = "gui")]
+pub mod maintenance;
+#[cfg(feature = "gui")]
+pub mod media;
+#[cfg(feature = "gui")]
+pub mod model_check;
+#[cfg(feature = "gui")]
+pub mod shared;
+#[cfg(feature = "gui")]
+pub mod sync;
+#[cfg(feature = "gui")]
+pub mod timeline;
+#[cfg(feature = "gui")]
+pub mod undo;
+#[cfg(feature = "gui")]
 pub mod view;
+#[cfg(feature = "gui")]
+pub mod viewer;
+#[cfg(feature = "gui")]
+pub mod window;
 
-use arboard::Clipboard;
+#[cfg(feature = "gui")]
 use std::error::Error;
 
-/// Copies the given text to the system clipboard
+/// Copies the given text to the system clipboard, where it stays once the app is closed
+#[cfg(feature = "gui")]
 pub fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn Error>> {
-    let mut clipboard = Clipboard::new()?;
-    clipboard.set_text(text)?;
-    Ok(())
+    clipboard::set_text(text)
+}
+
+/// Copies HTML to the system clipboard, with the text pasted where formatting isn't supported
+#[cfg(feature = "gui")]
+pub fn copy_html_to_clipboard(html: &str, text: &str) -> Result<(), Box<dyn Error>> {
+    clipboard::set_html(html, text)
 }
 
 #[macro_export]
@@ -0,0 +1,123 @@
+use std::{fs, path::Path};
+
+use crate::{prompt::Prompt, script};
+
+const LIBRARY_VERSION: u32 = 1;
+
+/// File format of an exported library, picked by the file extension
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LibraryFormat {
+    Json,
+    Toml,
+}
+
+impl LibraryFormat {
+    /// JSON unless the file has the `toml` extension
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// What to do with the prompts already in the library when importing
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LibraryImportMode {
+    /// Add the new prompts, and the missing responses to the prompts with the same content
+    Merge,
+    /// Replace all prompts with the imported ones
+    Replace,
+}
+
+/// Prompts exported to a file, to back them up or to move them to another machine
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+pub struct PromptLibrary {
+    pub version: u32,
+    pub prompts: Vec<Prompt>,
+}
+
+impl PromptLibrary {
+    /// Copies the prompts, with or without their history
+    pub fn new(prompts: &[Prompt], include_history: bool) -> anyhow::Result<Self> {
+        let prompts = prompts
+            .iter()
+            .map(|prompt| {
+                let mut prompt: Prompt = serde_json::from_value(serde_json::to_value(prompt)?)?;
+
+                // The watched folder is a path of this machine
+                prompt.stop_watching_folder();
+
+                if !include_history {
+                    prompt.prune_history(0);
+                }
+
+                Ok(prompt)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            version: LIBRARY_VERSION,
+            prompts,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let text = match LibraryFormat::of(path) {
+            LibraryFormat::Json => serde_json::to_string_pretty(self)?,
+            LibraryFormat::Toml => toml::to_string_pretty(self)?,
+        };
+
+        fs::write(path, text)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?, LibraryFormat::of(path))
+    }
+
+    pub fn parse(text: &str, format: LibraryFormat) -> anyhow::Result<Self> {
+        let library: Self = match format {
+            LibraryFormat::Json => serde_json::from_str(text)?,
+            LibraryFormat::Toml => toml::from_str(text)?,
+        };
+
+        anyhow::ensure!(
+            library.version <= LIBRARY_VERSION,
+            "the file was exported by a newer version of Reprompt"
+        );
+
+        Ok(library)
+    }
+}
+
+/// A loaded library waiting for a decision on how to import it
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct LibraryImport {
+    pub library: PromptLibrary,
+    /// Number of imported prompts with the same content as one of the library
+    pub existing: usize,
+}
+
+impl LibraryImport {
+    pub fn new(library: PromptLibrary, prompts: &[Prompt]) -> Self {
+        let existing = library
+            .prompts
+            .iter()
+            .filter(|imported| find_same_content(prompts, imported).is_some())
+            .count();
+
+        Self { library, existing }
+    }
+}
+
+/// The prompt of the library with the same content as the given one
+pub fn find_same_content(prompts: &[Prompt], prompt: &Prompt) -> Option<usize> {
+    let content_hash = script::content_hash(&prompt.content);
+
+    prompts
+        .iter()
+        .position(|existing| script::content_hash(&existing.content) == content_hash)
+}
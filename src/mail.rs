@@ -0,0 +1,481 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, FixedOffset, Local};
+
+/// A message read from a local mailbox, with its text body only
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MailMessage {
+    pub from: String,
+    pub subject: String,
+    pub date: Option<DateTime<FixedOffset>>,
+    pub body: String,
+}
+
+impl MailMessage {
+    /// Reads the headers and the text body of a raw message
+    pub fn parse(raw: &str) -> Self {
+        let (headers, body) = split_headers(raw);
+
+        Self {
+            from: decode_words(headers.get("from").map_or("", String::as_str)),
+            subject: decode_words(headers.get("subject").map_or("", String::as_str)),
+            date: headers
+                .get("date")
+                .and_then(|date| DateTime::parse_from_rfc2822(date.trim()).ok()),
+            body: text_body(&headers, body).trim().to_string(),
+        }
+    }
+
+    /// The message as an input, headed by its sender, subject and date
+    pub fn to_input(&self) -> String {
+        format!(
+            "From: {}\nSubject: {}\nDate: {}\n\n{}\n",
+            self.from,
+            self.subject,
+            self.formatted_date(),
+            self.body
+        )
+    }
+
+    pub fn formatted_date(&self) -> String {
+        self.date
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Reads the messages of a maildir folder or an mbox file, the newest first
+pub fn read_mailbox(path: &Path) -> anyhow::Result<Vec<MailMessage>> {
+    let mut messages = if path.is_dir() {
+        read_maildir(path)?
+    } else {
+        let raw = fs::read(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+
+        split_mbox(&String::from_utf8_lossy(&raw))
+            .iter()
+            .map(|raw| MailMessage::parse(raw))
+            .collect()
+    };
+
+    messages.sort_by_key(|message| std::cmp::Reverse(message.date));
+
+    Ok(messages)
+}
+
+/// Reads the delivered messages of a maildir, the folder holding the `new` and `cur` folders or
+/// the messages themselves
+fn read_maildir(dir: &Path) -> anyhow::Result<Vec<MailMessage>> {
+    let folders: Vec<PathBuf> = ["new", "cur"]
+        .into_iter()
+        .map(|folder| dir.join(folder))
+        .filter(|folder| folder.is_dir())
+        .collect();
+    let folders = if folders.is_empty() {
+        vec![dir.to_path_buf()]
+    } else {
+        folders
+    };
+    let mut messages = Vec::new();
+
+    for folder in folders {
+        let entries = fs::read_dir(&folder)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", folder.display()))?;
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.is_file() {
+                messages.push(MailMessage::parse(&String::from_utf8_lossy(&fs::read(
+                    &path,
+                )?)));
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Splits an mbox into its raw messages, each starting with a `From ` line
+fn split_mbox(mbox: &str) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut message: Option<String> = None;
+    let mut after_blank_line = true;
+
+    for line in mbox.lines() {
+        if after_blank_line && line.starts_with("From ") {
+            messages.extend(message.replace(String::new()));
+        } else if let Some(message) = message.as_mut() {
+            // Lines of the body starting with "From " are escaped with a ">"
+            message.push_str(
+                line.strip_prefix('>')
+                    .filter(|line| line.starts_with("From "))
+                    .unwrap_or(line),
+            );
+            message.push('\n');
+        }
+
+        after_blank_line = line.trim().is_empty();
+    }
+
+    messages.extend(message);
+
+    messages
+}
+
+/// The headers by their lowercase name, with the folded lines joined, and the body
+fn split_headers(raw: &str) -> (HashMap<String, String>, &str) {
+    // The headers end with a blank line, which comes first when there are none
+    if let Some(body) = raw.strip_prefix("\r\n").or_else(|| raw.strip_prefix('\n')) {
+        return (HashMap::new(), body);
+    }
+
+    let (head, body) = raw
+        .split_once("\r\n\r\n")
+        .or_else(|| raw.split_once("\n\n"))
+        .unwrap_or((raw, ""));
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut last = None;
+
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some(value) = last.as_ref().and_then(|name| headers.get_mut(name)) {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            let name = name.trim().to_lowercase();
+
+            // The first of the repeated headers is kept, e.g. the first "Received"
+            headers
+                .entry(name.clone())
+                .or_insert_with(|| value.trim().to_string());
+            last = Some(name);
+        }
+    }
+
+    (headers, body)
+}
+
+/// The text of a body, the plain text part of a multipart one, or the HTML one without its tags
+fn text_body(headers: &HashMap<String, String>, body: &str) -> String {
+    let content_type = headers
+        .get("content-type")
+        .map(|content_type| content_type.to_lowercase())
+        .unwrap_or_default();
+
+    if content_type.starts_with("multipart/")
+        && let Some(boundary) = headers
+            .get("content-type")
+            .and_then(|content_type| parameter(content_type, "boundary"))
+    {
+        let parts: Vec<(HashMap<String, String>, &str)> = body
+            .split(&format!("--{boundary}"))
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            // The rest of the boundary line
+            .filter_map(|part| part.split_once('\n'))
+            .map(|(_, part)| split_headers(part))
+            .collect();
+        let is_type = |headers: &HashMap<String, String>, prefix: &str| {
+            headers
+                .get("content-type")
+                .map_or(prefix == "text/plain", |content_type| {
+                    content_type.to_lowercase().starts_with(prefix)
+                })
+        };
+
+        return parts
+            .iter()
+            .find(|(headers, _)| is_type(headers, "text/plain"))
+            .or_else(|| {
+                parts
+                    .iter()
+                    .find(|(headers, _)| is_type(headers, "multipart/"))
+            })
+            .or_else(|| {
+                parts
+                    .iter()
+                    .find(|(headers, _)| is_type(headers, "text/html"))
+            })
+            .map(|(headers, body)| text_body(headers, body))
+            .unwrap_or_default();
+    }
+
+    let text = match headers
+        .get("content-transfer-encoding")
+        .map(|encoding| encoding.trim().to_lowercase())
+        .as_deref()
+    {
+        Some("base64") => {
+            let encoded: String = body.split_whitespace().collect();
+
+            STANDARD
+                .decode(encoded)
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                .unwrap_or_else(|_| body.to_string())
+        }
+        Some("quoted-printable") => decode_quoted_printable(body, false),
+        _ => body.to_string(),
+    };
+
+    if content_type.starts_with("text/html") {
+        strip_tags(&text)
+    } else {
+        text
+    }
+}
+
+/// Value of a parameter of a header, e.g. the boundary of the content type
+fn parameter(header: &str, name: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|parameter| {
+        let (key, value) = parameter.split_once('=')?;
+
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Decodes quoted-printable text, where underscores are spaces in the encoded words of headers
+fn decode_quoted_printable(text: &str, underscore_is_space: bool) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.as_bytes().iter().copied().peekable();
+
+    while let Some(byte) = input.next() {
+        match byte {
+            b'=' => {
+                let high = input.next();
+                let low = input.peek().copied();
+
+                match (high, low) {
+                    // A soft line break
+                    (Some(b'\n'), _) => {}
+                    (Some(b'\r'), Some(b'\n')) => {
+                        input.next();
+                    }
+                    (Some(high), Some(low))
+                        if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() =>
+                    {
+                        input.next();
+                        bytes.extend(u8::from_str_radix(
+                            &format!("{}{}", high as char, low as char),
+                            16,
+                        ));
+                    }
+                    (high, _) => {
+                        bytes.push(b'=');
+                        bytes.extend(high);
+                    }
+                }
+            }
+            b'_' if underscore_is_space => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Decodes the `=?charset?B?…?=` and `=?charset?Q?…?=` words of a header
+fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let word = &rest[start + 2..];
+        let mut fields = word.splitn(3, '?');
+        let (Some(charset), Some(encoding), Some(encoded)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            break;
+        };
+        let Some(end) = encoded.find("?=") else {
+            break;
+        };
+        let text = &encoded[..end];
+        let bytes = match encoding.to_uppercase().as_str() {
+            "B" => STANDARD.decode(text).unwrap_or_default(),
+            "Q" => decode_quoted_printable(text, true).into_bytes(),
+            _ => break,
+        };
+        let between = &rest[..start];
+
+        // The whitespace between two encoded words is left out
+        if !(after_word && between.trim().is_empty()) {
+            decoded.push_str(between);
+        }
+
+        if charset.eq_ignore_ascii_case("iso-8859-1") || charset.eq_ignore_ascii_case("latin1") {
+            decoded.extend(bytes.iter().map(|&byte| byte as char));
+        } else {
+            decoded.push_str(&String::from_utf8_lossy(&bytes));
+        }
+
+        after_word = true;
+        rest = &encoded[end + 2..];
+    }
+
+    decoded.push_str(rest);
+
+    decoded.trim().to_string()
+}
+
+/// The text of HTML, without the tags, the styles and the scripts
+pub fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+
+        let tag = &rest[start..];
+        // Lowercased in ASCII only, so that the byte offsets stay the same
+        let lowercase = tag.to_ascii_lowercase();
+        let skipped_until = if lowercase.starts_with("<style") {
+            "</style>"
+        } else if lowercase.starts_with("<script") {
+            "</script>"
+        } else {
+            ">"
+        };
+
+        match lowercase.find(skipped_until) {
+            Some(end) => {
+                if lowercase.starts_with("<br") || lowercase.starts_with("</p") {
+                    text.push('\n');
+                }
+
+                rest = &tag[end + skipped_until.len()..];
+            }
+            None => rest = "",
+        }
+    }
+
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Messages of a mailbox summarized one by one with a prompt, put together into a digest once
+/// they are all summarized
+#[derive(Default)]
+pub struct MailDigest {
+    /// The mailbox the messages were read from
+    pub source: PathBuf,
+    pub messages: Vec<MailMessage>,
+    /// Messages picked to be summarized, by their index
+    pub selected: BTreeSet<usize>,
+    /// Picked messages still to summarize
+    queue: VecDeque<usize>,
+    /// Summarized messages with the creation time of their response, none until it's added
+    summaries: Vec<(usize, Option<DateTime<Local>>)>,
+    running: bool,
+    /// The digest of the summaries, once they are all generated
+    pub document: Option<String>,
+}
+
+impl MailDigest {
+    pub fn new(source: PathBuf, messages: Vec<MailMessage>) -> Self {
+        Self {
+            source,
+            messages,
+            ..Default::default()
+        }
+    }
+
+    /// Queues the picked messages to be summarized, in the order they are listed
+    pub fn start(&mut self) {
+        self.queue = self.selected.iter().copied().collect();
+        self.summaries.clear();
+        self.document = None;
+        self.running = !self.queue.is_empty();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Messages still to summarize
+    pub fn queued_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Takes the next message to summarize as an input
+    pub fn next_input(&mut self) -> Option<String> {
+        let idx = self.queue.pop_front()?;
+
+        self.summaries.push((idx, None));
+
+        self.messages.get(idx).map(MailMessage::to_input)
+    }
+
+    /// Records the response the last message taken is summarized in
+    pub fn record_response(&mut self, created_at: DateTime<Local>) {
+        if let Some((_, response @ None)) = self.summaries.last_mut() {
+            *response = Some(created_at);
+        }
+    }
+
+    /// Puts the summaries together once the queue is done, with the outputs of the responses by
+    /// their creation time
+    pub fn finish<'a>(&mut self, output: impl Fn(DateTime<Local>) -> Option<&'a str>) {
+        // The last message taken may not have started generating yet
+        let started = self
+            .summaries
+            .last()
+            .is_none_or(|(_, created_at)| created_at.is_some());
+
+        if !self.running || !self.queue.is_empty() || !started {
+            return;
+        }
+
+        self.running = false;
+
+        let mut document = format!(
+            "# Mail digest of {}\n\n{} message(s) from {}\n",
+            Local::now().format("%Y-%m-%d"),
+            self.summaries.len(),
+            self.source.display()
+        );
+
+        for (idx, created_at) in &self.summaries {
+            let Some(message) = self.messages.get(*idx) else {
+                continue;
+            };
+            let summary = created_at
+                .and_then(&output)
+                .filter(|output| !output.trim().is_empty())
+                .unwrap_or("*No summary was generated.*");
+
+            document.push_str(&format!(
+                "\n## {}\n\n*{} · {}*\n\n{}\n",
+                if message.subject.is_empty() {
+                    "(no subject)"
+                } else {
+                    &message.subject
+                },
+                message.from,
+                message.formatted_date(),
+                summary.trim()
+            ));
+        }
+
+        self.document = Some(document);
+    }
+}
@@ -1,10 +1,17 @@
-use reprompt::app::App;
+use reprompt::{app::App, window::WindowGeometry};
 
 fn main() -> eframe::Result {
     env_logger::init();
 
+    let viewport = egui::ViewportBuilder::default();
+
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default(),
+        viewport: match WindowGeometry::load() {
+            Some(window) => window.apply(viewport),
+            None => viewport,
+        },
+        // The geometry is kept in the app state instead
+        persist_window: false,
         ..Default::default()
     };
 
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::prompt::Prompt;
+
+/// Histories longer than this are suggested for pruning
+pub const OVERSIZED_HISTORY_LEN: usize = 200;
+/// Number of the most recent responses kept when a history is pruned
+pub const PRUNED_HISTORY_LEN: usize = 50;
+/// Share of common words above which two prompts are considered duplicates
+const DUPLICATE_SIMILARITY: f32 = 0.8;
+
+/// Two prompts with nearly the same content, where `remove` is suggested to be merged into `keep`.
+///
+/// The prompts are referred to by their sync identifier, which stays the same when other prompts
+/// are removed or moved while the report is shown.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct DuplicatePrompts {
+    pub keep: String,
+    pub remove: String,
+    pub similarity: f32,
+}
+
+/// Statistics and cleanup suggestions for the prompt library
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct LibraryReport {
+    pub prompts: usize,
+    pub responses: usize,
+    /// Words of the responses of each prompt, by prompt index
+    pub words: Vec<usize>,
+    pub unused: Vec<usize>,
+    pub duplicates: Vec<DuplicatePrompts>,
+    pub oversized: Vec<usize>,
+    pub archived: Vec<usize>,
+}
+
+impl LibraryReport {
+    pub fn analyze(prompts: &[Prompt]) -> Self {
+        let active = prompts
+            .iter()
+            .enumerate()
+            .filter(|(_, prompt)| !prompt.archived)
+            .collect::<Vec<_>>();
+        let words = active
+            .iter()
+            .map(|(_, prompt)| Self::words(&prompt.content))
+            .collect::<Vec<_>>();
+
+        let mut duplicates = Vec::new();
+
+        for (a, (_, prompt_a)) in active.iter().enumerate() {
+            for (b, (_, prompt_b)) in active.iter().enumerate().skip(a + 1) {
+                if prompt_a.kind != prompt_b.kind {
+                    continue;
+                }
+
+                let similarity = Self::similarity(&words[a], &words[b]);
+
+                if similarity >= DUPLICATE_SIMILARITY {
+                    // Keep the prompt that was used the most
+                    let (keep, remove) = if prompt_b.history_count() > prompt_a.history_count() {
+                        (prompt_b, prompt_a)
+                    } else {
+                        (prompt_a, prompt_b)
+                    };
+
+                    duplicates.push(DuplicatePrompts {
+                        keep: keep.sync_id.clone(),
+                        remove: remove.sync_id.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+
+        Self {
+            prompts: prompts.len(),
+            responses: prompts.iter().map(Prompt::history_count).sum(),
+            words: prompts.iter().map(Prompt::word_count).collect(),
+            unused: active
+                .iter()
+                .filter(|(_, prompt)| prompt.history_count() == 0)
+                .map(|(idx, _)| *idx)
+                .collect(),
+            duplicates,
+            oversized: active
+                .iter()
+                .filter(|(_, prompt)| prompt.history_count() > OVERSIZED_HISTORY_LEN)
+                .map(|(idx, _)| *idx)
+                .collect(),
+            archived: prompts
+                .iter()
+                .enumerate()
+                .filter(|(_, prompt)| prompt.archived)
+                .map(|(idx, _)| idx)
+                .collect(),
+        }
+    }
+
+    pub fn has_suggestions(&self) -> bool {
+        !self.unused.is_empty() || !self.duplicates.is_empty() || !self.oversized.is_empty()
+    }
+
+    fn words(content: &str) -> HashSet<String> {
+        content
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// Jaccard similarity of the word sets, none for prompts without words like image-only ones
+    fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+        if a.is_empty() || b.is_empty() {
+            return 0.0;
+        }
+
+        a.intersection(b).count() as f32 / a.union(b).count() as f32
+    }
+}
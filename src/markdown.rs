@@ -0,0 +1,534 @@
+/// Converts the markdown of a response to plain text, for pasting into fields without formatting.
+///
+/// Code blocks keep their code, headings, quotes and emphasis lose their markers, list items are
+/// bulleted with `•`, links become `text (url)` and table rows are separated by tabs.
+pub fn to_plain_text(markdown: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_fence = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        if fence_language(trimmed).is_some() {
+            in_fence = !in_fence;
+            continue;
+        }
+
+        if in_fence {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        if is_rule(trimmed) || is_table_separator(trimmed) {
+            continue;
+        }
+
+        lines.push(plain_line(line));
+    }
+
+    let mut plain = lines.join("\n");
+
+    // Blank lines left by the dropped rules and fences are collapsed
+    while plain.contains("\n\n\n") {
+        plain = plain.replace("\n\n\n", "\n\n");
+    }
+
+    plain.trim().to_string()
+}
+
+/// Renders the markdown of a response to HTML, for pasting into rich text editors.
+///
+/// Covers what the models commonly write: headings, paragraphs, emphasis, code, links, images,
+/// quotes, tables and lists, nested list items being flattened into their list.
+pub fn to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut block = Block::None;
+    let mut fence: Option<(String, String)> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some((language, code)) = &mut fence {
+            if fence_language(trimmed).is_some() {
+                html.push_str(&code_block(language, code));
+                fence = None;
+            } else {
+                code.push_str(line);
+                code.push('\n');
+            }
+
+            continue;
+        }
+
+        if let Some(language) = fence_language(trimmed) {
+            block.close(&mut html);
+            fence = Some((language.to_string(), String::new()));
+        } else if trimmed.is_empty() {
+            block.close(&mut html);
+        } else if is_rule(trimmed) {
+            block.close(&mut html);
+            html.push_str("<hr>\n");
+        } else if let Some((level, heading)) = heading(trimmed) {
+            block.close(&mut html);
+            html.push_str(&format!("<h{level}>{}</h{level}>\n", html_inline(heading)));
+        } else if let Some(quoted) = trimmed.strip_prefix('>') {
+            block.push_quote(&mut html, quoted.trim_start());
+        } else if is_table_separator(trimmed) {
+            if let Block::Table { header, .. } = &mut block {
+                *header = true;
+            }
+        } else if trimmed.starts_with('|') {
+            block.push_row(&mut html, table_cells(trimmed));
+        } else if let Some(item) = unordered_item(trimmed) {
+            block.push_item(&mut html, "ul", item);
+        } else if let Some(item) = ordered_item(trimmed) {
+            block.push_item(&mut html, "ol", item.to_string());
+        } else {
+            block.push_text(&mut html, trimmed);
+        }
+    }
+
+    // An unterminated fence runs to the end, like while the response is streamed
+    if let Some((language, code)) = fence {
+        html.push_str(&code_block(&language, &code));
+    }
+
+    block.close(&mut html);
+
+    html
+}
+
+/// Block being rendered to HTML, closed by a blank line or a block of another kind
+enum Block {
+    None,
+    Paragraph(Vec<String>),
+    Quote(Vec<String>),
+    List {
+        tag: &'static str,
+        items: Vec<String>,
+    },
+    Table {
+        rows: Vec<Vec<String>>,
+        /// Set by the separator line, making the first row the header
+        header: bool,
+    },
+}
+
+impl Block {
+    fn push_text(&mut self, html: &mut String, text: &str) {
+        match self {
+            Block::Paragraph(lines) => lines.push(text.to_string()),
+            // A lazy continuation of the last item
+            Block::List { items, .. } => {
+                if let Some(item) = items.last_mut() {
+                    item.push(' ');
+                    item.push_str(text);
+                }
+            }
+            _ => {
+                self.close(html);
+                *self = Block::Paragraph(vec![text.to_string()]);
+            }
+        }
+    }
+
+    fn push_quote(&mut self, html: &mut String, text: &str) {
+        if let Block::Quote(lines) = self {
+            lines.push(text.to_string());
+        } else {
+            self.close(html);
+            *self = Block::Quote(vec![text.to_string()]);
+        }
+    }
+
+    fn push_item(&mut self, html: &mut String, tag: &'static str, item: String) {
+        match self {
+            Block::List {
+                tag: list_tag,
+                items,
+            } if *list_tag == tag => items.push(item),
+            _ => {
+                self.close(html);
+                *self = Block::List {
+                    tag,
+                    items: vec![item],
+                };
+            }
+        }
+    }
+
+    fn push_row(&mut self, html: &mut String, cells: Vec<String>) {
+        if let Block::Table { rows, .. } = self {
+            rows.push(cells);
+        } else {
+            self.close(html);
+            *self = Block::Table {
+                rows: vec![cells],
+                header: false,
+            };
+        }
+    }
+
+    fn close(&mut self, html: &mut String) {
+        match std::mem::replace(self, Block::None) {
+            Block::None => {}
+            Block::Paragraph(lines) => {
+                html.push_str(&format!("<p>{}</p>\n", html_inline(&lines.join("\n"))));
+            }
+            Block::Quote(lines) => {
+                html.push_str(&format!(
+                    "<blockquote><p>{}</p></blockquote>\n",
+                    html_inline(&lines.join("\n"))
+                ));
+            }
+            Block::List { tag, items } => {
+                html.push_str(&format!("<{tag}>\n"));
+
+                for item in items {
+                    html.push_str(&format!("<li>{}</li>\n", html_inline(&item)));
+                }
+
+                html.push_str(&format!("</{tag}>\n"));
+            }
+            Block::Table { rows, header } => {
+                html.push_str("<table>\n");
+
+                for (idx, row) in rows.iter().enumerate() {
+                    let cell_tag = if header && idx == 0 { "th" } else { "td" };
+
+                    html.push_str("<tr>");
+
+                    for cell in row {
+                        html.push_str(&format!("<{cell_tag}>{}</{cell_tag}>", html_inline(cell)));
+                    }
+
+                    html.push_str("</tr>\n");
+                }
+
+                html.push_str("</table>\n");
+            }
+        }
+    }
+}
+
+fn code_block(language: &str, code: &str) -> String {
+    let class = if language.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", escape(language))
+    };
+
+    format!("<pre><code{class}>{}</code></pre>\n", escape(code))
+}
+
+fn plain_line(line: &str) -> String {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let mut rest = line.trim_start();
+
+    while let Some(quoted) = rest.strip_prefix('>') {
+        rest = quoted.trim_start();
+    }
+
+    if let Some((_, heading)) = heading(rest) {
+        return plain_inline(heading);
+    }
+
+    if rest.starts_with('|') {
+        return table_cells(rest)
+            .iter()
+            .map(|cell| plain_inline(cell))
+            .collect::<Vec<_>>()
+            .join("\t");
+    }
+
+    match unordered_item(rest) {
+        Some(item) => format!("{indent}• {}", plain_inline(&item)),
+        None => format!("{indent}{}", plain_inline(rest)),
+    }
+}
+
+/// Language of the code block a fence line opens, empty when not given
+fn fence_language(line: &str) -> Option<&str> {
+    line.strip_prefix("```")
+        .or_else(|| line.strip_prefix("~~~"))
+        .map(str::trim)
+}
+
+/// Level and text of an ATX heading, `## Title ##` giving `(2, "Title")`
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+
+    ((1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')))
+        .then(|| (level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Text of a bulleted list item, with its task checkbox as a symbol
+fn unordered_item(line: &str) -> Option<String> {
+    let item = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))?;
+
+    let item = item
+        .strip_prefix("[ ] ")
+        .map(|task| format!("☐ {task}"))
+        .or_else(|| {
+            item.strip_prefix("[x] ")
+                .or_else(|| item.strip_prefix("[X] "))
+                .map(|task| format!("☑ {task}"))
+        })
+        .unwrap_or_else(|| item.to_string());
+
+    Some(item)
+}
+
+/// Text of a numbered list item, `1. ` or `1) `
+fn ordered_item(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+
+    if rest.len() == line.len() {
+        return None;
+    }
+
+    rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    line.trim()
+        .trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim_end();
+
+    ['-', '*', '_'].iter().any(|&marker| {
+        line.chars().filter(|c| !c.is_whitespace()).count() >= 3
+            && line.chars().all(|c| c == marker || c == ' ')
+    })
+}
+
+fn is_table_separator(line: &str) -> bool {
+    line.starts_with('|')
+        && line.contains('-')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+/// Inline markup of a line
+enum Inline {
+    Text(String),
+    Code(String),
+    Link {
+        label: String,
+        url: String,
+    },
+    Image {
+        alt: String,
+        url: String,
+    },
+    /// A run of `*` or `_`, one for emphasis, two for strong emphasis, three for both
+    Emphasis(usize),
+    Strikethrough,
+}
+
+/// Removes the inline markup: emphasis, code spans, links, images and escapes
+fn plain_inline(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+
+    for inline in parse_inline(text) {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) => plain.push_str(&text),
+            Inline::Link { label, url } => {
+                let label = plain_inline(&label);
+
+                if label == url || url.is_empty() {
+                    plain.push_str(&label);
+                } else {
+                    plain.push_str(&format!("{label} ({url})"));
+                }
+            }
+            Inline::Image { alt, .. } => plain.push_str(&plain_inline(&alt)),
+            Inline::Emphasis(_) | Inline::Strikethrough => {}
+        }
+    }
+
+    plain
+}
+
+/// Renders the inline markup, closing the emphasis left open at the end
+fn html_inline(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut open: Vec<&'static str> = Vec::new();
+
+    for inline in parse_inline(text) {
+        match inline {
+            Inline::Text(text) => html.push_str(&escape(&text).replace('\n', "<br>\n")),
+            Inline::Code(code) => html.push_str(&format!("<code>{}</code>", escape(&code))),
+            Inline::Link { label, url } => html.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape(&url),
+                html_inline(&label)
+            )),
+            Inline::Image { alt, url } => html.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\">",
+                escape(&url),
+                escape(&plain_inline(&alt))
+            )),
+            Inline::Emphasis(1) => toggle(&mut html, &mut open, "em"),
+            Inline::Emphasis(2) => toggle(&mut html, &mut open, "strong"),
+            Inline::Emphasis(_) => {
+                // The inner tag is closed first when both are open
+                if open.ends_with(&["strong", "em"]) {
+                    toggle(&mut html, &mut open, "em");
+                    toggle(&mut html, &mut open, "strong");
+                } else {
+                    toggle(&mut html, &mut open, "strong");
+                    toggle(&mut html, &mut open, "em");
+                }
+            }
+            Inline::Strikethrough => toggle(&mut html, &mut open, "del"),
+        }
+    }
+
+    while let Some(tag) = open.pop() {
+        html.push_str(&format!("</{tag}>"));
+    }
+
+    html
+}
+
+/// Opens the emphasis tag, or closes it when it is the innermost open one
+fn toggle(html: &mut String, open: &mut Vec<&'static str>, tag: &'static str) {
+    if open.last() == Some(&tag) {
+        open.pop();
+        html.push_str(&format!("</{tag}>"));
+    } else {
+        open.push(tag);
+        html.push_str(&format!("<{tag}>"));
+    }
+}
+
+fn parse_inline(text: &str) -> Vec<Inline> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let run = chars[idx..].iter().take_while(|&&other| other == c).count();
+        let previous = idx.checked_sub(1).map(|previous| chars[previous]);
+        let next = chars.get(idx + 1).copied();
+        let after_run = chars.get(idx + run).copied();
+
+        let (inline, len) = match c {
+            '\\' if next.is_some_and(|next| next.is_ascii_punctuation()) => {
+                plain.extend(next);
+                idx += 2;
+                continue;
+            }
+            '`' => {
+                let fence = "`".repeat(run);
+                let rest: String = chars[idx + run..].iter().collect();
+
+                match rest.find(&fence) {
+                    Some(end) => (
+                        Inline::Code(rest[..end].trim().to_string()),
+                        run + rest[..end].chars().count() + run,
+                    ),
+                    None => (Inline::Text(fence), run),
+                }
+            }
+            '!' if next == Some('[') => match link(&chars[idx + 1..]) {
+                Some((alt, url, len)) => (Inline::Image { alt, url }, 1 + len),
+                None => (Inline::Text(c.to_string()), 1),
+            },
+            '[' => match link(&chars[idx..]) {
+                Some((label, url, len)) => (Inline::Link { label, url }, len),
+                None => (Inline::Text(c.to_string()), 1),
+            },
+            // A lone `*` between spaces is not emphasis, e.g. in `2 * 3`
+            '*' if run <= 3
+                && !(previous.is_none_or(char::is_whitespace)
+                    && after_run.is_none_or(char::is_whitespace)) =>
+            {
+                (Inline::Emphasis(run), run)
+            }
+            // Underscores inside words, e.g. in `snake_case`, are kept
+            '_' if run <= 3
+                && !(previous.is_some_and(char::is_alphanumeric)
+                    && after_run.is_some_and(char::is_alphanumeric)) =>
+            {
+                (Inline::Emphasis(run), run)
+            }
+            // Only doubled tildes strike through, `~/` is a path
+            '~' if run == 2 => (Inline::Strikethrough, run),
+            // The whole run is text, so that its remaining characters aren't taken as markup
+            _ => {
+                plain.extend(&chars[idx..idx + run]);
+                idx += run;
+                continue;
+            }
+        };
+
+        if !plain.is_empty() {
+            inlines.push(Inline::Text(std::mem::take(&mut plain)));
+        }
+
+        inlines.push(inline);
+        idx += len;
+    }
+
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+
+    inlines
+}
+
+/// Parses `[label](url)` at the start, returning the label, the url and the length in chars
+fn link(chars: &[char]) -> Option<(String, String, usize)> {
+    let label_end = chars.iter().position(|&c| c == ']')?;
+
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+
+    let url_len = chars[label_end + 2..].iter().position(|&c| c == ')')?;
+    let label = chars[1..label_end].iter().collect();
+    let url: String = chars[label_end + 2..label_end + 2 + url_len]
+        .iter()
+        .collect();
+    // A title may follow the url, e.g. `(https://example.com "Example")`
+    let url = url
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    Some((label, url, label_end + 2 + url_len + 1))
+}
+
+/// Wraps the HTML of the markdown into a standalone page, readable without a stylesheet
+pub fn to_html_document(title: &str, markdown: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{DOCUMENT_STYLE}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        to_html(markdown)
+    )
+}
+
+const DOCUMENT_STYLE: &str = "body { max-width: 50em; margin: 2em auto; padding: 0 1em; font-family: sans-serif; line-height: 1.5; } \
+pre { padding: 0.8em; overflow-x: auto; background: #f4f4f4; } \
+table { border-collapse: collapse; } th, td { padding: 0.3em 0.6em; border: 1px solid #ccc; }";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
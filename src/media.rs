@@ -0,0 +1,145 @@
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use egui::{ImageSource, load::Bytes};
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "bmp", "tiff"];
+
+/// An image referenced from a response output, either a local file or an inline base64 payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseImage {
+    File(PathBuf),
+    Base64 { data: String },
+}
+
+impl ResponseImage {
+    /// Finds images referenced by markdown image links, bare local paths and `data:image/` URIs
+    pub fn find_all(text: &str) -> Vec<Self> {
+        let mut images = Vec::new();
+        let mut push = |image: Self| {
+            if !images.contains(&image) {
+                images.push(image);
+            }
+        };
+
+        let mut rest = text;
+        while let Some(start) = rest.find("![") {
+            rest = &rest[start + 2..];
+
+            let Some(target_start) = rest.find("](") else {
+                break;
+            };
+            let target = &rest[target_start + 2..];
+            let Some(target_end) = target.find(')') else {
+                break;
+            };
+
+            // Targets are either `<path with spaces>` or end at the first whitespace, which
+            // drops link titles such as `![alt](/tmp/cat.png "A cat")`
+            let target = target[..target_end].trim();
+            let target_path = match target.strip_prefix('<') {
+                Some(target) => target.split('>').next().unwrap_or_default(),
+                None => target.split_whitespace().next().unwrap_or_default(),
+            };
+
+            if let Some(image) = Self::parse(target_path) {
+                push(image);
+            }
+            rest = &target[target_end + 1..];
+        }
+
+        for token in text.split_whitespace() {
+            let token =
+                token.trim_matches(|c| matches!(c, '`' | '"' | '\'' | '<' | '>' | ',' | ';'));
+
+            if let Some(image) = Self::parse(token) {
+                push(image);
+            }
+        }
+
+        images
+    }
+
+    fn parse(target: &str) -> Option<Self> {
+        if let Some(data) = target.strip_prefix("data:image/") {
+            let (_, data) = data.split_once(";base64,")?;
+
+            return Some(Self::Base64 {
+                data: data.to_string(),
+            });
+        }
+
+        let path = Path::new(target.strip_prefix("file://").unwrap_or(target));
+        let has_image_extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+
+        (has_image_extension && path.is_absolute()).then(|| Self::File(path.to_path_buf()))
+    }
+
+    pub fn uri(&self) -> String {
+        match self {
+            Self::File(path) => format!("file://{}", path.display()),
+            Self::Base64 { data } => {
+                let mut hasher = DefaultHasher::new();
+                data.hash(&mut hasher);
+
+                format!("bytes://response-image-{:x}", hasher.finish())
+            }
+        }
+    }
+
+    /// Returns the image source for egui, decoding base64 payloads once and caching them in memory
+    pub fn source(&self, ctx: &egui::Context) -> Option<ImageSource<'static>> {
+        match self {
+            Self::File(_) => Some(ImageSource::Uri(self.uri().into())),
+            Self::Base64 { data } => {
+                let uri = self.uri();
+                let id = egui::Id::new(&uri);
+                let bytes = match ctx.data(|d| d.get_temp::<Arc<[u8]>>(id)) {
+                    Some(bytes) => bytes,
+                    None => {
+                        let bytes: Arc<[u8]> = STANDARD.decode(data.trim()).ok()?.into();
+                        ctx.data_mut(|d| d.insert_temp(id, bytes.clone()));
+                        bytes
+                    }
+                };
+
+                Some(ImageSource::Bytes {
+                    uri: uri.into(),
+                    bytes: Bytes::Shared(bytes),
+                })
+            }
+        }
+    }
+
+    /// Suggests a file name for saving the image to disk
+    pub fn file_name(&self) -> String {
+        match self {
+            Self::File(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "image.png".to_string()),
+            Self::Base64 { .. } => "image.png".to_string(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        match self {
+            Self::File(source) => {
+                fs::copy(source, path)?;
+            }
+            Self::Base64 { data } => {
+                fs::write(path, STANDARD.decode(data.trim())?)?;
+            }
+        }
+
+        Ok(())
+    }
+}
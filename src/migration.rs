@@ -0,0 +1,40 @@
+use serde::de::DeserializeOwned;
+
+/// Upgrades the persisted state by one schema version
+pub struct Migration<T> {
+    pub description: &'static str,
+    pub apply: fn(&mut T),
+}
+
+/// Deserializes a stored state.
+///
+/// Unlike `eframe::get_value` a state that fails to deserialize is reported instead of being
+/// replaced with the defaults.
+pub fn parse<T: DeserializeOwned>(serialized: &str) -> anyhow::Result<T> {
+    ron::from_str(serialized).map_err(|e| anyhow::anyhow!("Failed to read the stored state: {e}"))
+}
+
+/// Applies the migrations from `version` on, `migrations[n]` upgrading version `n` to `n + 1`.
+///
+/// Returns the version the state was upgraded to.
+pub fn run<T>(state: &mut T, version: u32, migrations: &[Migration<T>]) -> anyhow::Result<u32> {
+    let current = migrations.len() as u32;
+
+    if version > current {
+        anyhow::bail!(
+            "The stored state has schema version {version}, this build only knows up to {current}"
+        );
+    }
+
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize) {
+        log::info!(
+            "Migrating the stored state from schema version {from} to {}: {}",
+            from + 1,
+            migration.description
+        );
+
+        (migration.apply)(state);
+    }
+
+    Ok(current)
+}
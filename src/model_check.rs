@@ -0,0 +1,108 @@
+use ollama_rs::models::LocalModel;
+
+use crate::{hardware::Hardware, ollama::PullProgress, prompt::Prompt};
+
+/// A model the state refers to that the server no longer has
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct MissingModel {
+    pub name: String,
+    /// Whether it was the selected model
+    pub selected: bool,
+    /// Prompts referring to it in their debate, ensemble or judge settings
+    pub prompts: Vec<usize>,
+    /// Model it is remapped to, kept as it is when empty
+    pub replacement: String,
+}
+
+/// The missing models offered to be pulled or remapped, with the one being pulled
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct MissingModels {
+    pub models: Vec<MissingModel>,
+    pub pulling: Option<String>,
+    #[serde(skip)]
+    pub progress: Option<PullProgress>,
+    pub pull_error: Option<String>,
+}
+
+/// The models recommended for the memory of the machine, offered when the server has none
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ModelOnboarding {
+    pub hardware: Hardware,
+    pub pulling: Option<String>,
+    #[serde(skip)]
+    pub progress: Option<PullProgress>,
+    pub pull_error: Option<String>,
+}
+
+impl ModelOnboarding {
+    pub fn new(hardware: Hardware) -> Self {
+        Self {
+            hardware,
+            ..Default::default()
+        }
+    }
+}
+
+impl MissingModels {
+    pub fn names(&self) -> Vec<&str> {
+        self.models
+            .iter()
+            .map(|model| model.name.as_str())
+            .collect()
+    }
+}
+
+/// Finds the selected model and the models bound to prompts that are not in `available`.
+///
+/// The replacement of each of them defaults to `fallback`, the model selected in their place.
+pub fn find(
+    selected: Option<&LocalModel>,
+    prompts: &[Prompt],
+    available: &[LocalModel],
+    fallback: Option<&LocalModel>,
+) -> MissingModels {
+    let is_missing = |name: &str| !available.iter().any(|model| model.name == name);
+    let replacement = fallback.map(|model| model.name.clone()).unwrap_or_default();
+    let mut models: Vec<MissingModel> = Vec::new();
+
+    if let Some(selected) = selected.filter(|model| is_missing(&model.name)) {
+        entry(&mut models, &selected.name, &replacement).selected = true;
+    }
+
+    for (idx, prompt) in prompts.iter().enumerate() {
+        for name in prompt.bound_models().filter(|name| is_missing(name)) {
+            let model = entry(&mut models, name, &replacement);
+
+            if !model.prompts.contains(&idx) {
+                model.prompts.push(idx);
+            }
+        }
+    }
+
+    MissingModels {
+        models,
+        ..Default::default()
+    }
+}
+
+fn entry<'a>(
+    models: &'a mut Vec<MissingModel>,
+    name: &str,
+    replacement: &str,
+) -> &'a mut MissingModel {
+    let position = match models.iter().position(|model| model.name == name) {
+        Some(position) => position,
+        None => {
+            models.push(MissingModel {
+                name: name.to_string(),
+                selected: false,
+                prompts: Vec::new(),
+                replacement: replacement.to_string(),
+            });
+
+            models.len() - 1
+        }
+    };
+
+    &mut models[position]
+}
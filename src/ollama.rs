@@ -1,17 +1,235 @@
-use ollama_rs::{Ollama, generation::completion::request::GenerationRequest, models::LocalModel};
+use ollama_rs::{
+    Ollama,
+    error::OllamaError,
+    generation::{
+        chat::{
+            ChatMessage, ChatMessageFinalResponseData, MessageRole, request::ChatMessageRequest,
+        },
+        completion::{GenerationResponse, request::GenerationRequest},
+        parameters::{KeepAlive, TimeUnit},
+    },
+    models::{LocalModel, ModelOptions},
+};
+use serde_json::{Map, Value};
+use std::{sync::Arc, time::Duration};
+
 use tokio::sync::broadcast;
 use tokio_stream::StreamExt;
 
+use crate::{
+    backend::{Backend, BoxFuture, Chunk, GenerationOptions, TextStream},
+    compose::ComposedPrompt,
+    demo,
+    history::{ModelMetrics, StopReason},
+};
+
+/// How long Ollama keeps a model loaded after its last request, matching the server default
+pub const KEEP_ALIVE_SECS: u64 = 300;
+
+/// Wait before the first retry of a request that failed to connect, doubled for each next one
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Connects to the Ollama server at `url` through the given HTTP client.
+///
+/// The path of the URL is kept, for a server behind a reverse proxy at e.g. `https://host/ollama`.
+pub fn connect(url: &str, client: reqwest::Client) -> anyhow::Result<Ollama> {
+    let mut url = reqwest::Url::parse(url.trim())
+        .map_err(|e| anyhow::anyhow!("invalid Ollama URL \"{url}\": {e}"))?;
+
+    if url.host_str().is_none() {
+        anyhow::bail!("the Ollama URL \"{url}\" has no host");
+    }
+
+    let port = url.port_or_known_default().unwrap_or(11434);
+
+    // The API paths are joined to the URL, which has to end with a slash for them to follow it
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+
+    url.set_query(None);
+    url.set_fragment(None);
+
+    Ok(Ollama::new_with_client(url, port, client))
+}
+
+/// Status of a model download, with the bytes of the layer being downloaded
+#[derive(Clone, Debug, Default)]
+pub struct PullProgress {
+    pub status: String,
+    pub completed: u64,
+    pub total: u64,
+}
+
+impl PullProgress {
+    pub fn fraction(&self) -> Option<f32> {
+        (self.total > 0).then(|| self.completed as f32 / self.total as f32)
+    }
+}
+
+/// Returns the largest available model that is smaller than the one named `name`
+pub fn smaller_model<'a>(models: &'a [LocalModel], name: &str) -> Option<&'a LocalModel> {
+    let current = models.iter().find(|model| model.name == name)?;
+
+    models
+        .iter()
+        .filter(|model| model.size < current.size)
+        .max_by_key(|model| model.size)
+}
+
+/// The `num_ctx` of the Modelfile, or else the context length the model was trained with
+fn context_window(parameters: &str, model_info: &Map<String, Value>) -> Option<u64> {
+    parameters
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("num_ctx")?.trim().parse().ok())
+        .or_else(|| {
+            model_info
+                .iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, length)| length.as_u64())
+        })
+}
+
+/// An answer with what the server reported about its generation
+#[derive(Debug, Default)]
+pub struct Answer {
+    pub output: String,
+    pub metrics: Option<ModelMetrics>,
+    pub stop_reason: Option<StopReason>,
+}
+
+impl Answer {
+    /// Cut off by the length limit, even after the continuations
+    pub fn is_truncated(&self) -> bool {
+        self.stop_reason == Some(StopReason::Length)
+    }
+}
+
+/// The statistics of a finished completion, none while it is streaming
+fn completion_metrics(response: &GenerationResponse) -> Option<ModelMetrics> {
+    response.done.then(|| ModelMetrics {
+        prompt_eval_count: response.prompt_eval_count.unwrap_or_default(),
+        eval_count: response.eval_count.unwrap_or_default(),
+        total_duration: response.total_duration.unwrap_or_default(),
+        load_duration: response.load_duration.unwrap_or_default(),
+        prompt_eval_duration: response.prompt_eval_duration.unwrap_or_default(),
+        eval_duration: response.eval_duration.unwrap_or_default(),
+    })
+}
+
+/// The server couldn't be reached or didn't answer in time, which may pass when retried
+fn is_connection_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|error| error.is_connect() || error.is_timeout())
+}
+
+/// Why a finished answer ended, Ollama doesn't say it but the answer was cut off when it used all
+/// the tokens allowed
+fn stop_reason(metrics: Option<&ModelMetrics>, options: GenerationOptions) -> Option<StopReason> {
+    metrics.map(|metrics| {
+        if options
+            .max_tokens
+            .is_some_and(|max_tokens| metrics.eval_count >= u64::from(max_tokens))
+        {
+            StopReason::Length
+        } else {
+            StopReason::Stop
+        }
+    })
+}
+
+fn model_options(options: GenerationOptions) -> ModelOptions {
+    let mut model_options = ModelOptions::default();
+
+    if let Some(seed) = options.seed {
+        model_options = model_options.seed(seed);
+    }
+
+    if let Some(max_tokens) = options.max_tokens {
+        model_options = model_options.num_predict(i32::try_from(max_tokens).unwrap_or(i32::MAX));
+    }
+
+    model_options
+}
+
+fn chat_metrics(data: &ChatMessageFinalResponseData) -> ModelMetrics {
+    ModelMetrics {
+        prompt_eval_count: data.prompt_eval_count,
+        eval_count: data.eval_count,
+        total_duration: data.total_duration,
+        load_duration: data.load_duration,
+        prompt_eval_duration: data.prompt_eval_duration,
+        eval_duration: data.eval_duration,
+    }
+}
+
+/// Client of the backend the text models answer from, Ollama unless another one is set in the
+/// settings, with the demo model answering without any
 #[derive(Clone)]
 pub struct OllamaClient {
-    ollama: Ollama,
+    backend: Arc<dyn Backend>,
     cancel_tx: broadcast::Sender<()>,
+    /// Delay between the tokens of the demo model, which is only listed when set
+    demo_delay: Option<Duration>,
+    max_output_tokens: Option<u32>,
+    /// Continuations of an answer cut off by the length limit at most
+    max_continuations: u32,
+    /// Retries of a request that failed to connect at most
+    max_retries: u32,
 }
 
 impl OllamaClient {
     pub fn new(ollama: Ollama) -> Self {
         let (cancel_tx, _) = broadcast::channel(1);
-        Self { ollama, cancel_tx }
+        Self {
+            backend: Arc::new(OllamaBackend { ollama }),
+            cancel_tx,
+            demo_delay: None,
+            max_output_tokens: None,
+            max_continuations: 0,
+            max_retries: 0,
+        }
+    }
+
+    /// Replaces the backend, keeping the generations running on the old one cancellable
+    pub fn with_backend(&self, backend: Arc<dyn Backend>) -> Self {
+        Self {
+            backend,
+            cancel_tx: self.cancel_tx.clone(),
+            demo_delay: self.demo_delay,
+            max_output_tokens: self.max_output_tokens,
+            max_continuations: self.max_continuations,
+            max_retries: self.max_retries,
+        }
+    }
+
+    /// Retries the requests that fail to connect up to `max_retries` times, waiting longer
+    /// before each retry
+    pub fn with_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Caps the tokens of the answers, 0 leaving it to the model, and continues the ones cut off
+    /// up to `max_continuations` times
+    pub fn with_output_limit(self, max_tokens: u32, max_continuations: u32) -> Self {
+        Self {
+            max_output_tokens: (max_tokens > 0).then_some(max_tokens),
+            max_continuations,
+            ..self
+        }
+    }
+
+    pub fn with_demo(self, delay: Option<Duration>) -> Self {
+        Self {
+            demo_delay: delay,
+            ..self
+        }
     }
 
     pub fn get_cancel_receiver(&self) -> broadcast::Receiver<()> {
@@ -24,15 +242,173 @@ impl OllamaClient {
 
     pub async fn generate_completion(
         &self,
-        prompt: String,
+        prompt: ComposedPrompt,
         model: &LocalModel,
         on_next: impl Fn(String),
     ) -> anyhow::Result<String> {
-        let mut stream = self
-            .ollama
-            .generate_stream(GenerationRequest::new(model.name.clone(), prompt))
+        self.generate_completion_with_seed(prompt, model, None, on_next)
+            .await
+    }
+
+    pub async fn generate_completion_with_seed(
+        &self,
+        prompt: ComposedPrompt,
+        model: &LocalModel,
+        seed: Option<i32>,
+        on_next: impl Fn(String),
+    ) -> anyhow::Result<String> {
+        let answer = self
+            .generate(prompt, None, model, seed, on_next, |_, _| {})
             .await?;
-        let mut response = String::new();
+
+        Ok(answer.output)
+    }
+
+    /// Answers the prompt, or the last message of the conversation when there is one, along with
+    /// the statistics the server reported. `on_retry` is called with the retry and the retries at
+    /// most before retrying a request that failed to connect.
+    pub async fn generate_answer(
+        &self,
+        prompt: ComposedPrompt,
+        conversation: Option<Vec<ChatMessage>>,
+        model: &LocalModel,
+        on_next: impl Fn(String),
+        on_retry: impl Fn(u32, u32),
+    ) -> anyhow::Result<Answer> {
+        self.generate(prompt, conversation, model, None, on_next, on_retry)
+            .await
+    }
+
+    /// Streams the answer, asking the model to continue it while it is cut off by the length
+    /// limit and auto-continue allows it
+    async fn generate(
+        &self,
+        prompt: ComposedPrompt,
+        conversation: Option<Vec<ChatMessage>>,
+        model: &LocalModel,
+        seed: Option<i32>,
+        on_next: impl Fn(String),
+        on_retry: impl Fn(u32, u32),
+    ) -> anyhow::Result<Answer> {
+        if demo::is_demo(model) {
+            let prompt = match conversation {
+                Some(messages) => ComposedPrompt {
+                    system: None,
+                    prompt: messages
+                        .iter()
+                        .rfind(|message| message.role == MessageRole::User)
+                        .map(|message| message.content.clone())
+                        .unwrap_or_default(),
+                },
+                None => prompt,
+            };
+
+            return Ok(Answer {
+                output: self.generate_demo_completion(&prompt, on_next).await,
+                ..Default::default()
+            });
+        }
+
+        let options = GenerationOptions {
+            seed,
+            max_tokens: self.max_output_tokens,
+        };
+        let stream = self
+            .request_with_retries(
+                || match conversation.clone() {
+                    Some(messages) => self.backend.chat(model.name.clone(), messages, options),
+                    None => self
+                        .backend
+                        .complete(model.name.clone(), prompt.clone(), options),
+                },
+                &on_retry,
+            )
+            .await?;
+        let mut answer = self.collect_stream(stream, &on_next).await?;
+
+        for continuation in 1..=self.max_continuations {
+            if !answer.is_truncated() {
+                break;
+            }
+
+            log::info!(
+                "Continuing the answer of {} cut off by the length limit ({continuation}/{})",
+                model.name,
+                self.max_continuations
+            );
+
+            let messages = prompt
+                .clone()
+                .continue_answer(conversation.clone(), &answer.output);
+            let stream = self
+                .request_with_retries(
+                    || {
+                        self.backend
+                            .chat(model.name.clone(), messages.clone(), options)
+                    },
+                    &on_retry,
+                )
+                .await?;
+            let next = self
+                .collect_stream(stream, |output| {
+                    on_next(format!("{}{output}", answer.output));
+                })
+                .await?;
+
+            answer.output += &next.output;
+            answer.stop_reason = next.stop_reason;
+            answer.metrics = match (answer.metrics, next.metrics) {
+                (Some(mut metrics), Some(next)) => {
+                    metrics.add(&next);
+                    Some(metrics)
+                }
+                (metrics, next) => metrics.or(next),
+            };
+        }
+
+        Ok(answer)
+    }
+
+    /// Sends the request, retrying it with a growing delay while the server can't be reached.
+    ///
+    /// A generation cancelled while waiting gets an empty answer.
+    async fn request_with_retries<'a>(
+        &'a self,
+        request: impl Fn() -> BoxFuture<'a, anyhow::Result<TextStream>>,
+        on_retry: &impl Fn(u32, u32),
+    ) -> anyhow::Result<TextStream> {
+        let mut cancel_rx = self.get_cancel_receiver();
+        let mut backoff = RETRY_BACKOFF;
+        let mut retry = 0;
+
+        loop {
+            match request().await {
+                Err(e) if retry < self.max_retries && is_connection_error(&e) => {
+                    retry += 1;
+
+                    log::warn!(
+                        "Retrying the request ({retry}/{}) in {backoff:?}: {e}",
+                        self.max_retries
+                    );
+                    on_retry(retry, self.max_retries);
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(backoff) => backoff *= 2,
+                        _ = cancel_rx.recv() => return Ok(Box::pin(tokio_stream::empty())),
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Accumulates the streamed answer until it ends or the generation is cancelled
+    async fn collect_stream(
+        &self,
+        mut stream: TextStream,
+        on_next: impl Fn(String),
+    ) -> anyhow::Result<Answer> {
+        let mut answer = Answer::default();
         let mut cancel_rx = self.get_cancel_receiver();
 
         loop {
@@ -40,28 +416,232 @@ impl OllamaClient {
                 maybe_next = stream.next() => {
                     match maybe_next {
                         Some(Ok(next)) => {
-                            for n in next {
-                                response += &n.response;
-                                on_next(response.clone());
+                            answer.metrics = next.metrics.or(answer.metrics);
+                            answer.stop_reason = next.stop_reason.or(answer.stop_reason);
+
+                            if !next.text.is_empty() {
+                                answer.output += &next.text;
+                                on_next(answer.output.clone());
                             }
                         }
                         None => break,
-                        Some(Err(e)) => return Err(e.into()),
+                        Some(Err(e)) => return Err(e),
                     }
                 }
+                _ = cancel_rx.recv() => {
+                    // A cancelled answer is never continued
+                    answer.stop_reason = Some(StopReason::Cancelled);
+                    break;
+                }
+            }
+        }
+
+        Ok(answer)
+    }
+
+    /// Streams the canned response of the demo model
+    async fn generate_demo_completion(
+        &self,
+        prompt: &ComposedPrompt,
+        on_next: impl Fn(String),
+    ) -> String {
+        let delay = self.demo_delay.unwrap_or_default();
+        let mut response = String::new();
+        let mut cancel_rx = self.get_cancel_receiver();
+
+        for token in demo::tokens(demo::response(&prompt.prompt)) {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {
+                    response += token;
+                    on_next(response.clone());
+                }
                 _ = cancel_rx.recv() => {
                     break;
                 }
             }
         }
 
-        Ok(response)
+        response
+    }
+
+    /// Loads the model into memory with an empty request, so that the next one starts right away
+    pub async fn warm_up(&self, model: &LocalModel) -> anyhow::Result<()> {
+        if demo::is_demo(model) {
+            return Ok(());
+        }
+
+        self.backend.warm_up(model.name.clone()).await
+    }
+
+    /// Tokens the model attends to, none for the demo model or when the backend doesn't report them
+    pub async fn context_window(&self, model: &LocalModel) -> anyhow::Result<Option<u64>> {
+        if demo::is_demo(model) {
+            return Ok(None);
+        }
+
+        self.backend.context_window(model.name.clone()).await
+    }
+
+    /// Downloads the model from the Ollama library, waiting until it is ready
+    pub async fn pull_model(
+        &self,
+        name: &str,
+        on_progress: impl Fn(PullProgress) + Send + Sync,
+    ) -> anyhow::Result<()> {
+        self.backend
+            .pull_model(name.to_string(), &on_progress)
+            .await?;
+
+        log::info!("Pulled {name}");
+
+        Ok(())
     }
 
+    /// Lists the models of the backend, followed by the demo model when it is enabled
     pub async fn list_models(&self) -> anyhow::Result<Vec<LocalModel>> {
-        self.ollama
-            .list_local_models()
-            .await
-            .map_err(anyhow::Error::new)
+        let models = self.backend.list_models().await;
+
+        if self.demo_delay.is_none() {
+            return models;
+        }
+
+        // The demo model stays usable without a running backend
+        let mut models = models
+            .inspect_err(|e| log::warn!("Failed to list the models: {e}"))
+            .unwrap_or_default();
+        models.push(demo::model());
+
+        Ok(models)
+    }
+}
+
+/// The Ollama server, with its model library
+pub struct OllamaBackend {
+    ollama: Ollama,
+}
+
+impl OllamaBackend {
+    pub fn new(ollama: Ollama) -> Self {
+        Self { ollama }
+    }
+}
+
+impl Backend for OllamaBackend {
+    fn list_models(&self) -> BoxFuture<'_, anyhow::Result<Vec<LocalModel>>> {
+        Box::pin(async { Ok(self.ollama.list_local_models().await?) })
+    }
+
+    fn complete(
+        &self,
+        model: String,
+        prompt: ComposedPrompt,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>> {
+        Box::pin(async move {
+            let mut request =
+                GenerationRequest::new(model, prompt.prompt).options(model_options(options));
+
+            if let Some(system) = prompt.system {
+                request = request.system(system);
+            }
+
+            let stream = self
+                .ollama
+                .generate_stream(request)
+                .await?
+                .map(move |next| {
+                    next.map(|responses| {
+                        let mut chunk =
+                            responses
+                                .iter()
+                                .fold(Chunk::default(), |mut chunk, response| {
+                                    chunk.text += &response.response;
+                                    chunk.metrics = completion_metrics(response).or(chunk.metrics);
+                                    chunk
+                                });
+                        chunk.stop_reason = stop_reason(chunk.metrics.as_ref(), options);
+                        chunk
+                    })
+                    .map_err(anyhow::Error::new)
+                });
+
+            Ok(Box::pin(stream) as TextStream)
+        })
+    }
+
+    fn chat(
+        &self,
+        model: String,
+        messages: Vec<ChatMessage>,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>> {
+        Box::pin(async move {
+            let request = ChatMessageRequest::new(model, messages).options(model_options(options));
+            let stream = self
+                .ollama
+                .send_chat_messages_stream(request)
+                .await?
+                .map(move |next| {
+                    next.map(|response| {
+                        let metrics = response.final_data.as_ref().map(chat_metrics);
+
+                        Chunk {
+                            text: response.message.content,
+                            stop_reason: stop_reason(metrics.as_ref(), options),
+                            metrics,
+                        }
+                    })
+                    .map_err(|()| anyhow::anyhow!("the chat stream failed"))
+                });
+
+            Ok(Box::pin(stream) as TextStream)
+        })
+    }
+
+    fn warm_up(&self, model: String) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            let request =
+                GenerationRequest::new(model, String::new()).keep_alive(KeepAlive::Until {
+                    time: KEEP_ALIVE_SECS,
+                    unit: TimeUnit::Seconds,
+                });
+
+            self.ollama.generate(request).await?;
+
+            Ok(())
+        })
+    }
+
+    fn context_window(&self, model: String) -> BoxFuture<'_, anyhow::Result<Option<u64>>> {
+        Box::pin(async move {
+            let info = self.ollama.show_model_info(model).await?;
+
+            Ok(context_window(&info.parameters, &info.model_info))
+        })
+    }
+
+    fn pull_model<'a>(
+        &'a self,
+        name: String,
+        on_progress: &'a (dyn Fn(PullProgress) + Send + Sync),
+    ) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut stream = self.ollama.pull_model_stream(name, false).await?;
+
+            while let Some(next) = stream.next().await {
+                match next {
+                    Ok(status) => on_progress(PullProgress {
+                        status: status.message,
+                        completed: status.completed.unwrap_or_default(),
+                        total: status.total.unwrap_or_default(),
+                    }),
+                    // A chunk holding several statuses doesn't parse, the next ones will
+                    Err(OllamaError::JsonError(e)) => log::debug!("Skipped a pull status: {e}"),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            Ok(())
+        })
     }
 }
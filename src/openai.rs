@@ -0,0 +1,232 @@
+use ollama_rs::{generation::chat::ChatMessage, models::LocalModel};
+use reqwest::{StatusCode, header};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::{
+    backend::{Backend, BoxFuture, Chunk, GenerationOptions, TextStream},
+    compose::ComposedPrompt,
+    history::StopReason,
+};
+
+/// Marks the end of a streamed answer
+const DONE_EVENT: &str = "[DONE]";
+
+/// Finish reason of an answer cut off by the length limit
+const LENGTH_FINISH_REASON: &str = "length";
+
+/// A server with the OpenAI API, e.g. LM Studio, vLLM, the llama.cpp server or OpenAI itself
+pub struct OpenAiBackend {
+    client: reqwest::Client,
+    /// URL the API paths are appended to, e.g. `http://127.0.0.1:1234/v1`
+    base_url: String,
+    /// Sent as a bearer token, none when empty as local servers mostly don't need one
+    api_key: String,
+}
+
+impl OpenAiBackend {
+    pub fn new(client: reqwest::Client, base_url: &str, api_key: &str) -> anyhow::Result<Self> {
+        let base_url = base_url.trim().trim_end_matches('/');
+
+        reqwest::Url::parse(base_url)
+            .map_err(|e| anyhow::anyhow!("invalid OpenAI-compatible URL \"{base_url}\": {e}"))?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.to_string(),
+            api_key: api_key.trim().to_string(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/{path}", self.base_url));
+
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.api_key)
+        }
+    }
+
+    /// Streams the chat completion of the messages, the answer arriving as server-sent events
+    async fn stream_chat_completion(
+        &self,
+        model: String,
+        messages: Vec<Value>,
+        options: GenerationOptions,
+    ) -> anyhow::Result<TextStream> {
+        let mut body = json!({
+            "model": model,
+            "messages": messages,
+            "stream": true,
+        });
+
+        if let Some(seed) = options.seed {
+            body["seed"] = json!(seed);
+        }
+
+        if let Some(max_tokens) = options.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+
+        let response = error_for_status(
+            self.request(reqwest::Method::POST, "chat/completions")
+                .header(header::ACCEPT, "text/event-stream")
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        // Stops reading once the stream is dropped, e.g. when the generation is cancelled
+        tokio::spawn(async move {
+            if let Err(e) = read_events(response, &tx).await {
+                let _ = tx.send(Err(e));
+            }
+        });
+
+        Ok(Box::pin(UnboundedReceiverStream::new(rx)))
+    }
+}
+
+impl Backend for OpenAiBackend {
+    fn list_models(&self) -> BoxFuture<'_, anyhow::Result<Vec<LocalModel>>> {
+        Box::pin(async {
+            let response =
+                error_for_status(self.request(reqwest::Method::GET, "models").send().await?)
+                    .await?;
+            let list: Value = response.json().await?;
+            let mut models: Vec<LocalModel> = list["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|model| model["id"].as_str())
+                .map(|id| LocalModel {
+                    name: id.to_string(),
+                    modified_at: String::new(),
+                    // Not reported, so the models are never recommended as smaller ones
+                    size: 0,
+                })
+                .collect();
+
+            models.sort_by(|a, b| a.name.cmp(&b.name));
+
+            Ok(models)
+        })
+    }
+
+    fn complete(
+        &self,
+        model: String,
+        prompt: ComposedPrompt,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>> {
+        let messages = prompt
+            .system
+            .map(|system| json!({ "role": "system", "content": system }))
+            .into_iter()
+            .chain([json!({ "role": "user", "content": prompt.prompt })])
+            .collect();
+
+        Box::pin(self.stream_chat_completion(model, messages, options))
+    }
+
+    fn chat(
+        &self,
+        model: String,
+        messages: Vec<ChatMessage>,
+        options: GenerationOptions,
+    ) -> BoxFuture<'_, anyhow::Result<TextStream>> {
+        let messages = messages
+            .into_iter()
+            .map(|message| json!({ "role": message.role, "content": message.content }))
+            .collect();
+
+        Box::pin(self.stream_chat_completion(model, messages, options))
+    }
+}
+
+/// Turns an error status into an error with the message of the server
+async fn error_for_status(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let text = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<Value>(&text)
+        .ok()
+        .and_then(|error| error["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or(text);
+
+    if status == StatusCode::UNAUTHORIZED {
+        anyhow::bail!("the server refused the API key: {message}");
+    }
+
+    anyhow::bail!("the server answered {status}: {message}")
+}
+
+/// Why the answer of the choice ended, none while it is streaming
+fn stop_reason(choice: &Value) -> Option<StopReason> {
+    match choice["finish_reason"].as_str()? {
+        LENGTH_FINISH_REASON => Some(StopReason::Length),
+        // vLLM names the stop sequence that ended the answer
+        _ if choice["stop_reason"].is_string() => Some(StopReason::StopSequence),
+        _ => Some(StopReason::Stop),
+    }
+}
+
+/// Sends the content of the `data:` events of the response, until the answer is done
+async fn read_events(
+    mut response: reqwest::Response,
+    tx: &mpsc::UnboundedSender<anyhow::Result<Chunk>>,
+) -> anyhow::Result<()> {
+    let mut buffer = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+
+        // Events may be split over chunks, so only complete lines are read
+        while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+
+            if data == DONE_EVENT {
+                return Ok(());
+            }
+
+            let event: Value = serde_json::from_str(data)?;
+
+            if let Some(message) = event["error"]["message"].as_str() {
+                anyhow::bail!("{message}");
+            }
+
+            let choice = &event["choices"][0];
+            let chunk = Chunk {
+                text: choice["delta"]["content"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                stop_reason: stop_reason(choice),
+                ..Default::default()
+            };
+
+            if (!chunk.text.is_empty() || chunk.stop_reason.is_some())
+                && tx.send(Ok(chunk)).is_err()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::{fs, path::PathBuf};
+
+/// Extension appended to the name of a patched file for the copy of its original content
+const BACKUP_EXTENSION: &str = "orig";
+
+/// A unified diff of a single file, as found in a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patch {
+    /// Path of the patched file according to the diff header, without the `a/` or `b/` prefix
+    pub target: Option<String>,
+    pub kind: PatchKind,
+    hunks: Vec<Hunk>,
+}
+
+/// What applying a diff does to its file, by the `/dev/null` side of its header
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatchKind {
+    #[default]
+    Modify,
+    Create,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Hunk {
+    /// 1-based line the hunk starts at in the original file, only a hint as models get it wrong
+    old_start: usize,
+    lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Removed(text) => Some(text.as_str()),
+                HunkLine::Added(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Added(text) => Some(text.as_str()),
+            HunkLine::Removed(_) => None,
+        })
+    }
+}
+
+/// The result of applying a diff, shown for confirmation before the file is written
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct PatchPreview {
+    pub path: PathBuf,
+    pub kind: PatchKind,
+    pub diff: String,
+    pub patched: String,
+}
+
+impl Patch {
+    pub fn parse(diff: &str) -> anyhow::Result<Self> {
+        let mut target = None;
+        let mut kind = PatchKind::Modify;
+        let mut hunks: Vec<Hunk> = Vec::new();
+        // Old and new lines of the current hunk still to come, by the counts of its header
+        let mut remaining = (0, 0);
+        let mut lines = diff.lines().peekable();
+
+        while let Some(line) = lines.next() {
+            // Removed and added lines starting with "-- " and "++ " look like headers, which only
+            // come between the hunks
+            if remaining == (0, 0)
+                && let Some(old_path) = line.strip_prefix("--- ")
+                && let Some(new_path) = lines.next_if(|next| next.starts_with("+++ "))
+            {
+                anyhow::ensure!(
+                    hunks.is_empty(),
+                    "the diff changes several files, only single-file diffs can be applied"
+                );
+
+                let new_path = &new_path[4..];
+
+                kind = if is_dev_null(old_path) {
+                    PatchKind::Create
+                } else if is_dev_null(new_path) {
+                    PatchKind::Delete
+                } else {
+                    PatchKind::Modify
+                };
+                target = header_path(new_path).or_else(|| header_path(old_path));
+            } else if let Some(ranges) = line.strip_prefix("@@ -") {
+                let mut ranges = ranges.split_whitespace();
+                let (old_start, old_len) = ranges
+                    .next()
+                    .and_then(hunk_range)
+                    .ok_or_else(|| anyhow::anyhow!("malformed hunk header: {line}"))?;
+                // Only the start of the old side is needed, the counts are for telling the
+                // headers apart
+                let new_len = ranges
+                    .next()
+                    .and_then(|range| hunk_range(range.strip_prefix('+')?))
+                    .map_or(0, |(_, len)| len);
+
+                remaining = (old_len, new_len);
+                hunks.push(Hunk {
+                    old_start,
+                    lines: Vec::new(),
+                });
+            } else if let Some(hunk) = hunks.last_mut() {
+                let hunk_line = match line.chars().next() {
+                    Some('+') => HunkLine::Added(line[1..].to_string()),
+                    Some('-') => HunkLine::Removed(line[1..].to_string()),
+                    Some(' ') => HunkLine::Context(line[1..].to_string()),
+                    Some('\\') => continue,
+                    // Models often drop the leading space of empty context lines
+                    None => HunkLine::Context(String::new()),
+                    Some(_) => continue,
+                };
+
+                let (old, new) = &mut remaining;
+
+                match hunk_line {
+                    HunkLine::Context(_) => {
+                        *old = old.saturating_sub(1);
+                        *new = new.saturating_sub(1);
+                    }
+                    HunkLine::Removed(_) => *old = old.saturating_sub(1),
+                    HunkLine::Added(_) => *new = new.saturating_sub(1),
+                }
+
+                hunk.lines.push(hunk_line);
+            }
+        }
+
+        anyhow::ensure!(!hunks.is_empty(), "the diff has no hunks to apply");
+
+        Ok(Self {
+            target,
+            kind,
+            hunks,
+        })
+    }
+
+    /// Applies the hunks to the content of the file, locating each one by its context lines
+    pub fn apply(&self, original: &str) -> anyhow::Result<String> {
+        let lines: Vec<&str> = original.lines().collect();
+        let mut patched: Vec<&str> = Vec::with_capacity(lines.len());
+        let mut cursor = 0;
+
+        for (hunk_idx, hunk) in self.hunks.iter().enumerate() {
+            let old = hunk.old_lines();
+            // A hunk without old lines inserts after its start line, instead of at it
+            let hint = if old.is_empty() {
+                hunk.old_start
+            } else {
+                hunk.old_start.saturating_sub(1)
+            };
+            let start = find_lines(&lines, &old, cursor, hint).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "hunk {} does not match the file, it may have changed or be a different one",
+                    hunk_idx + 1
+                )
+            })?;
+
+            patched.extend(&lines[cursor..start]);
+            patched.extend(hunk.new_lines());
+            cursor = start + old.len();
+        }
+
+        patched.extend(&lines[cursor..]);
+
+        let mut result = patched.join("\n");
+
+        if original.ends_with('\n') || original.is_empty() {
+            result.push('\n');
+        }
+
+        Ok(result)
+    }
+}
+
+/// Writes the patched content, or removes the file a diff deletes, keeping the original next to
+/// it with the `.orig` extension. A file the diff creates has no original, unless it existed.
+pub fn write_with_backup(preview: &PatchPreview) -> anyhow::Result<()> {
+    let mut backup = preview.path.as_os_str().to_owned();
+    backup.push(".");
+    backup.push(BACKUP_EXTENSION);
+    let backup = PathBuf::from(backup);
+
+    match preview.kind {
+        PatchKind::Delete => fs::rename(&preview.path, &backup)?,
+        PatchKind::Create if !preview.path.exists() => fs::write(&preview.path, &preview.patched)?,
+        PatchKind::Create | PatchKind::Modify => {
+            fs::copy(&preview.path, &backup)?;
+            fs::write(&preview.path, &preview.patched)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Start and length of a side of a hunk header, e.g. `12,3`, the length being 1 when left out
+fn hunk_range(range: &str) -> Option<(usize, usize)> {
+    let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+
+    Some((start.parse().ok()?, len.parse().ok()?))
+}
+
+fn is_dev_null(header: &str) -> bool {
+    header
+        .split('\t')
+        .next()
+        .is_some_and(|path| path.trim() == "/dev/null")
+}
+
+fn header_path(header: &str) -> Option<String> {
+    let path = header.split('\t').next()?.trim();
+
+    if is_dev_null(path) || path.is_empty() {
+        return None;
+    }
+
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+
+    Some(path.to_string())
+}
+
+/// Finds the lines at or after `from`, the closest to the `hint` first, ignoring trailing whitespace
+fn find_lines(lines: &[&str], needle: &[&str], from: usize, hint: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(hint.clamp(from, lines.len()));
+    }
+
+    let last_start = lines.len().checked_sub(needle.len())?;
+    let matches_at = |start: usize| {
+        lines[start..start + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|(line, expected)| line.trim_end() == expected.trim_end())
+    };
+
+    let hint = hint.clamp(from, last_start.max(from));
+
+    (0..=last_start.saturating_sub(from))
+        .flat_map(|distance| [hint.checked_add(distance), hint.checked_sub(distance)])
+        .flatten()
+        .filter(|start| (from..=last_start).contains(start))
+        .find(|&start| matches_at(start))
+}
@@ -1,37 +1,292 @@
 use std::{
-    collections::VecDeque,
-    time::{Duration, Instant},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    time::Instant,
 };
 
 use chrono::{DateTime, Local};
-
-use egui::RichText;
-use egui::{
-    Color32, CornerRadius, Frame, Key, KeyboardShortcut, Label, Layout, Modifiers, ScrollArea,
-    Sense, Stroke, UiBuilder,
-};
-use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
-use flowync::{CompactFlower, error::Compact};
 use ollama_rs::models::LocalModel;
-use tokio::runtime;
+use tokio::sync::oneshot;
+
+use crate::{
+    chat::ChatSession,
+    compare::CompareSettings,
+    compose::{self, Exchange, FewShotExample, PromptTemplate},
+    content::{ContentType, HighlightCache},
+    debate::DebateSettings,
+    ensemble::EnsembleSettings,
+    feed::{Briefing, FeedSubscription},
+    history::{self, Annotation, PromptResponse},
+    mail::{MailDigest, MailMessage},
+    queue::Priority,
+    script::{Script, ScriptReplay, ScriptStep},
+    store::{HistoryPage, PromptRecord, StoredPrompt, UnloadedHistory},
+    style_guide::StyleGuide,
+    tokens,
+    tts::AudioPlayer,
+    typewriter::Typewriter,
+    watch::FolderWatch,
+};
 
-use crate::{app::AppAction, assign_if_some, ollama::OllamaClient};
+#[cfg(feature = "gui")]
+use crate::media::ResponseImage;
 
-const COPY_FEEDBACK_DURATION_MS: u64 = 1500;
+#[cfg(feature = "gui")]
+mod panel;
+
+#[cfg(feature = "gui")]
+pub use panel::tag_chip;
+
+const RECENT_INPUTS_CAPACITY: usize = 20;
+const RECENT_INPUT_LABEL_LEN: usize = 60;
+pub const HISTORY_SEARCH_ID: &str = "history_search";
+pub const PROMPT_INPUT_ID: &str = "prompt_input";
 
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 pub struct Prompt {
     pub title: String,
     pub content: String,
+    pub kind: PromptKind,
+    pub appearance: PromptAppearance,
+    pub template: PromptTemplate,
+    pub examples: Vec<FewShotExample>,
+    pub banned_phrases: Vec<String>,
+    pub regenerate_on_banned_phrases: bool,
+    pub style_guide: Option<PathBuf>,
+    /// Runs a second pass where the model reviews and improves its own answer
+    pub critique_and_revise: bool,
+    /// New responses wait in the drafts tray until they are accepted into the history or rejected
+    pub review_drafts: bool,
+    pub debate: DebateSettings,
+    pub compare: CompareSettings,
+    pub ensemble: EnsembleSettings,
+    /// Sends the previous exchanges along with each input, as a chat
+    pub conversation: bool,
+    /// Folder whose new documents are queued as inputs
+    folder_watch: Option<FolderWatch>,
+    /// Feeds whose new items a briefing summarizes
+    pub feeds: Vec<FeedSubscription>,
+    /// Conversations of the conversation mode, the oldest first
+    pub chat_sessions: Vec<ChatSession>,
+    /// Conversation the inputs are sent in, by its creation time, a new one being started with the
+    /// next input when there's none
+    active_chat: Option<DateTime<Local>>,
+    /// Criteria the judge model scores responses against, the default rubric when empty
+    pub judge_rubric: String,
+    pub judge_model: String,
+    /// Hidden from the prompt list, but kept with its history
+    pub archived: bool,
+    /// Greyed out in the prompt list and not selectable, e.g. while its content is under revision
+    pub disabled: bool,
+    /// Can't be edited or removed until it is unlocked, protecting carefully tuned prompts
+    pub locked: bool,
+    /// Name of the group it's listed under in the left panel, ungrouped when empty
+    pub group: String,
+    /// Lowercase labels the prompt list can be filtered by
+    pub tags: Vec<String>,
+    /// Identifies the prompt on the machines it is synced between, assigned by the first sync
+    pub sync_id: String,
+    /// Fingerprint of the settings at the last sync, telling which machine changed them since
+    pub synced_fingerprint: Option<u64>,
+    /// When the last response was generated, kept when the history is pruned
+    last_used_at: Option<DateTime<Local>>,
+    /// Number of responses generated, kept when the history is pruned
+    reply_count: usize,
+    /// Values of the `{{name}}` placeholders of the content, filled in above the input
+    variables: BTreeMap<String, String>,
+    #[serde(serialize_with = "history::serialize_saved")]
     history: VecDeque<PromptResponse>,
+    /// Responses waiting for review, the newest first
+    #[serde(serialize_with = "history::serialize_saved")]
+    drafts: VecDeque<PromptResponse>,
+    /// Older responses left in the database until they are asked for
+    #[serde(skip)]
+    unloaded_history: Option<UnloadedHistory>,
+    /// The next exchanges are kept in memory only, left out of the saved history and the exports
+    #[serde(skip)]
+    pub incognito: bool,
     new_input: String,
+    #[cfg(feature = "gui")]
     #[serde(skip)]
-    ask_flower: PromptAskFlower,
+    ask_flower: panel::PromptAskFlower,
     #[serde(skip)]
     pub state: PromptState,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    copy_feedback: Option<panel::CopyFeedback>,
+    #[serde(skip)]
+    replay: Option<ScriptReplay>,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    zoomed_image: Option<ResponseImage>,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    speech_flower: panel::PromptSpeechFlower,
+    #[serde(skip)]
+    speech_created_at: Option<DateTime<Local>>,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    snippet_flower: panel::PromptSnippetFlower,
+    /// History entry whose snippet is running
+    #[serde(skip)]
+    snippet_created_at: Option<DateTime<Local>>,
+    #[serde(skip)]
+    audio_player: Option<AudioPlayer>,
+    #[serde(skip)]
+    recent_inputs: VecDeque<String>,
+    #[serde(skip)]
+    banned_phrases_retried: bool,
+    #[serde(skip)]
+    style_guide_cache: Option<StyleGuide>,
+    #[serde(skip)]
+    style_guide_error: Option<String>,
+    #[serde(skip)]
+    critique_pending: bool,
+    #[serde(skip)]
+    revising: bool,
+    /// Output of the latest response before continuing it, the continuation being appended to it
+    #[serde(skip)]
+    continued_output: Option<String>,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    debate_flower: panel::PromptDebateFlower,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    ensemble_flower: panel::PromptEnsembleFlower,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    compare_flower: panel::PromptCompareFlower,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    score_flower: panel::PromptScoreFlower,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    feed_flower: panel::PromptFeedFlower,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    chat_title_flower: panel::PromptChatTitleFlower,
+    /// Conversation being renamed in the list, with the title being written
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    renaming_chat: Option<(DateTime<Local>, String)>,
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    show_archived_chats: bool,
+    /// Responses waiting to be scored by the judge, by their creation time
+    #[serde(skip)]
+    scoring_queue: Vec<DateTime<Local>>,
+    /// When the user chose to keep waiting for a generation flagged by the watchdog
+    #[serde(skip)]
+    watchdog_snoozed_at: Option<Instant>,
+    /// Input to send again once the stopped generation has finished, with the model answering it
+    /// instead of the selected one
+    #[serde(skip)]
+    resubmit: Option<(String, LocalModel)>,
+    /// Documents added to the watched folder, waiting to be processed
+    #[serde(skip)]
+    watched_files: VecDeque<PathBuf>,
+    /// Why the watched folder can't be read, e.g. when it was removed
+    #[serde(skip)]
+    folder_watch_error: Option<String>,
+    /// Name of the document the next response processes
+    #[serde(skip)]
+    pending_source_file: Option<String>,
+    /// Messages of a mailbox being picked or summarized into a digest
+    #[serde(skip)]
+    mail_digest: Option<MailDigest>,
+    /// New items of the feeds being summarized into a briefing
+    #[serde(skip)]
+    briefing: Option<Briefing>,
+    /// URL of a feed being added
+    #[serde(skip)]
+    new_feed_url: String,
+    /// History entries picked for a transcript, by their creation time
+    #[serde(skip)]
+    transcript_selection: HashSet<DateTime<Local>>,
+    /// History entry shown as selectable text to quote a passage from
+    #[serde(skip)]
+    quoting: Option<DateTime<Local>>,
+    #[serde(skip)]
+    quote_selection: String,
+    /// History entry shown as selectable text to highlight a passage of
+    #[serde(skip)]
+    annotating: Option<DateTime<Local>>,
+    /// The highlight being added, its passage taken from the selection
+    #[serde(skip)]
+    annotation_draft: Annotation,
+    /// Entry the input was quoted from, becomes the parent of the response to it
     #[serde(skip)]
-    copy_feedback: Option<CopyFeedback>,
+    quoted_from: Option<DateTime<Local>>,
+    /// Parent of the next response added to the history
+    #[serde(skip)]
+    pending_parent: Option<DateTime<Local>>,
+    /// Variables of the regenerated entry, reused by the next response instead of the form ones
+    #[serde(skip)]
+    pending_variables: Option<BTreeMap<String, String>>,
+    /// History entry the next response becomes a new version of, by its creation time
+    #[serde(skip)]
+    pending_regeneration: Option<DateTime<Local>>,
+    /// The regenerated entry as it was, restored when the regeneration fails
+    #[serde(skip)]
+    replaced_response: Option<PromptResponse>,
+    /// History entry the latest generation streams its output to, by its creation time
+    #[serde(skip)]
+    streaming_to: Option<DateTime<Local>>,
+    /// History entry to scroll to, set by clicking a breadcrumb
+    #[serde(skip)]
+    jump_to: Option<DateTime<Local>>,
+    /// Filters the history entries by their input or output text
+    #[serde(skip)]
+    history_search: String,
+    /// Reveals the output of the newest entry smoothly, with the entry it was started for
+    #[serde(skip)]
+    typewriter: Option<(DateTime<Local>, Typewriter)>,
+    /// Detected content type of the history entries, with the output length it was detected for
+    #[serde(skip)]
+    content_types: HashMap<DateTime<Local>, (usize, ContentType)>,
+    /// History entries shown as markdown despite having a specialized viewer
+    #[serde(skip)]
+    markdown_view: HashSet<DateTime<Local>>,
+    #[serde(skip)]
+    highlighted_outputs: HighlightCache,
+    /// Windows of older history entries shown below the newest ones
+    #[serde(skip)]
+    older_history_windows: usize,
+    /// Width and height of the history entries when they were last rendered, so that the ones
+    /// scrolled out of view only take up their space
+    #[serde(skip)]
+    history_entry_sizes: HashMap<DateTime<Local>, (f32, f32)>,
+    /// Content of the prompt before it was edited, while re-running the starred inputs is offered
+    #[serde(skip)]
+    benchmark_offer: Option<String>,
+    #[serde(skip)]
+    benchmark: Option<Benchmark>,
+    /// Priority set for the queued generations in the queue panel
+    #[serde(skip)]
+    queue_priority: Option<Priority>,
+    /// Aborts the stream of the running generation
+    #[serde(skip)]
+    cancel_stream: Option<oneshot::Sender<()>>,
+    /// Misspelled word of the input right-clicked, with its suggestions
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    spelling_menu: Option<panel::SpellingMenu>,
+    /// Estimated tokens of the request the input would send
+    #[serde(skip)]
+    token_estimate: tokens::Estimate,
+}
+
+/// Starred inputs re-run against the edited prompt, to compare the responses before and after
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+struct Benchmark {
+    /// Starred entries, by their creation time
+    starred: Vec<DateTime<Local>>,
+    /// Starred entries still to re-run
+    queue: VecDeque<DateTime<Local>>,
+    started_at: DateTime<Local>,
+    previous_content: String,
 }
 
 impl Default for Prompt {
@@ -39,39 +294,135 @@ impl Default for Prompt {
         Self {
             title: Default::default(),
             content: Default::default(),
+            kind: Default::default(),
+            appearance: Default::default(),
+            template: Default::default(),
+            examples: Default::default(),
+            banned_phrases: Default::default(),
+            regenerate_on_banned_phrases: false,
+            style_guide: None,
+            critique_and_revise: false,
+            review_drafts: false,
+            debate: Default::default(),
+            compare: Default::default(),
+            ensemble: Default::default(),
+            conversation: false,
+            folder_watch: None,
+            feeds: Vec::new(),
+            chat_sessions: Vec::new(),
+            active_chat: None,
+            judge_rubric: Default::default(),
+            judge_model: Default::default(),
+            archived: false,
+            disabled: false,
+            locked: false,
+            group: String::new(),
+            tags: Vec::new(),
+            sync_id: String::new(),
+            synced_fingerprint: None,
+            last_used_at: None,
+            reply_count: 0,
+            variables: BTreeMap::new(),
             history: Default::default(),
+            drafts: Default::default(),
+            unloaded_history: None,
+            incognito: false,
             new_input: Default::default(),
-            ask_flower: PromptAskFlower::new(1),
+            #[cfg(feature = "gui")]
+            ask_flower: panel::PromptAskFlower::new(1),
             state: Default::default(),
+            #[cfg(feature = "gui")]
             copy_feedback: None,
+            replay: None,
+            #[cfg(feature = "gui")]
+            zoomed_image: None,
+            #[cfg(feature = "gui")]
+            speech_flower: panel::PromptSpeechFlower::new(1),
+            speech_created_at: None,
+            #[cfg(feature = "gui")]
+            snippet_flower: panel::PromptSnippetFlower::new(1),
+            snippet_created_at: None,
+            audio_player: None,
+            recent_inputs: VecDeque::with_capacity(RECENT_INPUTS_CAPACITY),
+            banned_phrases_retried: false,
+            style_guide_cache: None,
+            style_guide_error: None,
+            critique_pending: false,
+            revising: false,
+            continued_output: None,
+            #[cfg(feature = "gui")]
+            debate_flower: panel::PromptDebateFlower::new(1),
+            #[cfg(feature = "gui")]
+            ensemble_flower: panel::PromptEnsembleFlower::new(1),
+            #[cfg(feature = "gui")]
+            compare_flower: panel::PromptCompareFlower::new(1),
+            #[cfg(feature = "gui")]
+            score_flower: panel::PromptScoreFlower::new(1),
+            #[cfg(feature = "gui")]
+            feed_flower: panel::PromptFeedFlower::new(1),
+            #[cfg(feature = "gui")]
+            chat_title_flower: panel::PromptChatTitleFlower::new(1),
+            #[cfg(feature = "gui")]
+            renaming_chat: None,
+            #[cfg(feature = "gui")]
+            show_archived_chats: false,
+            scoring_queue: Vec::new(),
+            watchdog_snoozed_at: None,
+            resubmit: None,
+            watched_files: VecDeque::new(),
+            folder_watch_error: None,
+            pending_source_file: None,
+            mail_digest: None,
+            briefing: None,
+            new_feed_url: String::new(),
+            transcript_selection: HashSet::new(),
+            quoting: None,
+            quote_selection: String::new(),
+            annotating: None,
+            annotation_draft: Annotation::default(),
+            quoted_from: None,
+            pending_parent: None,
+            pending_variables: None,
+            pending_regeneration: None,
+            replaced_response: None,
+            streaming_to: None,
+            jump_to: None,
+            history_search: String::new(),
+            typewriter: None,
+            content_types: HashMap::new(),
+            markdown_view: HashSet::new(),
+            highlighted_outputs: HighlightCache::default(),
+            older_history_windows: 0,
+            history_entry_sizes: HashMap::new(),
+            benchmark_offer: None,
+            benchmark: None,
+            queue_priority: None,
+            cancel_stream: None,
+            #[cfg(feature = "gui")]
+            spelling_menu: None,
+            token_estimate: Default::default(),
         }
     }
 }
 
-type PromptAskFlower = CompactFlower<String, String, String>;
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PromptKind {
+    #[default]
+    Text,
+    Image,
+}
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+/// Icon and accent color that make a prompt easy to spot in a long list
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Default, Debug)]
 #[serde(default)]
-struct PromptResponse {
-    input: String,
-    output: String,
-    local_model_name: String,
-    #[serde(skip)]
-    requested_at: Instant,
-    created_at: DateTime<Local>,
+pub struct PromptAppearance {
+    pub icon: String,
+    pub color: Option<AccentColor>,
 }
 
-impl Default for PromptResponse {
-    fn default() -> Self {
-        Self {
-            input: Default::default(),
-            output: Default::default(),
-            local_model_name: "unknown_model".to_owned(),
-            requested_at: Instant::now(),
-            created_at: Local::now(),
-        }
-    }
-}
+/// A color as its premultiplied RGBA bytes, stored the way egui stores its colors
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AccentColor(pub [u8; 4]);
 
 #[derive(Default)]
 pub enum PromptState {
@@ -86,475 +437,970 @@ impl PromptState {
     }
 }
 
-impl PromptResponse {
-    pub fn new(input: String, output: String, local_model_name: String) -> Self {
-        Self {
-            input,
-            output,
-            local_model_name,
-            ..Default::default()
-        }
-    }
-}
-
+// Some of the methods are only used by the panel
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
 impl Prompt {
+    #[cfg_attr(not(feature = "gui"), allow(unused_variables))]
     pub fn new(title: String, content: String, id: usize) -> Self {
         Self {
             title,
             content,
-            ask_flower: PromptAskFlower::new(id),
+            #[cfg(feature = "gui")]
+            ask_flower: panel::PromptAskFlower::new(id),
+            #[cfg(feature = "gui")]
+            speech_flower: panel::PromptSpeechFlower::new(id),
+            #[cfg(feature = "gui")]
+            snippet_flower: panel::PromptSnippetFlower::new(id),
+            #[cfg(feature = "gui")]
+            debate_flower: panel::PromptDebateFlower::new(id),
+            #[cfg(feature = "gui")]
+            ensemble_flower: panel::PromptEnsembleFlower::new(id),
+            #[cfg(feature = "gui")]
+            compare_flower: panel::PromptCompareFlower::new(id),
+            #[cfg(feature = "gui")]
+            score_flower: panel::PromptScoreFlower::new(id),
+            #[cfg(feature = "gui")]
+            feed_flower: panel::PromptFeedFlower::new(id),
+            #[cfg(feature = "gui")]
+            chat_title_flower: panel::PromptChatTitleFlower::new(id),
             ..Default::default()
         }
     }
 
-    pub fn remove_history(&mut self, history_idx: usize) {
-        self.history.remove(history_idx);
+    /// Removes a history entry, its files being kept until it's discarded so that it can be restored
+    pub fn remove_history(&mut self, history_idx: usize) -> Option<PromptResponse> {
+        let response = self.history.remove(history_idx)?;
+
+        if self
+            .audio_player
+            .as_ref()
+            .is_some_and(|player| player.response_created_at == response.created_at)
+        {
+            self.audio_player = None;
+        }
+
+        Some(response)
+    }
+
+    /// Adds an entry to the history in the order of creation, e.g. a removed one put back
+    pub fn insert_history(&mut self, response: PromptResponse) {
+        let pos = self
+            .history
+            .iter()
+            .position(|existing| existing.created_at < response.created_at)
+            .unwrap_or(self.history.len());
+
+        self.history.insert(pos, response);
+    }
+
+    /// Returns the input of a history entry, making it the parent of the next response
+    pub fn derive_input(&mut self, history_idx: usize) -> Option<String> {
+        let response = self.history.get(history_idx)?;
+
+        self.pending_parent = Some(response.created_at);
+        self.pending_variables = Some(response.variables.clone());
+
+        Some(response.input.clone())
+    }
+
+    /// Returns the input of a history entry, the next response becoming its latest version
+    pub fn regenerate_input(&mut self, history_idx: usize) -> Option<String> {
+        let response = self.history.get(history_idx)?;
+
+        self.pending_regeneration = Some(response.created_at);
+        self.pending_variables = Some(response.variables.clone());
+
+        Some(response.input.clone())
+    }
+
+    /// Forgets the entry to regenerate when the generation couldn't start
+    pub fn clear_pending_regeneration(&mut self) {
+        self.pending_regeneration = None;
+        self.pending_variables = None;
+    }
+
+    pub fn starred_count(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|response| response.starred)
+            .count()
+    }
+
+    /// Offers to re-run the starred inputs after the content changed from `previous_content`
+    pub fn offer_benchmark(&mut self, previous_content: String) {
+        if self.starred_count() > 0 {
+            self.benchmark_offer = Some(previous_content);
+        }
+    }
+
+    fn start_benchmark(&mut self) {
+        let starred: Vec<DateTime<Local>> = self
+            .history
+            .iter()
+            .filter(|response| response.starred)
+            .map(|response| response.created_at)
+            .collect();
+
+        self.benchmark = Some(Benchmark {
+            queue: starred.iter().copied().collect(),
+            starred,
+            started_at: Local::now(),
+            previous_content: self.benchmark_offer.take().unwrap_or_default(),
+        });
+    }
+
+    /// Takes the input of the next starred entry to re-run, its response being derived from it
+    fn next_benchmark_input(&mut self) -> Option<String> {
+        let created_at = self.benchmark.as_mut()?.queue.pop_front()?;
+        let history_idx = self
+            .history
+            .iter()
+            .position(|response| response.created_at == created_at)?;
+
+        self.derive_input(history_idx)
+    }
+
+    fn push_response(&mut self, mut response: PromptResponse) {
+        self.replaced_response = None;
+
+        // A regenerated entry keeps its place in the history, the generation streaming to it there
+        if let Some(created_at) = self.pending_regeneration.take()
+            && let Some(entry) = Self::streamed_response(&mut self.history, Some(created_at))
+        {
+            self.pending_parent = None;
+            self.pending_variables = None;
+            response.incognito = entry.incognito;
+            self.replaced_response = Some(entry.clone());
+            entry.push_version(response);
+            self.streaming_to = Some(created_at);
+            return;
+        }
+
+        response.parent = self.pending_parent.take();
+        response.source_file = self.pending_source_file.take();
+
+        if let Some(mail_digest) = self.mail_digest.as_mut() {
+            mail_digest.record_response(response.created_at);
+        }
+
+        if let Some(briefing) = self.briefing.as_mut() {
+            briefing.record_response(response.created_at);
+        }
+
+        if self.conversation {
+            response.chat_session = Some(self.chat_session_for_input());
+        }
+
+        response.variables = self
+            .pending_variables
+            .take()
+            .unwrap_or_else(|| self.filled_variables());
+        response.incognito = self.incognito;
+
+        if !response.incognito {
+            self.last_used_at = Some(response.created_at);
+            self.reply_count += 1;
+        }
+
+        self.streaming_to = Some(response.created_at);
+        self.history.push_front(response);
     }
 
+    /// The history entry created at `streaming_to`, which the generation streams its output to
+    fn streamed_response(
+        history: &mut VecDeque<PromptResponse>,
+        streaming_to: Option<DateTime<Local>>,
+    ) -> Option<&mut PromptResponse> {
+        history
+            .iter_mut()
+            .find(|response| Some(response.created_at) == streaming_to)
+    }
+
+    fn is_streamed_to(&self, response: &PromptResponse) -> bool {
+        self.streaming_to == Some(response.created_at)
+    }
+
+    /// Removes the response whose generation failed, or restores the entry it regenerated
+    fn discard_failed_response(
+        history: &mut VecDeque<PromptResponse>,
+        replaced_response: &mut Option<PromptResponse>,
+        streaming_to: Option<DateTime<Local>>,
+    ) {
+        match replaced_response.take() {
+            Some(previous) => {
+                if let Some(response) = Self::streamed_response(history, streaming_to) {
+                    *response = previous;
+                }
+            }
+            None => {
+                history.retain(|response| Some(response.created_at) != streaming_to);
+            }
+        }
+    }
+
+    /// Values of the form for the variables of the content
+    fn filled_variables(&self) -> BTreeMap<String, String> {
+        compose::variables(&self.content)
+            .into_iter()
+            .map(|name| {
+                let value = self.variables.get(&name).cloned().unwrap_or_default();
+
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// The content with the variables of the latest response filled in
+    fn latest_content(&self) -> String {
+        match self.history.front() {
+            Some(response) => compose::fill_variables(&self.content, &response.variables),
+            None => self.content.clone(),
+        }
+    }
+
+    /// Names of the models the debate, comparison, ensemble and judge settings refer to
+    pub fn bound_models(&self) -> impl Iterator<Item = &str> {
+        [
+            &self.debate.first_model,
+            &self.debate.second_model,
+            &self.debate.synthesizer_model,
+            &self.ensemble.judge_model,
+            &self.judge_model,
+        ]
+        .into_iter()
+        .chain(&self.compare.models)
+        .map(String::as_str)
+        .filter(|name| !name.is_empty())
+    }
+
+    /// Makes the settings referring to the model `from` refer to `to` instead
+    pub fn remap_model(&mut self, from: &str, to: &str) {
+        for name in [
+            &mut self.debate.first_model,
+            &mut self.debate.second_model,
+            &mut self.debate.synthesizer_model,
+            &mut self.ensemble.judge_model,
+            &mut self.judge_model,
+        ]
+        .into_iter()
+        .chain(&mut self.compare.models)
+        {
+            if name == from {
+                *name = to.to_string();
+            }
+        }
+    }
+
+    /// Responses in the history, including the ones not loaded from the database
     pub fn history_count(&self) -> usize {
-        self.history.len()
+        self.history.len() + self.unloaded_history.map_or(0, |unloaded| unloaded.len)
+    }
+
+    pub fn unloaded_history(&self) -> Option<UnloadedHistory> {
+        self.unloaded_history
+    }
+
+    /// Reads a prompt from the database, with the newest page of its history
+    pub fn from_stored(stored: StoredPrompt) -> anyhow::Result<Self> {
+        let mut prompt: Self = ron::from_str(&stored.settings)?;
+
+        prompt.load_history(stored.history);
+
+        Ok(prompt)
+    }
+
+    /// Adds a page of responses read from the database, older than the loaded ones, and shows them
+    pub fn load_older_history(&mut self, page: HistoryPage) {
+        self.load_history(page);
+        self.older_history_windows += 1;
+    }
+
+    /// Adds a page of responses read from the database, older than the loaded ones
+    pub fn load_history(&mut self, page: HistoryPage) {
+        for response in page.responses {
+            match ron::from_str(&response) {
+                Ok(response) => self.insert_history(response),
+                Err(e) => log::warn!("Skipped an unreadable response of \"{}\": {e}", self.title),
+            }
+        }
+
+        self.unloaded_history = page.unloaded;
+    }
+
+    /// The prompt as it's written to the database, its history apart from its settings
+    pub fn to_record(&mut self) -> anyhow::Result<PromptRecord> {
+        let history = std::mem::take(&mut self.history);
+        let settings = ron::to_string(self);
+
+        self.history = history;
+
+        let history = self
+            .history
+            .iter()
+            .filter(|response| !response.incognito)
+            .map(|response| Ok((response.created_at, ron::to_string(response)?)))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(PromptRecord {
+            key: self.sync_id.clone(),
+            settings: settings?,
+            history,
+            unloaded: self.unloaded_history,
+        })
     }
 
-    pub fn get_last_used_time(&self) -> Option<DateTime<Local>> {
-        // Find the most recently created response in history
+    /// Words of all responses
+    pub fn word_count(&self) -> usize {
+        self.history.iter().map(PromptResponse::word_count).sum()
+    }
+
+    /// Name of the model that generated the latest response
+    pub fn last_model_name(&self) -> Option<&str> {
         self.history
+            .front()
+            .map(|response| response.local_model_name.as_str())
+    }
+
+    pub fn responses(&self) -> impl Iterator<Item = &PromptResponse> {
+        self.history.iter()
+    }
+
+    /// Scrolls the history to a response once it is shown
+    pub fn jump_to_response(&mut self, created_at: DateTime<Local>) {
+        self.jump_to = Some(created_at);
+        // The response could be filtered out
+        self.history_search.clear();
+    }
+
+    pub fn response_output(&self, history_idx: usize) -> Option<&str> {
+        self.history
+            .get(history_idx)
+            .map(|response| response.output.as_str())
+    }
+
+    pub fn last_used_at(&self) -> Option<DateTime<Local>> {
+        self.last_used_at
+    }
+
+    pub fn reply_count(&self) -> usize {
+        self.reply_count
+    }
+
+    /// Records the usage of the responses in the history, stored before it was tracked
+    pub fn record_usage_from_history(&mut self) {
+        self.last_used_at = self
+            .history
             .iter()
             .map(|response| response.created_at)
             .max()
+            .max(self.last_used_at);
+        self.reply_count = self.reply_count.max(self.history.len());
     }
 
-    pub fn show_left_panel(
-        &self,
-        ui: &mut egui::Ui,
-        selected: bool,
-        idx: usize,
-    ) -> Option<AppAction> {
-        let mut action = None;
-
-        let response = ui.scope_builder(
-            UiBuilder::new()
-                .id_salt("left_panel_prompt")
-                .sense(Sense::click()),
-            |ui| {
-                ui.with_layout(
-                    Layout::left_to_right(egui::Align::TOP)
-                        .with_main_justify(true)
-                        .with_main_align(egui::Align::LEFT),
-                    |ui| {
-                        let fill_style = if selected {
-                            ui.style().visuals.faint_bg_color
-                        } else {
-                            ui.style().visuals.window_fill
-                        };
-                        let stroke_style_color = if selected {
-                            Color32::ORANGE
-                        } else {
-                            ui.style().visuals.window_stroke.color
-                        };
-
-                        Frame::group(ui.style())
-                            .inner_margin(egui::Margin::same(10))
-                            .corner_radius(CornerRadius::same(6))
-                            .stroke(Stroke::new(2.0, stroke_style_color))
-                            .fill(fill_style)
-                            .show(ui, |ui| {
-                                ui.horizontal(|ui| {
-                                    let label_response =
-                                        ui.add(egui::Label::wrap(egui::Label::new(&self.title)));
-
-                                    ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
-                                        let remove_response = ui.add_enabled(
-                                            !self.state.is_generating(),
-                                            egui::Button::new("❌")
-                                                .fill(Color32::TRANSPARENT)
-                                                .small()
-                                                .stroke(Stroke::NONE),
-                                        );
-
-                                        let edit_response = ui.add(
-                                            egui::Button::new("\u{270f}")
-                                                .fill(Color32::TRANSPARENT)
-                                                .small()
-                                                .stroke(Stroke::NONE),
-                                        );
-
-                                        let count_text = format!("{:3}", self.history.len());
-
-                                        ui.add(egui::Label::new(RichText::new(count_text)));
-
-                                        if remove_response
-                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                            .on_hover_text("Remove prompt")
-                                            .clicked()
-                                        {
-                                            action = Some(AppAction::OpenRemovePromptDialog(idx));
-                                        }
-
-                                        if edit_response
-                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                            .on_hover_text("Edit prompt")
-                                            .clicked()
-                                        {
-                                            action = Some(AppAction::OpenEditPromptDialog(idx));
-                                        }
-                                    });
-
-                                    label_response
-                                })
-                                .inner
-                            })
-                            .inner
-                    },
-                )
-                .inner
-            },
-        );
-        let response = response.response.union(response.inner);
+    /// Moves the history of a duplicate prompt into this one, skipping responses it already has
+    pub fn merge_history(&mut self, other: Prompt) {
+        self.extend_history(other.history);
+        self.reply_count = self.reply_count.max(other.reply_count);
+    }
 
-        if response
-            .on_hover_cursor(egui::CursorIcon::PointingHand)
-            .clicked()
-        {
-            action = Some(AppAction::SelectPrompt(idx));
+    /// Hashes of the exchanges in the history
+    pub fn response_hashes(&self) -> HashSet<u64> {
+        history::exchange_hashes(&self.history)
+    }
+
+    /// Restores the recorded exchanges missing from the history, returns how many were added
+    pub fn import_steps(&mut self, steps: Vec<ScriptStep>) -> usize {
+        let len = self.history.len();
+
+        self.extend_history(steps.into_iter().map(|step| PromptResponse {
+            input: step.input,
+            output: step.output,
+            local_model_name: step.local_model_name,
+            created_at: step.created_at,
+            ..Default::default()
+        }));
+
+        self.history.len() - len
+    }
+
+    fn extend_history(&mut self, responses: impl IntoIterator<Item = PromptResponse>) {
+        history::extend(&mut self.history, responses);
+        self.record_usage_from_history();
+    }
+
+    /// Keeps only the most recent responses
+    pub fn prune_history(&mut self, len: usize) {
+        self.history.truncate(len);
+        // The unloaded responses are older than the kept ones
+        self.unloaded_history = None;
+    }
+
+    /// Records the history as a script, oldest exchange first
+    pub fn to_script(&self) -> Script {
+        let steps = self
+            .history
+            .iter()
+            .rev()
+            .filter(|response| !response.incognito)
+            .map(|response| ScriptStep {
+                input: response.input.clone(),
+                output: response.output.clone(),
+                local_model_name: response.local_model_name.clone(),
+                created_at: response.created_at,
+            })
+            .collect();
+
+        Script {
+            title: self.title.clone(),
+            content: self.content.clone(),
+            steps,
+        }
+    }
+
+    /// Formats the history, or only the selected entries, as a markdown transcript, oldest first
+    pub fn to_transcript(&self, selected_only: bool) -> String {
+        history::transcript(
+            &self.title,
+            self.history.iter().rev().filter(|response| {
+                !selected_only || self.transcript_selection.contains(&response.created_at)
+            }),
+        )
+    }
+
+    /// Formats the history as a markdown transcript for a file, oldest first, leaving out the
+    /// incognito exchanges
+    pub fn to_saved_transcript(&self) -> String {
+        history::transcript(
+            &self.title,
+            self.history
+                .iter()
+                .rev()
+                .filter(|response| !response.incognito),
+        )
+    }
+
+    /// Appends the text of a document to the input, headed by its name
+    pub fn attach_to_input(&mut self, name: &str, text: &str) {
+        if !self.new_input.is_empty() && !self.new_input.ends_with('\n') {
+            self.new_input.push('\n');
         }
 
-        action
+        self.new_input.push_str(&document_input(name, text));
     }
 
-    pub fn show_main_panel(
-        &mut self,
-        ui: &mut egui::Ui,
-        is_modal_shown: bool,
-        idx: usize,
-        commonmark_cache: &mut CommonMarkCache,
-    ) -> Option<AppAction> {
-        let mut action = None;
-        let is_input_interactive = !self.state.is_generating();
+    pub fn is_watching_folder(&self) -> bool {
+        self.folder_watch.is_some()
+    }
 
-        ui.add_space(16.0);
+    /// Processes the documents added to the folder from now on, each as the input of a response
+    pub fn watch_folder(&mut self, dir: PathBuf) -> anyhow::Result<()> {
+        self.folder_watch = Some(FolderWatch::new(dir)?);
+        self.folder_watch_error = None;
 
-        ui.with_layout(
-            Layout::left_to_right(egui::Align::TOP).with_main_justify(true),
-            |ui| {
-                let text_edit = egui::TextEdit::multiline(&mut self.new_input)
-                    .hint_text(format!("Ask for the following prompt: {}", self.content))
-                    .interactive(is_input_interactive)
-                    .return_key(KeyboardShortcut::new(Modifiers::SHIFT, Key::Enter));
+        Ok(())
+    }
 
-                let response = text_edit.show(ui);
+    /// Stops watching the folder, dropping the documents that weren't processed yet
+    pub fn stop_watching_folder(&mut self) {
+        self.folder_watch = None;
+        self.folder_watch_error = None;
+        self.watched_files.clear();
+    }
 
-                // Request focus when prompt is first displayed or navigated to
-                if !is_modal_shown && is_input_interactive {
-                    response.response.request_focus();
+    /// Queues the documents added to the watched folder since the last check
+    pub fn poll_folder_watch(&mut self) {
+        let Some(folder_watch) = self.folder_watch.as_mut() else {
+            return;
+        };
+
+        match folder_watch.poll() {
+            Ok(added) => {
+                self.folder_watch_error = None;
+                self.watched_files.extend(added);
+            }
+            Err(e) => self.folder_watch_error = Some(e.to_string()),
+        }
+    }
+
+    /// Takes the next document added to the watched folder as an input
+    fn next_watched_input(&mut self) -> Option<String> {
+        while let Some(path) = self.watched_files.pop_front() {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    self.pending_source_file = Some(name.clone());
+
+                    return Some(document_input(&name, &text));
                 }
-            },
-        );
+                Err(e) => log::warn!("Skipped {} of the watched folder: {e}", path.display()),
+            }
+        }
 
-        ui.add_space(6.0);
+        None
+    }
 
-        ui.separator();
+    /// Puts the selected passage into the input using the quote template
+    fn quote_into_input(&mut self, template: &str) {
+        let quote = template.replace("{selection}", self.quote_selection.trim());
 
-        if is_input_interactive
-            && !is_modal_shown
-            && !self.new_input.is_empty()
-            && ui.input(|i| i.key_pressed(Key::Enter) && i.modifiers.is_none())
-        {
-            action = Some(AppAction::GeneratePromptResponse {
-                idx,
-                input: self.new_input.clone(),
-            });
-            self.new_input.clear();
-        }
-
-        if self.ask_flower.is_active() {
-            assign_if_some!(action, self.poll_ask_flower());
-        }
-
-        assign_if_some!(action, self.show_prompt_history(ui, idx, commonmark_cache));
-
-        action
-    }
-
-    fn show_prompt_history(
-        &mut self,
-        ui: &mut egui::Ui,
-        idx: usize,
-        commonmark_cache: &mut CommonMarkCache,
-    ) -> Option<AppAction> {
-        let mut action = None;
-
-        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
-            for (history_idx, prompt_response) in self.history.iter().enumerate() {
-                ui.add_space(6.0);
-
-                ui.with_layout(
-                    Layout::left_to_right(egui::Align::TOP)
-                        .with_main_justify(true)
-                        .with_main_align(egui::Align::LEFT),
-                    |ui| {
-                        Frame::group(ui.style())
-                            .inner_margin(egui::Margin::same(10))
-                            .corner_radius(CornerRadius::same(6))
-                            .stroke(Stroke::new(1.0, ui.style().visuals.window_stroke.color))
-                            .show(ui, |ui| {
-                                ui.with_layout(
-                                    Layout::top_down(egui::Align::TOP)
-                                        .with_cross_justify(true)
-                                        .with_cross_align(egui::Align::LEFT),
-                                    |ui| {
-                                        ui.horizontal(|ui| {
-                                            ui.label("🖳");
-                                            ui.label(&prompt_response.local_model_name);
-                                            ui.label(
-                                                RichText::new(
-                                                    format!(" {} ", prompt_response.created_at.format("%Y-%m-%d %H:%M:%S"))
-                                                ).weak()
-                                            );
-
-                                            ui.with_layout(
-                                                Layout::right_to_left(egui::Align::Min),
-                                                |ui| {
-                                                    let remove_response = ui.add_enabled(
-                                                        !self.state.is_generating(),
-                                                        egui::Button::new("❌")
-                                                            .fill(Color32::TRANSPARENT)
-                                                            .small()
-                                                            .stroke(Stroke::NONE),
-                                                    );
-
-                                                    let regenerate_response = ui.add_enabled(
-                                                        !self.state.is_generating(),
-                                                        egui::Button::new("🔄")
-                                                            .fill(Color32::TRANSPARENT)
-                                                            .small()
-                                                            .stroke(Stroke::NONE),
-                                                    );
-
-                                                    if self.state.is_generating() && history_idx == 0
-                                                        && ui.add_enabled(
-                                                            true,
-                                                            egui::Button::new("⏹")
-                                                                .fill(Color32::TRANSPARENT)
-                                                                .small()
-                                                                .stroke(Stroke::NONE),
-                                                        )
-                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                                        .on_hover_text("Stop generation")
-                                                        .clicked() {
-                                                            action = Some(AppAction::StopPromptGeneration(idx));
-                                                    }
-
-                                                    if remove_response
-                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                                        .on_hover_text("Remove from prompt history")
-                                                        .clicked()
-                                                    {
-                                                        action = Some(AppAction::OpenRemovePromptHistoryDialog { idx, history_idx});
-                                                    }
-
-                                                    if regenerate_response
-                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                                        .on_hover_text("Regenerate with current model")
-                                                        .clicked()
-                                                    {
-                                                        action = Some(AppAction::RegeneratePromptResponse { idx, history_idx });
-                                                    }
-                                                },
-                                            );
-                                        });
-
-                                        ui.add_space(6.0);
-
-                                        Frame::group(ui.style())
-                                            .stroke(Stroke::new(
-                                                1.0,
-                                                ui.style().visuals.window_stroke.color,
-                                            ))
-                                            .fill(ui.style().visuals.faint_bg_color)
-                                            .show(ui, |ui| {
-                                                ui.add(egui::Label::wrap(Label::new(
-                                                    &prompt_response.input,
-                                                )));
-                                            });
-
-                                        CommonMarkViewer::new().show(
-                                            ui,
-                                            commonmark_cache,
-                                            &prompt_response.output,
-                                        );
-
-                                        ui.add_space(6.0);
-
-                                        // Add copy button at the bottom left with feedback
-                                        ui.with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
-                                            let copy_response = ui.add(
-                                                egui::Button::new("🗐")
-                                                    .fill(Color32::TRANSPARENT)
-                                                    .small()
-                                                    .stroke(Stroke::NONE),
-                                            );
-
-                                            if copy_response
-                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                                .on_hover_text("Copy response")
-                                                .clicked()
-                                            {
-                                                match crate::copy_to_clipboard(&prompt_response.output) {
-                                                    Ok(()) => {
-                                                        self.copy_feedback = Some(CopyFeedback::new(
-                                                            prompt_response.created_at,
-                                                        ));
-                                                    }
-                                                    Err(e) => {
-                                                        action = Some(AppAction::ShowErrorDialog {
-                                                            title: "Copy Error".to_string(),
-                                                            message: format!(
-                                                                "Failed to copy to clipboard: {e}"
-                                                            ),
-                                                        });
-                                                    }
-                                                }
-                                            }
-
-                                            if matches!(
-                                                self.copy_feedback.as_ref(),
-                                                Some(feedback) if !feedback.is_active()
-                                            ) {
-                                                self.copy_feedback = None;
-                                            }
-
-                                            if let Some(feedback) = self.copy_feedback.as_ref()
-                                                && feedback.is_active()
-                                                && feedback.response_created_at
-                                                    == prompt_response.created_at
-                                            {
-                                                // ui.add_space(6.0);
-                                                ui.label(
-                                                    RichText::new("Copied!")
-                                                        .color(Color32::from_rgb(80, 200, 120))
-                                                        .strong(),
-                                                );
-                                            }
-                                        });
-                                    },
-                                );
-                            });
-                    },
-                );
+        if !self.new_input.is_empty() && !self.new_input.ends_with('\n') {
+            self.new_input.push('\n');
+        }
+
+        self.new_input.push_str(&quote);
+        self.quoted_from = self.quoting.take();
+        self.quote_selection.clear();
+    }
+
+    pub fn start_replay(&mut self, script: Script) {
+        self.replay = Some(ScriptReplay::new(script));
+    }
+
+    /// Replaces the input with a past one, the replaced text staying among the recent inputs
+    pub fn recall_input(&mut self, input: String) {
+        let replaced = std::mem::replace(&mut self.new_input, input);
+
+        if replaced != self.new_input {
+            self.remember_input(replaced);
+        }
+    }
+
+    /// The inputs sent with the prompt, the newest first, each once with its latest time
+    pub fn past_inputs(&self) -> impl Iterator<Item = (&str, DateTime<Local>)> {
+        let mut seen = HashSet::new();
+
+        self.history
+            .iter()
+            .filter(|response| !response.input.trim().is_empty())
+            .filter(move |response| seen.insert(response.input.as_str()))
+            .map(|response| (response.input.as_str(), response.created_at))
+    }
+
+    /// Keeps submitted and cleared inputs around so that they can be restored later
+    fn remember_input(&mut self, input: String) {
+        if input.trim().is_empty() {
+            return;
+        }
+
+        self.recent_inputs.retain(|recent| *recent != input);
+        self.recent_inputs.push_front(input);
+        self.recent_inputs.truncate(RECENT_INPUTS_CAPACITY);
+    }
+
+    /// Returns the current text of the style guide, loading it again if the file changed on disk
+    fn refresh_style_guide(&mut self) -> Option<&str> {
+        let Some(path) = &self.style_guide else {
+            self.style_guide_cache = None;
+            self.style_guide_error = None;
+            return None;
+        };
+
+        let result = match self.style_guide_cache.as_mut() {
+            Some(cache) if cache.path() == path => cache.refresh(),
+            _ => StyleGuide::load(path).map(|cache| {
+                self.style_guide_cache = Some(cache);
+            }),
+        };
+
+        match result {
+            Ok(()) => {
+                self.style_guide_error = None;
+                self.style_guide_cache
+                    .as_ref()
+                    .map(|cache| cache.text.as_str())
+            }
+            Err(e) => {
+                self.style_guide_cache = None;
+                self.style_guide_error = Some(e.to_string());
+                None
             }
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Adds the briefing of the summarized items to the history, as an entry of its own
+    fn finish_briefing(&mut self) {
+        let Some(briefing) = self.briefing.take() else {
+            return;
+        };
+        let history = &self.history;
+        let drafts = &self.drafts;
+        let document = briefing.document(|created_at| {
+            history
+                .iter()
+                .chain(drafts)
+                .find(|response| response.created_at == created_at)
+                .map(|response| response.output.as_str())
         });
+        let mut response = PromptResponse::new(
+            format!(
+                "Briefing of {} new item(s) from {} feed(s)",
+                briefing.summarized_len(),
+                self.feeds.len()
+            ),
+            document,
+            "briefing".to_string(),
+        );
+
+        response.incognito = self.incognito;
+
+        let created_at = response.created_at;
 
-        action
+        self.insert_history(response);
+        self.jump_to_response(created_at);
     }
 
-    pub fn generate_response(
-        &mut self,
-        input: String,
-        local_model: &LocalModel,
-        rt: &runtime::Runtime,
-        ollama_client: &OllamaClient,
-    ) {
-        self.state = PromptState::Generating;
+    /// Lists the messages of a mailbox to pick the ones summarized into a digest
+    pub fn open_mail_digest(&mut self, source: PathBuf, messages: Vec<MailMessage>) {
+        self.mail_digest = Some(MailDigest::new(source, messages));
+    }
 
-        let response = PromptResponse::new(input.clone(), String::new(), local_model.name.clone());
-        self.history.push_front(response);
+    pub fn mail_digest_document(&self) -> Option<&str> {
+        self.mail_digest.as_ref()?.document.as_deref()
+    }
 
-        self.ask_ollama(input, local_model, rt, ollama_client.clone());
+    /// Moves the response just generated into the drafts tray when drafts are reviewed.
+    ///
+    /// Regenerated entries stay in the history, their new version being reviewed in place.
+    fn hold_for_review(&mut self) {
+        if self.review_drafts
+            && self.replaced_response.is_none()
+            && let Some(position) = self
+                .history
+                .iter()
+                .position(|response| self.is_streamed_to(response))
+            && let Some(response) = self.history.remove(position)
+        {
+            self.drafts.push_front(response);
+        }
     }
 
-    pub fn regenerate_response(
-        &mut self,
-        history_idx: usize,
-        local_model: &LocalModel,
-        rt: &runtime::Runtime,
-        ollama_client: &OllamaClient,
-    ) {
-        if let Some(original_response) = self.history.get(history_idx) {
-            let input = original_response.input.clone();
-            self.generate_response(input, local_model, rt, ollama_client);
+    /// Replaces the output of a history entry with one of its ensemble candidates
+    pub fn select_candidate(&mut self, history_idx: usize, candidate_idx: usize) {
+        if let Some(response) = self.history.get_mut(history_idx)
+            && let Some(candidate) = response.candidates.get(candidate_idx)
+        {
+            response.output = candidate.output.clone();
+            response.selected_candidate = Some(candidate_idx);
+            response.audio = None;
         }
     }
 
-    fn ask_ollama(
-        &self,
-        question: String,
-        local_model: &LocalModel,
-        rt: &runtime::Runtime,
-        ollama_client: OllamaClient,
-    ) {
-        let handle = self.ask_flower.handle();
-        let prompt = format!("{}:\n{}", self.content, question);
-        let local_model = local_model.clone();
-
-        rt.spawn(async move {
-            handle.activate();
-
-            match ollama_client
-                .generate_completion(prompt, &local_model, |response| handle.send(response))
-                .await
-            {
-                Ok(response) => handle.success(response),
-                Err(e) => handle.error(e.to_string()),
+    /// Stops the generation and sends its input again once it has finished
+    /// Stops the generation of this prompt and queues its input again for the model to answer,
+    /// the other prompts and the selected model staying as they are
+    pub fn switch_model(&mut self, model: LocalModel) {
+        self.cancel_generation();
+        self.resubmit = self.derive_input(0).map(|input| (input, model));
+    }
+
+    /// The text of the request the input would send, without the style guide read from its file
+    fn request_text(&self) -> String {
+        let exchanges = self.conversation_exchanges(0);
+        let first_input = exchanges
+            .first()
+            .map_or(self.new_input.as_str(), |exchange| exchange.input.as_str());
+        let content = compose::fill_variables(&self.content, &self.variables);
+        let prompt = self.template.compose(&content, &self.examples, first_input);
+
+        if exchanges.is_empty() {
+            return [prompt.system.as_deref(), Some(&prompt.prompt)]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        prompt
+            .continue_conversation(&exchanges, &self.new_input)
+            .into_iter()
+            .map(|message| message.content)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Changes with the request the input would send, without making its text
+    fn request_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        (
+            &self.new_input,
+            &self.content,
+            &self.variables,
+            &self.template,
+            &self.examples,
+            self.conversation,
+            self.active_chat,
+        )
+            .hash(&mut hasher);
+        // A new or removed entry changes the conversation, as does the streamed one
+        (
+            self.history.len(),
+            self.history
+                .iter()
+                .find(|response| self.is_streamed_to(response))
+                .map(|response| response.output.len()),
+        )
+            .hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    pub fn stop_generation(&mut self) {
+        self.state = PromptState::Idle;
+        self.critique_pending = false;
+    }
+
+    pub fn active_chat_session(&self) -> Option<&ChatSession> {
+        self.active_chat
+            .and_then(|created_at| self.chat_session(created_at))
+    }
+
+    fn chat_session(&self, created_at: DateTime<Local>) -> Option<&ChatSession> {
+        self.chat_sessions
+            .iter()
+            .find(|session| session.created_at == created_at)
+    }
+
+    fn chat_session_mut(&mut self, created_at: DateTime<Local>) -> Option<&mut ChatSession> {
+        self.chat_sessions
+            .iter_mut()
+            .find(|session| session.created_at == created_at)
+    }
+
+    /// Starts a new conversation, the next input being sent without context
+    pub fn new_chat_session(&mut self) {
+        let session = ChatSession::default();
+
+        self.active_chat = Some(session.created_at);
+        self.chat_sessions.push(session);
+    }
+
+    /// The conversation the next input is sent in, started when there's none
+    fn chat_session_for_input(&mut self) -> DateTime<Local> {
+        match self.active_chat_session() {
+            Some(session) => session.created_at,
+            None => {
+                self.new_chat_session();
+                self.chat_sessions
+                    .last()
+                    .map(|session| session.created_at)
+                    .unwrap_or_default()
             }
-        });
+        }
+    }
+
+    /// Goes on with an earlier conversation
+    pub fn select_chat_session(&mut self, created_at: DateTime<Local>) {
+        if let Some(session) = self.chat_session_mut(created_at) {
+            session.archived = false;
+            self.active_chat = Some(created_at);
+        }
+    }
+
+    /// Names a conversation, its title being no longer generated
+    pub fn rename_chat_session(&mut self, created_at: DateTime<Local>, title: &str) {
+        if let Some(session) = self.chat_session_mut(created_at)
+            && !title.trim().is_empty()
+        {
+            session.title = title.trim().to_string();
+            session.titled = true;
+        }
+    }
+
+    /// Names a conversation with a generated title, unless it was renamed in the meantime
+    pub fn set_generated_chat_title(&mut self, created_at: DateTime<Local>, title: String) {
+        if let Some(session) = self.chat_session_mut(created_at)
+            && !session.titled
+        {
+            session.title = title;
+            session.titled = true;
+        }
+    }
+
+    /// Hides a conversation from the list or shows it again, the next input starting a new one
+    /// when it was the active one
+    pub fn archive_chat_session(&mut self, created_at: DateTime<Local>, archived: bool) {
+        if let Some(session) = self.chat_session_mut(created_at) {
+            session.archived = archived;
+        }
+
+        if archived && self.active_chat == Some(created_at) {
+            self.active_chat = None;
+        }
+    }
+
+    /// Removes a conversation along with its responses
+    pub fn delete_chat_session(&mut self, created_at: DateTime<Local>) {
+        self.chat_sessions
+            .retain(|session| session.created_at != created_at);
+        self.history
+            .retain(|response| response.chat_session != Some(created_at));
+
+        if self.active_chat == Some(created_at) {
+            self.active_chat = None;
+        }
     }
 
-    fn poll_ask_flower(&mut self) -> Option<AppAction> {
-        let mut action = None;
+    /// Whether a response is shown in the history, only the ones of the active conversation
+    /// being shown in the conversation mode
+    pub fn is_in_active_chat(&self, response: &PromptResponse) -> bool {
+        !self.conversation || response.chat_session == self.active_chat
+    }
 
-        self.ask_flower
-            .extract(|output| {
-                let response = self.history.get_mut(0).unwrap();
-                response.output = output;
+    /// The first exchange of the active conversation when it's the only one and the conversation
+    /// isn't titled yet, to generate its title from
+    fn untitled_chat_exchange(&self) -> Option<(DateTime<Local>, Exchange)> {
+        let session = self
+            .active_chat_session()
+            .filter(|session| !session.titled)?;
+        let exchanges = self.conversation_exchanges(0);
+
+        match exchanges.as_slice() {
+            [exchange] => Some((session.created_at, exchange.clone())),
+            _ => None,
+        }
+    }
+
+    /// Inputs and answers of the conversation, the oldest first, skipping the `skip` latest responses
+    fn conversation_exchanges(&self, skip: usize) -> Vec<Exchange> {
+        let Some(active_chat) = self.active_chat.filter(|_| self.conversation) else {
+            return Vec::new();
+        };
+
+        let mut exchanges: Vec<Exchange> = self
+            .history
+            .iter()
+            .skip(skip)
+            .filter(|response| response.chat_session == Some(active_chat))
+            .filter(|response| !response.output.is_empty())
+            .map(|response| Exchange {
+                input: response.input.clone(),
+                output: response.output.clone(),
             })
-            .finalize(|result| {
-                match result {
-                    Ok(output) => {
-                        let response = self.history.get_mut(0).unwrap();
-                        response.output = output;
-                    }
-                    Err(Compact::Suppose(e)) => {
-                        // Remove the failed response from history
-                        self.history.pop_front();
-
-                        action = Some(AppAction::ShowErrorDialog {
-                            title: "Response Generation Error".to_string(),
-                            message: format!("Failed to generate response from Ollama. Please check your connection and try again.\n\nError: {e}"),
-                        });
-                    }
-                    Err(Compact::Panicked(e)) => {
-                        // Remove the failed response from history
-                        self.history.pop_front();
-
-                        action = Some(AppAction::ShowErrorDialog {
-                            title: "Response Generation Error".to_string(),
-                            message: format!("An unexpected error occurred while generating the response.\n\nError: {e}"),
-                        });
-                    }
-                }
+            .collect();
+        exchanges.reverse();
 
-                self.state = PromptState::Idle;
-                self.new_input.clear();
-            });
+        exchanges
+    }
+
+    /// Aborts the stream of the running generation, keeping the output received so far in the
+    /// history entry marked as cancelled
+    pub fn cancel_generation(&mut self) {
+        if !self.state.is_generating() {
+            return;
+        }
+
+        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+            response.cancelled = true;
+        }
+
+        self.stop_generation();
 
-        action
+        // The stream may have ended meanwhile, nothing is left to cancel then
+        if let Some(cancel_tx) = self.cancel_stream.take() {
+            let _ = cancel_tx.send(());
+        }
     }
-}
 
-struct CopyFeedback {
-    response_created_at: DateTime<Local>,
-    triggered_at: Instant,
+    /// Priority of the queued generations, unless overridden a resubmitted input comes before the
+    /// batch work of benchmarks and replays
+    pub fn queued_priority(&self) -> Option<Priority> {
+        if self.queued_count() == 0 {
+            return None;
+        }
+
+        Some(self.queue_priority.unwrap_or(if self.resubmit.is_some() {
+            Priority::Normal
+        } else {
+            Priority::Background
+        }))
+    }
+
+    pub fn set_queue_priority(&mut self, priority: Priority) {
+        self.queue_priority = Some(priority);
+    }
+
+    /// Generations waiting to start: a resubmitted input, benchmark inputs, replayed steps, the
+    /// documents added to the watched folder and the messages and feed items to summarize
+    pub fn queued_count(&self) -> usize {
+        let benchmark = self
+            .benchmark
+            .as_ref()
+            .map_or(0, |benchmark| benchmark.queue.len());
+        let replay = self.replay.as_ref().map_or(0, |replay| {
+            let (done, total) = replay.progress();
+            total - done
+        });
+
+        let mail_digest = self.mail_digest.as_ref().map_or(0, MailDigest::queued_len);
+        let briefing = self.briefing.as_ref().map_or(0, Briefing::queued_len);
+
+        usize::from(self.resubmit.is_some())
+            + benchmark
+            + replay
+            + self.watched_files.len()
+            + mail_digest
+            + briefing
+    }
+
+    /// Drops the generations waiting to start, the benchmark keeping the responses re-run so far
+    pub fn clear_queue(&mut self) {
+        self.resubmit = None;
+        self.replay = None;
+        self.watched_files.clear();
+
+        if let Some(mail_digest) = self.mail_digest.as_mut() {
+            mail_digest.clear_queue();
+        }
+
+        if let Some(briefing) = self.briefing.as_mut() {
+            briefing.clear_queue();
+        }
+
+        if let Some(benchmark) = self.benchmark.as_mut() {
+            benchmark.queue.clear();
+        }
+    }
 }
 
-impl CopyFeedback {
-    fn new(response_created_at: DateTime<Local>) -> Self {
-        Self {
-            response_created_at,
-            triggered_at: Instant::now(),
+/// Adds the comma-separated tags of the text, lowercase and without a leading `#`, skipping the
+/// ones already there
+pub fn push_tags(tags: &mut Vec<String>, text: &str) {
+    for tag in text.split(',') {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
         }
     }
+}
 
-    fn is_active(&self) -> bool {
-        self.triggered_at.elapsed() <= Duration::from_millis(COPY_FEEDBACK_DURATION_MS)
+/// The first line of the input, shortened to fit into a menu
+pub fn input_label(input: &str) -> String {
+    let mut label = input
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(RECENT_INPUT_LABEL_LEN)
+        .collect::<String>();
+
+    if label.len() < input.len() {
+        label.push('…');
     }
+
+    label
+}
+
+/// The text of a document as an input, headed by its name
+fn document_input(name: &str, text: &str) -> String {
+    format!("{name}:\n\n{}\n", text.trim_end())
 }
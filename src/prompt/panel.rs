@@ -0,0 +1,4761 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+use egui::RichText;
+use egui::{
+    Color32, CornerRadius, Frame, Key, KeyboardShortcut, Label, Layout, Modifiers, ScrollArea,
+    Sense, Stroke, UiBuilder,
+};
+use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
+use flowync::{CompactFlower, error::Compact};
+use ollama_rs::{generation::chat::ChatMessage, models::LocalModel};
+use tokio::{runtime, sync::oneshot, task::JoinSet};
+
+use super::{
+    AccentColor, HISTORY_SEARCH_ID, PROMPT_INPUT_ID, Prompt, PromptAppearance, PromptKind,
+    PromptState, input_label,
+};
+use crate::{
+    app::{self, AppAction},
+    assign_if_some, chat,
+    compare::{self, CompareSettings, ComparedAnswer},
+    compose::{self, ComposedPrompt, Exchange},
+    content::{self, ContentType},
+    debate::{self, DebateAnswer, DebateModels},
+    ensemble::{self, EnsembleCandidate, EnsembleSettings},
+    feed::{self, Briefing, Feed, FeedSubscription},
+    history::{
+        self, Annotation, GenerationStats, GenerationTimer, HighlightColor, ModelMetrics,
+        PromptResponse, StopReason,
+    },
+    icons::{self, Icon, IconButton},
+    image_gen::StableDiffusionClient,
+    judge::{self, JudgeScore},
+    markdown,
+    media::ResponseImage,
+    ollama::{self, Answer, OllamaClient},
+    settings::Settings,
+    snippet::{self, Snippet, SnippetPolicy, SnippetRun},
+    spell::SpellChecker,
+    tokens,
+    tts::{self, AudioPlayer, ReadAlong},
+    typewriter::Typewriter,
+    viewer,
+};
+
+const COPY_FEEDBACK_DURATION_MS: u64 = 1500;
+const LEFT_PANEL_CARD_MARGIN: i8 = 10;
+const LEFT_PANEL_CARD_STROKE: f32 = 2.0;
+const IMAGE_THUMBNAIL_HEIGHT: f32 = 160.0;
+/// How often the answers of the compared models are refreshed while they stream in
+const COMPARE_PROGRESS_MS: u64 = 100;
+/// Space left of an annotated output for the markers of its highlights
+const ANNOTATION_MARGIN: f32 = 10.0;
+/// History entries shown at first, and added each time older ones are asked for
+const HISTORY_WINDOW_LEN: usize = 20;
+
+/// Context menu of a misspelled word of the input
+pub struct SpellingMenu {
+    /// Byte range of the word in the input
+    range: Range<usize>,
+    word: String,
+    suggestions: Vec<String>,
+}
+
+pub type PromptAskFlower = CompactFlower<String, (Answer, Option<GenerationStats>), String>;
+pub type PromptSpeechFlower = CompactFlower<(), PathBuf, String>;
+pub type PromptSnippetFlower = CompactFlower<(), SnippetRun, String>;
+pub type PromptDebateFlower = CompactFlower<String, (Vec<DebateAnswer>, String), String>;
+pub type PromptCompareFlower = CompactFlower<Vec<ComparedAnswer>, Vec<ComparedAnswer>, String>;
+pub type PromptEnsembleFlower =
+    CompactFlower<String, (Vec<EnsembleCandidate>, Option<usize>), String>;
+pub type PromptScoreFlower = CompactFlower<(DateTime<Local>, JudgeScore), (), String>;
+pub type PromptFeedFlower = CompactFlower<(), Vec<(String, Result<Feed, String>)>, String>;
+pub type PromptChatTitleFlower = CompactFlower<(), (DateTime<Local>, String), String>;
+
+impl From<AccentColor> for Color32 {
+    fn from(AccentColor([r, g, b, a]): AccentColor) -> Self {
+        Color32::from_rgba_premultiplied(r, g, b, a)
+    }
+}
+
+impl From<Color32> for AccentColor {
+    fn from(color: Color32) -> Self {
+        AccentColor(color.to_array())
+    }
+}
+
+impl PromptAppearance {
+    /// Returns the icon as a label colored with the accent color, if any
+    pub fn icon_text(&self) -> Option<RichText> {
+        if self.icon.is_empty() {
+            return None;
+        }
+
+        let text = RichText::new(&self.icon);
+
+        Some(match self.color {
+            Some(color) => text.color(Color32::from(color)),
+            None => text,
+        })
+    }
+}
+
+impl Prompt {
+    pub fn is_scoring(&self) -> bool {
+        self.score_flower.is_active()
+    }
+
+    pub fn is_running_snippet(&self) -> bool {
+        self.snippet_flower.is_active()
+    }
+
+    pub fn is_speaking(&mut self) -> bool {
+        self.speech_flower.is_active()
+            || self
+                .audio_player
+                .as_mut()
+                .is_some_and(|player| player.is_playing())
+    }
+
+    /// Cards in the left panel are a single row high so that the list can be virtualized
+    pub fn left_panel_card_height(ui: &egui::Ui) -> f32 {
+        ui.spacing().interact_size.y
+            + 2.0 * (LEFT_PANEL_CARD_MARGIN as f32 + LEFT_PANEL_CARD_STROKE)
+    }
+
+    pub fn show_left_panel(
+        &self,
+        ui: &mut egui::Ui,
+        selected: bool,
+        idx: usize,
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        let response = ui.scope_builder(
+            UiBuilder::new()
+                .id_salt("left_panel_prompt")
+                .sense(Sense::click()),
+            |ui| {
+                ui.with_layout(
+                    Layout::left_to_right(egui::Align::TOP)
+                        .with_main_justify(true)
+                        .with_main_align(egui::Align::LEFT),
+                    |ui| {
+                        let fill_style = if selected {
+                            ui.style().visuals.faint_bg_color
+                        } else {
+                            ui.style().visuals.window_fill
+                        };
+                        let stroke_style_color = if selected {
+                            Color32::ORANGE
+                        } else {
+                            self.appearance
+                                .color
+                                .map_or(ui.style().visuals.window_stroke.color, Color32::from)
+                        };
+
+                        Frame::group(ui.style())
+                            .inner_margin(egui::Margin::same(LEFT_PANEL_CARD_MARGIN))
+                            .corner_radius(CornerRadius::same(6))
+                            .stroke(Stroke::new(LEFT_PANEL_CARD_STROKE, stroke_style_color))
+                            .fill(fill_style)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    if let Some(icon) = self.appearance.icon_text() {
+                                        ui.label(icon);
+                                    }
+
+                                    let title = if self.disabled {
+                                        RichText::new(&self.title).weak().strikethrough()
+                                    } else {
+                                        RichText::new(&self.title)
+                                    };
+                                    let label_response = ui.add(egui::Label::new(title).truncate());
+
+                                    ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                                        let remove_response = ui.add_enabled(
+                                            !self.state.is_generating() && !self.locked,
+                                            IconButton::new(Icon::Remove),
+                                        );
+
+                                        let edit_response =
+                                            ui.add_enabled(!self.locked, IconButton::new(Icon::Edit));
+
+                                        if self.locked {
+                                            ui.label("🔒")
+                                                .on_hover_text("Locked, right-click to unlock it before editing or removing it");
+                                        }
+
+                                        let count_text = format!("{:3}", self.history.len());
+
+                                        ui.add(egui::Label::new(RichText::new(count_text)));
+
+                                        if !self.tags.is_empty() {
+                                            Self::show_left_panel_tags(ui, &self.tags);
+                                        }
+
+                                        if self.state.is_generating() {
+                                            ui.spinner().on_hover_text("Generating");
+                                        }
+
+                                        if remove_response
+                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                            .on_hover_text("Remove prompt")
+                                            .clicked()
+                                        {
+                                            action = Some(AppAction::OpenRemovePromptDialog(idx));
+                                        }
+
+                                        if edit_response
+                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                            .on_hover_text("Edit prompt")
+                                            .clicked()
+                                        {
+                                            action = Some(AppAction::OpenEditPromptDialog(idx));
+                                        }
+                                    });
+
+                                    label_response
+                                })
+                                .inner
+                            })
+                            .inner
+                    },
+                )
+                .inner
+            },
+        );
+        let response = response.response.union(response.inner);
+
+        // Screen readers announce the whole card as a button named after the prompt
+        response
+            .widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, true, &self.title));
+
+        response.context_menu(|ui| {
+            let (text, hover_text) = if self.disabled {
+                ("Enable", "Make the prompt selectable again")
+            } else {
+                (
+                    "Disable",
+                    "Grey the prompt out and make it unselectable, e.g. while revising it",
+                )
+            };
+
+            if ui.button(text).on_hover_text(hover_text).clicked() {
+                action = Some(AppAction::SetPromptDisabled {
+                    idx,
+                    disabled: !self.disabled,
+                });
+                ui.close_menu();
+            }
+
+            let (text, hover_text) = if self.locked {
+                ("Unlock", "Allow editing and removing the prompt")
+            } else {
+                (
+                    "Lock",
+                    "Protect the prompt against accidental edits and removal",
+                )
+            };
+
+            if ui.button(text).on_hover_text(hover_text).clicked() {
+                action = Some(AppAction::SetPromptLocked {
+                    idx,
+                    locked: !self.locked,
+                });
+                ui.close_menu();
+            }
+
+            if !self.group.is_empty()
+                && ui
+                    .button("Remove from group")
+                    .on_hover_text(format!("List the prompt outside of \"{}\"", self.group))
+                    .clicked()
+            {
+                action = Some(AppAction::SetPromptGroup {
+                    idx,
+                    group: String::new(),
+                });
+                ui.close_menu();
+            }
+        });
+
+        if self.disabled {
+            response.on_hover_text("Disabled, right-click to enable it");
+        } else if response
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .clicked()
+        {
+            action = Some(AppAction::SelectPrompt(idx));
+        }
+
+        action
+    }
+
+    /// The first tags of a card in the prompt list, the others counted, laid out right to left
+    fn show_left_panel_tags(ui: &mut egui::Ui, tags: &[String]) {
+        const SHOWN_TAGS: usize = 2;
+
+        let response = ui
+            .horizontal(|ui| {
+                if tags.len() > SHOWN_TAGS {
+                    ui.label(
+                        RichText::new(format!("+{}", tags.len() - SHOWN_TAGS))
+                            .small()
+                            .weak(),
+                    );
+                }
+
+                for tag in tags.iter().take(SHOWN_TAGS).rev() {
+                    ui.label(
+                        RichText::new(format!("#{tag}"))
+                            .small()
+                            .background_color(ui.visuals().faint_bg_color),
+                    );
+                }
+            })
+            .response;
+
+        if tags.len() > SHOWN_TAGS {
+            response.on_hover_text(
+                tags.iter()
+                    .map(|tag| format!("#{tag}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn show_main_panel(
+        &mut self,
+        ui: &mut egui::Ui,
+        is_modal_shown: bool,
+        hold_queue: bool,
+        idx: usize,
+        commonmark_cache: &mut CommonMarkCache,
+        settings: &Settings,
+        models: &[LocalModel],
+        context_window: Option<u64>,
+        spell_checker: Option<&SpellChecker>,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let is_input_interactive = !self.state.is_generating();
+
+        ui.add_space(6.0);
+
+        assign_if_some!(action, self.show_main_panel_toolbar(ui, idx, models));
+
+        ui.add_space(6.0);
+
+        if self.replay.is_some() {
+            assign_if_some!(action, self.show_replay_controls(ui, idx, hold_queue));
+
+            ui.add_space(6.0);
+        }
+
+        let variables = compose::variables(&self.content);
+
+        if !variables.is_empty() {
+            self.show_variables_form(ui, &variables, is_input_interactive);
+
+            ui.add_space(6.0);
+        }
+
+        let unfilled_variables = variables.iter().any(|name| {
+            self.variables
+                .get(name)
+                .is_none_or(|value| value.trim().is_empty())
+        });
+
+        // Ctrl+Z in an empty input brings back the last submitted or cleared text, without the
+        // focus it restores the latest removed prompt or response instead
+        if is_input_interactive
+            && !is_modal_shown
+            && self.new_input.is_empty()
+            && ui.memory(|memory| memory.has_focus(egui::Id::new(PROMPT_INPUT_ID)))
+            && ui.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::Z))
+            && let Some(input) = self.recent_inputs.front()
+        {
+            self.new_input = input.clone();
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add(
+                    IconButton::new(Icon::Incognito)
+                        .text("Incognito")
+                        .selected(self.incognito),
+                )
+                .on_hover_text("Keep the next exchanges in memory only, they are never saved")
+                .clicked()
+            {
+                self.incognito = !self.incognito;
+            }
+
+            if self.incognito {
+                ui.label(
+                    RichText::new("The exchanges are not saved and are gone once the app closes")
+                        .color(ui.visuals().warn_fg_color),
+                );
+            }
+        });
+
+        let previous_input = (self.new_input.chars().count() > 1).then(|| self.new_input.clone());
+
+        ui.with_layout(
+            Layout::left_to_right(egui::Align::TOP).with_main_justify(true),
+            |ui| {
+                let hint_text = if self.conversation_exchanges(0).is_empty() {
+                    format!("Ask for the following prompt: {}", self.content)
+                } else {
+                    "Follow up on the conversation".to_string()
+                };
+                let text_edit = egui::TextEdit::multiline(&mut self.new_input)
+                    .id(egui::Id::new(PROMPT_INPUT_ID))
+                    .hint_text(hint_text)
+                    .interactive(is_input_interactive)
+                    .return_key(KeyboardShortcut::new(Modifiers::SHIFT, Key::Enter));
+
+                let response = text_edit.show(ui);
+
+                if let Some(spell_checker) = spell_checker {
+                    assign_if_some!(
+                        action,
+                        self.show_spelling(
+                            ui,
+                            &response,
+                            spell_checker,
+                            &settings.spell_check_words
+                        )
+                    );
+                }
+
+                // Request focus when prompt is first displayed or navigated to, unless searching
+                let is_searching = ui.memory(|m| {
+                    m.focused().is_some_and(|id| {
+                        id == egui::Id::new(HISTORY_SEARCH_ID)
+                            || id == egui::Id::new(app::PROMPT_SEARCH_ID)
+                    })
+                });
+
+                if !is_modal_shown && is_input_interactive && !is_searching {
+                    response.response.request_focus();
+                }
+            },
+        );
+
+        if self.kind == PromptKind::Text {
+            self.show_token_estimate(ui, context_window);
+        }
+
+        // Clearing the whole input at once is easy to do by accident
+        if self.new_input.is_empty()
+            && let Some(previous_input) = previous_input
+        {
+            self.remember_input(previous_input);
+        }
+
+        // A cleared input no longer refers to the quoted entry
+        if self.new_input.is_empty() {
+            self.quoted_from = None;
+        }
+
+        if self.kind == PromptKind::Text {
+            ui.horizontal_wrapped(|ui| {
+                ui.checkbox(&mut self.review_drafts, "Review drafts")
+                    .on_hover_text(
+                        "Keep new responses in a drafts tray until they are accepted into the history or rejected",
+                    );
+
+                match &self.folder_watch {
+                    Some(folder_watch) => {
+                        ui.label(
+                            RichText::new(format!("👁 {}", folder_watch.dir.display())).weak(),
+                        )
+                        .on_hover_text("New documents of the folder are processed with this prompt");
+
+                        if ui
+                            .button("Stop watching")
+                            .on_hover_text("Stop processing the new documents of the folder")
+                            .clicked()
+                        {
+                            action = Some(AppAction::StopWatchingFolder(idx));
+                        }
+                    }
+                    None => {
+                        if ui
+                            .add(IconButton::new(Icon::Folder).text("Watch folder").frame(true))
+                            .on_hover_text(
+                                "Process every text document added to a folder with this prompt, e.g. meeting notes",
+                            )
+                            .clicked()
+                        {
+                            action = Some(AppAction::PickWatchedFolder(idx));
+                        }
+                    }
+                }
+
+                if let Some(error) = &self.folder_watch_error {
+                    ui.label(RichText::new(format!("⚠ {error}")).color(ui.style().visuals.warn_fg_color));
+                }
+
+                ui.checkbox(&mut self.critique_and_revise, "Critique & revise")
+                    .on_hover_text(
+                        "Ask the model to review and improve its answer in a second pass",
+                    );
+
+                if ui
+                    .checkbox(&mut self.debate.enabled, "Debate (experimental)")
+                    .on_hover_text(
+                        "Two models answer and a third one synthesizes the final answer",
+                    )
+                    .changed()
+                    && self.debate.enabled
+                {
+                    self.ensemble.enabled = false;
+                    self.compare.enabled = false;
+                    self.conversation = false;
+                }
+
+                if ui
+                    .checkbox(&mut self.compare.enabled, "Compare")
+                    .on_hover_text("Several models answer the input, shown side by side")
+                    .changed()
+                    && self.compare.enabled
+                {
+                    self.debate.enabled = false;
+                    self.ensemble.enabled = false;
+                    self.conversation = false;
+                }
+
+                if ui
+                    .checkbox(&mut self.ensemble.enabled, "Ensemble")
+                    .on_hover_text(
+                        "Sample several answers with different seeds and show all or let a judge pick",
+                    )
+                    .changed()
+                    && self.ensemble.enabled
+                {
+                    self.debate.enabled = false;
+                    self.compare.enabled = false;
+                    self.conversation = false;
+                }
+
+                if ui
+                    .checkbox(&mut self.conversation, "Conversation")
+                    .on_hover_text("Send the previous inputs and answers along with each input")
+                    .changed()
+                    && self.conversation
+                {
+                    self.debate.enabled = false;
+                    self.ensemble.enabled = false;
+                    self.compare.enabled = false;
+                }
+
+                let exchanges = self.conversation_exchanges(0).len();
+
+                if self.conversation && exchanges > 0 {
+                    ui.label(RichText::new(format!("💬 {exchanges} exchanges")).weak());
+                }
+
+                if self.ensemble.enabled {
+                    ui.add(
+                        egui::DragValue::new(&mut self.ensemble.samples)
+                            .range(EnsembleSettings::SAMPLES_RANGE)
+                            .suffix(" samples"),
+                    );
+                    ui.label("judge:");
+                    Self::show_model_selector(
+                        ui,
+                        "ensemble_judge_model",
+                        &mut self.ensemble.judge_model,
+                        models,
+                        Some("show all"),
+                    );
+                }
+
+                if self.compare.enabled {
+                    let selected = self.compare.models.len();
+
+                    ui.menu_button(format!("{selected} models"), |ui| {
+                        for model in models {
+                            let mut checked = self.compare.models.contains(&model.name);
+
+                            if ui
+                                .add_enabled(
+                                    checked || selected < CompareSettings::MAX_MODELS,
+                                    egui::Checkbox::new(&mut checked, &model.name),
+                                )
+                                .changed()
+                            {
+                                self.compare.toggle_model(&model.name);
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(format!(
+                        "Pick {} to {} models to compare",
+                        CompareSettings::MIN_MODELS,
+                        CompareSettings::MAX_MODELS
+                    ));
+                }
+
+                if self.debate.enabled {
+                    Self::show_model_selector(
+                        ui,
+                        "debate_first_model",
+                        &mut self.debate.first_model,
+                        models,
+                        None,
+                    );
+                    ui.label("vs");
+                    Self::show_model_selector(
+                        ui,
+                        "debate_second_model",
+                        &mut self.debate.second_model,
+                        models,
+                        None,
+                    );
+                    ui.label("→");
+                    Self::show_model_selector(
+                        ui,
+                        "debate_synthesizer_model",
+                        &mut self.debate.synthesizer_model,
+                        models,
+                        Some("first model"),
+                    );
+                }
+            });
+        }
+
+        ui.add_space(6.0);
+
+        ui.separator();
+
+        if is_input_interactive
+            && !is_modal_shown
+            && !self.new_input.is_empty()
+            && !unfilled_variables
+            && ui.input(|i| i.key_pressed(Key::Enter) && i.modifiers.is_none())
+        {
+            let input = std::mem::take(&mut self.new_input);
+
+            self.pending_parent = self.quoted_from.take();
+            action = Some(AppAction::GeneratePromptResponse {
+                idx,
+                input: input.clone(),
+                model: None,
+            });
+            self.remember_input(input);
+        }
+
+        assign_if_some!(action, self.poll_generations(idx));
+
+        self.show_benchmark(ui, commonmark_cache);
+        self.show_drafts(ui, commonmark_cache);
+        self.show_processed_files(ui);
+        assign_if_some!(action, self.show_mail_digest(ui, idx, commonmark_cache));
+        self.show_briefing(ui);
+
+        assign_if_some!(action, self.show_watchdog(ui, idx, settings, models));
+
+        if self.conversation {
+            egui::SidePanel::right(format!("chat_sessions_{idx}"))
+                .resizable(true)
+                .default_width(200.0)
+                .show_inside(ui, |ui| {
+                    assign_if_some!(action, self.show_chat_sessions(ui, idx));
+                });
+        }
+
+        assign_if_some!(
+            action,
+            self.show_prompt_history(ui, idx, commonmark_cache, settings)
+        );
+
+        assign_if_some!(action, self.show_zoomed_image(ui.ctx()));
+
+        action
+    }
+
+    /// Groups the responses by the document of the watched folder they processed, to jump to them
+    fn show_processed_files(&mut self, ui: &mut egui::Ui) {
+        // The newest response of each document with the number of its responses
+        let mut files: Vec<(&str, DateTime<Local>, usize)> = Vec::new();
+
+        for response in &self.history {
+            let Some(name) = response.source_file.as_deref() else {
+                continue;
+            };
+
+            match files.iter_mut().find(|(file, ..)| *file == name) {
+                Some((_, _, count)) => *count += 1,
+                None => files.push((name, response.created_at, 1)),
+            }
+        }
+
+        if files.is_empty() {
+            return;
+        }
+
+        let mut jump_to = None;
+
+        egui::CollapsingHeader::new(format!("Processed files ({})", files.len())).show(ui, |ui| {
+            ScrollArea::vertical()
+                .id_salt("processed_files")
+                .max_height(ui.available_height() / 3.0)
+                .show(ui, |ui| {
+                    for (name, created_at, count) in &files {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .link(*name)
+                                .on_hover_text("Show its latest response")
+                                .clicked()
+                            {
+                                jump_to = Some(*created_at);
+                            }
+
+                            ui.label(
+                                RichText::new(format!(
+                                    "{count} response(s) · {}",
+                                    created_at.format("%Y-%m-%d %H:%M")
+                                ))
+                                .small()
+                                .weak(),
+                            );
+                        });
+                    }
+                });
+        });
+
+        if let Some(created_at) = jump_to {
+            self.jump_to_response(created_at);
+        }
+    }
+
+    /// Shows the progress of the briefing, which is added to the history once all the new items are
+    /// summarized
+    fn show_briefing(&mut self, ui: &mut egui::Ui) {
+        if self.feed_flower.is_active() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Fetching the feeds…");
+            });
+        }
+
+        let Some(briefing) = &self.briefing else {
+            return;
+        };
+
+        if briefing.has_items() && briefing.is_done() && !self.is_busy() {
+            self.finish_briefing();
+            return;
+        }
+
+        let mut close = false;
+
+        egui::CollapsingHeader::new("Briefing")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if briefing.has_items() {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Summarizing, {} of {} left",
+                            briefing.queued_len(),
+                            briefing.queued_len() + briefing.summarized_len()
+                        ));
+                    } else {
+                        ui.label("There are no new items since the last briefing.");
+                    }
+
+                    if ui
+                        .add(IconButton::new(Icon::Remove).text("Close").frame(true))
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+
+                for (feed_title, error) in &briefing.errors {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Failed to fetch {feed_title}: {error}"),
+                    );
+                }
+            });
+
+        if close {
+            self.briefing = None;
+        }
+    }
+
+    /// Fetches the feeds in the background, their new items being summarized once they arrive
+    pub fn fetch_feeds(&mut self, client: reqwest::Client, rt: &runtime::Runtime) {
+        let handle = self.feed_flower.handle();
+        let urls: Vec<String> = self.feeds.iter().map(|feed| feed.url.clone()).collect();
+
+        self.briefing = None;
+
+        rt.spawn(async move {
+            handle.activate();
+
+            let mut feeds = Vec::new();
+
+            for url in urls {
+                let feed = feed::fetch_feed(&client, &url)
+                    .await
+                    .map_err(|e| e.to_string());
+
+                feeds.push((url, feed));
+            }
+
+            handle.success(feeds);
+        });
+    }
+
+    fn poll_feed_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+
+        self.feed_flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok(fetched) => {
+                    let mut items = Vec::new();
+                    let mut errors = Vec::new();
+
+                    for (url, feed) in fetched {
+                        let Some(subscription) = self.feeds.iter_mut().find(|feed| feed.url == url)
+                        else {
+                            continue;
+                        };
+
+                        match feed {
+                            Ok(feed) => items.extend(
+                                subscription
+                                    .take_new_items(feed)
+                                    .into_iter()
+                                    .map(|item| (subscription.title.clone(), item)),
+                            ),
+                            Err(e) => errors.push((subscription.title.clone(), e)),
+                        }
+                    }
+
+                    self.briefing = Some(Briefing::new(items, errors));
+                }
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Briefing Error".to_string(),
+                        message: format!("Failed to fetch the feeds.\n\nError: {e}"),
+                    });
+                }
+            });
+
+        action
+    }
+
+    /// Lists the feeds of the briefings, with a field to add one and the action to generate a briefing
+    fn show_feeds_menu(&mut self, ui: &mut egui::Ui, idx: usize) -> Option<AppAction> {
+        let mut action = None;
+        let mut removed = None;
+
+        for (feed_idx, feed) in self.feeds.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add(IconButton::new(Icon::Remove).small().frame(true))
+                    .on_hover_text("Remove the feed")
+                    .clicked()
+                {
+                    removed = Some(feed_idx);
+                }
+
+                ui.label(&feed.title).on_hover_text(&feed.url);
+            });
+        }
+
+        if let Some(feed_idx) = removed {
+            self.feeds.remove(feed_idx);
+        }
+
+        ui.horizontal(|ui| {
+            let url = self.new_feed_url.trim();
+            let is_valid = (url.starts_with("http://") || url.starts_with("https://"))
+                && !self.feeds.iter().any(|feed| feed.url == url);
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_feed_url)
+                    .hint_text("https://example.com/feed.xml")
+                    .desired_width(240.0),
+            );
+
+            if ui
+                .add_enabled(is_valid, IconButton::new(Icon::Add).text("Add").frame(true))
+                .clicked()
+            {
+                let url = std::mem::take(&mut self.new_feed_url).trim().to_string();
+
+                self.feeds.push(FeedSubscription::new(url));
+            }
+        });
+
+        ui.separator();
+
+        let can_generate = !self.feeds.is_empty()
+            && !self.feed_flower.is_active()
+            && self
+                .briefing
+                .as_ref()
+                .is_none_or(|briefing| !briefing.has_items());
+
+        if ui
+            .add_enabled(can_generate, IconButton::new(Icon::Feed).text("Generate briefing").frame(true))
+            .on_hover_text(format!(
+                "Summarize the items published since the last briefing with this prompt, at most {} per feed",
+                feed::ITEMS_PER_FEED
+            ))
+            .clicked()
+        {
+            action = Some(AppAction::GenerateBriefing(idx));
+            ui.close_menu();
+        }
+
+        action
+    }
+
+    /// Lists the messages of the opened mailbox to pick the ones to summarize, then their digest
+    fn show_mail_digest(
+        &mut self,
+        ui: &mut egui::Ui,
+        idx: usize,
+        commonmark_cache: &mut CommonMarkCache,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let mut close = false;
+        let is_busy = self.is_busy();
+        let history = &self.history;
+        let drafts = &self.drafts;
+        let mail_digest = self.mail_digest.as_mut()?;
+
+        if !is_busy {
+            mail_digest.finish(|created_at| {
+                history
+                    .iter()
+                    .chain(drafts)
+                    .find(|response| response.created_at == created_at)
+                    .map(|response| response.output.as_str())
+            });
+        }
+
+        let source = mail_digest
+            .source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        egui::CollapsingHeader::new(format!("Mail digest of {source}"))
+            .default_open(true)
+            .show(ui, |ui| {
+                if let Some(document) = mail_digest.document.clone() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(IconButton::new(Icon::Copy).text("Copy").frame(true))
+                            .clicked()
+                            && let Err(e) = crate::copy_to_clipboard(&document)
+                        {
+                            action = Some(AppAction::ShowErrorDialog {
+                                title: "Copy Error".to_string(),
+                                message: format!("Failed to copy to clipboard: {e}"),
+                            });
+                        }
+
+                        if ui
+                            .add(IconButton::new(Icon::Save).text("Save").frame(true))
+                            .clicked()
+                        {
+                            action = Some(AppAction::SaveMailDigest(idx));
+                        }
+
+                        if ui
+                            .add(IconButton::new(Icon::Back).text("Messages").frame(true))
+                            .on_hover_text("Pick other messages to summarize")
+                            .clicked()
+                        {
+                            mail_digest.document = None;
+                        }
+
+                        if ui
+                            .add(IconButton::new(Icon::Remove).text("Close").frame(true))
+                            .clicked()
+                        {
+                            close = true;
+                        }
+                    });
+
+                    ScrollArea::vertical()
+                        .id_salt("mail_digest_document")
+                        .max_height(ui.available_height() / 2.0)
+                        .show(ui, |ui| {
+                            CommonMarkViewer::new().show(ui, commonmark_cache, &document);
+                        });
+
+                    return;
+                }
+
+                let selected = mail_digest.selected.len();
+
+                ui.horizontal(|ui| {
+                    if mail_digest.is_running() {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Summarizing, {} of {selected} left",
+                            mail_digest.queued_len()
+                        ));
+                    } else {
+                        if ui
+                            .add_enabled(
+                                selected > 0,
+                                egui::Button::new(format!("Summarize {selected} message(s)")),
+                            )
+                            .on_hover_text(
+                                "Run each message through this prompt and put the summaries together",
+                            )
+                            .clicked()
+                        {
+                            mail_digest.start();
+                        }
+
+                        if ui.button("Select all").clicked() {
+                            mail_digest.selected = (0..mail_digest.messages.len()).collect();
+                        }
+
+                        if ui.button("Select none").clicked() {
+                            mail_digest.selected.clear();
+                        }
+                    }
+
+                    if ui
+                        .add(IconButton::new(Icon::Remove).text("Close").frame(true))
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                });
+
+                ui.add_enabled_ui(!mail_digest.is_running(), |ui| {
+                    ScrollArea::vertical()
+                        .id_salt("mail_digest_messages")
+                        .max_height(ui.available_height() / 2.0)
+                        .show(ui, |ui| {
+                            for (message_idx, message) in mail_digest.messages.iter().enumerate() {
+                                let mut checked = mail_digest.selected.contains(&message_idx);
+
+                                ui.horizontal(|ui| {
+                                    if ui.checkbox(&mut checked, &message.subject).changed() {
+                                        if checked {
+                                            mail_digest.selected.insert(message_idx);
+                                        } else {
+                                            mail_digest.selected.remove(&message_idx);
+                                        }
+                                    }
+
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{} · {}",
+                                            message.from,
+                                            message.formatted_date()
+                                        ))
+                                        .small()
+                                        .weak(),
+                                    );
+                                });
+                            }
+                        });
+                });
+            });
+
+        if close {
+            self.mail_digest = None;
+        }
+
+        action
+    }
+
+    /// Lists the responses waiting for review, to accept them into the history or reject them
+    fn show_drafts(&mut self, ui: &mut egui::Ui, commonmark_cache: &mut CommonMarkCache) {
+        if self.drafts.is_empty() {
+            return;
+        }
+
+        let mut accept = Vec::new();
+        let mut reject = Vec::new();
+
+        egui::CollapsingHeader::new(format!("Drafts ({})", self.drafts.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add(IconButton::new(Icon::Accept).text("Accept all").frame(true))
+                        .on_hover_text("Add all the drafts to the history")
+                        .clicked()
+                    {
+                        accept.extend(0..self.drafts.len());
+                    }
+
+                    if ui
+                        .add(IconButton::new(Icon::Remove).text("Reject all").frame(true))
+                        .on_hover_text("Discard all the drafts")
+                        .clicked()
+                    {
+                        reject.extend(0..self.drafts.len());
+                    }
+                });
+
+                ScrollArea::vertical()
+                    .id_salt("prompt_drafts")
+                    .max_height(ui.available_height() / 2.0)
+                    .show(ui, |ui| {
+                        for (draft_idx, draft) in self.drafts.iter().enumerate() {
+                            egui::Frame::group(ui.style()).show(ui, |ui| {
+                                ui.set_width(ui.available_width());
+
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{} · {}",
+                                            draft.created_at.format("%Y-%m-%d %H:%M:%S"),
+                                            draft.local_model_name
+                                        ))
+                                        .small()
+                                        .weak(),
+                                    );
+
+                                    ui.with_layout(
+                                        Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            if ui
+                                                .add(
+                                                    IconButton::new(Icon::Remove)
+                                                        .text("Reject")
+                                                        .small()
+                                                        .frame(true),
+                                                )
+                                                .on_hover_text("Discard the draft")
+                                                .clicked()
+                                            {
+                                                reject.push(draft_idx);
+                                            }
+
+                                            if ui
+                                                .add(
+                                                    IconButton::new(Icon::Accept)
+                                                        .text("Accept")
+                                                        .small()
+                                                        .frame(true),
+                                                )
+                                                .on_hover_text("Add the draft to the history")
+                                                .clicked()
+                                            {
+                                                accept.push(draft_idx);
+                                            }
+                                        },
+                                    );
+                                });
+
+                                ui.label(RichText::new(&draft.input).weak());
+                                CommonMarkViewer::new().show(ui, commonmark_cache, &draft.output);
+                            });
+                        }
+                    });
+            });
+
+        ui.add_space(6.0);
+
+        // Removed from the back so that the indices stay valid
+        for draft_idx in (0..self.drafts.len()).rev() {
+            let Some(draft) = (accept.contains(&draft_idx) || reject.contains(&draft_idx))
+                .then(|| self.drafts.remove(draft_idx))
+                .flatten()
+            else {
+                continue;
+            };
+
+            if accept.contains(&draft_idx) {
+                self.insert_history(draft);
+            } else {
+                draft.discard();
+            }
+        }
+    }
+
+    /// Offers to re-run the starred inputs, then compares their responses before and after the edit
+    fn show_benchmark(&mut self, ui: &mut egui::Ui, commonmark_cache: &mut CommonMarkCache) {
+        if self.benchmark_offer.is_some() {
+            let starred = self.starred_count();
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label(format!(
+                    "The prompt changed, re-run its {starred} starred inputs to compare the responses?"
+                ));
+
+                if ui
+                    .add_enabled(!self.state.is_generating(), IconButton::new(Icon::Reload).text("Re-run benchmark").frame(true))
+                    .clicked()
+                {
+                    self.start_benchmark();
+                }
+
+                if ui.button("Dismiss").clicked() {
+                    self.benchmark_offer = None;
+                }
+            });
+
+            ui.add_space(6.0);
+        }
+
+        let Some(benchmark) = &self.benchmark else {
+            return;
+        };
+
+        let mut close = false;
+        let remaining = benchmark.queue.len();
+
+        egui::CollapsingHeader::new(if remaining > 0 {
+            format!("Benchmark, {remaining} inputs left to re-run")
+        } else {
+            "Benchmark".to_string()
+        })
+        .id_salt("benchmark")
+        .default_open(true)
+        .show(ui, |ui| {
+            if ui
+                .add(
+                    IconButton::new(Icon::Remove)
+                        .text("Close comparison")
+                        .frame(true),
+                )
+                .clicked()
+            {
+                close = true;
+            }
+
+            ScrollArea::vertical()
+                .id_salt("benchmark_scroll")
+                .max_height(ui.available_height() * 0.5)
+                .show(ui, |ui| {
+                    for before in &benchmark.starred {
+                        let Some(before) = self
+                            .history
+                            .iter()
+                            .find(|response| response.created_at == *before)
+                        else {
+                            continue;
+                        };
+                        let after = self.history.iter().find(|response| {
+                            response.parent == Some(before.created_at)
+                                && response.created_at >= benchmark.started_at
+                        });
+
+                        ui.separator();
+                        ui.label(RichText::new(&before.input).weak());
+
+                        ui.columns(2, |columns| {
+                            columns[0]
+                                .label(RichText::new("Before").strong())
+                                .on_hover_text(&benchmark.previous_content);
+                            CommonMarkViewer::new().show(
+                                &mut columns[0],
+                                commonmark_cache,
+                                &before.output,
+                            );
+
+                            columns[1]
+                                .label(RichText::new("After").strong())
+                                .on_hover_text(&self.content);
+
+                            match after {
+                                Some(after) => {
+                                    CommonMarkViewer::new().show(
+                                        &mut columns[1],
+                                        commonmark_cache,
+                                        &after.output,
+                                    );
+                                }
+                                None => {
+                                    columns[1].label(RichText::new("Waiting to re-run").weak());
+                                }
+                            }
+                        });
+                    }
+                });
+        });
+
+        if close {
+            self.benchmark = None;
+        }
+
+        ui.add_space(6.0);
+    }
+
+    fn show_zoomed_image(&mut self, ctx: &egui::Context) -> Option<AppAction> {
+        let mut action = None;
+        let mut open = true;
+
+        if let Some(image) = &self.zoomed_image
+            && let Some(source) = image.source(ctx)
+        {
+            egui::Window::new("Image")
+                .open(&mut open)
+                .collapsible(false)
+                .default_size(ctx.screen_rect().size() * 0.8)
+                .show(ctx, |ui| {
+                    if ui
+                        .add(IconButton::new(Icon::Save).text("Save image").frame(true))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .set_title("Save image")
+                            .set_file_name(image.file_name())
+                            .save_file()
+                        && let Err(e) = image.save(&path)
+                    {
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Save Error".to_string(),
+                            message: format!("Failed to save the image.\n\nError: {e}"),
+                        });
+                    }
+
+                    ui.add(egui::Image::new(source).shrink_to_fit());
+                });
+        }
+
+        if !open {
+            self.zoomed_image = None;
+        }
+
+        action
+    }
+
+    fn show_main_panel_toolbar(
+        &mut self,
+        ui: &mut egui::Ui,
+        idx: usize,
+        models: &[LocalModel],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+            ui.add_enabled_ui(!self.history.is_empty(), |ui| {
+                icons::menu_button(
+                    ui,
+                    IconButton::new(Icon::Copy).text("Transcript").frame(true),
+                    |ui| {
+                        let selected = self.transcript_selection.len();
+                        let mut copy = None;
+
+                        if ui.button("Copy all entries").clicked() {
+                            copy = Some(false);
+                        }
+
+                        if ui
+                            .add_enabled(
+                                selected > 0,
+                                egui::Button::new(format!("Copy {selected} selected entries")),
+                            )
+                            .clicked()
+                        {
+                            copy = Some(true);
+                        }
+
+                        if ui
+                            .add_enabled(selected > 0, egui::Button::new("Clear selection"))
+                            .clicked()
+                        {
+                            self.transcript_selection.clear();
+                            ui.close_menu();
+                        }
+
+                        if let Some(selected_only) = copy {
+                            if let Err(e) =
+                                crate::copy_to_clipboard(&self.to_transcript(selected_only))
+                            {
+                                action = Some(AppAction::ShowErrorDialog {
+                                    title: "Copy Error".to_string(),
+                                    message: format!("Failed to copy to clipboard: {e}"),
+                                });
+                            }
+
+                            ui.close_menu();
+                        }
+                    },
+                )
+                .response
+                .on_hover_text("Copy the history as a Q/A transcript in markdown");
+            });
+
+            if self.kind == PromptKind::Text {
+                icons::menu_button(
+                    ui,
+                    IconButton::new(Icon::Mail).text("Mail digest").frame(true),
+                    |ui| {
+                        if ui.button("Open an mbox file").clicked() {
+                            action = Some(AppAction::OpenMailbox {
+                                idx,
+                                maildir: false,
+                            });
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Open a maildir folder").clicked() {
+                            action = Some(AppAction::OpenMailbox { idx, maildir: true });
+                            ui.close_menu();
+                        }
+                    },
+                )
+                .response
+                .on_hover_text(
+                    "Summarize messages of a local mailbox with this prompt into a digest",
+                );
+            }
+
+            if self.kind == PromptKind::Text {
+                icons::menu_button(
+                    ui,
+                    IconButton::new(Icon::Feed).text("Briefing").frame(true),
+                    |ui| {
+                        assign_if_some!(action, self.show_feeds_menu(ui, idx));
+                    },
+                )
+                .response
+                .on_hover_text("Summarize the new items of RSS and Atom feeds into a briefing");
+            }
+
+            if self.kind == PromptKind::Text {
+                icons::menu_button(
+                    ui,
+                    IconButton::new(Icon::Judge).text("Judge").frame(true),
+                    |ui| {
+                        ui.label(RichText::new("Judge model").weak());
+
+                        for model in models {
+                            ui.radio_value(&mut self.judge_model, model.name.clone(), &model.name);
+                        }
+
+                        ui.separator();
+
+                        let unscored = self
+                            .history
+                            .iter()
+                            .filter(|response| response.score.is_none())
+                            .count();
+
+                        if ui
+                            .add_enabled(
+                                !self.judge_model.is_empty()
+                                    && unscored > 0
+                                    && !self.score_flower.is_active()
+                                    && !self.state.is_generating(),
+                                egui::Button::new(format!("Score {unscored} unscored responses")),
+                            )
+                            .clicked()
+                        {
+                            action = Some(AppAction::ScorePromptResponses {
+                                idx,
+                                history_idx: None,
+                            });
+                            ui.close_menu();
+                        }
+                    },
+                )
+                .response
+                .on_hover_text(
+                    "Score responses with a judge model against the rubric of the prompt",
+                );
+            }
+
+            if ui
+                .add_enabled(
+                    !self.history.is_empty(),
+                    IconButton::new(Icon::Save).text("Export script").small(),
+                )
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Export history as a replayable script")
+                .clicked()
+            {
+                action = Some(AppAction::ExportPromptScript(idx));
+            }
+
+            if ui
+                .add_enabled(
+                    !self.history.is_empty(),
+                    IconButton::new(Icon::Document).text("Export…").small(),
+                )
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Save the history to a Markdown or HTML file")
+                .clicked()
+            {
+                action = Some(AppAction::ExportPromptHistory(idx));
+            }
+
+            if ui
+                .add(IconButton::new(Icon::Card).text("Export card").small())
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text(
+                    "Export the prompt and its settings, without the history, as a shareable file",
+                )
+                .clicked()
+            {
+                action = Some(AppAction::ExportPromptCard(idx));
+            }
+
+            if ui
+                .add_enabled(
+                    !self.state.is_generating() && self.replay.is_none(),
+                    IconButton::new(Icon::Play).text("Replay script").small(),
+                )
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Replay the inputs of a recorded script with the current model")
+                .clicked()
+            {
+                action = Some(AppAction::ReplayPromptScript(idx));
+            }
+
+            ui.add_enabled_ui(
+                !self.recent_inputs.is_empty() && !self.state.is_generating(),
+                |ui| {
+                    icons::menu_button(
+                        ui,
+                        IconButton::new(Icon::Clock)
+                            .text("Recent inputs")
+                            .frame(true),
+                        |ui| {
+                            for input in &self.recent_inputs {
+                                if ui.button(input_label(input)).on_hover_text(input).clicked() {
+                                    self.new_input = input.clone();
+                                    ui.close_menu();
+                                }
+                            }
+                        },
+                    )
+                    .response
+                    .on_hover_text(
+                        "Restore a recently sent or cleared input (Ctrl+Z in an empty input)",
+                    );
+                },
+            );
+
+            ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                if let Some(icon) = self.appearance.icon_text() {
+                    ui.label(icon.heading());
+                }
+
+                let title = RichText::new(&self.title).heading();
+
+                ui.label(match self.appearance.color {
+                    Some(color) => title.color(Color32::from(color)),
+                    None => title,
+                });
+
+                if let Some(path) = &self.style_guide {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+
+                    match (&self.style_guide_error, &self.style_guide_cache) {
+                        (Some(e), _) => {
+                            ui.label(
+                                RichText::new(format!("📘 {name}"))
+                                    .color(ui.style().visuals.error_fg_color),
+                            )
+                            .on_hover_text(format!("Failed to load the style guide: {e}"));
+                        }
+                        (None, Some(cache)) => {
+                            ui.label(RichText::new(format!("📘 {name}")).weak())
+                                .on_hover_text(format!(
+                                    "Style guide {}, loaded at {}",
+                                    path.display(),
+                                    cache.loaded_at.format("%Y-%m-%d %H:%M:%S")
+                                ));
+                        }
+                        (None, None) => {
+                            ui.label(RichText::new(format!("📘 {name}")).weak())
+                                .on_hover_text(format!(
+                                    "Style guide {}, loaded on the next request",
+                                    path.display()
+                                ));
+                        }
+                    }
+                }
+            });
+        });
+
+        action
+    }
+
+    fn show_replay_controls(
+        &mut self,
+        ui: &mut egui::Ui,
+        idx: usize,
+        hold_queue: bool,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let is_generating = self.state.is_generating();
+        let mut stop = false;
+
+        if let Some(replay) = self.replay.as_mut() {
+            if let Some(input) = replay.poll(is_generating || hold_queue) {
+                action = Some(AppAction::GeneratePromptResponse {
+                    idx,
+                    input,
+                    model: None,
+                });
+            }
+
+            ui.horizontal(|ui| {
+                let (done, total) = replay.progress();
+
+                ui.label(format!("Replaying script: {done}/{total}"));
+
+                let pause_icon = if replay.paused {
+                    Icon::Play
+                } else {
+                    Icon::Pause
+                };
+                let pause_hint = if replay.paused { "Resume" } else { "Pause" };
+
+                if ui
+                    .add(IconButton::new(pause_icon).small().frame(true))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text(pause_hint)
+                    .clicked()
+                {
+                    replay.paused = !replay.paused;
+                }
+
+                if ui
+                    .add_enabled(
+                        !replay.is_finished(),
+                        IconButton::new(Icon::SkipForward).small().frame(true),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Skip the next step")
+                    .clicked()
+                {
+                    replay.skip();
+                }
+
+                if ui
+                    .add(IconButton::new(Icon::Stop).small().frame(true))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Stop replaying")
+                    .clicked()
+                {
+                    stop = true;
+                }
+
+                ui.add(
+                    egui::Slider::new(&mut replay.delay_secs, 0.0..=30.0)
+                        .step_by(0.5)
+                        .suffix(" s")
+                        .text("delay"),
+                );
+            });
+
+            if replay.is_finished() && !is_generating {
+                stop = true;
+            }
+        }
+
+        if stop {
+            self.replay = None;
+        }
+
+        action
+    }
+
+    /// Length of the output of the streamed entry revealed so far, `None` once it is fully shown
+    fn reveal_streamed_output(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &Settings,
+    ) -> Option<usize> {
+        let Some(response) = self
+            .history
+            .iter()
+            .find(|response| self.is_streamed_to(response))
+            .filter(|_| settings.smooth_streaming)
+        else {
+            self.typewriter = None;
+            return None;
+        };
+
+        if self.state.is_generating()
+            && self
+                .typewriter
+                .as_ref()
+                .is_none_or(|(created_at, _)| *created_at != response.created_at)
+        {
+            self.typewriter = Some((response.created_at, Typewriter::default()));
+        }
+
+        let (created_at, typewriter) = self.typewriter.as_mut()?;
+
+        if *created_at != response.created_at {
+            self.typewriter = None;
+            return None;
+        }
+
+        let len = typewriter.reveal(
+            &response.output,
+            f64::from(settings.smooth_streaming_chars_per_sec),
+        );
+
+        if len == response.output.len() && !self.state.is_generating() {
+            self.typewriter = None;
+            return None;
+        }
+
+        ctx.request_repaint();
+
+        Some(len)
+    }
+
+    fn show_prompt_history(
+        &mut self,
+        ui: &mut egui::Ui,
+        idx: usize,
+        commonmark_cache: &mut CommonMarkCache,
+        settings: &Settings,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let mut quote = false;
+        let mut toggle_star = None;
+        let mut show_version = None;
+        let mut add_annotation = None;
+        let mut remove_annotation = None;
+
+        if !self.history.is_empty() {
+            ui.add_space(6.0);
+            ui.add(
+                egui::TextEdit::singleline(&mut self.history_search)
+                    .id(egui::Id::new(HISTORY_SEARCH_ID))
+                    .hint_text("🔍 Search the history")
+                    .desired_width(240.0),
+            );
+        }
+
+        let search = self.history_search.trim().to_string();
+        let revealed = self.reveal_streamed_output(ui.ctx(), settings);
+
+        // The entry jumped to is shown even when it's older than the shown ones
+        if let Some(jump_to) = self.jump_to
+            && let Some(position) = self
+                .history
+                .iter()
+                .position(|response| response.created_at == jump_to)
+        {
+            self.older_history_windows = self
+                .older_history_windows
+                .max(position / HISTORY_WINDOW_LEN);
+        }
+
+        let window_len = HISTORY_WINDOW_LEN * (self.older_history_windows + 1);
+        let history_count = self.history_count();
+        let mut shown = 0;
+        let mut has_hidden = false;
+        let mut show_older = false;
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            for (history_idx, prompt_response) in self.history.iter().enumerate() {
+                // The output is still being revealed for a moment after the generation finishes
+                let is_streaming = Some(prompt_response.created_at) == self.streaming_to
+                    && (self.state.is_generating() || revealed.is_some());
+
+                if !self.is_in_active_chat(prompt_response) {
+                    continue;
+                }
+
+                if !search.is_empty()
+                    && !content::contains_query(&prompt_response.input, &search)
+                    && !content::contains_query(&prompt_response.output, &search)
+                    && !prompt_response
+                        .source_file
+                        .as_deref()
+                        .is_some_and(|name| content::contains_query(name, &search))
+                {
+                    continue;
+                }
+
+                if shown == window_len {
+                    has_hidden = true;
+                    break;
+                }
+
+                shown += 1;
+
+                let top = ui.cursor().top();
+                let width = ui.available_width();
+
+                // Rendering the markdown of every entry each frame is slow for long histories, so
+                // the entries scrolled out of view only take up the space they were measured at
+                if !is_streaming
+                    && self.jump_to != Some(prompt_response.created_at)
+                    && let Some(&(measured_width, height)) =
+                        self.history_entry_sizes.get(&prompt_response.created_at)
+                    && measured_width == width
+                    && !ui.is_rect_visible(egui::Rect::from_min_size(
+                        ui.cursor().min,
+                        egui::vec2(width, height),
+                    ))
+                {
+                    ui.add_space(height);
+                    continue;
+                }
+
+                ui.add_space(6.0);
+
+                ui.with_layout(
+                    Layout::left_to_right(egui::Align::TOP)
+                        .with_main_justify(true)
+                        .with_main_align(egui::Align::LEFT),
+                    |ui| {
+                        Frame::group(ui.style())
+                            .inner_margin(egui::Margin::same(10))
+                            .corner_radius(CornerRadius::same(6))
+                            .stroke(Stroke::new(1.0, ui.style().visuals.window_stroke.color))
+                            .show(ui, |ui| {
+                                ui.with_layout(
+                                    Layout::top_down(egui::Align::TOP)
+                                        .with_cross_justify(true)
+                                        .with_cross_align(egui::Align::LEFT),
+                                    |ui| {
+                                        if self.jump_to == Some(prompt_response.created_at) {
+                                            ui.scroll_to_cursor(Some(egui::Align::TOP));
+                                            self.jump_to = None;
+                                        }
+
+                                        ui.horizontal(|ui| {
+                                            ui.add(Icon::Model);
+                                            ui.label(&prompt_response.local_model_name);
+                                            ui.label(
+                                                RichText::new(
+                                                    format!(" {} ", prompt_response.created_at.format("%Y-%m-%d %H:%M:%S"))
+                                                ).weak()
+                                            );
+
+                                            if !prompt_response.output.is_empty() {
+                                                let words = prompt_response.word_count();
+
+                                                ui.label(
+                                                    RichText::new(format!("{words} words · {}", history::reading_time(words)))
+                                                        .small()
+                                                        .weak(),
+                                                );
+                                            }
+
+                                            if let Some(generation) = &prompt_response.generation {
+                                                ui.label(RichText::new(generation.summary()).small().weak())
+                                                    .on_hover_text("Generation speed, counted from the first token, the total time and the streamed tokens");
+                                            }
+
+                                            if let Some(name) = &prompt_response.source_file {
+                                                ui.label(RichText::new(name).small().weak())
+                                                    .on_hover_text("Processed from the watched folder");
+                                            }
+
+                                            if prompt_response.incognito {
+                                                ui.label(RichText::new("🕶 not saved").small().weak())
+                                                    .on_hover_text("Asked in incognito, the exchange is gone once the app closes");
+                                            }
+
+                                            if let Some(stop_reason) = prompt_response.stop_reason() {
+                                                ui.label(RichText::new(stop_reason.badge()).small().weak())
+                                                    .on_hover_text(stop_reason.description());
+                                            }
+
+                                            if !prompt_response.variables.is_empty() {
+                                                let values = prompt_response
+                                                    .variables
+                                                    .iter()
+                                                    .map(|(name, value)| format!("{name} = {value}"))
+                                                    .collect::<Vec<_>>()
+                                                    .join("\n");
+
+                                                ui.label(RichText::new("{{…}}").small().weak())
+                                                    .on_hover_text(values);
+                                            }
+
+                                            if let Some(parent) = prompt_response.parent {
+                                                let label = format!("↳ derived from {}", parent.format("%Y-%m-%d %H:%M:%S"));
+
+                                                if self.history.iter().any(|response| response.created_at == parent) {
+                                                    if ui
+                                                        .link(RichText::new(label).small())
+                                                        .on_hover_text("Jump to the entry this one was derived from")
+                                                        .clicked()
+                                                    {
+                                                        self.jump_to = Some(parent);
+                                                    }
+                                                } else {
+                                                    ui.label(RichText::new(label).small().weak())
+                                                        .on_hover_text("The entry was removed");
+                                                }
+                                            }
+
+                                            ui.with_layout(
+                                                Layout::right_to_left(egui::Align::Min),
+                                                |ui| {
+                                                    let remove_response = ui.add_enabled(
+                                                        !self.state.is_generating(),
+                                                        IconButton::new(Icon::Remove),
+                                                    );
+
+                                                    if ui
+                                                        .add(
+                                                            IconButton::new(if prompt_response.starred { Icon::Starred } else { Icon::Star }).small(),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Star the input to re-run it when the prompt changes")
+                                                        .clicked()
+                                                    {
+                                                        toggle_star = Some(history_idx);
+                                                    }
+
+                                                    let regenerate_response = ui.add_enabled(
+                                                        !self.state.is_generating(),
+                                                        IconButton::new(Icon::Reload),
+                                                    );
+
+                                                    if self.state.is_generating() && is_streaming
+                                                        && ui.add_enabled(
+                                                            true,
+                                                            IconButton::new(Icon::Stop).small(),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Stop generation")
+                                                        .clicked() {
+                                                            action = Some(AppAction::CancelGeneration { idx });
+                                                    }
+
+                                                    if remove_response
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Remove from prompt history")
+                                                        .clicked()
+                                                    {
+                                                        action = Some(AppAction::OpenRemovePromptHistoryDialog { idx, history_idx});
+                                                    }
+
+                                                    if regenerate_response
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Generate a new version with current model")
+                                                        .clicked()
+                                                    {
+                                                        action = Some(AppAction::RegeneratePromptResponse { idx, history_idx });
+                                                    }
+
+                                                    if history_idx == 0
+                                                        && self.kind == PromptKind::Text
+                                                        && !self.state.is_generating()
+                                                        && prompt_response.can_continue()
+                                                        && ui
+                                                            .add(
+                                                                IconButton::new(Icon::Continue).small(),
+                                                            )
+                                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                            .on_hover_text("Continue the response from where it stopped, e.g. when it was cut off")
+                                                            .clicked()
+                                                    {
+                                                        action = Some(AppAction::ContinuePromptResponse(idx));
+                                                    }
+
+                                                    let version_count = prompt_response.version_count();
+
+                                                    if version_count > 1 {
+                                                        let version = prompt_response.shown_version.unwrap_or(version_count - 1);
+                                                        let arrow = |icon| {
+                                                            IconButton::new(icon).small()
+                                                        };
+
+                                                        if ui
+                                                            .add_enabled(!is_streaming && version + 1 < version_count, arrow(Icon::Next))
+                                                            .on_hover_text("Newer version")
+                                                            .clicked()
+                                                        {
+                                                            show_version = Some((history_idx, version + 1));
+                                                        }
+
+                                                        ui.label(RichText::new(format!("{}/{version_count}", version + 1)).small().weak());
+
+                                                        if ui
+                                                            .add_enabled(!is_streaming && version > 0, arrow(Icon::Previous))
+                                                            .on_hover_text("Older version")
+                                                            .clicked()
+                                                        {
+                                                            show_version = Some((history_idx, version - 1));
+                                                        }
+                                                    }
+
+                                                    if self.scoring_queue.contains(&prompt_response.created_at) {
+                                                        ui.spinner().on_hover_text("Scoring with judge");
+                                                    } else if prompt_response.score.is_none()
+                                                        && self.kind == PromptKind::Text
+                                                        && ui.add_enabled(
+                                                            !self.judge_model.is_empty()
+                                                                && !self.score_flower.is_active()
+                                                                && !prompt_response.output.is_empty()
+                                                                && !is_streaming,
+                                                            IconButton::new(Icon::Judge).small(),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Score with judge")
+                                                        .clicked()
+                                                    {
+                                                        action = Some(AppAction::ScorePromptResponses {
+                                                            idx,
+                                                            history_idx: Some(history_idx),
+                                                        });
+                                                    }
+
+                                                    if self.kind == PromptKind::Text
+                                                        && ui.add_enabled(
+                                                            !prompt_response.output.is_empty() && !is_streaming,
+                                                            IconButton::new(Icon::Edit),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Open in external editor")
+                                                        .clicked()
+                                                    {
+                                                        action = Some(AppAction::OpenInEditor { idx, history_idx });
+                                                    }
+
+                                                    if self.speech_created_at == Some(prompt_response.created_at) {
+                                                        ui.spinner().on_hover_text("Synthesizing speech");
+                                                    } else if prompt_response.audio.is_none()
+                                                        && ui.add_enabled(
+                                                            !self.speech_flower.is_active()
+                                                                && !prompt_response.output.is_empty()
+                                                                && !is_streaming,
+                                                            IconButton::new(Icon::Speak).small(),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                        .on_hover_text("Read aloud")
+                                                        .clicked()
+                                                    {
+                                                        action = Some(AppAction::SynthesizePromptResponse { idx, history_idx });
+                                                    }
+                                                },
+                                            );
+                                        });
+
+                                        ui.add_space(6.0);
+
+                                        Frame::group(ui.style())
+                                            .stroke(Stroke::new(
+                                                1.0,
+                                                ui.style().visuals.window_stroke.color,
+                                            ))
+                                            .fill(ui.style().visuals.faint_bg_color)
+                                            .show(ui, |ui| {
+                                                ui.add(egui::Label::wrap(Label::new(
+                                                    &prompt_response.input,
+                                                )));
+                                            });
+
+                                        if !prompt_response.debate.is_empty() {
+                                            egui::CollapsingHeader::new(
+                                                RichText::new("Debate exchange").weak(),
+                                            )
+                                            .id_salt(("response_debate", prompt_response.created_at))
+                                            .show(ui, |ui| {
+                                                for answer in &prompt_response.debate {
+                                                    ui.horizontal(|ui| {
+                                                        ui.add(Icon::Model);
+                                                        ui.label(RichText::new(&answer.model).strong());
+                                                    });
+                                                    CommonMarkViewer::new().show(ui, commonmark_cache, &answer.output);
+                                                    ui.separator();
+                                                }
+                                            });
+                                        }
+
+                                        if !prompt_response.candidates.is_empty() && !is_streaming {
+                                            egui::CollapsingHeader::new(
+                                                RichText::new(format!("Candidates ({})", prompt_response.candidates.len())).weak(),
+                                            )
+                                            .id_salt(("response_candidates", prompt_response.created_at))
+                                            .show(ui, |ui| {
+                                                for (candidate_idx, candidate) in prompt_response.candidates.iter().enumerate() {
+                                                    ui.horizontal(|ui| {
+                                                        ui.label(
+                                                            RichText::new(format!("Candidate {} (seed {})", candidate_idx + 1, candidate.seed))
+                                                                .strong(),
+                                                        );
+
+                                                        if prompt_response.selected_candidate == Some(candidate_idx) {
+                                                            ui.label(RichText::new("✔ selected").weak());
+                                                        } else if ui
+                                                            .add_enabled(!self.state.is_generating(), egui::Button::new("Use").small())
+                                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                            .on_hover_text("Use this candidate as the response")
+                                                            .clicked()
+                                                        {
+                                                            action = Some(AppAction::SelectEnsembleCandidate {
+                                                                idx,
+                                                                history_idx,
+                                                                candidate_idx,
+                                                            });
+                                                        }
+                                                    });
+                                                    CommonMarkViewer::new().show(ui, commonmark_cache, &candidate.output);
+                                                    ui.separator();
+                                                }
+                                            });
+                                        }
+
+                                        if let Some(draft) = &prompt_response.draft {
+                                            egui::CollapsingHeader::new(
+                                                RichText::new("First draft, revised after self-critique").weak(),
+                                            )
+                                            .id_salt(("response_draft", prompt_response.created_at))
+                                            .show(ui, |ui| {
+                                                CommonMarkViewer::new().show(ui, commonmark_cache, draft);
+                                            });
+                                        }
+
+                                        if let Some(metrics) = &prompt_response.metrics {
+                                            egui::CollapsingHeader::new(RichText::new("Details").weak())
+                                                .id_salt(("response_metrics", prompt_response.created_at))
+                                                .show(ui, |ui| Self::show_metrics(ui, metrics));
+                                        }
+
+                                        let is_read_aloud = self.audio_player.as_mut().is_some_and(|player| {
+                                            player.response_created_at == prompt_response.created_at
+                                                && player.is_started()
+                                        });
+
+                                        if !prompt_response.comparison.is_empty() {
+                                            Self::show_comparison(ui, commonmark_cache, &prompt_response.comparison);
+                                        } else if is_read_aloud && let Some(player) = self.audio_player.as_mut() {
+                                            assign_if_some!(action, Self::show_read_along(
+                                                ui,
+                                                player,
+                                                &prompt_response.output,
+                                                &settings.audio_player_command,
+                                            ));
+                                        } else if self.annotating == Some(prompt_response.created_at) {
+                                            if Self::show_annotation_editor(
+                                                ui,
+                                                &prompt_response.output,
+                                                &prompt_response.annotations,
+                                                &mut self.annotation_draft,
+                                                &mut self.annotating,
+                                            ) {
+                                                add_annotation = Some(history_idx);
+                                            }
+                                        } else if self.quoting == Some(prompt_response.created_at) {
+                                            let output = egui::TextEdit::multiline(&mut prompt_response.output.as_str())
+                                                .desired_width(f32::INFINITY)
+                                                .show(ui);
+
+                                            if let Some(range) = output.cursor_range
+                                                && !range.is_empty()
+                                            {
+                                                self.quote_selection =
+                                                    range.slice_str(&prompt_response.output).to_string();
+                                            }
+
+                                            ui.horizontal(|ui| {
+                                                if ui
+                                                    .add_enabled(
+                                                        !self.quote_selection.trim().is_empty(),
+                                                        IconButton::new(Icon::Quote).text("Ask about selection").frame(true),
+                                                    )
+                                                    .on_disabled_hover_text("Select a passage first")
+                                                    .clicked()
+                                                {
+                                                    quote = true;
+                                                }
+
+                                                // Incognito exchanges are never saved, neither are passages of them
+                                                if !prompt_response.incognito
+                                                    && ui
+                                                        .add_enabled(
+                                                            !self.quote_selection.trim().is_empty(),
+                                                            IconButton::new(Icon::Cut).text("Clip").frame(true),
+                                                        )
+                                                        .on_hover_text("Save the passage to the clippings")
+                                                        .on_disabled_hover_text("Select a passage first")
+                                                        .clicked()
+                                                {
+                                                    action = Some(AppAction::ClipQuote {
+                                                        idx,
+                                                        created_at: prompt_response.created_at,
+                                                        model: prompt_response.local_model_name.clone(),
+                                                        quote: self.quote_selection.trim().to_string(),
+                                                    });
+                                                }
+
+                                                if ui.button("Cancel").clicked() {
+                                                    self.quoting = None;
+                                                    self.quote_selection.clear();
+                                                }
+                                            });
+                                        } else {
+                                            let created_at = prompt_response.created_at;
+                                            // Older versions are shown as markdown, the content type being
+                                            // detected on the latest one
+                                            let content_type = if is_streaming || prompt_response.shown_version.is_some() {
+                                                None
+                                            } else {
+                                                let (len, content_type) = self
+                                                    .content_types
+                                                    .entry(created_at)
+                                                    .or_insert_with(|| {
+                                                        (prompt_response.output.len(), content::detect(&prompt_response.output))
+                                                    });
+
+                                                if *len != prompt_response.output.len() {
+                                                    *len = prompt_response.output.len();
+                                                    *content_type = content::detect(&prompt_response.output);
+                                                }
+
+                                                Some(&*content_type)
+                                            };
+
+                                            let mut shown = false;
+
+                                            if let Some(content_type) = content_type
+                                                && *content_type == ContentType::Markdown
+                                                && !prompt_response.annotations.is_empty()
+                                                && search.is_empty()
+                                            {
+                                                let mut markdown = self.markdown_view.contains(&created_at);
+
+                                                ui.horizontal(|ui| {
+                                                    ui.selectable_value(&mut markdown, false, RichText::new("Highlights").small());
+                                                    ui.selectable_value(&mut markdown, true, RichText::new("Markdown").small());
+                                                });
+
+                                                if markdown {
+                                                    self.markdown_view.insert(created_at);
+                                                } else {
+                                                    self.markdown_view.remove(&created_at);
+                                                    Self::show_annotated_output(
+                                                        ui,
+                                                        &prompt_response.output,
+                                                        &prompt_response.annotations,
+                                                        created_at,
+                                                    );
+                                                    shown = true;
+                                                }
+                                            } else if let Some(content_type) = content_type
+                                                && *content_type != ContentType::Markdown
+                                            {
+                                                let mut markdown = self.markdown_view.contains(&created_at);
+
+                                                ui.horizontal(|ui| {
+                                                    ui.selectable_value(&mut markdown, false, RichText::new(content_type.label()).small());
+                                                    ui.selectable_value(&mut markdown, true, RichText::new("Markdown").small());
+
+                                                    if matches!(content_type, ContentType::Diff(_))
+                                                        && ui
+                                                            .add_enabled(!self.state.is_generating(), IconButton::new(Icon::Patch).text("Apply to file…").small().frame(true))
+                                                            .on_hover_text("Patch a file on disk with this diff, after a preview")
+                                                            .clicked()
+                                                    {
+                                                        action = Some(AppAction::ApplyDiff { idx, history_idx });
+                                                    }
+                                                });
+
+                                                if markdown {
+                                                    self.markdown_view.insert(created_at);
+                                                } else {
+                                                    self.markdown_view.remove(&created_at);
+                                                    shown = viewer::show(ui, content_type, created_at);
+                                                }
+                                            }
+
+                                            if !shown {
+                                                let output = match revealed {
+                                                    Some(len) if is_streaming => &prompt_response.output[..len],
+                                                    _ => prompt_response.shown_output(),
+                                                };
+                                                let output = if search.is_empty() {
+                                                    output
+                                                } else {
+                                                    self.highlighted_outputs.get(prompt_response.created_at, output, &search)
+                                                };
+
+                                                CommonMarkViewer::new().show(ui, commonmark_cache, output);
+                                            }
+                                        }
+
+                                        if !prompt_response.annotations.is_empty() {
+                                            assign_if_some!(
+                                                remove_annotation,
+                                                Self::show_annotations(ui, &prompt_response.annotations, prompt_response.created_at)
+                                                    .map(|annotation_idx| (history_idx, annotation_idx))
+                                            );
+                                        }
+
+                                        if settings.run_snippets && !is_streaming {
+                                            let snippets = snippet::find(&prompt_response.output);
+
+                                            if !snippets.is_empty() {
+                                                ui.horizontal_wrapped(|ui| {
+                                                    if self.snippet_created_at == Some(prompt_response.created_at) {
+                                                        ui.spinner().on_hover_text("Running snippet");
+                                                    }
+
+                                                    for (snippet_idx, snippet) in snippets.into_iter().enumerate() {
+                                                        if ui
+                                                            .add_enabled(
+                                                                !self.snippet_flower.is_active(),
+                                                                IconButton::new(Icon::Play).text(format!("Run {} #{}", snippet.language, snippet_idx + 1)).small().frame(true),
+                                                            )
+                                                            .on_hover_text("Run the snippet in a sandboxed subprocess, after confirmation")
+                                                            .clicked()
+                                                        {
+                                                            action = Some(AppAction::OpenRunSnippetDialog {
+                                                                idx,
+                                                                history_idx,
+                                                                snippet,
+                                                            });
+                                                        }
+                                                    }
+                                                });
+                                            }
+                                        }
+
+                                        for (run_idx, run) in prompt_response.snippet_runs.iter().enumerate() {
+                                            let outcome = match run.status {
+                                                _ if run.timed_out => "timed out".to_string(),
+                                                Some(code) => format!("exited with {code}"),
+                                                None => "was killed".to_string(),
+                                            };
+
+                                            egui::CollapsingHeader::new(
+                                                RichText::new(format!(
+                                                    "▶ {} snippet {outcome} at {}",
+                                                    run.snippet.language,
+                                                    run.ran_at.format("%H:%M:%S")
+                                                ))
+                                                .weak(),
+                                            )
+                                            .id_salt(("snippet_run", prompt_response.created_at, run_idx))
+                                            .show(ui, |ui| {
+                                                if !run.stdout.is_empty() {
+                                                    ui.label(RichText::new(&run.stdout).monospace());
+                                                }
+
+                                                if !run.stderr.is_empty() {
+                                                    ui.label(
+                                                        RichText::new(&run.stderr)
+                                                            .monospace()
+                                                            .color(ui.style().visuals.error_fg_color),
+                                                    );
+                                                }
+
+                                                if run.stdout.is_empty() && run.stderr.is_empty() {
+                                                    ui.label(RichText::new("No output").weak());
+                                                }
+                                            });
+                                        }
+
+                                        let banned_phrases = compose::find_banned_phrases(
+                                            &prompt_response.output,
+                                            &self.banned_phrases,
+                                        );
+
+                                        if !banned_phrases.is_empty() && !is_streaming {
+                                            ui.horizontal_wrapped(|ui| {
+                                                ui.label(
+                                                    RichText::new("⚠ Banned words:")
+                                                        .color(ui.style().visuals.warn_fg_color),
+                                                );
+
+                                                for phrase in banned_phrases {
+                                                    ui.label(
+                                                        RichText::new(phrase)
+                                                            .color(ui.style().visuals.error_fg_color)
+                                                            .background_color(ui.style().visuals.faint_bg_color)
+                                                            .strong(),
+                                                    );
+                                                }
+                                            });
+                                        }
+
+                                        if let Some(score) = &prompt_response.score {
+                                            ui.label(
+                                                RichText::new(format!("⚖ {}/10 by {}", score.score, score.model))
+                                                    .strong(),
+                                            )
+                                            .on_hover_text(&score.rationale);
+                                        }
+
+                                        let images = ResponseImage::find_all(&prompt_response.output);
+
+                                        if !images.is_empty() {
+                                            ui.add_space(6.0);
+
+                                            ui.horizontal_wrapped(|ui| {
+                                                for image in images {
+                                                    let Some(source) = image.source(ui.ctx()) else {
+                                                        continue;
+                                                    };
+
+                                                    if ui
+                                                        .add(
+                                                            egui::Image::new(source)
+                                                                .max_height(IMAGE_THUMBNAIL_HEIGHT)
+                                                                .sense(Sense::click()),
+                                                        )
+                                                        .on_hover_cursor(egui::CursorIcon::ZoomIn)
+                                                        .on_hover_text("Click to zoom")
+                                                        .clicked()
+                                                    {
+                                                        self.zoomed_image = Some(image);
+                                                    }
+                                                }
+                                            });
+                                        }
+
+                                        ui.add_space(6.0);
+
+                                        // Add copy button at the bottom left with feedback
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
+                                            let copy_response = ui.add(
+                                                IconButton::new(Icon::Copy).small(),
+                                            );
+
+                                            if ui
+                                                .add_enabled(
+                                                    !is_streaming,
+                                                    IconButton::new(Icon::Quote).small(),
+                                                )
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text("Select a passage to ask about or clip")
+                                                .clicked()
+                                            {
+                                                self.quoting = Some(prompt_response.created_at);
+                                                self.quote_selection.clear();
+                                                self.annotating = None;
+                                            }
+
+                                            if ui
+                                                .add_enabled(
+                                                    !is_streaming,
+                                                    IconButton::new(Icon::Highlight).small(),
+                                                )
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text("Highlight a passage and comment on it")
+                                                .clicked()
+                                            {
+                                                self.annotating = Some(prompt_response.created_at);
+                                                self.annotation_draft = Annotation {
+                                                    color: self.annotation_draft.color,
+                                                    ..Default::default()
+                                                };
+                                                self.quoting = None;
+                                            }
+
+                                            let mut is_selected = self.transcript_selection.contains(&prompt_response.created_at);
+
+                                            if ui
+                                                .checkbox(&mut is_selected, "")
+                                                .on_hover_text("Select for the transcript")
+                                                .changed()
+                                            {
+                                                if is_selected {
+                                                    self.transcript_selection.insert(prompt_response.created_at);
+                                                } else {
+                                                    self.transcript_selection.remove(&prompt_response.created_at);
+                                                }
+                                            }
+
+                                            if copy_response
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text("Copy the response as markdown")
+                                                .clicked()
+                                            {
+                                                assign_if_some!(action, Self::copy_with_feedback(
+                                                    crate::copy_to_clipboard(prompt_response.shown_output()),
+                                                    prompt_response.created_at,
+                                                    &mut self.copy_feedback,
+                                                ));
+                                            }
+
+                                            if ui
+                                                .add(
+                                                    egui::Button::new(RichText::new("Q&A").small())
+                                                        .fill(Color32::TRANSPARENT)
+                                                        .small()
+                                                        .stroke(Stroke::NONE),
+                                                )
+                                                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                .on_hover_text("Copy the input and the response")
+                                                .clicked()
+                                            {
+                                                assign_if_some!(action, Self::copy_with_feedback(
+                                                    crate::copy_to_clipboard(&prompt_response.to_markdown()),
+                                                    prompt_response.created_at,
+                                                    &mut self.copy_feedback,
+                                                ));
+                                            }
+
+                                            icons::menu_button(ui, IconButton::new(Icon::Expand).small(), |ui| {
+                                                if ui.button("Copy as plain text").clicked() {
+                                                    ui.close_menu();
+                                                    assign_if_some!(action, Self::copy_with_feedback(
+                                                        crate::copy_to_clipboard(&markdown::to_plain_text(&prompt_response.output)),
+                                                        prompt_response.created_at,
+                                                        &mut self.copy_feedback,
+                                                    ));
+                                                }
+
+                                                if ui
+                                                    .button("Copy as rich text")
+                                                    .on_hover_text("Keeps the formatting when pasted into an email or a document")
+                                                    .clicked()
+                                                {
+                                                    ui.close_menu();
+
+                                                    assign_if_some!(action, Self::copy_with_feedback(
+                                                        crate::copy_html_to_clipboard(
+                                                            &markdown::to_html(&prompt_response.output),
+                                                            &markdown::to_plain_text(&prompt_response.output),
+                                                        ),
+                                                        prompt_response.created_at,
+                                                        &mut self.copy_feedback,
+                                                    ));
+                                                }
+                                            })
+                                            .response
+                                            .on_hover_text("Copy as…");
+
+                                            if matches!(
+                                                self.copy_feedback.as_ref(),
+                                                Some(feedback) if !feedback.is_active()
+                                            ) {
+                                                self.copy_feedback = None;
+                                            }
+
+                                            if let Some(feedback) = self.copy_feedback.as_ref()
+                                                && feedback.is_active()
+                                                && feedback.response_created_at
+                                                    == prompt_response.created_at
+                                            {
+                                                // ui.add_space(6.0);
+                                                ui.label(
+                                                    RichText::new("Copied!")
+                                                        .color(Color32::from_rgb(80, 200, 120))
+                                                        .strong(),
+                                                );
+                                            }
+
+                                            if let Some(audio) = &prompt_response.audio {
+                                                ui.separator();
+
+                                                assign_if_some!(action, Self::show_audio_player(
+                                                    ui,
+                                                    &mut self.audio_player,
+                                                    audio,
+                                                    prompt_response.created_at,
+                                                    &settings.audio_player_command,
+                                                ));
+                                            }
+                                        });
+                                    },
+                                );
+                            });
+                    },
+                );
+
+                self.history_entry_sizes.insert(
+                    prompt_response.created_at,
+                    (width, ui.cursor().top() - top),
+                );
+            }
+
+            if has_hidden || self.unloaded_history.is_some() {
+                let label = if search.is_empty() {
+                    format!(
+                        "Load older responses ({} more)",
+                        history_count - shown
+                    )
+                } else {
+                    "Load older responses".to_string()
+                };
+
+                ui.add_space(6.0);
+                ui.vertical_centered(|ui| {
+                    if ui
+                        .add(IconButton::new(Icon::Download).text(label).frame(true))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        // The loaded entries are shown first, then the next page is read from the
+                        // database
+                        if has_hidden {
+                            show_older = true;
+                        } else {
+                            action = Some(AppAction::LoadOlderHistory(idx));
+                        }
+                    }
+                });
+            }
+        });
+
+        if show_older {
+            self.older_history_windows += 1;
+        }
+
+        if quote {
+            self.quote_into_input(&settings.quote_template);
+        }
+
+        if let Some(response) =
+            toggle_star.and_then(|history_idx| self.history.get_mut(history_idx))
+        {
+            response.starred = !response.starred;
+        }
+
+        if let Some(response) =
+            add_annotation.and_then(|history_idx| self.history.get_mut(history_idx))
+        {
+            let color = self.annotation_draft.color;
+
+            response.annotations.push(std::mem::replace(
+                &mut self.annotation_draft,
+                Annotation {
+                    color,
+                    ..Default::default()
+                },
+            ));
+        }
+
+        if let Some((history_idx, annotation_idx)) = remove_annotation
+            && let Some(response) = self.history.get_mut(history_idx)
+            && annotation_idx < response.annotations.len()
+        {
+            response.annotations.remove(annotation_idx);
+        }
+
+        if let Some((response, version)) = show_version.and_then(|(history_idx, version)| {
+            self.history
+                .get_mut(history_idx)
+                .map(|response| (response, version))
+        }) {
+            // The latest version is the output itself
+            response.shown_version = (version < response.versions.len()).then_some(version);
+        }
+
+        action
+    }
+
+    fn highlight_fill(color: HighlightColor) -> Color32 {
+        match color {
+            HighlightColor::Yellow => Color32::from_rgba_unmultiplied(240, 200, 40, 90),
+            HighlightColor::Green => Color32::from_rgba_unmultiplied(80, 200, 120, 90),
+            HighlightColor::Blue => Color32::from_rgba_unmultiplied(90, 150, 240, 90),
+            HighlightColor::Red => Color32::from_rgba_unmultiplied(240, 100, 100, 90),
+        }
+    }
+
+    /// The output as plain text with the highlighted passages on a colored background
+    fn annotated_layout_job(
+        ui: &egui::Ui,
+        output: &str,
+        annotations: &[Annotation],
+        wrap_width: f32,
+    ) -> egui::text::LayoutJob {
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        let plain = egui::TextFormat::simple(font_id, ui.visuals().text_color());
+        let mut job = egui::text::LayoutJob::default();
+        let mut end = 0;
+
+        for (range, annotation_idx) in history::annotated_ranges(output, annotations) {
+            job.append(&output[end..range.start], 0.0, plain.clone());
+            job.append(
+                &output[range.clone()],
+                0.0,
+                egui::TextFormat {
+                    background: Self::highlight_fill(annotations[annotation_idx].color),
+                    ..plain.clone()
+                },
+            );
+            end = range.end;
+        }
+
+        job.append(&output[end..], 0.0, plain);
+        job.wrap.max_width = wrap_width;
+
+        job
+    }
+
+    /// The output as selectable text, with the form highlighting the selected passage. Returns
+    /// whether to add the drafted highlight.
+    fn show_annotation_editor(
+        ui: &mut egui::Ui,
+        output: &str,
+        annotations: &[Annotation],
+        draft: &mut Annotation,
+        annotating: &mut Option<DateTime<Local>>,
+    ) -> bool {
+        let mut add = false;
+        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+            let job = Self::annotated_layout_job(ui, text, annotations, wrap_width);
+
+            ui.fonts(|fonts| fonts.layout_job(job))
+        };
+
+        let edit = egui::TextEdit::multiline(&mut &*output)
+            .desired_width(f32::INFINITY)
+            .layouter(&mut layouter)
+            .show(ui);
+
+        if let Some(range) = edit.cursor_range
+            && !range.is_empty()
+        {
+            let chars = range.as_sorted_char_range();
+
+            draft.start = chars.start;
+            draft.end = chars.end;
+            draft.passage = range.slice_str(output).to_string();
+        }
+
+        ui.horizontal(|ui| {
+            for color in HighlightColor::ALL {
+                ui.selectable_value(
+                    &mut draft.color,
+                    color,
+                    RichText::new("⏺").color(Self::highlight_fill(color).to_opaque()),
+                )
+                .on_hover_text(color.label());
+            }
+
+            egui::TextEdit::singleline(&mut draft.comment)
+                .hint_text("Comment")
+                .desired_width(240.0)
+                .show(ui);
+
+            add = ui
+                .add_enabled(
+                    !draft.passage.trim().is_empty(),
+                    IconButton::new(Icon::Highlight)
+                        .text("Highlight")
+                        .frame(true),
+                )
+                .on_disabled_hover_text("Select a passage first")
+                .clicked();
+
+            if ui.button("Done").clicked() {
+                *annotating = None;
+            }
+        });
+
+        add
+    }
+
+    /// The output with its highlights, marked in the margin with their comment on hover
+    fn show_annotated_output(
+        ui: &mut egui::Ui,
+        output: &str,
+        annotations: &[Annotation],
+        created_at: DateTime<Local>,
+    ) {
+        let job = Self::annotated_layout_job(
+            ui,
+            output,
+            annotations,
+            ui.available_width() - ANNOTATION_MARGIN,
+        );
+        let galley = ui.fonts(|fonts| fonts.layout_job(job));
+        let (rect, _) = ui.allocate_exact_size(
+            galley.size() + egui::vec2(ANNOTATION_MARGIN, 0.0),
+            Sense::hover(),
+        );
+        let text_pos = rect.min + egui::vec2(ANNOTATION_MARGIN, 0.0);
+
+        for (range, annotation_idx) in history::annotated_ranges(output, annotations) {
+            let annotation = &annotations[annotation_idx];
+            let cursor_at = |byte: usize| {
+                galley.pos_from_ccursor(egui::text::CCursor::new(output[..byte].chars().count()))
+            };
+            let marker = egui::Rect::from_x_y_ranges(
+                rect.left() + 2.0..=rect.left() + 5.0,
+                text_pos.y + cursor_at(range.start).top()
+                    ..=text_pos.y + cursor_at(range.end).bottom(),
+            );
+
+            ui.painter().rect_filled(
+                marker,
+                1.0,
+                Self::highlight_fill(annotation.color).to_opaque(),
+            );
+
+            let hover_text = if annotation.comment.is_empty() {
+                "No comment"
+            } else {
+                annotation.comment.as_str()
+            };
+
+            ui.interact(
+                marker.expand2(egui::vec2(3.0, 0.0)),
+                egui::Id::new(("annotation_marker", created_at, annotation_idx)),
+                Sense::hover(),
+            )
+            .on_hover_text(hover_text);
+        }
+
+        ui.painter()
+            .galley(text_pos, galley, ui.visuals().text_color());
+    }
+
+    /// The highlights of a response with their comment, returns the one to remove
+    fn show_annotations(
+        ui: &mut egui::Ui,
+        annotations: &[Annotation],
+        created_at: DateTime<Local>,
+    ) -> Option<usize> {
+        let mut remove = None;
+
+        egui::CollapsingHeader::new(
+            RichText::new(format!("🖍 Highlights ({})", annotations.len())).weak(),
+        )
+        .id_salt(("response_annotations", created_at))
+        .show(ui, |ui| {
+            for (annotation_idx, annotation) in annotations.iter().enumerate() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        RichText::new("⏺")
+                            .color(Self::highlight_fill(annotation.color).to_opaque()),
+                    );
+                    ui.label(RichText::new(format!("\"{}\"", annotation.passage.trim())).italics());
+
+                    if !annotation.comment.is_empty() {
+                        ui.label(format!("— {}", annotation.comment));
+                    }
+
+                    if ui
+                        .add(IconButton::new(Icon::Remove))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text("Remove the highlight")
+                        .clicked()
+                    {
+                        remove = Some(annotation_idx);
+                    }
+                });
+            }
+        });
+
+        remove
+    }
+
+    /// Shows "Copied!" under the response that was copied, or the error
+    fn copy_with_feedback(
+        copied: Result<(), Box<dyn std::error::Error>>,
+        created_at: DateTime<Local>,
+        copy_feedback: &mut Option<CopyFeedback>,
+    ) -> Option<AppAction> {
+        match copied {
+            Ok(()) => {
+                *copy_feedback = Some(CopyFeedback::new(created_at));
+                None
+            }
+            Err(e) => Some(AppAction::ShowErrorDialog {
+                title: "Copy Error".to_string(),
+                message: format!("Failed to copy to clipboard: {e}"),
+            }),
+        }
+    }
+
+    /// Shows play/pause, a seek bar and a save button for the cached speech of a response
+    fn show_audio_player(
+        ui: &mut egui::Ui,
+        audio_player: &mut Option<AudioPlayer>,
+        audio: &Path,
+        response_created_at: DateTime<Local>,
+        player_command: &str,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let mut result = Ok(());
+
+        let player = audio_player
+            .as_mut()
+            .filter(|player| player.response_created_at == response_created_at);
+        let is_playing = player.is_some_and(|player| player.is_playing());
+
+        let (play_icon, play_hint) = if is_playing {
+            (Icon::Pause, "Pause")
+        } else {
+            (Icon::Play, "Play")
+        };
+
+        if ui
+            .add(IconButton::new(play_icon).small())
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .on_hover_text(play_hint)
+            .clicked()
+        {
+            if is_playing {
+                if let Some(player) = audio_player.as_mut() {
+                    player.pause();
+                }
+            } else {
+                // Only one response is played at a time
+                if audio_player
+                    .as_ref()
+                    .is_none_or(|player| player.response_created_at != response_created_at)
+                {
+                    *audio_player = None;
+
+                    match AudioPlayer::new(audio.to_path_buf(), response_created_at) {
+                        Ok(player) => *audio_player = Some(player),
+                        Err(e) => result = Err(e),
+                    }
+                }
+
+                if let Some(player) = audio_player.as_mut() {
+                    result = player.play(player_command);
+                }
+            }
+        }
+
+        if let Some(player) = audio_player
+            .as_mut()
+            .filter(|player| player.response_created_at == response_created_at)
+        {
+            let duration = player.duration().as_secs_f32();
+            let mut position = player.position().as_secs_f32();
+
+            let slider = ui.add(
+                egui::Slider::new(&mut position, 0.0..=duration)
+                    .show_value(false)
+                    .trailing_fill(true),
+            );
+
+            if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+                result = player.seek(Duration::from_secs_f32(position), player_command);
+            }
+
+            ui.label(
+                RichText::new(format!(
+                    "{} / {}",
+                    format_duration(position),
+                    format_duration(duration)
+                ))
+                .weak(),
+            );
+        }
+
+        if result.is_ok()
+            && ui
+                .add(IconButton::new(Icon::Save).small())
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Save audio")
+                .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_title("Save audio")
+                .set_file_name("response.wav")
+                .add_filter("WAV audio", &["wav"])
+                .save_file()
+        {
+            result = std::fs::copy(audio, path).map(|_| ()).map_err(Into::into);
+        }
+
+        if let Err(e) = result {
+            action = Some(AppAction::ShowErrorDialog {
+                title: "Audio Error".to_string(),
+                message: format!("Failed to play or save the audio.\n\nError: {e}"),
+            });
+        }
+
+        action
+    }
+
+    /// Shows the output sentence by sentence while it is read aloud, highlighting the spoken one.
+    ///
+    /// Clicking a sentence seeks the audio to it.
+    fn show_read_along(
+        ui: &mut egui::Ui,
+        player: &mut AudioPlayer,
+        output: &str,
+        player_command: &str,
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let read_along = ReadAlong::new(output);
+        let duration = player.duration().as_secs_f32();
+        let progress = if duration > 0.0 {
+            player.position().as_secs_f32() / duration
+        } else {
+            0.0
+        };
+        let current = read_along.sentence_at(progress);
+
+        ui.horizontal_wrapped(|ui| {
+            for (idx, sentence) in read_along.sentences.iter().enumerate() {
+                let mut text = RichText::new(sentence.trim());
+
+                if idx == current {
+                    text = text
+                        .background_color(ui.style().visuals.selection.bg_fill)
+                        .color(ui.style().visuals.strong_text_color());
+                }
+
+                if ui
+                    .add(Label::new(text).sense(Sense::click()))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked()
+                    && let Err(e) = player.seek(
+                        Duration::from_secs_f32(read_along.start_of(idx) * duration),
+                        player_command,
+                    )
+                {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Audio Error".to_string(),
+                        message: format!("Failed to seek the audio.\n\nError: {e}"),
+                    });
+                }
+
+                if sentence.ends_with('\n') {
+                    ui.end_row();
+                }
+            }
+        });
+
+        action
+    }
+
+    /// Synthesizes the output of a history entry in the background, caching it under `audio_dir`
+    pub fn synthesize_speech(
+        &mut self,
+        history_idx: usize,
+        audio_dir: PathBuf,
+        tts_command: &str,
+        rt: &runtime::Runtime,
+    ) {
+        let Some(response) = self.history.get(history_idx) else {
+            return;
+        };
+
+        let handle = self.speech_flower.handle();
+        let text = response.output.clone();
+        let tts_command = tts_command.to_string();
+        let output = audio_dir.join(format!(
+            "{}.wav",
+            response.created_at.format("%Y%m%d-%H%M%S%.f")
+        ));
+
+        self.speech_created_at = Some(response.created_at);
+
+        rt.spawn_blocking(move || {
+            handle.activate();
+
+            match tts::synthesize(&tts_command, &text, &output) {
+                Ok(()) => handle.success(output),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Runs a snippet of a history entry in the background, its output being added to the entry
+    pub fn run_snippet(
+        &mut self,
+        history_idx: usize,
+        snippet: Snippet,
+        policy: SnippetPolicy,
+        rt: &runtime::Runtime,
+    ) {
+        let Some(response) = self.history.get(history_idx) else {
+            return;
+        };
+
+        let handle = self.snippet_flower.handle();
+
+        self.snippet_created_at = Some(response.created_at);
+
+        rt.spawn_blocking(move || {
+            handle.activate();
+
+            match policy.run(&snippet) {
+                Ok(run) => handle.success(run),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    /// Shows a model picker, where `none_label` names the choice of leaving the model unset
+    fn show_model_selector(
+        ui: &mut egui::Ui,
+        id_salt: &str,
+        selected: &mut String,
+        models: &[LocalModel],
+        none_label: Option<&str>,
+    ) {
+        let selected_text = match none_label {
+            Some(label) if selected.is_empty() => label.to_string(),
+            _ if selected.is_empty() => "select model".to_string(),
+            _ => selected.clone(),
+        };
+
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if let Some(label) = none_label {
+                    ui.selectable_value(selected, String::new(), label);
+                }
+
+                for model in models {
+                    ui.selectable_value(selected, model.name.clone(), &model.name);
+                }
+            });
+    }
+
+    /// Lets two models answer the input concurrently, then asks the synthesizer to merge their answers
+    pub fn generate_debate(
+        &mut self,
+        input: String,
+        models: DebateModels,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        self.state = PromptState::Generating;
+
+        let response = PromptResponse::new(input.clone(), String::new(), models.label());
+        self.push_response(response);
+
+        let mut prompt = self
+            .template
+            .compose(&self.latest_content(), &self.examples, &input);
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        let handle = self.debate_flower.handle();
+        let ollama_client = ollama_client.clone();
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+
+        self.cancel_stream = Some(cancel_tx);
+
+        rt.spawn(async move {
+            handle.activate();
+            handle.send(format!(
+                "*Waiting for {} and {}…*",
+                models.first.name, models.second.name
+            ));
+
+            let debate_answers = |first: String, second: String| {
+                vec![
+                    DebateAnswer {
+                        model: models.first.name.clone(),
+                        output: first,
+                    },
+                    DebateAnswer {
+                        model: models.second.name.clone(),
+                        output: second,
+                    },
+                ]
+            };
+            let partial = Mutex::new((String::new(), String::new()));
+            let generation = async {
+                tokio::join!(
+                    ollama_client.generate_completion(prompt.clone(), &models.first, |output| {
+                        partial.lock().unwrap().0 = output;
+                    }),
+                    ollama_client.generate_completion(prompt.clone(), &models.second, |output| {
+                        partial.lock().unwrap().1 = output;
+                    }),
+                )
+            };
+
+            let answers = tokio::select! {
+                answers = generation => match answers {
+                    (Ok(first), Ok(second)) => debate_answers(first, second),
+                    (Err(e), _) | (_, Err(e)) => {
+                        handle.error(e.to_string());
+                        return;
+                    }
+                },
+                // Keep whatever the models answered when the generation was stopped
+                _ = &mut cancel_rx => {
+                    let (first, second) = partial.lock().unwrap().clone();
+                    handle.success((debate_answers(first, second), String::new()));
+                    return;
+                }
+            };
+
+            let synthesis = debate::synthesis_prompt(&prompt, &answers);
+            let partial = Mutex::new(String::new());
+            let generation =
+                ollama_client.generate_completion(synthesis, &models.synthesizer, |response| {
+                    *partial.lock().unwrap() = response.clone();
+                    handle.send(response);
+                });
+
+            tokio::select! {
+                result = generation => match result {
+                    Ok(output) => handle.success((answers, output)),
+                    Err(e) => handle.error(e.to_string()),
+                },
+                _ = &mut cancel_rx => handle.success((answers, partial.lock().unwrap().clone())),
+            }
+        });
+    }
+
+    /// Lets all the compared models answer the input concurrently, streaming their answers
+    pub fn generate_comparison(
+        &mut self,
+        input: String,
+        models: Vec<LocalModel>,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        self.state = PromptState::Generating;
+
+        let answers: Vec<ComparedAnswer> = models
+            .iter()
+            .map(|model| ComparedAnswer::new(&model.name))
+            .collect();
+        let mut response =
+            PromptResponse::new(input.clone(), String::new(), compare::label(&models));
+        response.comparison = answers.clone();
+        self.push_response(response);
+
+        let mut prompt = self
+            .template
+            .compose(&self.latest_content(), &self.examples, &input);
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        let handle = self.compare_flower.handle();
+        let ollama_client = ollama_client.clone();
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+
+        self.cancel_stream = Some(cancel_tx);
+
+        rt.spawn(async move {
+            handle.activate();
+
+            let answers = Arc::new(Mutex::new(answers));
+            let mut generations = JoinSet::new();
+
+            for (pos, model) in models.into_iter().enumerate() {
+                let ollama_client = ollama_client.clone();
+                let prompt = prompt.clone();
+                let answers = Arc::clone(&answers);
+
+                generations.spawn(async move {
+                    let timer = Mutex::new(GenerationTimer::default());
+                    let result = ollama_client
+                        .generate_completion(prompt, &model, |output| {
+                            timer.lock().unwrap().chunk();
+                            answers.lock().unwrap()[pos].output = output;
+                        })
+                        .await;
+
+                    let answer = &mut answers.lock().unwrap()[pos];
+
+                    match result {
+                        Ok(output) => {
+                            answer.output = output;
+                            answer.generation = Some(timer.lock().unwrap().finish());
+                        }
+                        Err(e) => answer.error = Some(e.to_string()),
+                    }
+                });
+            }
+
+            // The answers stream in concurrently, their progress is sent at a steady pace
+            let mut progress = tokio::time::interval(Duration::from_millis(COMPARE_PROGRESS_MS));
+
+            loop {
+                tokio::select! {
+                    next = generations.join_next() => {
+                        if next.is_none() {
+                            break;
+                        }
+                    }
+                    _ = progress.tick() => handle.send(answers.lock().unwrap().clone()),
+                    // The answers keep what streamed in until then
+                    _ = &mut cancel_rx => {
+                        generations.abort_all();
+                        break;
+                    }
+                }
+            }
+
+            let answers = answers.lock().unwrap().clone();
+
+            match answers.iter().find_map(|answer| answer.error.as_ref()) {
+                Some(error) if answers.iter().all(|answer| answer.error.is_some()) => {
+                    handle.error(error.clone());
+                }
+                _ => handle.success(answers),
+            }
+        });
+    }
+
+    fn poll_compare_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+        let mut is_done = false;
+
+        self.compare_flower
+            .extract(|answers| {
+                if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                    response.output = compare::answers_overview(&answers);
+                    response.comparison = answers;
+                }
+            })
+            .finalize(|result| {
+                match result {
+                    Ok(answers) => {
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = compare::answers_overview(&answers);
+                            response.comparison = answers;
+                        }
+
+                        is_done = true;
+                    }
+                    Err(Compact::Suppose(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Comparison Error".to_string(),
+                            message: format!("None of the models answered.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Panicked(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Comparison Error".to_string(),
+                            message: format!(
+                                "An unexpected error occurred while comparing the models.\n\nError: {e}"
+                            ),
+                        });
+                    }
+                }
+
+                self.state = PromptState::Idle;
+            });
+
+        if is_done {
+            self.hold_for_review();
+        }
+
+        action
+    }
+
+    /// Token counts and durations the server reported for the response
+    fn show_metrics(ui: &mut egui::Ui, metrics: &ModelMetrics) {
+        egui::Grid::new("response_metrics")
+            .num_columns(2)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                let rows = [
+                    ("Prompt tokens", metrics.prompt_eval_count.to_string()),
+                    ("Output tokens", metrics.eval_count.to_string()),
+                    (
+                        "Output speed",
+                        metrics
+                            .eval_rate()
+                            .map_or("-".to_string(), |rate| format!("{rate:.1} tok/s")),
+                    ),
+                    (
+                        "Total time",
+                        format!("{:.2} s", history::secs(metrics.total_duration)),
+                    ),
+                    (
+                        "Model loading",
+                        format!("{:.2} s", history::secs(metrics.load_duration)),
+                    ),
+                    (
+                        "Prompt evaluation",
+                        format!("{:.2} s", history::secs(metrics.prompt_eval_duration)),
+                    ),
+                    (
+                        "Generation",
+                        format!("{:.2} s", history::secs(metrics.eval_duration)),
+                    ),
+                ];
+
+                for (label, value) in rows {
+                    ui.label(RichText::new(label).weak());
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_comparison(
+        ui: &mut egui::Ui,
+        commonmark_cache: &mut CommonMarkCache,
+        answers: &[ComparedAnswer],
+    ) {
+        ui.columns(answers.len(), |columns| {
+            for (ui, answer) in columns.iter_mut().zip(answers) {
+                ui.horizontal(|ui| {
+                    ui.add(Icon::Model);
+                    ui.label(RichText::new(&answer.model).strong());
+                });
+
+                if let Some(generation) = &answer.generation {
+                    ui.label(RichText::new(generation.summary()).small().weak());
+                }
+
+                match &answer.error {
+                    Some(error) => {
+                        ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                    }
+                    None if answer.output.is_empty() && !answer.is_finished() => {
+                        ui.spinner();
+                    }
+                    None => {
+                        CommonMarkViewer::new().show(ui, commonmark_cache, &answer.output);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Samples the input several times with different seeds, then lets the judge pick the best answer
+    pub fn generate_ensemble(
+        &mut self,
+        input: String,
+        model: &LocalModel,
+        judge: Option<LocalModel>,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        self.state = PromptState::Generating;
+
+        let samples = self.ensemble.samples;
+        let model_name = match &judge {
+            Some(judge) => format!("{} ×{samples} → {}", model.name, judge.name),
+            None => format!("{} ×{samples}", model.name),
+        };
+        let response = PromptResponse::new(input.clone(), String::new(), model_name);
+        self.push_response(response);
+
+        let mut prompt = self
+            .template
+            .compose(&self.latest_content(), &self.examples, &input);
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        let handle = self.ensemble_flower.handle();
+        let ollama_client = ollama_client.clone();
+        let model = model.clone();
+        let base_seed = Local::now().timestamp_subsec_micros() as i32;
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+
+        self.cancel_stream = Some(cancel_tx);
+
+        rt.spawn(async move {
+            handle.activate();
+
+            let mut candidates = Vec::with_capacity(samples);
+
+            for sample in 0..samples {
+                handle.send(format!("*Sampling candidate {} of {samples}…*", sample + 1));
+
+                let seed = base_seed.wrapping_add(sample as i32);
+                let generation = ollama_client.generate_completion_with_seed(
+                    prompt.clone(),
+                    &model,
+                    Some(seed),
+                    |_| {},
+                );
+
+                let result = tokio::select! {
+                    result = generation => result,
+                    // Keep the candidates sampled so far when the generation was stopped
+                    _ = &mut cancel_rx => {
+                        handle.success((candidates, None));
+                        return;
+                    }
+                };
+
+                match result {
+                    Ok(output) => candidates.push(EnsembleCandidate { seed, output }),
+                    Err(e) => {
+                        handle.error(e.to_string());
+                        return;
+                    }
+                }
+            }
+
+            let Some(judge) = judge else {
+                handle.success((candidates, None));
+                return;
+            };
+
+            handle.send(format!("*Judging candidates with {}…*", judge.name));
+
+            let judging = ollama_client.generate_completion(
+                ensemble::judge_prompt(&prompt, &candidates),
+                &judge,
+                |_| {},
+            );
+
+            tokio::select! {
+                result = judging => match result {
+                    Ok(reply) => {
+                        let choice = ensemble::parse_judge_choice(&reply, candidates.len());
+                        handle.success((candidates, choice));
+                    }
+                    Err(e) => handle.error(e.to_string()),
+                },
+                // The candidates are shown unjudged
+                _ = &mut cancel_rx => handle.success((candidates, None)),
+            }
+        });
+    }
+
+    fn poll_ensemble_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+        let mut is_done = false;
+
+        self.ensemble_flower
+            .extract(|output| {
+                if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                    response.output = output;
+                }
+            })
+            .finalize(|result| {
+                match result {
+                    Ok((candidates, choice)) => {
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = match choice {
+                                Some(choice) => candidates[choice].output.clone(),
+                                None => ensemble::candidates_overview(&candidates),
+                            };
+                            response.candidates = candidates;
+                            response.selected_candidate = choice;
+                        }
+
+                        is_done = true;
+                    }
+                    Err(Compact::Suppose(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Ensemble Error".to_string(),
+                            message: format!("Failed to sample the candidates.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Panicked(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Ensemble Error".to_string(),
+                            message: format!(
+                                "An unexpected error occurred while sampling the candidates.\n\nError: {e}"
+                            ),
+                        });
+                    }
+                }
+
+                self.state = PromptState::Idle;
+            });
+
+        if is_done {
+            self.hold_for_review();
+        }
+
+        action
+    }
+
+    /// Scores the given responses one by one with the judge, or all unscored ones when none are given
+    pub fn score_responses(
+        &mut self,
+        history_idx: Option<usize>,
+        judge: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        let responses = self
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(idx, response)| match history_idx {
+                Some(history_idx) => *idx == history_idx,
+                None => response.score.is_none(),
+            })
+            .filter(|(_, response)| !response.output.is_empty())
+            .map(|(_, response)| {
+                (
+                    response.created_at,
+                    judge::scoring_prompt(&response.input, &response.output, &self.judge_rubric),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if responses.is_empty() {
+            return;
+        }
+
+        self.scoring_queue = responses
+            .iter()
+            .map(|(created_at, _)| *created_at)
+            .collect();
+
+        let handle = self.score_flower.handle();
+        let ollama_client = ollama_client.clone();
+        let judge = judge.clone();
+
+        rt.spawn(async move {
+            handle.activate();
+
+            let mut failures = Vec::new();
+
+            for (created_at, prompt) in responses {
+                let score = ollama_client
+                    .generate_completion(prompt, &judge, |_| {})
+                    .await
+                    .and_then(|reply| judge::parse_score(&reply, &judge.name));
+
+                match score {
+                    Ok(score) => handle.send((created_at, score)),
+                    Err(e) => failures.push(e.to_string()),
+                }
+            }
+
+            if failures.is_empty() {
+                handle.success(());
+            } else {
+                handle.error(failures.join("\n"));
+            }
+        });
+    }
+
+    fn poll_score_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+
+        self.score_flower
+            .extract(|(created_at, score)| {
+                self.scoring_queue.retain(|queued| *queued != created_at);
+
+                if let Some(response) = self
+                    .history
+                    .iter_mut()
+                    .find(|response| response.created_at == created_at)
+                {
+                    response.score = Some(score);
+                }
+            })
+            .finalize(|result| {
+                match result {
+                    Ok(()) => {}
+                    Err(Compact::Suppose(e)) => {
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Judge Error".to_string(),
+                            message: format!("Failed to score some of the responses.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Panicked(e)) => {
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Judge Error".to_string(),
+                            message: format!(
+                                "An unexpected error occurred while scoring the responses.\n\nError: {e}"
+                            ),
+                        });
+                    }
+                }
+
+                self.scoring_queue.clear();
+            });
+
+        action
+    }
+
+    fn poll_debate_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+        let mut is_done = false;
+
+        self.debate_flower
+            .extract(|output| {
+                if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                    response.output = output;
+                }
+            })
+            .finalize(|result| {
+                match result {
+                    Ok((answers, output)) => {
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.debate = answers;
+                            response.output = output;
+                        }
+
+                        is_done = true;
+                    }
+                    Err(Compact::Suppose(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Debate Error".to_string(),
+                            message: format!("Failed to run the debate.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Panicked(e)) => {
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Debate Error".to_string(),
+                            message: format!("An unexpected error occurred while running the debate.\n\nError: {e}"),
+                        });
+                    }
+                }
+
+                self.state = PromptState::Idle;
+            });
+
+        if is_done {
+            self.hold_for_review();
+        }
+
+        action
+    }
+
+    fn poll_speech_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+
+        self.speech_flower.extract(|()| {}).finalize(|result| {
+            let created_at = self.speech_created_at.take();
+
+            match result {
+                Ok(path) => {
+                    if let Some(response) = self
+                        .history
+                        .iter_mut()
+                        .find(|response| Some(response.created_at) == created_at)
+                    {
+                        response.audio = Some(path);
+                    }
+                }
+                Err(Compact::Suppose(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Speech Synthesis Error".to_string(),
+                        message: format!("Failed to synthesize speech. Please check the text-to-speech command in the settings.\n\nError: {e}"),
+                    });
+                }
+                Err(Compact::Panicked(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Speech Synthesis Error".to_string(),
+                        message: format!("An unexpected error occurred while synthesizing speech.\n\nError: {e}"),
+                    });
+                }
+            }
+        });
+
+        action
+    }
+
+    fn poll_snippet_flower(&mut self) -> Option<AppAction> {
+        let mut action = None;
+
+        self.snippet_flower.extract(|()| {}).finalize(|result| {
+            let created_at = self.snippet_created_at.take();
+
+            match result {
+                Ok(run) => {
+                    if let Some(response) = self
+                        .history
+                        .iter_mut()
+                        .find(|response| Some(response.created_at) == created_at)
+                    {
+                        response.snippet_runs.push(run);
+                    }
+                }
+                Err(Compact::Suppose(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Snippet Error".to_string(),
+                        message: format!("Failed to run the snippet. Please check the snippet settings.\n\nError: {e}"),
+                    });
+                }
+                Err(Compact::Panicked(e)) => {
+                    action = Some(AppAction::ShowErrorDialog {
+                        title: "Snippet Error".to_string(),
+                        message: format!("An unexpected error occurred while running the snippet.\n\nError: {e}"),
+                    });
+                }
+            }
+        });
+
+        action
+    }
+
+    pub fn generate_response(
+        &mut self,
+        input: String,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        self.state = PromptState::Generating;
+
+        let response = PromptResponse::new(input.clone(), String::new(), local_model.name.clone());
+        self.push_response(response);
+        self.critique_pending = self.critique_and_revise;
+
+        self.ask_ollama(input, local_model, rt, ollama_client.clone());
+    }
+
+    /// Shows the estimated tokens of the request the input would send, warning when it likely
+    /// doesn't fit into the context window of the model
+    fn show_token_estimate(&mut self, ui: &mut egui::Ui, context_window: Option<u64>) {
+        // Taken out for the request text to be made from the prompt while it's counted
+        let mut estimate = std::mem::take(&mut self.token_estimate);
+        let tokens = estimate.tokens(self.request_key(), Instant::now(), || self.request_text());
+
+        if estimate.is_pending() {
+            ui.ctx().request_repaint_after(tokens::RECOUNT_DELAY);
+        }
+
+        self.token_estimate = estimate;
+
+        let label = match context_window {
+            Some(context_window) if tokens::exceeds(tokens, context_window) => RichText::new(
+                format!(
+                    "⚠ ≈ {tokens} of {context_window} tokens, the start of the request will likely be cut off"
+                ),
+            )
+            .color(ui.visuals().warn_fg_color),
+            Some(context_window) => {
+                RichText::new(format!("≈ {tokens} of {context_window} tokens")).weak()
+            }
+            None => RichText::new(format!("≈ {tokens} tokens")).weak(),
+        };
+
+        ui.label(label.small()).on_hover_text(
+            "Estimated tokens of the prompt, the input and the conversation sent along",
+        );
+    }
+
+    /// Underlines the misspelled words of the input, a right-click on one of them offers its
+    /// suggestions and to add it to the dictionary
+    fn show_spelling(
+        &mut self,
+        ui: &mut egui::Ui,
+        output: &egui::text_edit::TextEditOutput,
+        spell_checker: &SpellChecker,
+        added_words: &[String],
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let misspelled = spell_checker.misspelled(&self.new_input, added_words);
+        let galley = &output.galley;
+        let char_index = |byte: usize| self.new_input[..byte].chars().count();
+        let painter = ui.painter().with_clip_rect(output.text_clip_rect);
+        let stroke = Stroke::new(1.0, ui.visuals().error_fg_color);
+
+        for range in &misspelled {
+            let start = galley.pos_from_ccursor(egui::text::CCursor::new(char_index(range.start)));
+            let end = galley.pos_from_ccursor(egui::text::CCursor::new(char_index(range.end)));
+
+            // Words wrapped over two rows are left without an underline
+            if start.min.y == end.min.y {
+                let offset = output.galley_pos.to_vec2();
+
+                painter.add(egui::Shape::line(
+                    squiggle(
+                        start.left() + offset.x,
+                        end.left() + offset.x,
+                        start.bottom() + offset.y,
+                    ),
+                    stroke,
+                ));
+            }
+        }
+
+        if output.response.secondary_clicked() {
+            self.spelling_menu = output
+                .response
+                .interact_pointer_pos()
+                .and_then(|pos| {
+                    let char_idx = galley
+                        .cursor_from_pos(pos - output.galley_pos)
+                        .ccursor
+                        .index;
+                    let byte = self
+                        .new_input
+                        .char_indices()
+                        .nth(char_idx)
+                        .map_or(self.new_input.len(), |(byte, _)| byte);
+
+                    misspelled
+                        .into_iter()
+                        .find(|range| range.start <= byte && byte <= range.end)
+                })
+                .map(|range| {
+                    let word = self.new_input[range.clone()].to_string();
+
+                    SpellingMenu {
+                        suggestions: spell_checker.suggest(&word),
+                        range,
+                        word,
+                    }
+                });
+        }
+
+        if self.spelling_menu.is_some() {
+            output.response.context_menu(|ui| {
+                let Some(menu) = &self.spelling_menu else {
+                    return;
+                };
+                // The input may have changed since the menu was opened
+                let still_there = self.new_input.get(menu.range.clone()) == Some(&menu.word);
+                let mut replacement = None;
+
+                if menu.suggestions.is_empty() {
+                    ui.label(RichText::new("No suggestions").weak());
+                }
+
+                for suggestion in &menu.suggestions {
+                    if ui
+                        .add_enabled(still_there, egui::Button::new(suggestion))
+                        .clicked()
+                    {
+                        replacement = Some(suggestion.clone());
+                        ui.close_menu();
+                    }
+                }
+
+                ui.separator();
+
+                if ui
+                    .button(format!("Add \"{}\" to the dictionary", menu.word))
+                    .clicked()
+                {
+                    action = Some(AppAction::AddSpellingWord(menu.word.clone()));
+                    ui.close_menu();
+                }
+
+                if let Some(replacement) = replacement {
+                    self.new_input
+                        .replace_range(menu.range.clone(), &replacement);
+                }
+            });
+        }
+
+        action
+    }
+
+    /// Fields for the `{{name}}` placeholders of the content, all of them needed to send the input
+    fn show_variables_form(&mut self, ui: &mut egui::Ui, variables: &[String], enabled: bool) {
+        egui::Grid::new("prompt_variables")
+            .num_columns(2)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for name in variables {
+                    let value = self.variables.entry(name.clone()).or_default();
+
+                    ui.label(RichText::new(format!("{{{{{name}}}}}")).monospace());
+                    ui.add_enabled(
+                        enabled,
+                        egui::TextEdit::singleline(value)
+                            .hint_text("Required to send the input")
+                            .desired_width(240.0),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Flags a generation running longer than the configured limit, offering ways out of it
+    fn show_watchdog(
+        &mut self,
+        ui: &mut egui::Ui,
+        idx: usize,
+        settings: &Settings,
+        models: &[LocalModel],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if !self.state.is_generating() || settings.generation_watchdog_secs == 0 {
+            return None;
+        }
+
+        let response = self
+            .history
+            .iter()
+            .find(|response| self.is_streamed_to(response))?;
+
+        let since = self
+            .watchdog_snoozed_at
+            .filter(|snoozed_at| *snoozed_at > response.requested_at)
+            .unwrap_or(response.requested_at);
+
+        if since.elapsed() < Duration::from_secs(settings.generation_watchdog_secs) {
+            return None;
+        }
+
+        let smaller_model = ollama::smaller_model(models, &response.local_model_name);
+
+        Frame::group(ui.style())
+            .stroke(Stroke::new(1.0, ui.style().visuals.warn_fg_color))
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "⏱ {} has been generating for {}",
+                            response.local_model_name,
+                            format_duration(response.requested_at.elapsed().as_secs_f32())
+                        ))
+                        .color(ui.style().visuals.warn_fg_color),
+                    );
+
+                    if ui
+                        .add(IconButton::new(Icon::Stop).text("Cancel").frame(true))
+                        .clicked()
+                    {
+                        action = Some(AppAction::CancelGeneration { idx });
+                    }
+
+                    if ui.button("Keep waiting").clicked() {
+                        self.watchdog_snoozed_at = Some(Instant::now());
+                    }
+
+                    if let Some(model) = smaller_model
+                        && ui
+                            .button(format!("Switch to {}", model.name))
+                            .on_hover_text("Stop and send the same input to a smaller model")
+                            .clicked()
+                    {
+                        action = Some(AppAction::SwitchToSmallerModel {
+                            idx,
+                            model: model.clone(),
+                        });
+                    }
+                });
+            });
+
+        action
+    }
+
+    /// Takes the next queued input once the previous generation is over, replays excepted as they
+    /// pace their steps themselves. A resubmitted input comes with the model to send it to.
+    pub fn take_queued_input(&mut self) -> Option<(String, Option<LocalModel>)> {
+        if self.is_busy() {
+            return None;
+        }
+
+        if let Some((input, model)) = self.resubmit.take() {
+            return Some((input, Some(model)));
+        }
+
+        self.next_benchmark_input()
+            .or_else(|| self.next_watched_input())
+            .or_else(|| self.mail_digest.as_mut()?.next_input())
+            .or_else(|| self.briefing.as_mut()?.next_input())
+            .map(|input| (input, None))
+    }
+
+    /// Collects what the running tasks of the prompt produced since the last frame
+    pub fn poll_generations(&mut self, idx: usize) -> Option<AppAction> {
+        let mut action = None;
+
+        if self.ask_flower.is_active() {
+            assign_if_some!(action, self.poll_ask_flower(idx));
+        }
+
+        if self.speech_flower.is_active() {
+            assign_if_some!(action, self.poll_speech_flower());
+        }
+
+        if self.snippet_flower.is_active() {
+            assign_if_some!(action, self.poll_snippet_flower());
+        }
+
+        if self.debate_flower.is_active() {
+            assign_if_some!(action, self.poll_debate_flower());
+        }
+
+        if self.ensemble_flower.is_active() {
+            assign_if_some!(action, self.poll_ensemble_flower());
+        }
+
+        if self.compare_flower.is_active() {
+            assign_if_some!(action, self.poll_compare_flower());
+        }
+
+        if self.score_flower.is_active() {
+            assign_if_some!(action, self.poll_score_flower());
+        }
+
+        if self.feed_flower.is_active() {
+            assign_if_some!(action, self.poll_feed_flower());
+        }
+
+        if self.chat_title_flower.is_active() {
+            self.poll_chat_title_flower();
+        }
+
+        action
+    }
+
+    /// Whether a generation is running or its result is still to be collected
+    fn is_busy(&self) -> bool {
+        self.state.is_generating()
+            || self.ask_flower.is_active()
+            || self.debate_flower.is_active()
+            || self.ensemble_flower.is_active()
+            || self.compare_flower.is_active()
+    }
+
+    /// Keeps the response just generated as a draft and asks the model to critique and revise it
+    pub fn critique_response(
+        &mut self,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) else {
+            return;
+        };
+
+        let draft = std::mem::take(&mut response.output);
+        let content = compose::fill_variables(&self.content, &response.variables);
+        let mut prompt = self
+            .template
+            .compose(&content, &self.examples, &response.input);
+
+        response.draft = Some(draft.clone());
+        response.local_model_name = local_model.name.clone();
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        self.state = PromptState::Generating;
+        self.revising = true;
+
+        self.ask_ollama_composed(
+            prompt.critique(&draft),
+            None,
+            local_model,
+            rt,
+            ollama_client.clone(),
+        );
+    }
+
+    /// Asks the model to continue the latest response from where it stopped, appending to it
+    pub fn continue_response(
+        &mut self,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        let Some(response) = self
+            .history
+            .front()
+            .filter(|response| response.can_continue())
+        else {
+            return;
+        };
+
+        let output = response.output.clone();
+        let content = compose::fill_variables(&self.content, &response.variables);
+        let mut exchanges = self.conversation_exchanges(0);
+
+        // Outside of a conversation the response is the only exchange
+        if exchanges.is_empty() {
+            exchanges.push(Exchange {
+                input: response.input.clone(),
+                output: output.clone(),
+            });
+        }
+
+        let mut prompt = self
+            .template
+            .compose(&content, &self.examples, &exchanges[0].input);
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        let conversation = prompt
+            .clone()
+            .continue_conversation(&exchanges, compose::CONTINUE_INSTRUCTION);
+
+        self.streaming_to = self.history.front().map(|response| response.created_at);
+
+        if let Some(response) = self.history.get_mut(0) {
+            response.cancelled = false;
+            response.local_model_name = local_model.name.clone();
+        }
+
+        self.state = PromptState::Generating;
+        self.continued_output = Some(output);
+
+        self.ask_ollama_composed(
+            prompt,
+            Some(conversation),
+            local_model,
+            rt,
+            ollama_client.clone(),
+        );
+    }
+
+    /// Replaces the response just generated with one under an instruction to avoid the banned phrases
+    pub fn regenerate_avoiding_banned_phrases(
+        &mut self,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) else {
+            return;
+        };
+
+        let banned_phrases = compose::find_banned_phrases(&response.output, &self.banned_phrases);
+        let input = format!(
+            "{}\n\n{}",
+            response.input,
+            compose::banned_phrases_instruction(&banned_phrases)
+        );
+
+        response.output.clear();
+        response.local_model_name = local_model.name.clone();
+
+        self.state = PromptState::Generating;
+        self.banned_phrases_retried = true;
+
+        self.ask_ollama(input, local_model, rt, ollama_client.clone());
+    }
+
+    pub fn generate_images(
+        &mut self,
+        input: String,
+        images_dir: PathBuf,
+        rt: &runtime::Runtime,
+        stable_diffusion_client: &StableDiffusionClient,
+    ) {
+        self.state = PromptState::Generating;
+
+        let response =
+            PromptResponse::new(input.clone(), String::new(), "stable-diffusion".to_string());
+        self.push_response(response);
+
+        let handle = self.ask_flower.handle();
+        let prompt = format!("{}, {}", self.latest_content(), input);
+        let stable_diffusion_client = stable_diffusion_client.clone();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+
+        self.cancel_stream = Some(cancel_tx);
+
+        rt.spawn(async move {
+            handle.activate();
+
+            // Dropping the request on cancellation leaves the images it would have saved unwritten
+            tokio::select! {
+                result = stable_diffusion_client.generate_images(&prompt, &images_dir) => match result {
+                    Ok(paths) => handle.success((
+                        Answer {
+                            output: paths
+                                .iter()
+                                .map(|path| format!("![generated image](<{}>)", path.display()))
+                                .collect::<Vec<_>>()
+                                .join("\n\n"),
+                            ..Default::default()
+                        },
+                        None,
+                    )),
+                    Err(e) => handle.error(e.to_string()),
+                },
+                _ = cancel_rx => handle.success((
+                    Answer {
+                        stop_reason: Some(StopReason::Cancelled),
+                        ..Default::default()
+                    },
+                    None,
+                )),
+            }
+        });
+    }
+
+    fn ask_ollama(
+        &mut self,
+        question: String,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: OllamaClient,
+    ) {
+        // The latest response is the one being generated
+        let exchanges = self.conversation_exchanges(1);
+        let first_input = exchanges
+            .first()
+            .map_or(question.as_str(), |exchange| exchange.input.as_str());
+
+        let mut prompt = self
+            .template
+            .compose(&self.latest_content(), &self.examples, first_input);
+
+        if let Some(style_guide) = self.refresh_style_guide() {
+            prompt = prompt.with_style_guide(style_guide);
+        }
+
+        let conversation = (!exchanges.is_empty())
+            .then(|| prompt.clone().continue_conversation(&exchanges, &question));
+
+        self.ask_ollama_composed(prompt, conversation, local_model, rt, ollama_client);
+    }
+
+    fn ask_ollama_composed(
+        &mut self,
+        prompt: ComposedPrompt,
+        conversation: Option<Vec<ChatMessage>>,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: OllamaClient,
+    ) {
+        let handle = self.ask_flower.handle();
+        let local_model = local_model.clone();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+
+        self.cancel_stream = Some(cancel_tx);
+
+        rt.spawn(async move {
+            handle.activate();
+
+            let partial = Mutex::new(String::new());
+            let timer = Mutex::new(GenerationTimer::default());
+            let on_next = |response: String| {
+                timer.lock().unwrap().chunk();
+                *partial.lock().unwrap() = response.clone();
+                handle.send(response);
+            };
+            let on_retry = |retry, max_retries| {
+                handle.send(format!("*Retrying ({retry}/{max_retries})…*"));
+            };
+            let generation = ollama_client.generate_answer(
+                prompt,
+                conversation,
+                &local_model,
+                on_next,
+                on_retry,
+            );
+
+            // Dropping the stream on cancellation closes the request, which stops the model
+            tokio::select! {
+                result = generation => match result {
+                    Ok(answer) => handle.success((answer, Some(timer.lock().unwrap().finish()))),
+                    Err(e) => {
+                        let partial = partial.lock().unwrap().clone();
+
+                        if partial.is_empty() {
+                            handle.error(e.to_string());
+                        } else {
+                            // Keep what arrived before the stream failed
+                            handle.success((
+                                Answer {
+                                    output: partial,
+                                    stop_reason: Some(StopReason::Error(e.to_string())),
+                                    ..Default::default()
+                                },
+                                Some(timer.lock().unwrap().finish()),
+                            ));
+                        }
+                    }
+                },
+                _ = cancel_rx => handle.success((
+                    Answer {
+                        output: partial.lock().unwrap().clone(),
+                        stop_reason: Some(StopReason::Cancelled),
+                        ..Default::default()
+                    },
+                    Some(timer.lock().unwrap().finish()),
+                )),
+            }
+        });
+    }
+
+    fn poll_ask_flower(&mut self, idx: usize) -> Option<AppAction> {
+        let mut action = None;
+        let mut is_done = false;
+
+        self.ask_flower
+            .extract(|output| {
+                if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                    response.output = match &self.continued_output {
+                        Some(continued) => format!("{continued}{output}"),
+                        None => output,
+                    };
+                }
+            })
+            .finalize(|result| {
+                match result {
+                    Ok((answer, _)) if self.continued_output.is_some() => {
+                        let continued = self.continued_output.take().unwrap_or_default();
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = format!("{continued}{}", answer.output);
+                            response.stop_reason = answer.stop_reason;
+                        }
+                    }
+                    Err(Compact::Suppose(e) | Compact::Panicked(e))
+                        if self.continued_output.is_some() =>
+                    {
+                        // Keep the response as it was before continuing it
+                        let continued = self.continued_output.take().unwrap_or_default();
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = continued;
+                        }
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Response Continuation Error".to_string(),
+                            message: format!("Failed to continue the response, it is kept as it was.\n\nError: {e}"),
+                        });
+                    }
+                    Ok((answer, generation)) if self.critique_pending => {
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = answer.output;
+                            response.generation = generation;
+                            response.metrics = answer.metrics;
+                            response.stop_reason = answer.stop_reason;
+                        }
+
+                        self.critique_pending = false;
+                        action = Some(AppAction::CritiquePromptResponse(idx));
+                    }
+                    Ok((answer, generation)) => {
+                        let retry = self.kind == PromptKind::Text
+                            && !Self::streamed_response(&mut self.history, self.streaming_to)
+                                .is_some_and(|response| response.cancelled)
+                            && self.regenerate_on_banned_phrases
+                            && !self.banned_phrases_retried
+                            && !compose::find_banned_phrases(&answer.output, &self.banned_phrases)
+                                .is_empty();
+
+                        if let Some(StopReason::Error(e)) = &answer.stop_reason {
+                            action = Some(AppAction::ShowErrorDialog {
+                                title: "Response Generation Error".to_string(),
+                                message: format!("The generation failed midway, the partial response is kept.\n\nError: {e}"),
+                            });
+                        }
+
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = answer.output;
+                            response.generation = generation;
+                            response.metrics = answer.metrics;
+                            response.stop_reason = answer.stop_reason;
+                        }
+
+                        // Only retry once so that a stubborn model doesn't loop forever
+                        self.banned_phrases_retried = false;
+
+                        if retry {
+                            action = Some(AppAction::AvoidBannedPhrases(idx));
+                        } else {
+                            is_done = true;
+                        }
+                    }
+                    Err(Compact::Suppose(e) | Compact::Panicked(e)) if self.revising => {
+                        // Keep the first pass when the revision fails
+                        if let Some(response) = Self::streamed_response(&mut self.history, self.streaming_to) {
+                            response.output = response.draft.take().unwrap_or_default();
+                        }
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Response Revision Error".to_string(),
+                            message: format!("Failed to revise the response, the first draft is kept.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Suppose(e)) => {
+                        // Remove the failed response from history
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Response Generation Error".to_string(),
+                            message: format!("Failed to generate response from Ollama. Please check your connection and try again.\n\nError: {e}"),
+                        });
+                    }
+                    Err(Compact::Panicked(e)) => {
+                        // Remove the failed response from history
+                        Self::discard_failed_response(
+                            &mut self.history,
+                            &mut self.replaced_response,
+                            self.streaming_to,
+                        );
+
+                        action = Some(AppAction::ShowErrorDialog {
+                            title: "Response Generation Error".to_string(),
+                            message: format!("An unexpected error occurred while generating the response.\n\nError: {e}"),
+                        });
+                    }
+                }
+
+                self.state = PromptState::Idle;
+                self.revising = false;
+                self.new_input.clear();
+            });
+
+        if is_done {
+            self.hold_for_review();
+
+            if action.is_none() && self.untitled_chat_exchange().is_some() {
+                action = Some(AppAction::GenerateChatTitle(idx));
+            }
+        }
+
+        action
+    }
+
+    /// Lists the conversations of the conversation mode, to go on with one, start a new one, or
+    /// rename, archive or delete them
+    fn show_chat_sessions(&mut self, ui: &mut egui::Ui, idx: usize) -> Option<AppAction> {
+        let mut action = None;
+        let is_generating = self.state.is_generating();
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.strong("Conversations");
+
+            if ui
+                .add_enabled(!is_generating, IconButton::new(Icon::Add))
+                .on_hover_text("Start a new conversation, the next input is sent without context")
+                .clicked()
+            {
+                action = Some(AppAction::NewChatSession(idx));
+            }
+        });
+        ui.checkbox(&mut self.show_archived_chats, "Show archived");
+        ui.separator();
+
+        let mut select = None;
+        let mut rename = None;
+        let mut archive = None;
+
+        ScrollArea::vertical()
+            .id_salt(format!("chat_sessions_scroll_{idx}"))
+            .show(ui, |ui| {
+                for session in self.chat_sessions.iter().rev() {
+                    if session.archived && !self.show_archived_chats {
+                        continue;
+                    }
+
+                    if let Some((created_at, title)) = self.renaming_chat.as_mut()
+                        && *created_at == session.created_at
+                    {
+                        let response = ui.text_edit_singleline(title);
+
+                        if response.lost_focus() {
+                            rename = Some((session.created_at, title.clone()));
+                        } else {
+                            response.request_focus();
+                        }
+
+                        continue;
+                    }
+
+                    let is_active = self.active_chat == Some(session.created_at);
+                    let title = if session.archived {
+                        RichText::new(&session.title).weak()
+                    } else {
+                        RichText::new(&session.title)
+                    };
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(
+                                !is_generating,
+                                egui::SelectableLabel::new(is_active, title),
+                            )
+                            .on_hover_text(session.created_at.format("%Y-%m-%d %H:%M").to_string())
+                            .clicked()
+                        {
+                            select = Some(session.created_at);
+                        }
+
+                        if ui
+                            .add(IconButton::new(Icon::Edit))
+                            .on_hover_text("Rename")
+                            .clicked()
+                        {
+                            self.renaming_chat = Some((session.created_at, session.title.clone()));
+                        }
+
+                        if ui
+                            .add_enabled(!is_generating, IconButton::new(Icon::Remove))
+                            .on_hover_text("Delete the conversation and its responses")
+                            .clicked()
+                        {
+                            action = Some(AppAction::DeleteChatSession {
+                                idx,
+                                created_at: session.created_at,
+                            });
+                        }
+                    })
+                    .response
+                    .context_menu(|ui| {
+                        let label = if session.archived {
+                            "Unarchive"
+                        } else {
+                            "Archive"
+                        };
+
+                        if ui
+                            .add_enabled(!is_generating, egui::Button::new(label))
+                            .clicked()
+                        {
+                            archive = Some((session.created_at, !session.archived));
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+
+        if let Some(created_at) = select {
+            self.select_chat_session(created_at);
+        }
+
+        if let Some((created_at, title)) = rename {
+            self.renaming_chat = None;
+            self.rename_chat_session(created_at, &title);
+        }
+
+        if let Some((created_at, archived)) = archive {
+            self.archive_chat_session(created_at, archived);
+        }
+
+        action
+    }
+
+    /// Names the active conversation from its first exchange with a quick model call, in the
+    /// background
+    pub fn generate_chat_title(
+        &mut self,
+        local_model: &LocalModel,
+        rt: &runtime::Runtime,
+        ollama_client: &OllamaClient,
+    ) {
+        let Some((created_at, exchange)) = self.untitled_chat_exchange() else {
+            return;
+        };
+        let handle = self.chat_title_flower.handle();
+        let local_model = local_model.clone();
+        let ollama_client = ollama_client.clone();
+
+        rt.spawn(async move {
+            handle.activate();
+
+            match ollama_client
+                .generate_completion(chat::title_prompt(&exchange), &local_model, |_| {})
+                .await
+            {
+                Ok(output) => handle.success((created_at, output)),
+                Err(e) => handle.error(e.to_string()),
+            }
+        });
+    }
+
+    fn poll_chat_title_flower(&mut self) {
+        let mut generated = None;
+
+        self.chat_title_flower
+            .extract(|()| {})
+            .finalize(|result| match result {
+                Ok((created_at, output)) => {
+                    generated = chat::clean_title(&output).map(|title| (created_at, title));
+                }
+                // The conversation keeps its title, it can still be renamed
+                Err(Compact::Suppose(e) | Compact::Panicked(e)) => {
+                    log::warn!("Failed to generate the title of a conversation: {e}");
+                }
+            });
+
+        if let Some((created_at, title)) = generated {
+            self.set_generated_chat_title(created_at, title);
+        }
+    }
+}
+
+/// A tag as a small rounded button
+pub fn tag_chip(ui: &mut egui::Ui, tag: &str) -> egui::Response {
+    ui.add(
+        egui::Button::new(RichText::new(format!("#{tag}")).small())
+            .small()
+            .corner_radius(8.0)
+            .fill(ui.visuals().faint_bg_color),
+    )
+}
+
+/// Zigzag points from `left` to `right` along `bottom`, underlining a misspelled word
+fn squiggle(left: f32, right: f32, bottom: f32) -> Vec<egui::Pos2> {
+    const STEP: f32 = 2.0;
+
+    let steps = ((right - left) / STEP).ceil().max(1.0) as usize;
+
+    (0..=steps)
+        .map(|step| {
+            let x = (left + step as f32 * STEP).min(right);
+            let y = if step % 2 == 0 { bottom } else { bottom - STEP };
+
+            egui::pos2(x, y)
+        })
+        .collect()
+}
+
+fn format_duration(secs: f32) -> String {
+    let secs = secs as u32;
+
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+pub struct CopyFeedback {
+    response_created_at: DateTime<Local>,
+    triggered_at: Instant,
+}
+
+impl CopyFeedback {
+    fn new(response_created_at: DateTime<Local>) -> Self {
+        Self {
+            response_created_at,
+            triggered_at: Instant::now(),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.triggered_at.elapsed() <= Duration::from_millis(COPY_FEEDBACK_DURATION_MS)
+    }
+}
@@ -0,0 +1,42 @@
+use std::cmp::Reverse;
+
+/// Priority of the generations a prompt has queued, higher ones starting first
+#[derive(
+    serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub enum Priority {
+    /// Batch work like replays and benchmarks, which can wait
+    Background,
+    Normal,
+    High,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Background];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Priority::High => "High",
+            Priority::Normal => "Normal",
+            Priority::Background => "Background",
+        }
+    }
+}
+
+/// The generations queued by a prompt
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueuedJobs {
+    pub idx: usize,
+    pub priority: Priority,
+    pub count: usize,
+}
+
+/// Orders the queued jobs by the priority they start in, keeping the prompt order for equal ones
+pub fn schedule(jobs: &mut [QueuedJobs]) {
+    jobs.sort_by_key(|job| (Reverse(job.priority), job.idx));
+}
+
+/// The priority the queued jobs must have to start, lower ones waiting for them
+pub fn top_priority(jobs: &[QueuedJobs]) -> Option<Priority> {
+    jobs.iter().map(|job| job.priority).max()
+}
@@ -0,0 +1,53 @@
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+/// Copy of the last stored state that loaded successfully
+const LATEST_BACKUP: &str = "state.ron";
+
+/// What was salvaged from a stored state that failed to load
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct StorageRecovery {
+    pub error: String,
+    /// Where the unreadable state was copied to, before it gets overwritten on the next save
+    pub corrupted_copy: Option<PathBuf>,
+    pub recovered_prompts: usize,
+    pub latest_backup: Option<PathBuf>,
+}
+
+/// Only the prompts of the stored state, the rest of it being ignored
+#[derive(serde::Deserialize)]
+struct PartialState<T> {
+    #[serde(default = "Vec::new")]
+    prompts: Vec<T>,
+}
+
+pub fn write_backup(dir: &Path, serialized: &str) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(LATEST_BACKUP), serialized)?;
+
+    Ok(())
+}
+
+pub fn latest_backup(dir: &Path) -> Option<PathBuf> {
+    Some(dir.join(LATEST_BACKUP)).filter(|path| path.is_file())
+}
+
+pub fn keep_corrupted(dir: &Path, serialized: &str) -> anyhow::Result<PathBuf> {
+    let path = dir.join(format!(
+        "corrupted-{}.ron",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, serialized)?;
+
+    Ok(path)
+}
+
+/// Reads the prompts alone, for states broken in another part, e.g. the settings
+pub fn recover_prompts<T: DeserializeOwned>(serialized: &str) -> Vec<T> {
+    ron::from_str::<PartialState<T>>(serialized)
+        .map(|state| state.prompts)
+        .unwrap_or_default()
+}
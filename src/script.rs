@@ -0,0 +1,135 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+/// A recorded prompt conversation that can be replayed later, possibly against another model
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Script {
+    pub title: String,
+    pub content: String,
+    pub steps: Vec<ScriptStep>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ScriptStep {
+    pub input: String,
+    pub output: String,
+    pub local_model_name: String,
+    pub created_at: DateTime<Local>,
+}
+
+/// What to do with a script whose prompt is already in the library
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStrategy {
+    /// Add only the responses missing from the history of the existing prompt
+    Merge,
+    /// Import the script as a separate prompt
+    KeepBoth,
+}
+
+/// A loaded script waiting for a decision on how to import it
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ScriptImport {
+    pub script: Script,
+    /// The prompt with the same content, if any
+    pub existing: Option<usize>,
+    /// Number of steps already present in the history of the existing prompt
+    pub duplicate_steps: usize,
+}
+
+/// Hashes text ignoring surrounding whitespace, to recognize content already in the library
+pub fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes an exchange, to recognize responses already in the history
+pub fn exchange_hash(input: &str, output: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    input.trim().hash(&mut hasher);
+    output.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Script {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, json)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Steps through the recorded inputs of a script, waiting `delay_secs` between generations
+pub struct ScriptReplay {
+    inputs: VecDeque<String>,
+    total: usize,
+    pub delay_secs: f32,
+    pub paused: bool,
+    next_at: Option<Instant>,
+}
+
+impl ScriptReplay {
+    pub fn new(script: Script) -> Self {
+        let inputs = script
+            .steps
+            .into_iter()
+            .map(|step| step.input)
+            .collect::<VecDeque<_>>();
+
+        Self {
+            total: inputs.len(),
+            inputs,
+            delay_secs: 1.0,
+            paused: false,
+            next_at: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the number of steps already sent and the total number of steps
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.inputs.len(), self.total)
+    }
+
+    pub fn skip(&mut self) {
+        self.inputs.pop_front();
+        self.next_at = None;
+    }
+
+    /// Returns the next input once the previous generation is over and the delay has elapsed
+    pub fn poll(&mut self, is_generating: bool) -> Option<String> {
+        if self.paused || is_generating {
+            self.next_at = None;
+            return None;
+        }
+
+        let next_at = *self
+            .next_at
+            .get_or_insert_with(|| Instant::now() + Duration::from_secs_f32(self.delay_secs));
+
+        if Instant::now() >= next_at {
+            self.next_at = None;
+            self.inputs.pop_front()
+        } else {
+            None
+        }
+    }
+}
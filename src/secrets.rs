@@ -0,0 +1,23 @@
+use keyring::Entry;
+
+const SERVICE: &str = "reprompt";
+
+/// Reads a secret kept in the OS keyring instead of the serialized app state
+pub fn get(account: &str) -> anyhow::Result<Option<String>> {
+    match Entry::new(SERVICE, account)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn set(account: &str, secret: &str) -> anyhow::Result<()> {
+    Ok(Entry::new(SERVICE, account)?.set_password(secret)?)
+}
+
+pub fn delete(account: &str) -> anyhow::Result<()> {
+    match Entry::new(SERVICE, account)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
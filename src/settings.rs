@@ -0,0 +1,248 @@
+use crate::{backend::BackendKind, secrets};
+
+/// Keyring account of the API key of the OpenAI-compatible backend
+const OPENAI_API_KEY_ACCOUNT: &str = "openai/api_key";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// Server the text models answer from
+    pub backend: BackendKind,
+    pub ollama_url: String,
+    pub ollama_tls: EndpointTls,
+    pub ollama_headers: Vec<ExtraHeader>,
+    /// Base URL of the OpenAI-compatible backend, up to the `/v1` of the API paths
+    pub openai_url: String,
+    /// Kept in the OS keyring, like the header values
+    #[serde(skip_serializing)]
+    pub openai_api_key: String,
+    pub openai_tls: EndpointTls,
+    pub openai_headers: Vec<ExtraHeader>,
+    pub stable_diffusion_url: String,
+    pub stable_diffusion_tls: EndpointTls,
+    pub stable_diffusion_headers: Vec<ExtraHeader>,
+    /// Proxy for all provider requests, the proxy environment variables are used when empty
+    pub proxy_url: String,
+    /// Reads the text on stdin and writes a WAV file to `{output}`
+    pub tts_command: String,
+    /// Plays `{file}` starting at `{start}` seconds
+    pub audio_player_command: String,
+    /// Opens `{file}` with the response, `$VISUAL` or `$EDITOR` are used when empty
+    pub editor_command: String,
+    /// Generations running longer than this are flagged, 0 disables the watchdog
+    pub generation_watchdog_secs: u64,
+    /// Retries of a generation that fails to connect, waiting longer before each of them
+    pub generation_retries: u32,
+    /// Caps the tokens of an answer, 0 leaves it to the model
+    pub max_output_tokens: u32,
+    /// Asks the model to go on with an answer cut off by the length limit, joining the parts
+    pub auto_continue: bool,
+    /// Continuations of one answer at most
+    pub auto_continue_max: u32,
+    /// How long removed prompts and responses can be restored, 0 disables undo
+    pub undo_secs: u64,
+    /// Loads the selected model in the background when a text prompt is selected
+    pub warm_up_on_select: bool,
+    /// Inserted into the input by "Ask about selection", `{selection}` is the quoted passage
+    pub quote_template: String,
+    /// Lists the demo model, streaming canned responses without Ollama
+    pub demo_provider: bool,
+    pub demo_token_delay_ms: u64,
+    /// Offers to run the shell and Python snippets of the responses, after confirmation
+    pub run_snippets: bool,
+    /// Languages of the snippets that may run, separated by whitespace
+    pub snippet_languages: String,
+    /// Commands shell snippets may use, separated by whitespace
+    pub snippet_shell_commands: String,
+    /// Prefix of the interpreter command, e.g. `bwrap` with its options, snippets don't run without
+    pub snippet_sandbox_command: String,
+    pub snippet_timeout_secs: u64,
+    /// Reveals the streamed responses at a steady rate instead of in the chunks they arrive in
+    pub smooth_streaming: bool,
+    pub smooth_streaming_chars_per_sec: u32,
+    pub fonts: FontSettings,
+    /// Shows the names of the icon buttons next to their icons, for accessibility
+    pub icon_labels: bool,
+    /// URL of an exported library or folder of prompt cards shared by a team, none when empty
+    pub shared_source: String,
+    pub shared_refresh_mins: u64,
+    /// Folder on a synced drive or WebDAV URL the library is synced through, no sync when empty
+    pub sync_target: String,
+    pub sync_headers: Vec<ExtraHeader>,
+    /// Underlines the misspelled words of the input
+    pub spell_check: bool,
+    /// Hunspell `.dic` file, next to its `.aff` file, the one of the locale is used when empty
+    pub spell_check_dictionary: String,
+    /// Words added to the dictionary from the input
+    pub spell_check_words: Vec<String>,
+}
+
+impl Settings {
+    /// Reads the header values from the OS keyring.
+    ///
+    /// Values still found in the app state, saved by an older version, are moved to the keyring.
+    pub fn load_secrets(&mut self) -> anyhow::Result<()> {
+        for (account, header) in self.secret_headers_mut() {
+            if header.value.is_empty() {
+                header.value = secrets::get(&account)?.unwrap_or_default();
+            } else {
+                secrets::set(&account, &header.value)?;
+            }
+        }
+
+        if self.openai_api_key.is_empty() {
+            self.openai_api_key = secrets::get(OPENAI_API_KEY_ACCOUNT)?.unwrap_or_default();
+        } else {
+            secrets::set(OPENAI_API_KEY_ACCOUNT, &self.openai_api_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the header values to the OS keyring, removing the ones of deleted headers
+    pub fn store_secrets(&mut self, previous: &mut Settings) -> anyhow::Result<()> {
+        let accounts = self
+            .secret_headers_mut()
+            .map(|(account, header)| {
+                secrets::set(&account, &header.value)?;
+                Ok(account)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for (account, _) in previous.secret_headers_mut() {
+            if !accounts.contains(&account) {
+                secrets::delete(&account)?;
+            }
+        }
+
+        if self.openai_api_key.is_empty() {
+            secrets::delete(OPENAI_API_KEY_ACCOUNT)?;
+        } else {
+            secrets::set(OPENAI_API_KEY_ACCOUNT, &self.openai_api_key)?;
+        }
+
+        Ok(())
+    }
+
+    fn secret_headers_mut(&mut self) -> impl Iterator<Item = (String, &mut ExtraHeader)> {
+        let ollama = self
+            .ollama_headers
+            .iter_mut()
+            .map(|header| (format!("ollama/{}", header.name.trim()), header));
+        // Apart from the API key account, which a header could be named like
+        let openai = self
+            .openai_headers
+            .iter_mut()
+            .map(|header| (format!("openai/headers/{}", header.name.trim()), header));
+        let stable_diffusion = self
+            .stable_diffusion_headers
+            .iter_mut()
+            .map(|header| (format!("stable_diffusion/{}", header.name.trim()), header));
+
+        let sync = self
+            .sync_headers
+            .iter_mut()
+            .map(|header| (format!("sync/{}", header.name.trim()), header));
+
+        ollama
+            .chain(openai)
+            .chain(stable_diffusion)
+            .chain(sync)
+            .filter(|(_, header)| !header.name.trim().is_empty())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            backend: Default::default(),
+            ollama_url: "http://127.0.0.1:11434".to_string(),
+            ollama_tls: Default::default(),
+            ollama_headers: Vec::new(),
+            openai_url: "http://127.0.0.1:1234/v1".to_string(),
+            openai_api_key: String::new(),
+            openai_tls: Default::default(),
+            openai_headers: Vec::new(),
+            stable_diffusion_url: "http://127.0.0.1:7860".to_string(),
+            stable_diffusion_tls: Default::default(),
+            stable_diffusion_headers: Vec::new(),
+            proxy_url: String::new(),
+            tts_command: "espeak-ng --stdin -w {output}".to_string(),
+            audio_player_command: "ffplay -nodisp -autoexit -loglevel quiet -ss {start} {file}"
+                .to_string(),
+            editor_command: String::new(),
+            generation_watchdog_secs: 120,
+            generation_retries: 3,
+            max_output_tokens: 0,
+            undo_secs: 30,
+            auto_continue: false,
+            auto_continue_max: 3,
+            warm_up_on_select: false,
+            quote_template: "> {selection}\n\n".to_string(),
+            demo_provider: false,
+            demo_token_delay_ms: 40,
+            run_snippets: false,
+            snippet_languages: "sh bash python".to_string(),
+            snippet_shell_commands:
+                "echo printf ls cat head tail wc grep cut tr date pwd seq expr test".to_string(),
+            snippet_sandbox_command: String::new(),
+            snippet_timeout_secs: 10,
+            smooth_streaming: false,
+            smooth_streaming_chars_per_sec: 200,
+            fonts: Default::default(),
+            icon_labels: false,
+            shared_source: String::new(),
+            shared_refresh_mins: 15,
+            sync_target: String::new(),
+            sync_headers: Vec::new(),
+            spell_check: true,
+            spell_check_dictionary: String::new(),
+            spell_check_words: Vec::new(),
+        }
+    }
+}
+
+/// TLS options of a remote endpoint served with an internal certificate
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EndpointTls {
+    /// PEM file with additional trusted certificates, empty when none
+    pub ca_bundle: String,
+    /// Accepts any certificate, including self-signed and expired ones
+    pub accept_invalid_certs: bool,
+}
+
+/// Fonts of the UI and the code, the bundled ones staying as fallbacks for the missing glyphs
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct FontSettings {
+    /// A font file or the name of an installed font, the bundled font when empty
+    pub ui_font: String,
+    pub ui_size: f32,
+    /// A font file or the name of an installed font, the bundled font when empty
+    pub code_font: String,
+    pub code_size: f32,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        Self {
+            ui_font: String::new(),
+            ui_size: 12.5,
+            code_font: String::new(),
+            code_size: 12.0,
+        }
+    }
+}
+
+/// A header added to every request of an endpoint, e.g. for a reverse proxy in front of it
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ExtraHeader {
+    pub name: String,
+    /// Kept in the OS keyring, only read from the app state to migrate older versions.
+    ///
+    /// `Basic user:password` values of `Authorization` are encoded when the request is built.
+    #[serde(skip_serializing)]
+    pub value: String,
+}
@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    card::{self, PromptCard},
+    library::{LibraryFormat, PromptLibrary},
+};
+
+/// Where the prompts shared by a team are read from, read-only
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SharedSource {
+    /// An exported library, JSON unless the path of the URL ends with `.toml`
+    Url(String),
+    /// A folder of prompt cards and exported libraries, e.g. on a network drive
+    Folder(PathBuf),
+}
+
+impl SharedSource {
+    /// The source of the settings, none when it's empty
+    pub fn parse(source: &str) -> Option<Self> {
+        let source = source.trim();
+
+        if source.is_empty() {
+            None
+        } else if source.starts_with("http://") || source.starts_with("https://") {
+            Some(Self::Url(source.to_string()))
+        } else {
+            Some(Self::Folder(PathBuf::from(source)))
+        }
+    }
+
+    /// Reads the shared prompts, sorted by title
+    pub async fn fetch(&self, client: &reqwest::Client) -> anyhow::Result<Vec<PromptCard>> {
+        let mut cards = match self {
+            Self::Url(url) => {
+                let url = reqwest::Url::parse(url)?;
+                let format = LibraryFormat::of(Path::new(url.path()));
+                let text = client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+
+                library_cards(&PromptLibrary::parse(&text, format)?)
+            }
+            Self::Folder(path) => {
+                let path = path.clone();
+
+                // Network drives may take a while to answer
+                tokio::task::spawn_blocking(move || read_folder(&path)).await??
+            }
+        };
+
+        cards.sort_by_key(|card| card.title.to_lowercase());
+
+        Ok(cards)
+    }
+}
+
+/// How often the shared prompts are read again, at least once a minute
+pub fn refresh_interval(minutes: u64) -> Duration {
+    Duration::from_secs(minutes.max(1) * 60)
+}
+
+fn library_cards(library: &PromptLibrary) -> Vec<PromptCard> {
+    library
+        .prompts
+        .iter()
+        .map(PromptCard::from_prompt)
+        .collect()
+}
+
+/// The prompt cards and the prompts of the exported libraries in the folder, without its
+/// subfolders. Files that fail to load are skipped, so that one broken file doesn't hide the rest.
+fn read_folder(path: &Path) -> anyhow::Result<Vec<PromptCard>> {
+    let mut cards = Vec::new();
+
+    for path in fs::read_dir(path)
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+    {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+
+        let loaded = match extension.as_deref() {
+            Some(card::EXTENSION) => PromptCard::load(&path).map(|card| vec![card]),
+            Some("json" | "toml") => {
+                PromptLibrary::load(&path).map(|library| library_cards(&library))
+            }
+            _ => continue,
+        };
+
+        match loaded {
+            Ok(loaded) => cards.extend(loaded),
+            Err(e) => log::warn!("Skipping the shared prompts of {}: {e}", path.display()),
+        }
+    }
+
+    Ok(cards)
+}
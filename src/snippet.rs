@@ -0,0 +1,283 @@
+use std::{
+    env, fs,
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::settings::Settings;
+
+/// Shell syntax that could bypass the allowed commands or write files
+const FORBIDDEN_SHELL_SYNTAX: [&str; 4] = ["$(", "`", ">", "<("];
+
+/// Words preceding the command of a shell statement
+const SHELL_KEYWORDS: [&str; 10] = [
+    "if", "then", "else", "elif", "fi", "do", "done", "while", "until", "!",
+];
+
+/// A fenced code block of a response, in a language it can be run with
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Snippet {
+    pub language: String,
+    pub code: String,
+}
+
+/// Captured output of a snippet run, kept under the response it comes from
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct SnippetRun {
+    pub snippet: Snippet,
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, `None` when the process was killed
+    pub status: Option<i32>,
+    pub timed_out: bool,
+    pub ran_at: DateTime<Local>,
+}
+
+/// What snippets may run and how, from the settings
+#[derive(Clone)]
+pub struct SnippetPolicy {
+    pub enabled: bool,
+    pub languages: Vec<String>,
+    pub shell_commands: Vec<String>,
+    /// Prefix of the interpreter command, e.g. `bwrap` or `firejail` with their options
+    pub sandbox_command: String,
+    pub timeout: Duration,
+}
+
+impl SnippetPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let words = |list: &str| list.split_whitespace().map(str::to_string).collect();
+
+        Self {
+            enabled: settings.run_snippets,
+            languages: words(&settings.snippet_languages),
+            shell_commands: words(&settings.snippet_shell_commands),
+            sandbox_command: settings.snippet_sandbox_command.trim().to_string(),
+            timeout: Duration::from_secs(settings.snippet_timeout_secs),
+        }
+    }
+
+    /// Tells why a snippet may not run, the allow-lists being checked before asking for confirmation
+    pub fn check(&self, snippet: &Snippet) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.enabled,
+            "running snippets is disabled, it can be enabled in the settings"
+        );
+        anyhow::ensure!(
+            self.languages.contains(&snippet.language),
+            "{} snippets are not in the allowed languages",
+            snippet.language
+        );
+        // The allowed commands can still read any file, and Python snippets anything at all
+        anyhow::ensure!(
+            !self.sandbox_command.is_empty(),
+            "snippets only run in a sandbox, the sandbox command can be set in the settings"
+        );
+
+        if is_shell(&snippet.language) {
+            if let Some(syntax) = FORBIDDEN_SHELL_SYNTAX
+                .iter()
+                .find(|syntax| snippet.code.contains(**syntax))
+            {
+                anyhow::bail!("the snippet uses `{syntax}`, which is not allowed");
+            }
+
+            for command in shell_commands(&snippet.code) {
+                anyhow::ensure!(
+                    self.shell_commands.iter().any(|allowed| allowed == command),
+                    "`{command}` is not in the allowed shell commands"
+                );
+            }
+
+            if let Some(path) = outside_paths(&snippet.code).next() {
+                anyhow::bail!("the snippet uses `{path}`, only paths in its directory are allowed");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The command line the snippet is run with, `{file}` standing for the snippet file
+    pub fn command_line(&self, snippet: &Snippet) -> String {
+        [
+            self.sandbox_command.as_str(),
+            interpreter(&snippet.language),
+            "{file}",
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+
+    /// Runs the snippet in an empty temporary directory, with a minimal environment and no stdin,
+    /// killing it once the timeout is reached.
+    pub fn run(&self, snippet: &Snippet) -> anyhow::Result<SnippetRun> {
+        self.check(snippet)?;
+
+        let dir = env::temp_dir().join(format!(
+            "reprompt-snippet-{}",
+            Local::now().format("%Y%m%d-%H%M%S%.f")
+        ));
+        fs::create_dir_all(&dir)?;
+
+        let result = self.run_in(snippet, &dir);
+
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            log::warn!(
+                "Failed to remove the snippet directory {}: {e}",
+                dir.display()
+            );
+        }
+
+        result
+    }
+
+    fn run_in(&self, snippet: &Snippet, dir: &Path) -> anyhow::Result<SnippetRun> {
+        let file = dir.join("snippet");
+        fs::write(&file, &snippet.code)?;
+
+        let command_line = self
+            .command_line(snippet)
+            .replace("{file}", &file.display().to_string());
+        let mut parts = command_line.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("the command is empty"))?;
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .current_dir(dir)
+            .env_clear()
+            .env("PATH", env::var("PATH").unwrap_or_default())
+            .env("HOME", dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to run `{program}`: {e}"))?;
+
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let started_at = Instant::now();
+        let mut timed_out = false;
+
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if started_at.elapsed() >= self.timeout {
+                timed_out = true;
+                child.kill()?;
+                break child.wait()?;
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        };
+
+        Ok(SnippetRun {
+            snippet: snippet.clone(),
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+            status: status.code(),
+            timed_out,
+            ran_at: Local::now(),
+        })
+    }
+}
+
+/// Finds the fenced code blocks of an output that are in a runnable language
+pub fn find(output: &str) -> Vec<Snippet> {
+    let mut snippets = Vec::new();
+    let mut current: Option<Snippet> = None;
+
+    for line in output.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+
+        match (fence, current.take()) {
+            (Some(_), Some(snippet)) => {
+                if !interpreter(&snippet.language).is_empty() {
+                    snippets.push(snippet);
+                }
+            }
+            (Some(language), None) => {
+                current = Some(Snippet {
+                    language: language.trim().to_lowercase(),
+                    code: String::new(),
+                });
+            }
+            (None, Some(mut snippet)) => {
+                snippet.code.push_str(line);
+                snippet.code.push('\n');
+                current = Some(snippet);
+            }
+            (None, None) => {}
+        }
+    }
+
+    snippets
+}
+
+fn is_shell(language: &str) -> bool {
+    matches!(language, "sh" | "shell" | "bash")
+}
+
+/// Interpreter running the snippets of a language, empty for the languages that cannot be run
+fn interpreter(language: &str) -> &'static str {
+    match language {
+        "sh" | "shell" => "sh",
+        "bash" => "bash",
+        "python" | "py" => "python3",
+        _ => "",
+    }
+}
+
+/// Commands a shell snippet runs, the first word of each pipeline or list element that is not a
+/// keyword or a variable assignment
+fn shell_commands(code: &str) -> Vec<&str> {
+    code.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .flat_map(|line| line.split(['|', ';', '&']))
+        .filter_map(|segment| {
+            segment
+                .split_whitespace()
+                .find(|word| !word.contains('=') && !SHELL_KEYWORDS.contains(word))
+        })
+        // The words of a loop header are values, not commands
+        .filter(|command| *command != "for")
+        .collect()
+}
+
+/// Paths of a shell snippet leading out of the directory it runs in, absolute, from the home
+/// directory or going up
+fn outside_paths(code: &str) -> impl Iterator<Item = &str> {
+    code.split_whitespace()
+        .flat_map(|word| word.split(['=', ':', '"', '\'']))
+        .filter(|path| {
+            path.starts_with('/')
+                || path.starts_with('~')
+                || path.split('/').any(|component| component == "..")
+        })
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = String::new();
+
+        if let Some(mut pipe) = pipe {
+            let mut bytes = Vec::new();
+            if pipe.read_to_end(&mut bytes).is_ok() {
+                output = String::from_utf8_lossy(&bytes).into_owned();
+            }
+        }
+
+        output
+    })
+}
@@ -0,0 +1,163 @@
+use std::{
+    env, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use spellbook::Dictionary;
+
+/// Suggestions offered for a misspelled word, the closest ones first
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Dictionary used when the locale has none installed
+const FALLBACK_LANGUAGE: &str = "en_US";
+
+/// Folders Hunspell dictionaries are installed to on Linux and macOS
+const DICTIONARY_DIRS: [&str; 4] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+
+/// Checks the spelling of the input against a Hunspell dictionary
+pub struct SpellChecker {
+    dictionary: Dictionary,
+}
+
+impl SpellChecker {
+    /// Loads the `.dic` file and the `.aff` file next to it
+    pub fn load(dic: &Path) -> anyhow::Result<Self> {
+        let aff = dic.with_extension("aff");
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))
+        };
+        let dictionary = Dictionary::new(&read(&aff)?, &read(dic)?)
+            .map_err(|e| anyhow::anyhow!("failed to parse {}: {e}", dic.display()))?;
+
+        Ok(Self { dictionary })
+    }
+
+    /// The installed dictionary of the language of the locale, English when there is none
+    pub fn find_dictionary() -> Option<PathBuf> {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        // E.g. "de_DE.UTF-8"
+        let language = locale.split(['.', '@']).next().unwrap_or_default();
+        let user_dir = dirs::home_dir().map(|home| home.join("Library/Spelling"));
+
+        [language, FALLBACK_LANGUAGE]
+            .into_iter()
+            .filter(|language| !language.is_empty())
+            .flat_map(|language| {
+                DICTIONARY_DIRS
+                    .iter()
+                    .map(PathBuf::from)
+                    .chain(user_dir.clone())
+                    .map(move |dir| dir.join(format!("{language}.dic")))
+            })
+            .find(|path| path.exists())
+    }
+
+    /// Byte ranges of the misspelled words of the text, the words added by the user excepted
+    pub fn misspelled(&self, text: &str, added_words: &[String]) -> Vec<Range<usize>> {
+        words(text)
+            .filter(|range| {
+                let word = &text[range.clone()];
+
+                !self.dictionary.check(word)
+                    && !added_words
+                        .iter()
+                        .any(|added| added.eq_ignore_ascii_case(word))
+            })
+            .collect()
+    }
+
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions.truncate(MAX_SUGGESTIONS);
+
+        suggestions
+    }
+}
+
+/// Byte ranges of the words worth checking: acronyms, identifiers, URLs, code spans and
+/// `{{placeholders}}` are left out
+pub fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut in_code = false;
+
+    chunks(text).flat_map(move |chunk| {
+        let code_marks = text[chunk.clone()].matches('`').count();
+        let skipped = in_code || code_marks > 0 || is_technical(&text[chunk.clone()]);
+
+        in_code ^= code_marks % 2 == 1;
+
+        if skipped {
+            Vec::new()
+        } else {
+            chunk_words(text, chunk)
+        }
+    })
+}
+
+/// Whitespace-separated chunks of the text
+fn chunks(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    text.split_whitespace().map(move |chunk| {
+        let start = chunk.as_ptr() as usize - text.as_ptr() as usize;
+
+        start..start + chunk.len()
+    })
+}
+
+fn is_technical(chunk: &str) -> bool {
+    chunk.contains("://")
+        || chunk.contains("{{")
+        || chunk
+            .chars()
+            .any(|c| matches!(c, '@' | '/' | '\\' | '_' | '=' | '<' | '>') || c.is_numeric())
+}
+
+/// The runs of letters of a chunk, with the apostrophes inside them
+fn chunk_words(text: &str, chunk: Range<usize>) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let is_word_char = |c: char| c.is_alphabetic() || c == '\'' || c == '’';
+
+    for (offset, c) in text[chunk.clone()]
+        .char_indices()
+        .chain(std::iter::once((chunk.len(), ' ')))
+    {
+        match (start, is_word_char(c)) {
+            (None, true) => start = Some(chunk.start + offset),
+            (Some(word_start), false) => {
+                words.push(trim_apostrophes(text, word_start..chunk.start + offset));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    words
+        .into_iter()
+        .filter(|range| {
+            let word = &text[range.clone()];
+
+            // Single letters, acronyms and camelCase identifiers are left out
+            word.chars().count() > 1 && !word.chars().skip(1).any(char::is_uppercase)
+        })
+        .collect()
+}
+
+fn trim_apostrophes(text: &str, range: Range<usize>) -> Range<usize> {
+    let word = &text[range.clone()];
+    let trimmed = word.trim_matches(['\'', '’']);
+    let start = range.start + (word.len() - word.trim_start_matches(['\'', '’']).len());
+
+    start..start + trimmed.len()
+}
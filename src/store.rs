@@ -0,0 +1,281 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::{Connection, params};
+
+/// Name of the database in the app data directory
+pub const FILE_NAME: &str = "library.sqlite3";
+/// Responses of a history read at once, the newest when the library is opened and the older ones
+/// when they are asked for
+pub const HISTORY_PAGE_LEN: usize = 50;
+
+/// The responses are ordered by their creation time in microseconds, which unlike the formatted
+/// time doesn't depend on the time zone offset they were created in, and then by their id, as
+/// several responses can be created at the same time
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS prompts (
+        key TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        settings TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS responses (
+        id INTEGER PRIMARY KEY,
+        prompt_key TEXT NOT NULL REFERENCES prompts (key) ON DELETE CASCADE,
+        created_at INTEGER NOT NULL,
+        response TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS responses_by_time ON responses (prompt_key, created_at);
+";
+
+/// Version of the schema, kept as the `user_version` of the database
+const SCHEMA_VERSION: i64 = 1;
+
+/// Gives the responses an id of their own instead of identifying them by their creation time
+const ADD_RESPONSE_IDS: &str = "
+    ALTER TABLE responses RENAME TO responses_by_created_at;
+    CREATE TABLE responses (
+        id INTEGER PRIMARY KEY,
+        prompt_key TEXT NOT NULL REFERENCES prompts (key) ON DELETE CASCADE,
+        created_at INTEGER NOT NULL,
+        response TEXT NOT NULL
+    );
+    INSERT INTO responses (prompt_key, created_at, response)
+    SELECT prompt_key, created_at, response FROM responses_by_created_at ORDER BY created_at;
+    DROP TABLE responses_by_created_at;
+";
+
+/// Older responses of a history left in the database until they are asked for
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct UnloadedHistory {
+    /// Creation time of the oldest loaded response, the unloaded ones being older
+    pub before: DateTime<Local>,
+    pub len: usize,
+}
+
+/// A page of a history read from the database
+#[derive(Debug, Default)]
+pub struct HistoryPage {
+    /// The serialized responses, the newest first
+    pub responses: Vec<String>,
+    /// The responses older than the page, none when it's the last one
+    pub unloaded: Option<UnloadedHistory>,
+}
+
+/// A prompt read from the database, with the newest page of its history
+#[derive(Debug)]
+pub struct StoredPrompt {
+    /// The prompt without its history, serialized as RON
+    pub settings: String,
+    pub history: HistoryPage,
+}
+
+/// A prompt as it's written to the database
+#[derive(Hash, Debug)]
+pub struct PromptRecord {
+    /// Identifies the prompt across saves, its sync identifier
+    pub key: String,
+    /// The prompt without its history, serialized as RON
+    pub settings: String,
+    /// The loaded responses by their creation time, serialized as RON
+    pub history: Vec<(DateTime<Local>, String)>,
+    /// The responses that weren't loaded, which are left as they are
+    pub unloaded: Option<UnloadedHistory>,
+}
+
+impl PromptRecord {
+    fn fingerprint(&self, position: usize) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        position.hash(&mut hasher);
+        self.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
+
+/// The prompts and their history in a SQLite database, so that only the prompts that changed are
+/// written and the long histories are read page by page
+pub struct Store {
+    connection: Connection,
+    /// Fingerprint of each prompt as it was last written, by its key
+    written: HashMap<String, u64>,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let connection = Connection::open(path)?;
+
+        connection.pragma_update(None, "foreign_keys", true)?;
+
+        let version: i64 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let has_responses: bool = connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'responses')",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if version < 1 && has_responses {
+            connection.execute_batch(ADD_RESPONSE_IDS)?;
+        }
+
+        connection.execute_batch(SCHEMA)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self {
+            connection,
+            written: HashMap::new(),
+        })
+    }
+
+    /// Reads the prompts in their order, each with the newest page of its history
+    pub fn load(&self) -> anyhow::Result<Vec<StoredPrompt>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT key, settings FROM prompts ORDER BY position")?;
+        let prompts = statement
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        prompts
+            .into_iter()
+            .map(|(key, settings)| {
+                Ok(StoredPrompt {
+                    settings,
+                    history: self.history_page(&key, None)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the responses of the prompt created before the given time, or the newest ones
+    pub fn history_page(
+        &self,
+        key: &str,
+        before: Option<DateTime<Local>>,
+    ) -> anyhow::Result<HistoryPage> {
+        let before = before.map_or(i64::MAX, |before| before.timestamp_micros());
+        let read_row = |row: &rusqlite::Row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        };
+        let mut statement = self.connection.prepare_cached(
+            "SELECT created_at, id, response FROM responses
+             WHERE prompt_key = ?1 AND created_at < ?2
+             ORDER BY created_at DESC, id DESC LIMIT ?3",
+        )?;
+        let mut page = statement
+            .query_map(params![key, before, HISTORY_PAGE_LEN as i64], read_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // The responses created at the same time as the oldest one of the page go with it, as the
+        // next page starts before that time
+        if let Some(&(oldest, oldest_id, _)) = page.last() {
+            let mut statement = self.connection.prepare_cached(
+                "SELECT created_at, id, response FROM responses
+                 WHERE prompt_key = ?1 AND created_at = ?2 AND id < ?3
+                 ORDER BY id DESC",
+            )?;
+            let same_time = statement
+                .query_map(params![key, oldest, oldest_id], read_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            page.extend(same_time);
+        }
+
+        let unloaded = match page.last() {
+            Some(&(oldest, _, _)) => {
+                let len: i64 = self.connection.query_row(
+                    "SELECT COUNT(*) FROM responses WHERE prompt_key = ?1 AND created_at < ?2",
+                    params![key, oldest],
+                    |row| row.get(0),
+                )?;
+
+                Local
+                    .timestamp_micros(oldest)
+                    .single()
+                    .filter(|_| len > 0)
+                    .map(|before| UnloadedHistory {
+                        before,
+                        len: len as usize,
+                    })
+            }
+            None => None,
+        };
+
+        Ok(HistoryPage {
+            responses: page.into_iter().map(|(_, _, response)| response).collect(),
+            unloaded,
+        })
+    }
+
+    /// Writes the prompts that changed since they were last written, in the given order, and
+    /// removes the others
+    pub fn save(&mut self, prompts: &[PromptRecord]) -> anyhow::Result<()> {
+        let transaction = self.connection.transaction()?;
+        let mut written = HashMap::new();
+
+        for (position, prompt) in prompts.iter().enumerate() {
+            let fingerprint = prompt.fingerprint(position);
+
+            written.insert(prompt.key.clone(), fingerprint);
+
+            if self.written.get(&prompt.key) == Some(&fingerprint) {
+                continue;
+            }
+
+            // Updated in place, as replacing the row would delete its responses
+            transaction.execute(
+                "INSERT INTO prompts (key, position, settings) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (key) DO UPDATE
+                 SET position = excluded.position, settings = excluded.settings",
+                params![prompt.key, position as i64, prompt.settings],
+            )?;
+
+            // The loaded part of the history is written again, the unloaded part being older
+            let loaded_since = prompt
+                .unloaded
+                .map_or(i64::MIN, |unloaded| unloaded.before.timestamp_micros());
+
+            transaction.execute(
+                "DELETE FROM responses WHERE prompt_key = ?1 AND created_at >= ?2",
+                params![prompt.key, loaded_since],
+            )?;
+
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO responses (prompt_key, created_at, response) VALUES (?1, ?2, ?3)",
+            )?;
+
+            // Oldest first, for the responses created at the same time to keep their order by id
+            for (created_at, response) in prompt.history.iter().rev() {
+                insert.execute(params![prompt.key, created_at.timestamp_micros(), response])?;
+            }
+        }
+
+        let stored = transaction
+            .prepare("SELECT key FROM prompts")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for key in stored {
+            if !written.contains_key(&key) {
+                transaction.execute("DELETE FROM prompts WHERE key = ?1", params![key])?;
+            }
+        }
+
+        transaction.commit()?;
+        self.written = written;
+
+        Ok(())
+    }
+}
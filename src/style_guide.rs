@@ -0,0 +1,41 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+
+/// A markdown style guide referenced by a prompt, reloaded whenever the file changes on disk
+pub struct StyleGuide {
+    path: PathBuf,
+    pub text: String,
+    modified: Option<SystemTime>,
+    pub loaded_at: DateTime<Local>,
+}
+
+impl StyleGuide {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            text: fs::read_to_string(path)?,
+            modified: fs::metadata(path)?.modified().ok(),
+            loaded_at: Local::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reloads the file if its modification time changed since the last load
+    pub fn refresh(&mut self) -> anyhow::Result<()> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+
+        if modified.is_none() || modified != self.modified {
+            *self = Self::load(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{StatusCode, header};
+
+use crate::{
+    card::PromptCard,
+    library::{LibraryFormat, PromptLibrary},
+    prompt::Prompt,
+};
+
+/// The synced library, in the folder or the WebDAV collection
+pub const FILE_NAME: &str = "reprompt-sync.json";
+
+const CHANGED_DURING_SYNC: &str =
+    "the synced library was changed by another machine during the sync, sync again";
+
+/// Where the library is synced between machines
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SyncBackend {
+    /// A folder on a drive synced by another tool, e.g. Syncthing or Dropbox
+    Folder(PathBuf),
+    /// A WebDAV collection, or any HTTP server answering GET and conditional PUT requests
+    WebDav(String),
+}
+
+/// The synced library, with the version it was read at
+#[derive(Default)]
+pub struct RemoteLibrary {
+    /// None until the first sync wrote it
+    pub library: Option<PromptLibrary>,
+    /// ETag of the file, or its modification time in a folder, to detect a concurrent write
+    pub version: Option<String>,
+}
+
+impl SyncBackend {
+    /// The backend of the settings, none when it's empty
+    pub fn parse(target: &str) -> Option<Self> {
+        let target = target.trim();
+
+        if target.is_empty() {
+            None
+        } else if target.starts_with("http://") || target.starts_with("https://") {
+            Some(Self::WebDav(target.to_string()))
+        } else {
+            Some(Self::Folder(PathBuf::from(target)))
+        }
+    }
+
+    pub async fn fetch(&self, client: &reqwest::Client) -> anyhow::Result<RemoteLibrary> {
+        match self {
+            Self::Folder(folder) => {
+                let path = folder.join(FILE_NAME);
+
+                tokio::task::spawn_blocking(move || fetch_file(&path)).await?
+            }
+            Self::WebDav(url) => {
+                let response = client.get(file_url(url)).send().await?;
+
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(RemoteLibrary::default());
+                }
+
+                let response = response.error_for_status()?;
+                let version = response
+                    .headers()
+                    .get(header::ETAG)
+                    .and_then(|etag| etag.to_str().ok())
+                    .map(str::to_string);
+                let text = response.text().await?;
+
+                Ok(RemoteLibrary {
+                    library: Some(PromptLibrary::parse(&text, LibraryFormat::Json)?),
+                    version,
+                })
+            }
+        }
+    }
+
+    /// Writes the library, refusing to when another machine wrote it since the fetched version
+    pub async fn push(
+        &self,
+        client: &reqwest::Client,
+        library: &PromptLibrary,
+        version: Option<String>,
+    ) -> anyhow::Result<()> {
+        let text = serde_json::to_string_pretty(library)?;
+
+        match self {
+            Self::Folder(folder) => {
+                let path = folder.join(FILE_NAME);
+
+                tokio::task::spawn_blocking(move || push_file(&path, &text, version.as_deref()))
+                    .await?
+            }
+            Self::WebDav(url) => {
+                let request = client
+                    .put(file_url(url))
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(text);
+                let request = match &version {
+                    Some(etag) => request.header(header::IF_MATCH, etag),
+                    None => request.header(header::IF_NONE_MATCH, "*"),
+                };
+                let response = request.send().await?;
+
+                anyhow::ensure!(
+                    response.status() != StatusCode::PRECONDITION_FAILED,
+                    CHANGED_DURING_SYNC
+                );
+                response.error_for_status()?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn file_url(url: &str) -> String {
+    format!("{}/{FILE_NAME}", url.trim_end_matches('/'))
+}
+
+fn modified_at(path: &Path) -> Option<String> {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+
+    Some(
+        modified
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos()
+            .to_string(),
+    )
+}
+
+fn fetch_file(path: &Path) -> anyhow::Result<RemoteLibrary> {
+    if !path.exists() {
+        return Ok(RemoteLibrary::default());
+    }
+
+    Ok(RemoteLibrary {
+        version: modified_at(path),
+        library: Some(PromptLibrary::load(path)?),
+    })
+}
+
+/// Writes to a temporary file renamed over the library, so that the sync tool never picks up a
+/// partly written one
+fn push_file(path: &Path, text: &str, version: Option<&str>) -> anyhow::Result<()> {
+    anyhow::ensure!(modified_at(path).as_deref() == version, CHANGED_DURING_SYNC);
+
+    let temporary = path.with_extension("json.tmp");
+
+    fs::write(&temporary, text)
+        .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", temporary.display()))?;
+    fs::rename(&temporary, path)
+        .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))?;
+
+    Ok(())
+}
+
+/// How a prompt changed on both machines is synced
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ConflictResolution {
+    #[default]
+    KeepMine,
+    TakeTheirs,
+    /// Adds the version of the other machine as a separate prompt
+    KeepBoth,
+}
+
+impl ConflictResolution {
+    pub const ALL: [Self; 3] = [Self::KeepMine, Self::TakeTheirs, Self::KeepBoth];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::KeepMine => "Keep mine",
+            Self::TakeTheirs => "Take theirs",
+            Self::KeepBoth => "Keep both",
+        }
+    }
+}
+
+/// A prompt whose settings were changed on both machines since the last sync, its history being
+/// merged regardless
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SyncConflict {
+    pub sync_id: String,
+    /// The prompt as the other machine changed it
+    pub theirs: PromptCard,
+    pub resolution: ConflictResolution,
+}
+
+/// What merging the synced library changed in the local one
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+pub struct SyncMerge {
+    pub conflicts: Vec<SyncConflict>,
+    /// Version of the synced library the merge is based on, the one the push expects
+    pub version: Option<String>,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+/// Hashes the settings of a prompt that are synced, leaving out its history and the settings
+/// kept per machine
+pub fn fingerprint(prompt: &Prompt) -> u64 {
+    card_fingerprint(&PromptCard::from_prompt(prompt))
+}
+
+/// Hashes the serialized card with FNV-1a, as the fingerprint is persisted and has to stay the
+/// same across builds, which `DefaultHasher` doesn't promise
+fn card_fingerprint(card: &PromptCard) -> u64 {
+    fingerprinted_json(card)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// The fingerprint the prompts synced by earlier versions were recorded with
+fn legacy_fingerprint(prompt: &Prompt) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    fingerprinted_json(&PromptCard::from_prompt(prompt)).hash(&mut hasher);
+
+    hasher.finish()
+}
+
+fn fingerprinted_json(card: &PromptCard) -> String {
+    // The recommended model follows the history, which is merged rather than compared
+    let card = PromptCard {
+        recommended_model: None,
+        ..card.clone()
+    };
+
+    serde_json::to_string(&card).unwrap_or_default()
+}
+
+/// A new identifier, unlikely to be picked by another machine as well
+pub fn new_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    // Seeded randomly by each process and call
+    let random = RandomState::new().hash_one(nanos);
+
+    format!("{nanos:x}-{random:016x}")
+}
+
+/// Gives the prompts without an identifier, or sharing one with a duplicated prompt, their own
+pub fn assign_ids(prompts: &mut [Prompt]) {
+    let mut seen = HashSet::new();
+
+    for prompt in prompts {
+        if prompt.sync_id.is_empty() || !seen.insert(prompt.sync_id.clone()) {
+            prompt.sync_id = new_id();
+            prompt.synced_fingerprint = None;
+            seen.insert(prompt.sync_id.clone());
+        }
+    }
+}
+
+/// Merges the synced library into the local prompts.
+///
+/// The settings changed on one machine only are taken over, the histories are joined, and the
+/// prompts changed on both machines are returned as conflicts, keeping the local settings until
+/// they are resolved. Prompts removed here, by their identifier in `removed`, stay removed.
+pub fn merge(
+    prompts: &mut Vec<Prompt>,
+    remote: RemoteLibrary,
+    removed: &HashSet<String>,
+) -> SyncMerge {
+    assign_ids(prompts);
+
+    let mut merge = SyncMerge {
+        version: remote.version,
+        ..Default::default()
+    };
+    // Prompts are only removed by a sync once the library was synced before
+    let first_sync = remote.library.is_none();
+    let remote_prompts: Vec<Prompt> = remote
+        .library
+        .map(|library| library.prompts)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|prompt| !prompt.sync_id.is_empty())
+        .collect();
+    let remote_order: Vec<String> = remote_prompts
+        .iter()
+        .map(|prompt| prompt.sync_id.clone())
+        .collect();
+    let mut remote_prompts: HashMap<String, Prompt> = remote_prompts
+        .into_iter()
+        .map(|prompt| (prompt.sync_id.clone(), prompt))
+        .collect();
+    let mut merged = Vec::with_capacity(prompts.len());
+
+    for mut prompt in prompts.drain(..) {
+        let mine = fingerprint(&prompt);
+
+        // Synced by an earlier version and unchanged since
+        if prompt.synced_fingerprint == Some(legacy_fingerprint(&prompt)) {
+            prompt.synced_fingerprint = Some(mine);
+        }
+
+        let Some(theirs) = remote_prompts.remove(&prompt.sync_id) else {
+            match prompt.synced_fingerprint {
+                // Removed by the other machine, and not changed here since
+                Some(synced) if synced == mine && !first_sync => merge.removed += 1,
+                // Changed here after the other machine removed it, so it is synced again
+                Some(_) => {
+                    prompt.synced_fingerprint = None;
+                    merged.push(prompt);
+                }
+                None => merged.push(prompt),
+            }
+
+            continue;
+        };
+
+        let their_fingerprint = fingerprint(&theirs);
+
+        if mine == their_fingerprint {
+            prompt.synced_fingerprint = Some(mine);
+        } else if prompt.synced_fingerprint == Some(mine) {
+            PromptCard::from_prompt(&theirs).apply_to(&mut prompt);
+            prompt.synced_fingerprint = Some(their_fingerprint);
+            merge.updated += 1;
+        } else if prompt.synced_fingerprint != Some(their_fingerprint) {
+            merge.conflicts.push(SyncConflict {
+                sync_id: prompt.sync_id.clone(),
+                theirs: PromptCard::from_prompt(&theirs),
+                resolution: ConflictResolution::default(),
+            });
+        }
+
+        prompt.merge_history(theirs);
+        merged.push(prompt);
+    }
+
+    for sync_id in remote_order {
+        if removed.contains(&sync_id) {
+            continue;
+        }
+
+        if let Some(mut theirs) = remote_prompts.remove(&sync_id) {
+            theirs.synced_fingerprint = Some(fingerprint(&theirs));
+            merged.push(theirs);
+            merge.added += 1;
+        }
+    }
+
+    *prompts = merged;
+
+    merge
+}
+
+/// Applies the chosen resolutions, the prompts being pushed as they are afterwards
+pub fn resolve(prompts: &mut Vec<Prompt>, conflicts: Vec<SyncConflict>) {
+    for conflict in conflicts {
+        let Some(prompt) = prompts
+            .iter_mut()
+            .find(|prompt| prompt.sync_id == conflict.sync_id)
+        else {
+            continue;
+        };
+
+        match conflict.resolution {
+            ConflictResolution::KeepMine => {}
+            ConflictResolution::TakeTheirs => conflict.theirs.apply_to(prompt),
+            ConflictResolution::KeepBoth => {
+                let mut copy = conflict.theirs.into_prompt(prompts.len());
+
+                copy.title = format!("{} (other machine)", copy.title);
+                copy.sync_id = new_id();
+                prompts.push(copy);
+            }
+        }
+    }
+}
+
+/// Fingerprints of the prompts by their identifier, recorded as synced once they were pushed
+pub fn fingerprints(prompts: &[Prompt]) -> HashMap<String, u64> {
+    prompts
+        .iter()
+        .map(|prompt| (prompt.sync_id.clone(), fingerprint(prompt)))
+        .collect()
+}
+
+/// Records the pushed prompts as synced, as they were when pushed
+pub fn mark_synced(prompts: &mut [Prompt], pushed: &HashMap<String, u64>) {
+    for prompt in prompts {
+        if let Some(&fingerprint) = pushed.get(&prompt.sync_id) {
+            prompt.synced_fingerprint = Some(fingerprint);
+        }
+    }
+}
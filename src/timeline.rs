@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Days, Local, NaiveDate};
+use egui::{Color32, Label, RichText, ScrollArea, Sense};
+
+use crate::{
+    app::AppAction,
+    history::{self, PromptResponse},
+    prompt::Prompt,
+};
+
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Weeks shown by the activity calendar, a year like on code forges
+const CALENDAR_WEEKS: u64 = 53;
+const CALENDAR_CELL_SIZE: f32 = 11.0;
+const CALENDAR_CELL_GAP: f32 = 2.0;
+
+/// Filters of the timeline of the responses of all prompts, empty ones matching everything
+#[derive(Default)]
+pub struct TimelineFilter {
+    pub model: Option<String>,
+    pub prompt: Option<usize>,
+    pub day: Option<NaiveDate>,
+    /// Text of the day field, only applied once it is a valid date
+    day_input: String,
+}
+
+impl TimelineFilter {
+    pub fn show_day(&mut self, day: NaiveDate) {
+        self.day = Some(day);
+        self.day_input = day.format(DAY_FORMAT).to_string();
+    }
+
+    fn matches(&self, idx: usize, response: &PromptResponse) -> bool {
+        self.model
+            .as_ref()
+            .is_none_or(|model| *model == response.local_model_name)
+            && self.prompt.is_none_or(|prompt| prompt == idx)
+            && self
+                .day
+                .is_none_or(|day| day == response.created_at.date_naive())
+    }
+}
+
+/// Shows the responses of all prompts, the newest first
+pub fn show(
+    ui: &mut egui::Ui,
+    filter: &mut TimelineFilter,
+    prompts: &[Prompt],
+) -> Option<AppAction> {
+    let mut action = None;
+
+    let models: BTreeSet<&str> = prompts
+        .iter()
+        .flat_map(Prompt::responses)
+        .map(|response| response.local_model_name.as_str())
+        .collect();
+    let mut entries: Vec<(usize, &PromptResponse)> = prompts
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, prompt)| prompt.responses().map(move |response| (idx, response)))
+        .filter(|(idx, response)| filter.matches(*idx, response))
+        .collect();
+    entries.sort_by_key(|(_, response)| std::cmp::Reverse(response.created_at));
+
+    ui.add_space(12.0);
+    ui.heading("🕑 Timeline");
+    ui.add_space(6.0);
+
+    egui::CollapsingHeader::new("Activity")
+        .id_salt("timeline_activity")
+        .default_open(true)
+        .show(ui, |ui| {
+            let activity = history::activity_by_day(prompts.iter().flat_map(Prompt::responses));
+
+            show_calendar(ui, filter, &activity);
+        });
+
+    ui.add_space(6.0);
+
+    ui.horizontal_wrapped(|ui| {
+        egui::ComboBox::from_id_salt("timeline_model")
+            .selected_text(filter.model.as_deref().unwrap_or("All models"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.model, None, "All models");
+
+                for model in &models {
+                    ui.selectable_value(&mut filter.model, Some(model.to_string()), *model);
+                }
+            });
+
+        egui::ComboBox::from_id_salt("timeline_prompt")
+            .selected_text(
+                filter
+                    .prompt
+                    .and_then(|idx| prompts.get(idx))
+                    .map_or("All prompts", |prompt| prompt.title.as_str()),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.prompt, None, "All prompts");
+
+                for (idx, prompt) in prompts.iter().enumerate() {
+                    ui.selectable_value(&mut filter.prompt, Some(idx), &prompt.title);
+                }
+            });
+
+        let day = ui.add(
+            egui::TextEdit::singleline(&mut filter.day_input)
+                .hint_text("YYYY-MM-DD")
+                .desired_width(90.0),
+        );
+
+        if day.changed() {
+            filter.day = NaiveDate::parse_from_str(filter.day_input.trim(), DAY_FORMAT).ok();
+        }
+
+        if !filter.day_input.trim().is_empty() && filter.day.is_none() {
+            ui.label(RichText::new("Invalid date").color(ui.style().visuals.error_fg_color));
+        }
+
+        if ui.button("Today").clicked() {
+            filter.show_day(chrono::Local::now().date_naive());
+        }
+
+        if ui.button("Clear filters").clicked() {
+            *filter = TimelineFilter::default();
+        }
+    });
+
+    ui.add_space(6.0);
+    ui.label(RichText::new(format!("{} responses", entries.len())).weak());
+    ui.separator();
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Body) + ui.spacing().item_spacing.y;
+
+    ScrollArea::vertical().auto_shrink(false).show_rows(
+        ui,
+        row_height,
+        entries.len(),
+        |ui, range| {
+            for (idx, response) in &entries[range] {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(response.created_at.format("%Y-%m-%d %H:%M").to_string())
+                            .monospace()
+                            .weak(),
+                    );
+
+                    if ui
+                        .link(&prompts[*idx].title)
+                        .on_hover_text("Show the response in its prompt")
+                        .clicked()
+                    {
+                        action = Some(AppAction::ShowHistoryEntry {
+                            idx: *idx,
+                            created_at: response.created_at,
+                        });
+                    }
+
+                    ui.label(RichText::new(&response.local_model_name).weak());
+                    ui.add(
+                        Label::new(response.output.lines().next().unwrap_or_default()).truncate(),
+                    )
+                    .on_hover_text(&response.input);
+                });
+            }
+        },
+    );
+
+    action
+}
+
+/// Shows the number of responses per day over the last year, clicking a day filters the timeline
+fn show_calendar(
+    ui: &mut egui::Ui,
+    filter: &mut TimelineFilter,
+    activity: &BTreeMap<NaiveDate, usize>,
+) {
+    let today = Local::now().date_naive();
+    let start = today
+        - Days::new((CALENDAR_WEEKS - 1) * 7 + u64::from(today.weekday().num_days_from_monday()));
+    let busiest = activity
+        .range(start..)
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or_default();
+
+    let step = CALENDAR_CELL_SIZE + CALENDAR_CELL_GAP;
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(CALENDAR_WEEKS as f32 * step, 7.0 * step),
+        Sense::hover(),
+    );
+    let empty = ui.style().visuals.faint_bg_color;
+    let active = Color32::from_rgb(0x40, 0xa0, 0x50);
+
+    for (offset, day) in start
+        .iter_days()
+        .take_while(|day| *day <= today)
+        .enumerate()
+    {
+        let count = activity.get(&day).copied().unwrap_or_default();
+        let cell = egui::Rect::from_min_size(
+            rect.min + egui::vec2((offset / 7) as f32 * step, (offset % 7) as f32 * step),
+            egui::Vec2::splat(CALENDAR_CELL_SIZE),
+        );
+        let color = if count == 0 {
+            empty
+        } else {
+            // At least a quarter of the intensity so that quiet days remain visible
+            empty.lerp_to_gamma(active, 0.25 + 0.75 * count as f32 / busiest as f32)
+        };
+
+        let response = ui
+            .interact(cell, ui.id().with(("activity_day", day)), Sense::click())
+            .on_hover_text(format!("{count} responses on {}", day.format(DAY_FORMAT)));
+
+        if response.clicked() {
+            filter.show_day(day);
+        }
+
+        let stroke = if filter.day == Some(day) {
+            egui::Stroke::new(1.5, ui.style().visuals.strong_text_color())
+        } else {
+            egui::Stroke::NONE
+        };
+
+        ui.painter()
+            .rect(cell, 2.0, color, stroke, egui::StrokeKind::Inside);
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+/// Share of the context window a request may fill before it likely doesn't fit, the estimate
+/// being off by a few percent depending on the model
+const ESTIMATE_MARGIN: f64 = 0.9;
+
+/// Time the request has to stay unchanged before its tokens are counted again, so that typing
+/// doesn't count them on every key press
+pub const RECOUNT_DELAY: Duration = Duration::from_millis(300);
+
+/// Estimates the tokens of the text.
+///
+/// Ollama has no endpoint to tokenize with the vocabulary of a model, so the text is counted with
+/// the `cl100k_base` encoding, close enough for most models. The encoding takes a moment to
+/// build, see [`warm_up`].
+pub fn estimate(text: &str) -> usize {
+    tiktoken_rs::cl100k_base_singleton()
+        .encode_ordinary(text)
+        .len()
+}
+
+/// Builds the encoding ahead of the first estimate, which would otherwise wait for it
+pub fn warm_up() {
+    tiktoken_rs::cl100k_base_singleton();
+}
+
+/// Whether the estimated tokens likely exceed the context window
+pub fn exceeds(tokens: usize, context_window: u64) -> bool {
+    tokens as f64 > context_window as f64 * ESTIMATE_MARGIN
+}
+
+/// Estimated tokens of a request that changes as it's typed, identified by a key that changes
+/// with it
+#[derive(Default)]
+pub struct Estimate {
+    /// Key of the counted request, with its tokens
+    counted: Option<(u64, usize)>,
+    /// Key of the request since it last changed, with when it did
+    changed: Option<(u64, Instant)>,
+}
+
+impl Estimate {
+    /// The tokens of the request with the key, its text only being made when it's counted. The
+    /// previous count is kept until the request stayed unchanged for [`RECOUNT_DELAY`], the
+    /// first one isn't delayed.
+    pub fn tokens(&mut self, key: u64, now: Instant, text: impl FnOnce() -> String) -> usize {
+        match self.counted {
+            Some((counted, tokens)) if counted == key => return tokens,
+            _ => {}
+        }
+
+        let changed_at = match self.changed {
+            Some((changed, changed_at)) if changed == key => changed_at,
+            _ => {
+                self.changed = Some((key, now));
+                now
+            }
+        };
+
+        match self.counted {
+            Some((_, tokens)) if now.duration_since(changed_at) < RECOUNT_DELAY => tokens,
+            _ => {
+                let tokens = estimate(&text());
+
+                self.counted = Some((key, tokens));
+                self.changed = None;
+                tokens
+            }
+        }
+    }
+
+    /// Whether the request changed since it was counted, and will be counted again
+    pub fn is_pending(&self) -> bool {
+        self.changed.is_some()
+    }
+}
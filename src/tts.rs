@@ -0,0 +1,263 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+/// Runs the text-to-speech command, feeding `text` on stdin and writing a WAV file to `output`.
+///
+/// The command is split on whitespace, `{output}` is replaced with the output path.
+pub fn synthesize(command: &str, text: &str, output: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut child = spawn(command, &[("{output}", output.display().to_string())])?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    anyhow::ensure!(
+        status.success(),
+        "text-to-speech command exited with {status}"
+    );
+
+    Ok(())
+}
+
+fn spawn(command: &str, replacements: &[(&str, String)]) -> anyhow::Result<Child> {
+    let mut parts = command.split_whitespace().map(|part| {
+        replacements
+            .iter()
+            .fold(part.to_string(), |part, (from, to)| part.replace(from, to))
+    });
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("the command is empty"))?;
+
+    Ok(Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?)
+}
+
+/// Reads the duration of a PCM WAV file from its `fmt ` and `data` chunks
+pub fn wav_duration(path: &Path) -> anyhow::Result<Duration> {
+    let bytes = fs::read(path)?;
+
+    anyhow::ensure!(
+        bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE",
+        "not a WAV file"
+    );
+
+    let read_u32 = |offset: usize| -> io::Result<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+    };
+
+    let mut offset = 12;
+    let mut byte_rate = None;
+
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = read_u32(offset + 4)?;
+
+        match id {
+            b"fmt " => byte_rate = Some(read_u32(offset + 16)?),
+            b"data" => {
+                let byte_rate = byte_rate.filter(|rate| *rate > 0).ok_or_else(|| {
+                    anyhow::anyhow!("the WAV file has no format chunk before its data")
+                })?;
+                // Streaming encoders write a placeholder size, fall back to the actual file length
+                let size = size.min((bytes.len() - offset - 8) as u32);
+
+                return Ok(Duration::from_secs_f64(size as f64 / byte_rate as f64));
+            }
+            _ => {}
+        }
+
+        offset += 8 + size as usize + (size as usize & 1);
+    }
+
+    anyhow::bail!("the WAV file has no data chunk")
+}
+
+/// The sentences of a spoken text with an estimate of when each of them starts.
+///
+/// The audio is assumed to progress proportionally to the number of characters.
+pub struct ReadAlong<'a> {
+    pub sentences: Vec<&'a str>,
+    /// Start of each sentence as a fraction of the whole text
+    starts: Vec<f32>,
+}
+
+impl<'a> ReadAlong<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((idx, c)) = chars.next() {
+            let ends_sentence = match c {
+                '\n' => true,
+                // A line break right after the punctuation ends the sentence on its own
+                '.' | '!' | '?' => chars
+                    .peek()
+                    .is_none_or(|(_, next)| next.is_whitespace() && *next != '\n'),
+                _ => false,
+            };
+
+            if ends_sentence {
+                let end = idx + c.len_utf8();
+                sentences.push(&text[start..end]);
+                start = end;
+            }
+        }
+
+        sentences.push(&text[start..]);
+        sentences.retain(|sentence| !sentence.trim().is_empty());
+
+        let total = sentences
+            .iter()
+            .map(|sentence| sentence.chars().count())
+            .sum::<usize>()
+            .max(1) as f32;
+        let starts = sentences
+            .iter()
+            .scan(0, |offset, sentence| {
+                let start = *offset as f32 / total;
+                *offset += sentence.chars().count();
+                Some(start)
+            })
+            .collect();
+
+        Self { sentences, starts }
+    }
+
+    /// Returns the index of the sentence spoken at `progress`, a fraction of the whole audio
+    pub fn sentence_at(&self, progress: f32) -> usize {
+        self.starts
+            .iter()
+            .rposition(|start| *start <= progress)
+            .unwrap_or_default()
+    }
+
+    /// Returns the start of a sentence as a fraction of the whole audio
+    pub fn start_of(&self, sentence: usize) -> f32 {
+        self.starts.get(sentence).copied().unwrap_or_default()
+    }
+}
+
+/// Plays a cached response audio file with an external player, restarting it on seek
+pub struct AudioPlayer {
+    pub response_created_at: DateTime<Local>,
+    path: PathBuf,
+    duration: Duration,
+    position: Duration,
+    started_at: Option<Instant>,
+    child: Option<Child>,
+}
+
+impl AudioPlayer {
+    pub fn new(path: PathBuf, response_created_at: DateTime<Local>) -> anyhow::Result<Self> {
+        Ok(Self {
+            response_created_at,
+            duration: wav_duration(&path)?,
+            path,
+            position: Duration::ZERO,
+            started_at: None,
+            child: None,
+        })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns whether playback was started and not rewound yet, even if it is paused
+    pub fn is_started(&mut self) -> bool {
+        self.is_playing() || self.position > Duration::ZERO
+    }
+
+    pub fn position(&self) -> Duration {
+        let elapsed = self
+            .started_at
+            .map(|started_at| started_at.elapsed())
+            .unwrap_or_default();
+
+        (self.position + elapsed).min(self.duration)
+    }
+
+    /// Returns whether the player is still running, rewinding once it reaches the end
+    pub fn is_playing(&mut self) -> bool {
+        if let Some(child) = self.child.as_mut()
+            && !matches!(child.try_wait(), Ok(None))
+        {
+            self.child = None;
+            self.started_at = None;
+            self.position = Duration::ZERO;
+        }
+
+        self.child.is_some()
+    }
+
+    /// Starts the player command at the current position.
+    ///
+    /// `{file}` is replaced with the audio path and `{start}` with the position in seconds.
+    pub fn play(&mut self, command: &str) -> anyhow::Result<()> {
+        self.stop_child();
+
+        self.child = Some(spawn(
+            command,
+            &[
+                ("{file}", self.path.display().to_string()),
+                ("{start}", format!("{:.2}", self.position.as_secs_f32())),
+            ],
+        )?);
+        self.started_at = Some(Instant::now());
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self) {
+        self.position = self.position();
+        self.started_at = None;
+        self.stop_child();
+    }
+
+    pub fn seek(&mut self, position: Duration, command: &str) -> anyhow::Result<()> {
+        let was_playing = self.is_playing();
+
+        self.pause();
+        self.position = position.min(self.duration);
+
+        if was_playing {
+            self.play(command)?;
+        }
+
+        Ok(())
+    }
+
+    fn stop_child(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for AudioPlayer {
+    fn drop(&mut self) {
+        self.stop_child();
+    }
+}
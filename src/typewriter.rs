@@ -0,0 +1,38 @@
+use std::time::Instant;
+
+/// Longest the revealed text lags behind the streamed one, the rate goes up beyond it
+const MAX_LAG_SECS: f64 = 1.0;
+
+/// Reveals a streamed text at a steady rate rather than in the bursts it arrives in
+#[derive(Debug, Clone)]
+pub struct Typewriter {
+    /// Characters revealed so far, fractional so that slow frames don't lose any
+    revealed: f64,
+    updated_at: Instant,
+}
+
+impl Default for Typewriter {
+    fn default() -> Self {
+        Self {
+            revealed: 0.0,
+            updated_at: Instant::now(),
+        }
+    }
+}
+
+impl Typewriter {
+    /// Advances the revealed part of the text, returning its length in bytes
+    pub fn reveal(&mut self, text: &str, chars_per_sec: f64) -> usize {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.updated_at = now;
+
+        let total = text.chars().count() as f64;
+        let rate = chars_per_sec.max((total - self.revealed) / MAX_LAG_SECS);
+        self.revealed = (self.revealed + rate * elapsed).min(total);
+
+        text.char_indices()
+            .nth(self.revealed as usize)
+            .map_or(text.len(), |(idx, _)| idx)
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+use egui::RichText;
+
+use crate::{app::AppAction, history::PromptResponse, prompt::Prompt};
+
+/// Distance of the undo toast from the bottom of the window
+const TOAST_MARGIN: f32 = 24.0;
+
+/// A prompt or history entry that was removed, kept in memory while it can be restored
+pub enum Removal {
+    Prompt {
+        /// Position the prompt was listed at
+        idx: usize,
+        prompt: Box<Prompt>,
+    },
+    History {
+        /// Sync identifier of the prompt the entry was removed from, which stays valid when the
+        /// prompts are reordered or removed in the meantime
+        sync_id: String,
+        title: String,
+        response: Box<PromptResponse>,
+    },
+}
+
+impl Removal {
+    fn label(&self) -> String {
+        match self {
+            Self::Prompt { prompt, .. } => format!("Removed the prompt \"{}\"", prompt.title),
+            Self::History { title, .. } => format!("Removed a response of \"{title}\""),
+        }
+    }
+
+    /// Deletes the files of the removal once it can no longer be undone
+    fn discard(self) {
+        if let Self::History { response, .. } = self {
+            response.discard();
+        }
+    }
+}
+
+/// Removals that can still be undone, the latest last
+#[derive(Default)]
+pub struct UndoStack {
+    removals: Vec<(Removal, Instant)>,
+}
+
+impl UndoStack {
+    /// Keeps the removal for `period`, discarding it right away when undo is disabled
+    pub fn push(&mut self, removal: Removal, period: Duration) {
+        if period.is_zero() {
+            removal.discard();
+        } else {
+            self.removals.push((removal, Instant::now()));
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Removal> {
+        self.removals.pop().map(|(removal, _)| removal)
+    }
+
+    /// Discards the removals kept longer than `period`
+    pub fn expire(&mut self, period: Duration) {
+        let (expired, kept) = std::mem::take(&mut self.removals)
+            .into_iter()
+            .partition(|(_, removed_at)| removed_at.elapsed() >= period);
+
+        self.removals = kept;
+
+        for (removal, _) in expired {
+            removal.discard();
+        }
+    }
+}
+
+/// Offers to undo the latest removal at the bottom of the window until it expires
+pub fn show_toast(
+    ctx: &egui::Context,
+    undo: &mut UndoStack,
+    period: Duration,
+) -> Option<AppAction> {
+    let mut action = None;
+
+    undo.expire(period);
+
+    let (removal, removed_at) = undo.removals.last()?;
+    let remaining = period.saturating_sub(removed_at.elapsed());
+
+    egui::Area::new(egui::Id::new("undo_toast"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -TOAST_MARGIN])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(removal.label());
+
+                    if ui
+                        .button("Undo")
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text("Restore it, also with Ctrl+Z")
+                        .clicked()
+                    {
+                        action = Some(AppAction::Undo);
+                    }
+
+                    ui.label(RichText::new(format!("{}s", remaining.as_secs() + 1)).weak());
+                });
+            });
+        });
+
+    // Counts down and hides the toast once the removal expires
+    ctx.request_repaint_after(remaining.min(Duration::from_secs(1)));
+
+    action
+}
@@ -1,7 +1,35 @@
-use egui::{Button, Color32, Layout, WidgetText};
+use std::{collections::HashSet, path::PathBuf};
+
+use chrono::{DateTime, Local};
+use egui::{Button, Color32, Key, Layout, RichText, WidgetText};
 use egui_modal::{Icon, Modal, ModalStyle};
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use ollama_rs::models::LocalModel;
 
-use crate::{app::AppAction, assign_if_some, prompt::Prompt};
+use crate::{
+    app::AppAction,
+    assign_if_some,
+    backend::BackendKind,
+    card::PromptCard,
+    compose::{FewShotExample, PromptTemplate},
+    config,
+    group::PromptGroup,
+    hardware, history,
+    icons::{self, IconButton},
+    judge,
+    library::{LibraryImport, LibraryImportMode},
+    maintenance::{self, LibraryReport},
+    model_check::{MissingModels, ModelOnboarding},
+    ollama::PullProgress,
+    patch::{PatchKind, PatchPreview},
+    prompt::{self, Prompt, PromptAppearance, PromptKind},
+    recovery::StorageRecovery,
+    script::{ImportStrategy, ScriptImport},
+    settings::{EndpointTls, ExtraHeader, Settings},
+    snippet::Snippet,
+    sync::{ConflictResolution, SyncMerge},
+    viewer,
+};
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 #[serde(default)]
@@ -14,15 +42,11 @@ pub struct View {
 enum ViewModal {
     #[default]
     None,
-    AddPrompt {
-        title: String,
-        content: String,
-    },
+    AddPrompt(PromptForm),
     RemovePrompt(usize),
     EditPrompt {
         idx: usize,
-        title: String,
-        content: String,
+        form: PromptForm,
     },
     RemovePromptHistory {
         idx: usize,
@@ -32,6 +56,173 @@ enum ViewModal {
         title: String,
         message: String,
     },
+    Settings(Box<Settings>),
+    QuickOpen {
+        query: String,
+        selected: usize,
+        /// Lists the palette commands along with the prompts
+        commands: bool,
+    },
+    /// Reverse search over the past inputs, recalled into the input of the prompt
+    InputSearch {
+        idx: usize,
+        query: String,
+        selected: usize,
+        /// Searches the inputs of all prompts rather than only the ones of the prompt
+        all_prompts: bool,
+    },
+    Maintenance(LibraryReport),
+    ImportScript(ScriptImport),
+    ExportPrompts {
+        include_history: bool,
+    },
+    ImportPrompts(LibraryImport),
+    SyncConflicts(SyncMerge),
+    Recovery(StorageRecovery),
+    ApplyPatch(PatchPreview),
+    MissingModels(MissingModels),
+    ModelOnboarding(ModelOnboarding),
+    RunSnippet {
+        idx: usize,
+        history_idx: usize,
+        snippet: Snippet,
+        command_line: String,
+    },
+}
+
+const QUICK_OPEN_MAX_RESULTS: usize = 12;
+
+/// Actions of the command palette, listed along with the prompts
+#[derive(Debug, Clone)]
+pub enum PaletteCommand {
+    NewPrompt,
+    SwitchModel(LocalModel),
+    ToggleTheme,
+    FocusInput,
+    ReloadModels,
+    OpenSettings,
+}
+
+impl PaletteCommand {
+    fn all(models: &[LocalModel]) -> Vec<Self> {
+        let mut commands = vec![
+            Self::NewPrompt,
+            Self::ToggleTheme,
+            Self::FocusInput,
+            Self::ReloadModels,
+            Self::OpenSettings,
+        ];
+        commands.extend(models.iter().cloned().map(Self::SwitchModel));
+
+        commands
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Self::NewPrompt => "New prompt".to_string(),
+            Self::SwitchModel(model) => format!("Switch model: {}", model.name),
+            Self::ToggleTheme => "Toggle theme".to_string(),
+            Self::FocusInput => "Focus the input".to_string(),
+            Self::ReloadModels => "Reload models".to_string(),
+            Self::OpenSettings => "Open settings".to_string(),
+        }
+    }
+
+    fn shortcut(&self) -> Option<&'static str> {
+        match self {
+            Self::NewPrompt => Some("Ctrl+N"),
+            Self::FocusInput => Some("Ctrl+L"),
+            _ => None,
+        }
+    }
+
+    fn action(self) -> AppAction {
+        match self {
+            Self::NewPrompt => AppAction::OpenAddPromptDialog,
+            Self::SwitchModel(model) => AppAction::SelectOllamaModel(model),
+            Self::ToggleTheme => AppAction::ToggleTheme,
+            Self::FocusInput => AppAction::FocusInput,
+            Self::ReloadModels => AppAction::ReloadOllamaModels,
+            Self::OpenSettings => AppAction::OpenSettingsDialog,
+        }
+    }
+}
+
+/// An entry of the quick open and the command palette
+enum PaletteItem {
+    Prompt(usize),
+    Command(PaletteCommand),
+}
+
+/// Editable copy of the prompt settings shown in the add and edit dialogs
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct PromptForm {
+    pub title: String,
+    pub content: String,
+    pub kind: PromptKind,
+    pub appearance: PromptAppearance,
+    pub template: PromptTemplate,
+    pub examples: Vec<FewShotExample>,
+    /// One banned word or phrase per line
+    pub banned_phrases: String,
+    pub regenerate_on_banned_phrases: bool,
+    /// Path to a markdown style guide, empty when none is attached
+    pub style_guide: String,
+    pub judge_rubric: String,
+    /// Name of the group, ungrouped when empty
+    pub group: String,
+    pub tags: Vec<String>,
+    /// Tag being typed, added on Enter or when the form is applied
+    #[serde(skip)]
+    pub new_tag: String,
+}
+
+impl PromptForm {
+    fn from_prompt(prompt: &Prompt) -> Self {
+        Self {
+            title: prompt.title.clone(),
+            content: prompt.content.clone(),
+            kind: prompt.kind,
+            appearance: prompt.appearance.clone(),
+            template: prompt.template.clone(),
+            examples: prompt.examples.clone(),
+            banned_phrases: prompt.banned_phrases.join("\n"),
+            regenerate_on_banned_phrases: prompt.regenerate_on_banned_phrases,
+            style_guide: prompt
+                .style_guide
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            judge_rubric: prompt.judge_rubric.clone(),
+            group: prompt.group.clone(),
+            tags: prompt.tags.clone(),
+            new_tag: String::new(),
+        }
+    }
+
+    pub fn apply(&self, prompt: &mut Prompt) {
+        prompt.title = self.title.clone();
+        prompt.content = self.content.clone();
+        prompt.kind = self.kind;
+        prompt.appearance = self.appearance.clone();
+        prompt.template = self.template.clone();
+        prompt.examples = self.examples.clone();
+        prompt.banned_phrases = self
+            .banned_phrases
+            .lines()
+            .map(|phrase| phrase.trim().to_string())
+            .filter(|phrase| !phrase.is_empty())
+            .collect();
+        prompt.regenerate_on_banned_phrases = self.regenerate_on_banned_phrases;
+        prompt.style_guide = Some(self.style_guide.trim())
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from);
+        prompt.judge_rubric = self.judge_rubric.trim().to_string();
+        prompt.group = self.group.trim().to_string();
+        prompt.tags = self.tags.clone();
+        prompt::push_tags(&mut prompt.tags, &self.new_tag);
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
@@ -39,6 +230,12 @@ pub enum ViewMainPanel {
     #[default]
     Welcome,
     Prompt(usize),
+    /// A read-only prompt of the shared source, by its position in the shared list
+    SharedPrompt(usize),
+    /// Responses of all prompts in chronological order
+    Timeline,
+    /// Passages clipped from the responses
+    Clippings,
 }
 
 impl View {
@@ -51,22 +248,23 @@ impl View {
     }
 
     pub fn open_add_prompt_modal(&mut self) {
-        self.modal = ViewModal::AddPrompt {
+        self.modal = ViewModal::AddPrompt(PromptForm {
             title: String::with_capacity(256),
             content: String::with_capacity(1024),
-        };
+            ..Default::default()
+        });
     }
 
-    pub fn get_add_prompt_modal_data_mut(&mut self) -> Option<(&mut String, &mut String)> {
+    pub fn get_add_prompt_modal_data_mut(&mut self) -> Option<&mut PromptForm> {
         match &mut self.modal {
-            ViewModal::AddPrompt { title, content } => Some((title, content)),
+            ViewModal::AddPrompt(form) => Some(form),
             _ => None,
         }
     }
 
-    pub fn get_add_prompt_modal_data(&self) -> Option<(&String, &String)> {
+    pub fn get_add_prompt_modal_data(&self) -> Option<&PromptForm> {
         match &self.modal {
-            ViewModal::AddPrompt { title, content } => Some((title, content)),
+            ViewModal::AddPrompt(form) => Some(form),
             _ => None,
         }
     }
@@ -78,25 +276,20 @@ impl View {
     pub fn open_edit_prompt_modal(&mut self, idx: usize, prompt: &Prompt) {
         self.modal = ViewModal::EditPrompt {
             idx,
-            title: prompt.title.clone(),
-            content: prompt.content.clone(),
+            form: PromptForm::from_prompt(prompt),
         };
     }
 
-    pub fn get_edit_prompt_modal_data_mut(&mut self) -> Option<(&mut String, &mut String)> {
+    pub fn get_edit_prompt_modal_data_mut(&mut self) -> Option<&mut PromptForm> {
         match &mut self.modal {
-            ViewModal::EditPrompt { title, content, .. } => Some((title, content)),
+            ViewModal::EditPrompt { form, .. } => Some(form),
             _ => None,
         }
     }
 
-    pub fn get_edit_prompt_modal_data(&self) -> Option<(usize, &String, &String)> {
+    pub fn get_edit_prompt_modal_data(&self) -> Option<(usize, &PromptForm)> {
         match &self.modal {
-            ViewModal::EditPrompt {
-                idx,
-                title,
-                content,
-            } => Some((*idx, title, content)),
+            ViewModal::EditPrompt { idx, form } => Some((*idx, form)),
             _ => None,
         }
     }
@@ -109,6 +302,42 @@ impl View {
         self.modal = ViewModal::ErrorDialog { title, message };
     }
 
+    pub fn open_settings_modal(&mut self, settings: &Settings) {
+        self.modal = ViewModal::Settings(Box::new(settings.clone()));
+    }
+
+    pub fn get_settings_modal_data(&self) -> Option<&Settings> {
+        match &self.modal {
+            ViewModal::Settings(settings) => Some(settings),
+            _ => None,
+        }
+    }
+
+    pub fn get_settings_modal_data_mut(&mut self) -> Option<&mut Settings> {
+        match &mut self.modal {
+            ViewModal::Settings(settings) => Some(settings),
+            _ => None,
+        }
+    }
+
+    /// Opens the quick open, or the command palette when `commands` is set
+    pub fn open_quick_open_modal(&mut self, commands: bool) {
+        self.modal = ViewModal::QuickOpen {
+            query: String::new(),
+            selected: 0,
+            commands,
+        };
+    }
+
+    pub fn open_input_search_modal(&mut self, idx: usize) {
+        self.modal = ViewModal::InputSearch {
+            idx,
+            query: String::new(),
+            selected: 0,
+            all_prompts: false,
+        };
+    }
+
     pub fn select_prompt(&mut self, idx: usize) {
         self.main_panel = ViewMainPanel::Prompt(idx);
     }
@@ -121,17 +350,18 @@ impl View {
         &mut self,
         ui: &mut egui::Ui,
         edit_prompt_modal: &Modal,
+        groups: &[PromptGroup],
     ) -> Option<AppAction> {
         let mut action = None;
 
-        if let Some((title, content)) = self.get_edit_prompt_modal_data_mut() {
+        if let Some(form) = self.get_edit_prompt_modal_data_mut() {
             assign_if_some!(
                 action,
                 Self::show_modify_prompt_modal(
                     ui,
                     edit_prompt_modal,
-                    title,
-                    content,
+                    form,
+                    groups,
                     AppAction::EditPrompt,
                     "Save",
                 )
@@ -144,20 +374,128 @@ impl View {
     fn show_modify_prompt_modal(
         ui: &mut egui::Ui,
         modal: &Modal,
-        title: &mut String,
-        content: &mut String,
+        form: &mut PromptForm,
+        groups: &[PromptGroup],
         ok_action: AppAction,
         ok_button_name: &str,
     ) -> Option<AppAction> {
         let mut action = None;
+        let PromptForm {
+            title,
+            content,
+            kind,
+            appearance,
+            template,
+            examples,
+            banned_phrases,
+            regenerate_on_banned_phrases,
+            style_guide,
+            judge_rubric,
+            group,
+            tags,
+            new_tag,
+        } = form;
 
         modal.frame(ui, |ui| {
             let text_width = Self::get_modify_prompt_modal_width(ui.ctx());
 
-            egui::TextEdit::singleline(title)
-                .hint_text("Write the title of your prompt here")
-                .desired_width(text_width)
-                .show(ui);
+            ui.horizontal(|ui| {
+                egui::TextEdit::singleline(&mut appearance.icon)
+                    .hint_text("🗒")
+                    .char_limit(4)
+                    .desired_width(24.0)
+                    .show(ui)
+                    .response
+                    .on_hover_text("Icon shown next to the title, e.g. an emoji");
+
+                let mut has_color = appearance.color.is_some();
+
+                if ui
+                    .checkbox(&mut has_color, "")
+                    .on_hover_text("Accent color")
+                    .changed()
+                {
+                    appearance.color = has_color.then_some(Color32::ORANGE.into());
+                }
+
+                if let Some(color) = appearance.color.as_mut() {
+                    let mut edited = Color32::from(*color);
+
+                    if ui.color_edit_button_srgba(&mut edited).changed() {
+                        *color = edited.into();
+                    }
+                }
+
+                egui::TextEdit::singleline(title)
+                    .hint_text("Write the title of your prompt here")
+                    .desired_width(ui.available_width())
+                    .show(ui);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Generates:");
+                ui.radio_value(kind, PromptKind::Text, "text");
+                ui.radio_value(kind, PromptKind::Image, "images")
+                    .on_hover_text("Uses the Stable Diffusion WebUI configured in the settings");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Group:");
+
+                ui.add_enabled_ui(!groups.is_empty(), |ui| {
+                    icons::menu_button(
+                        ui,
+                        IconButton::new(icons::Icon::Expand).frame(true),
+                        |ui| {
+                            for name in groups.iter().map(|group| &group.name) {
+                                if ui.selectable_label(group == name, name).clicked() {
+                                    group.clone_from(name);
+                                    ui.close_menu();
+                                }
+                            }
+                        },
+                    )
+                    .response
+                    .on_hover_text("Pick an existing group");
+                });
+
+                egui::TextEdit::singleline(group)
+                    .hint_text("None, type a name to create a group")
+                    .desired_width(ui.available_width())
+                    .show(ui);
+            });
+
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Tags:");
+
+                let mut remove = None;
+
+                for (idx, tag) in tags.iter().enumerate() {
+                    if prompt::tag_chip(ui, tag)
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text("Remove the tag")
+                        .clicked()
+                    {
+                        remove = Some(idx);
+                    }
+                }
+
+                if let Some(idx) = remove {
+                    tags.remove(idx);
+                }
+
+                let response = egui::TextEdit::singleline(new_tag)
+                    .hint_text("Add tags, comma-separated")
+                    .desired_width(180.0)
+                    .show(ui)
+                    .response;
+
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    prompt::push_tags(tags, new_tag);
+                    new_tag.clear();
+                    response.request_focus();
+                }
+            });
 
             egui::TextEdit::multiline(content)
                 .desired_rows(10)
@@ -166,6 +504,59 @@ impl View {
                 )
                 .desired_width(text_width)
                 .show(ui);
+
+            if *kind == PromptKind::Text {
+                Self::show_few_shot_examples_editor(ui, examples, text_width);
+
+                egui::CollapsingHeader::new("Banned words")
+                    .id_salt("banned_phrases")
+                    .show(ui, |ui| {
+                        egui::TextEdit::multiline(banned_phrases)
+                            .desired_rows(3)
+                            .hint_text("One word or phrase per line")
+                            .desired_width(text_width)
+                            .show(ui);
+
+                        ui.checkbox(
+                            regenerate_on_banned_phrases,
+                            "Regenerate once when a response uses them",
+                        );
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label("Style guide:");
+
+                    if ui
+                        .add(IconButton::new(icons::Icon::Folder).frame(true))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text("Pick a markdown file")
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .set_title("Attach style guide")
+                            .add_filter("Markdown", &["md", "markdown", "txt"])
+                            .pick_file()
+                    {
+                        *style_guide = path.display().to_string();
+                    }
+
+                    egui::TextEdit::singleline(style_guide)
+                        .hint_text("Markdown file embedded into every request")
+                        .desired_width(ui.available_width())
+                        .show(ui);
+                });
+
+                egui::CollapsingHeader::new("Judge rubric")
+                    .id_salt("judge_rubric")
+                    .show(ui, |ui| {
+                        egui::TextEdit::multiline(judge_rubric)
+                            .desired_rows(3)
+                            .hint_text(judge::DEFAULT_RUBRIC)
+                            .desired_width(text_width)
+                            .show(ui);
+                    });
+
+                Self::show_prompt_template_editor(ui, template, content, examples, text_width);
+            }
         });
 
         ui.with_layout(
@@ -203,21 +594,139 @@ impl View {
         action
     }
 
+    fn show_few_shot_examples_editor(
+        ui: &mut egui::Ui,
+        examples: &mut Vec<FewShotExample>,
+        text_width: f32,
+    ) {
+        let mut remove_idx = None;
+
+        egui::CollapsingHeader::new(format!("Examples ({})", examples.len()))
+            .id_salt("few_shot_examples")
+            .show(ui, |ui| {
+                let column_width = (text_width - 40.0) / 2.0;
+
+                for (idx, example) in examples.iter_mut().enumerate() {
+                    ui.horizontal_top(|ui| {
+                        egui::TextEdit::multiline(&mut example.input)
+                            .desired_rows(2)
+                            .hint_text("Example input")
+                            .desired_width(column_width)
+                            .show(ui);
+
+                        egui::TextEdit::multiline(&mut example.output)
+                            .desired_rows(2)
+                            .hint_text("Expected output")
+                            .desired_width(column_width)
+                            .show(ui);
+
+                        if ui
+                            .add(IconButton::new(icons::Icon::Remove))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Remove example")
+                            .clicked()
+                        {
+                            remove_idx = Some(idx);
+                        }
+                    });
+                }
+
+                if ui
+                    .add(
+                        IconButton::new(icons::Icon::Add)
+                            .text("Add example")
+                            .frame(true),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Included as a demonstration in every request")
+                    .clicked()
+                {
+                    examples.push(FewShotExample::default());
+                }
+            });
+
+        if let Some(idx) = remove_idx {
+            examples.remove(idx);
+        }
+    }
+
+    /// Lets the user pick how requests are composed and previews the result
+    fn show_prompt_template_editor(
+        ui: &mut egui::Ui,
+        template: &mut PromptTemplate,
+        content: &str,
+        examples: &[FewShotExample],
+        text_width: f32,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Request layout:");
+
+            egui::ComboBox::from_id_salt("prompt_template_selector")
+                .selected_text(template.label())
+                .show_ui(ui, |ui| {
+                    for option in [
+                        PromptTemplate::ContentFirst,
+                        PromptTemplate::InputFirst,
+                        PromptTemplate::ContentAsSystem,
+                    ] {
+                        let label = option.label();
+                        ui.selectable_value(template, option, label);
+                    }
+
+                    if ui
+                        .selectable_label(matches!(template, PromptTemplate::Custom(_)), "custom")
+                        .clicked()
+                        && !matches!(template, PromptTemplate::Custom(_))
+                    {
+                        *template =
+                            PromptTemplate::Custom(PromptTemplate::DEFAULT_CUSTOM.to_string());
+                    }
+                });
+        });
+
+        if let PromptTemplate::Custom(custom) = template {
+            egui::TextEdit::multiline(custom)
+                .desired_rows(2)
+                .hint_text("Use {content} and {input} placeholders")
+                .desired_width(text_width)
+                .show(ui);
+        }
+
+        let preview = template.compose(content, examples, "<input>");
+
+        ui.collapsing("Preview", |ui| {
+            egui::Frame::group(ui.style())
+                .fill(ui.style().visuals.faint_bg_color)
+                .show(ui, |ui| {
+                    ui.set_width(text_width);
+
+                    if let Some(system) = &preview.system {
+                        ui.label(RichText::new("System:").strong());
+                        ui.label(RichText::new(system).monospace());
+                        ui.separator();
+                    }
+
+                    ui.label(RichText::new(&preview.prompt).monospace());
+                });
+        });
+    }
+
     pub fn show_add_prompt_modal(
         &mut self,
         ui: &mut egui::Ui,
         add_prompt_modal: &Modal,
+        groups: &[PromptGroup],
     ) -> Option<AppAction> {
         let mut action = None;
 
-        if let Some((title, content)) = self.get_add_prompt_modal_data_mut() {
+        if let Some(form) = self.get_add_prompt_modal_data_mut() {
             assign_if_some!(
                 action,
                 Self::show_modify_prompt_modal(
                     ui,
                     add_prompt_modal,
-                    title,
-                    content,
+                    form,
+                    groups,
                     AppAction::CreatePrompt,
                     "Create",
                 )
@@ -308,6 +817,1708 @@ impl View {
         action
     }
 
+    pub fn show_settings_modal(&mut self, ui: &mut egui::Ui, modal: &Modal) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::Settings(settings) = &mut self.modal {
+            modal.title(ui, "Settings");
+
+            modal.frame(ui, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Backend").on_hover_text(
+                            "The server the text models answer from",
+                        );
+                        egui::ComboBox::from_id_salt("settings_backend")
+                            .selected_text(settings.backend.label())
+                            .show_ui(ui, |ui| {
+                                for kind in BackendKind::ALL {
+                                    ui.selectable_value(&mut settings.backend, kind, kind.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        if settings.backend == BackendKind::OpenAi {
+                            ui.label("OpenAI-compatible URL").on_hover_text(
+                                "LM Studio, vLLM, the llama.cpp server or https://api.openai.com/v1",
+                            );
+                            egui::TextEdit::singleline(&mut settings.openai_url)
+                                .hint_text("http://127.0.0.1:1234/v1")
+                                .show(ui);
+                            ui.end_row();
+
+                            ui.label("API key")
+                                .on_hover_text("Kept in the OS keyring, local servers mostly need none");
+                            egui::TextEdit::singleline(&mut settings.openai_api_key)
+                                .password(true)
+                                .show(ui);
+                            ui.end_row();
+
+                            ui.label("OpenAI-compatible TLS");
+                            Self::show_endpoint_tls_settings(ui, &mut settings.openai_tls);
+                            ui.end_row();
+
+                            ui.label("OpenAI-compatible headers");
+                            Self::show_extra_headers_editor(
+                                ui,
+                                "openai_headers",
+                                &mut settings.openai_headers,
+                            );
+                            ui.end_row();
+                        }
+
+                        ui.label("Ollama URL");
+                        ui.text_edit_singleline(&mut settings.ollama_url);
+                        ui.end_row();
+
+                        ui.label("Ollama TLS");
+                        Self::show_endpoint_tls_settings(ui, &mut settings.ollama_tls);
+                        ui.end_row();
+
+                        ui.label("Ollama headers");
+                        Self::show_extra_headers_editor(
+                            ui,
+                            "ollama_headers",
+                            &mut settings.ollama_headers,
+                        );
+                        ui.end_row();
+
+                        ui.label("Stable Diffusion WebUI URL");
+                        ui.text_edit_singleline(&mut settings.stable_diffusion_url);
+                        ui.end_row();
+
+                        ui.label("Stable Diffusion TLS");
+                        Self::show_endpoint_tls_settings(ui, &mut settings.stable_diffusion_tls);
+                        ui.end_row();
+
+                        ui.label("Stable Diffusion headers");
+                        Self::show_extra_headers_editor(
+                            ui,
+                            "stable_diffusion_headers",
+                            &mut settings.stable_diffusion_headers,
+                        );
+                        ui.end_row();
+
+                        ui.label("Proxy URL").on_hover_text(
+                            "Used for all providers, HTTP_PROXY and HTTPS_PROXY are respected when empty",
+                        );
+                        egui::TextEdit::singleline(&mut settings.proxy_url)
+                            .hint_text("http://proxy.example.com:8080")
+                            .show(ui);
+                        ui.end_row();
+
+                        ui.label("Text-to-speech command").on_hover_text(
+                            "Receives the response on stdin and writes a WAV file to {output}",
+                        );
+                        ui.text_edit_singleline(&mut settings.tts_command);
+                        ui.end_row();
+
+                        ui.label("Audio player command")
+                            .on_hover_text("Plays {file} starting at {start} seconds");
+                        ui.text_edit_singleline(&mut settings.audio_player_command);
+                        ui.end_row();
+
+                        ui.label("Run snippets").on_hover_text(
+                            "Offer to run the shell and Python snippets of the responses, \
+                             after confirmation",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut settings.run_snippets, "");
+                            ui.add_enabled(
+                                settings.run_snippets,
+                                egui::DragValue::new(&mut settings.snippet_timeout_secs)
+                                    .range(1..=300)
+                                    .suffix(" s timeout"),
+                            );
+                        });
+                        ui.end_row();
+
+                        ui.label("Snippet languages")
+                            .on_hover_text("Languages snippets may be run in: sh, bash, python");
+                        ui.add_enabled(
+                            settings.run_snippets,
+                            egui::TextEdit::singleline(&mut settings.snippet_languages),
+                        );
+                        ui.end_row();
+
+                        ui.label("Allowed shell commands")
+                            .on_hover_text("Shell snippets using other commands are refused");
+                        ui.add_enabled(
+                            settings.run_snippets,
+                            egui::TextEdit::multiline(&mut settings.snippet_shell_commands)
+                                .desired_rows(2),
+                        );
+                        ui.end_row();
+
+                        ui.label("Sandbox command").on_hover_text(
+                            "Prefix of the interpreter command, snippets only run once it's set, e.g. \
+                             \"bwrap --ro-bind /usr /usr --symlink usr/bin /bin --symlink usr/lib /lib \
+                             --symlink usr/lib64 /lib64 --bind /tmp /tmp --unshare-all --die-with-parent\"",
+                        );
+                        ui.add_enabled(
+                            settings.run_snippets,
+                            egui::TextEdit::singleline(&mut settings.snippet_sandbox_command),
+                        );
+                        ui.end_row();
+
+                        ui.label("Editor command").on_hover_text(
+                            "Opens {file} with the response, $VISUAL or $EDITOR when empty.\n\
+                             Terminal editors need a terminal, e.g. \"kitty nvim {file}\"",
+                        );
+                        ui.text_edit_singleline(&mut settings.editor_command);
+                        ui.end_row();
+
+                        ui.label("Generation watchdog").on_hover_text(
+                            "Flag generations running longer than this, 0 disables the watchdog",
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut settings.generation_watchdog_secs)
+                                .range(0..=3600)
+                                .suffix(" s"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Undo period").on_hover_text(
+                            "How long removed prompts and responses can be restored with Ctrl+Z, 0 disables undo",
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut settings.undo_secs)
+                                .range(0..=3600)
+                                .suffix(" s"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Retries").on_hover_text(
+                            "Retry a generation that fails to connect, waiting 1 s, then 2 s, 4 s and so on, \
+                             before showing the error. Stop the generation to cancel the retries",
+                        );
+                        ui.add(egui::DragValue::new(&mut settings.generation_retries).range(0..=10));
+                        ui.end_row();
+
+                        ui.label("Max output tokens").on_hover_text(
+                            "Cap the tokens of an answer, 0 leaves it to the model",
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut settings.max_output_tokens)
+                                .range(0..=131_072)
+                                .suffix(" tokens"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Auto-continue").on_hover_text(
+                            "Ask the model to go on when an answer is cut off by the length limit, \
+                             joining the parts, up to this many times",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut settings.auto_continue, "");
+                            ui.add_enabled(
+                                settings.auto_continue,
+                                egui::DragValue::new(&mut settings.auto_continue_max)
+                                    .range(1..=20)
+                                    .suffix(" times"),
+                            );
+                        });
+                        ui.end_row();
+
+                        ui.label("Warm up models").on_hover_text(
+                            "Load the selected model when a prompt is selected, so that the first request starts faster",
+                        );
+                        ui.checkbox(&mut settings.warm_up_on_select, "");
+                        ui.end_row();
+
+                        ui.label("UI font").on_hover_text(
+                            "A font file or the name of an installed font, e.g. \"Noto Sans\". \
+                             The bundled font is used when empty and for the glyphs the font lacks",
+                        );
+                        Self::show_font_settings(
+                            ui,
+                            &mut settings.fonts.ui_font,
+                            &mut settings.fonts.ui_size,
+                        );
+                        ui.end_row();
+
+                        ui.label("Code font").on_hover_text(
+                            "A monospace font file or the name of an installed one, \
+                             e.g. \"JetBrains Mono\"",
+                        );
+                        Self::show_font_settings(
+                            ui,
+                            &mut settings.fonts.code_font,
+                            &mut settings.fonts.code_size,
+                        );
+                        ui.end_row();
+
+                        ui.label("Shared prompts").on_hover_text(
+                            "A URL of an exported library, or a folder of prompt cards and libraries, shown read-only in the prompt list",
+                        );
+                        ui.horizontal(|ui| {
+                            egui::TextEdit::singleline(&mut settings.shared_source)
+                                .hint_text("https://example.com/prompts.json")
+                                .show(ui);
+                            ui.add(
+                                egui::DragValue::new(&mut settings.shared_refresh_mins)
+                                    .range(1..=1440)
+                                    .prefix("every ")
+                                    .suffix(" min"),
+                            );
+                        });
+                        ui.end_row();
+
+                        ui.label("Sync").on_hover_text(
+                            "A folder on a synced drive or a WebDAV URL, to sync the prompts with other machines",
+                        );
+                        egui::TextEdit::singleline(&mut settings.sync_target)
+                            .hint_text("https://dav.example.com/reprompt")
+                            .show(ui);
+                        ui.end_row();
+
+                        ui.label("Sync headers");
+                        Self::show_extra_headers_editor(ui, "sync_headers", &mut settings.sync_headers);
+                        ui.end_row();
+
+                        ui.label("Spell check").on_hover_text(
+                            "Underline the misspelled words of the input, with a Hunspell dictionary",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut settings.spell_check, "");
+                            ui.add_enabled(
+                                settings.spell_check,
+                                egui::TextEdit::singleline(&mut settings.spell_check_dictionary)
+                                    .hint_text("Dictionary of the locale"),
+                            );
+
+                            if ui
+                                .add_enabled(settings.spell_check, egui::Button::new("…"))
+                                .on_hover_text("Pick a Hunspell .dic file")
+                                .clicked()
+                                && let Some(path) = rfd::FileDialog::new()
+                                    .set_title("Pick a dictionary")
+                                    .add_filter("Hunspell dictionary", &["dic"])
+                                    .pick_file()
+                            {
+                                settings.spell_check_dictionary = path.display().to_string();
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Icon labels").on_hover_text(
+                            "Show the names of the icon buttons next to their icons",
+                        );
+                        ui.checkbox(&mut settings.icon_labels, "");
+                        ui.end_row();
+
+                        ui.label("Smooth streaming").on_hover_text(
+                            "Reveal the streamed responses at a steady rate instead of in chunks",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut settings.smooth_streaming, "");
+                            ui.add_enabled(
+                                settings.smooth_streaming,
+                                egui::DragValue::new(&mut settings.smooth_streaming_chars_per_sec)
+                                    .range(10..=2000)
+                                    .suffix(" chars/s"),
+                            );
+                        });
+                        ui.end_row();
+
+                        ui.label("Demo model").on_hover_text(
+                            "List a \"demo\" model that streams canned responses, for demos without Ollama",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut settings.demo_provider, "");
+                            ui.add_enabled(
+                                settings.demo_provider,
+                                egui::DragValue::new(&mut settings.demo_token_delay_ms)
+                                    .range(0..=1000)
+                                    .suffix(" ms per token"),
+                            );
+                        });
+                        ui.end_row();
+
+                        ui.label("Quote template").on_hover_text(
+                            "Inserted into the input by \"Ask about selection\", {selection} is the selected passage",
+                        );
+                        ui.add(
+                            egui::TextEdit::multiline(&mut settings.quote_template)
+                                .desired_rows(2)
+                                .code_editor(),
+                        );
+                        ui.end_row();
+                    });
+
+                if let Some(path) = config::path() {
+                    ui.add_space(6.0);
+                    ui.label(
+                        RichText::new(format!(
+                            "{} overrides these settings at startup and whenever it changes",
+                            path.display()
+                        ))
+                        .small()
+                        .weak(),
+                    );
+                }
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.suggested_button(ui, "Save").clicked() {
+                    action = Some(AppAction::SaveSettings);
+                }
+
+                if ui
+                    .button("Import profile…")
+                    .on_hover_text("Load settings shared by someone else, to review before saving")
+                    .clicked()
+                {
+                    action = Some(AppAction::ImportSettingsProfile);
+                }
+
+                if ui
+                    .button("Export profile…")
+                    .on_hover_text(
+                        "Save these settings and the theme to share them, without the prompts and the header values",
+                    )
+                    .clicked()
+                {
+                    action = Some(AppAction::ExportSettingsProfile);
+                }
+            });
+        }
+
+        action
+    }
+
+    fn show_font_settings(ui: &mut egui::Ui, font: &mut String, size: &mut f32) {
+        ui.horizontal(|ui| {
+            egui::TextEdit::singleline(font)
+                .hint_text("Bundled")
+                .desired_width(160.0)
+                .show(ui);
+
+            if ui.button("…").on_hover_text("Pick a font file").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .set_title("Pick a font")
+                    .add_filter("Font", &["ttf", "otf"])
+                    .pick_file()
+            {
+                *font = path.display().to_string();
+            }
+
+            ui.add(
+                egui::DragValue::new(size)
+                    .range(8.0..=32.0)
+                    .speed(0.5)
+                    .suffix(" pt"),
+            );
+        });
+    }
+
+    pub fn open_maintenance_modal(&mut self, prompts: &[Prompt]) {
+        self.modal = ViewModal::Maintenance(LibraryReport::analyze(prompts));
+    }
+
+    pub fn open_recovery_modal(&mut self, recovery: StorageRecovery) {
+        self.modal = ViewModal::Recovery(recovery);
+    }
+
+    pub fn show_recovery_modal(&self, ui: &mut egui::Ui, modal: &Modal) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::Recovery(recovery) = &self.modal {
+            let copy = match &recovery.corrupted_copy {
+                Some(path) => format!("A copy of it was kept in {}.", path.display()),
+                None => "Keeping a copy of it failed, see the logs.".to_string(),
+            };
+
+            modal.title(ui, "Stored state could not be loaded");
+            modal.body_and_icon(
+                ui,
+                format!(
+                    "{}\n\n{copy}\n\n{} prompts could be recovered from it.",
+                    recovery.error, recovery.recovered_prompts
+                ),
+                Icon::Warning,
+            );
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Start empty").clicked() {
+                    action = Some(AppAction::DiscardRecoveredPrompts);
+                }
+
+                if recovery.latest_backup.is_some()
+                    && modal
+                        .button(ui, "Restore latest backup")
+                        .on_hover_text("Restore the state as it was when last loaded successfully")
+                        .clicked()
+                {
+                    action = Some(AppAction::RestoreStateBackup);
+                }
+
+                if modal
+                    .suggested_button(ui, "Keep recovered prompts")
+                    .clicked()
+                {
+                    action = Some(AppAction::CloseDialog);
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_missing_models_modal(&mut self, missing: MissingModels) {
+        self.modal = ViewModal::MissingModels(missing);
+    }
+
+    pub fn get_missing_models_modal_data(&self) -> Option<&MissingModels> {
+        match &self.modal {
+            ViewModal::MissingModels(missing) => Some(missing),
+            _ => None,
+        }
+    }
+
+    pub fn get_missing_models_modal_data_mut(&mut self) -> Option<&mut MissingModels> {
+        match &mut self.modal {
+            ViewModal::MissingModels(missing) => Some(missing),
+            _ => None,
+        }
+    }
+
+    pub fn show_missing_models_modal(
+        &mut self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+        available: &[LocalModel],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::MissingModels(missing) = &mut self.modal {
+            modal.title(ui, "Missing models");
+
+            modal.frame(ui, |ui| {
+                ui.label("These models are no longer available on the Ollama server.");
+                ui.add_space(6.0);
+
+                egui::Grid::new("missing_models_grid")
+                    .num_columns(3)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        for model in &mut missing.models {
+                            let mut used_by: Vec<String> = model
+                                .prompts
+                                .iter()
+                                .filter_map(|idx| prompts.get(*idx))
+                                .map(|prompt| format!("\"{}\"", prompt.title))
+                                .collect();
+
+                            if model.selected {
+                                used_by.insert(0, "the selected model".to_string());
+                            }
+
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new(&model.name).strong());
+                                ui.label(
+                                    RichText::new(format!("Used by {}", used_by.join(", ")))
+                                        .small()
+                                        .weak(),
+                                );
+                            });
+
+                            egui::ComboBox::from_id_salt(("missing_model", &model.name))
+                                .selected_text(if model.replacement.is_empty() {
+                                    "Keep"
+                                } else {
+                                    model.replacement.as_str()
+                                })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut model.replacement,
+                                        String::new(),
+                                        "Keep",
+                                    );
+
+                                    for available in available {
+                                        ui.selectable_value(
+                                            &mut model.replacement,
+                                            available.name.clone(),
+                                            &available.name,
+                                        );
+                                    }
+                                });
+
+                            if missing.pulling.as_ref() == Some(&model.name) {
+                                Self::show_pull_progress(ui, missing.progress.as_ref());
+                            } else if ui
+                                .add_enabled(
+                                    missing.pulling.is_none(),
+                                    IconButton::new(icons::Icon::Download)
+                                        .text("Pull")
+                                        .frame(true),
+                                )
+                                .on_hover_text("Download the model from the Ollama library")
+                                .clicked()
+                            {
+                                action = Some(AppAction::PullModel(model.name.clone()));
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(error) = &missing.pull_error {
+                    ui.add_space(6.0);
+                    ui.label(RichText::new(error).color(ui.style().visuals.error_fg_color));
+                }
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Later").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal
+                    .suggested_button(ui, "Remap")
+                    .on_hover_text("Use the picked models instead of the missing ones everywhere")
+                    .clicked()
+                {
+                    action = Some(AppAction::RemapMissingModels);
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_model_onboarding_modal(&mut self, onboarding: ModelOnboarding) {
+        self.modal = ViewModal::ModelOnboarding(onboarding);
+    }
+
+    pub fn get_model_onboarding_modal_data_mut(&mut self) -> Option<&mut ModelOnboarding> {
+        match &mut self.modal {
+            ViewModal::ModelOnboarding(onboarding) => Some(onboarding),
+            _ => None,
+        }
+    }
+
+    pub fn show_model_onboarding_modal(
+        &self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        available: &[LocalModel],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::ModelOnboarding(onboarding) = &self.modal {
+            modal.title(ui, "Recommended models");
+
+            modal.frame(ui, |ui| {
+                match onboarding.hardware.describe() {
+                    Some(memory) => ui.label(format!(
+                        "This machine has {memory}, these models run well on it, \
+                         the best match first."
+                    )),
+                    None => ui.label(
+                        "The memory of this machine couldn't be detected, \
+                         these small models run well almost anywhere.",
+                    ),
+                };
+                ui.add_space(6.0);
+
+                let recommended = hardware::recommend(&onboarding.hardware);
+
+                if recommended.is_empty() {
+                    ui.label("Even the smallest model needs more memory than this machine has.");
+                }
+
+                egui::Grid::new("model_onboarding_grid")
+                    .num_columns(3)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        for model in recommended {
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new(model.name).strong());
+                                ui.label(RichText::new(model.description).small().weak());
+                            });
+
+                            ui.label(format!("{} GB", model.download_gb));
+
+                            if available.iter().any(|local| local.name == model.name) {
+                                ui.label("✔ Installed");
+                            } else if onboarding.pulling.as_deref() == Some(model.name) {
+                                Self::show_pull_progress(ui, onboarding.progress.as_ref());
+                            } else if ui
+                                .add_enabled(
+                                    onboarding.pulling.is_none(),
+                                    IconButton::new(icons::Icon::Download)
+                                        .text("Pull")
+                                        .frame(true),
+                                )
+                                .on_hover_text("Download the model from the Ollama library")
+                                .clicked()
+                            {
+                                action = Some(AppAction::PullModel(model.name.to_string()));
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(error) = &onboarding.pull_error {
+                    ui.add_space(6.0);
+                    ui.label(RichText::new(error).color(ui.style().visuals.error_fg_color));
+                }
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Close").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+            });
+        }
+
+        action
+    }
+
+    fn show_pull_progress(ui: &mut egui::Ui, progress: Option<&PullProgress>) {
+        match progress.and_then(|progress| progress.fraction().map(|fraction| (progress, fraction)))
+        {
+            Some((progress, fraction)) => {
+                ui.add(
+                    egui::ProgressBar::new(fraction)
+                        .desired_width(160.0)
+                        .show_percentage(),
+                )
+                .on_hover_text(&progress.status);
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(progress.map_or("Pulling…", |progress| progress.status.as_str()));
+                });
+            }
+        }
+    }
+
+    pub fn open_apply_patch_modal(&mut self, preview: PatchPreview) {
+        self.modal = ViewModal::ApplyPatch(preview);
+    }
+
+    pub fn get_apply_patch_modal_data(&self) -> Option<&PatchPreview> {
+        match &self.modal {
+            ViewModal::ApplyPatch(preview) => Some(preview),
+            _ => None,
+        }
+    }
+
+    pub fn show_apply_patch_modal(&self, ui: &mut egui::Ui, modal: &Modal) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::ApplyPatch(preview) = &self.modal {
+            modal.title(ui, "Apply diff");
+
+            modal.frame(ui, |ui| {
+                let path = preview.path.display();
+
+                ui.label(match preview.kind {
+                    PatchKind::Modify => {
+                        format!("{path} will be patched, its original content kept in {path}.orig")
+                    }
+                    PatchKind::Create if preview.path.exists() => {
+                        format!("{path} will be replaced, its original content kept in {path}.orig")
+                    }
+                    PatchKind::Create => format!("{path} will be created"),
+                    PatchKind::Delete => {
+                        format!("{path} will be deleted, its content kept in {path}.orig")
+                    }
+                });
+
+                ui.add_space(6.0);
+
+                egui::ScrollArea::both()
+                    .id_salt("apply_patch_diff")
+                    .max_height(ui.ctx().screen_rect().height() * 0.4)
+                    .show(ui, |ui| viewer::show_diff(ui, &preview.diff));
+
+                egui::CollapsingHeader::new("Patched file")
+                    .id_salt("apply_patch_result")
+                    .show(ui, |ui| {
+                        egui::ScrollArea::both()
+                            .max_height(ui.ctx().screen_rect().height() * 0.4)
+                            .show(ui, |ui| {
+                                ui.add(
+                                    egui::TextEdit::multiline(&mut preview.patched.as_str())
+                                        .code_editor()
+                                        .desired_width(f32::INFINITY),
+                                );
+                            });
+                    });
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.suggested_button(ui, "Apply").clicked() {
+                    action = Some(AppAction::ConfirmApplyPatch);
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_run_snippet_modal(
+        &mut self,
+        idx: usize,
+        history_idx: usize,
+        snippet: Snippet,
+        command_line: String,
+    ) {
+        self.modal = ViewModal::RunSnippet {
+            idx,
+            history_idx,
+            snippet,
+            command_line,
+        };
+    }
+
+    pub fn get_run_snippet_modal_data(&self) -> Option<(usize, usize, &Snippet)> {
+        match &self.modal {
+            ViewModal::RunSnippet {
+                idx,
+                history_idx,
+                snippet,
+                ..
+            } => Some((*idx, *history_idx, snippet)),
+            _ => None,
+        }
+    }
+
+    pub fn show_run_snippet_modal(&self, ui: &mut egui::Ui, modal: &Modal) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::RunSnippet {
+            snippet,
+            command_line,
+            ..
+        } = &self.modal
+        {
+            modal.title(ui, format!("Run {} snippet", snippet.language));
+
+            modal.frame(ui, |ui| {
+                ui.label(
+                    "Generated code can be harmful, read it before running it. It runs in an \
+                     empty temporary directory, with a minimal environment and a timeout.",
+                );
+
+                ui.add_space(6.0);
+
+                ui.label(RichText::new(command_line).monospace().weak());
+
+                egui::ScrollArea::both()
+                    .max_height(ui.ctx().screen_rect().height() * 0.4)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut snippet.code.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.caution_button(ui, "Run").clicked() {
+                    action = Some(AppAction::RunSnippet);
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_import_script_modal(&mut self, import: ScriptImport) {
+        self.modal = ViewModal::ImportScript(import);
+    }
+
+    pub fn get_import_script_modal_data(&self) -> Option<&ScriptImport> {
+        match &self.modal {
+            ViewModal::ImportScript(import) => Some(import),
+            _ => None,
+        }
+    }
+
+    pub fn show_import_script_modal(
+        &self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::ImportScript(import) = &self.modal {
+            let existing = import
+                .existing
+                .and_then(|idx| prompts.get(idx))
+                .map(|prompt| prompt.title.as_str())
+                .unwrap_or_default();
+
+            modal.title(ui, "Import script");
+            modal.body_and_icon(
+                ui,
+                format!(
+                    "The prompt \"{existing}\" already has the same content.\n\n\
+                     {} of the {} recorded responses are already in its history.",
+                    import.duplicate_steps,
+                    import.script.steps.len()
+                ),
+                Icon::Info,
+            );
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.button(ui, "Keep both").clicked() {
+                    action = Some(AppAction::ResolveScriptImport(ImportStrategy::KeepBoth));
+                }
+
+                if modal.suggested_button(ui, "Merge").clicked() {
+                    action = Some(AppAction::ResolveScriptImport(ImportStrategy::Merge));
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_export_prompts_modal(&mut self) {
+        self.modal = ViewModal::ExportPrompts {
+            include_history: true,
+        };
+    }
+
+    pub fn show_export_prompts_modal(
+        &mut self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::ExportPrompts { include_history } = &mut self.modal {
+            modal.title(ui, "Export prompts");
+
+            modal.frame(ui, |ui| {
+                ui.label(format!(
+                    "Saves the {} prompts with their settings to a JSON or TOML file.",
+                    prompts.len()
+                ));
+                ui.add_space(6.0);
+                ui.checkbox(include_history, "Include the history");
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.suggested_button(ui, "Export…").clicked() {
+                    action = Some(AppAction::ExportPrompts {
+                        include_history: *include_history,
+                    });
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_import_prompts_modal(&mut self, import: LibraryImport) {
+        self.modal = ViewModal::ImportPrompts(import);
+    }
+
+    /// Takes the loaded library out of the dialog, closing it
+    pub fn take_import_prompts_modal_data(&mut self) -> Option<LibraryImport> {
+        match std::mem::take(&mut self.modal) {
+            ViewModal::ImportPrompts(import) => Some(import),
+            modal => {
+                self.modal = modal;
+                None
+            }
+        }
+    }
+
+    pub fn show_import_prompts_modal(
+        &self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::ImportPrompts(import) = &self.modal {
+            let responses: usize = import
+                .library
+                .prompts
+                .iter()
+                .map(Prompt::history_count)
+                .sum();
+
+            modal.title(ui, "Import prompts");
+            modal.body_and_icon(
+                ui,
+                format!(
+                    "The file has {} prompts with {responses} responses, {} of them have the \
+                     same content as a prompt of the library.\n\n\
+                     Merging adds the new prompts and the missing responses, replacing removes \
+                     the {} prompts of the library.",
+                    import.library.prompts.len(),
+                    import.existing,
+                    prompts.len()
+                ),
+                Icon::Info,
+            );
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.caution_button(ui, "Replace").clicked() {
+                    action = Some(AppAction::ResolvePromptsImport(LibraryImportMode::Replace));
+                }
+
+                if modal.suggested_button(ui, "Merge").clicked() {
+                    action = Some(AppAction::ResolvePromptsImport(LibraryImportMode::Merge));
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn open_sync_conflicts_modal(&mut self, merge: SyncMerge) {
+        self.modal = ViewModal::SyncConflicts(merge);
+    }
+
+    pub fn take_sync_conflicts_modal_data(&mut self) -> Option<SyncMerge> {
+        match std::mem::take(&mut self.modal) {
+            ViewModal::SyncConflicts(merge) => Some(merge),
+            modal => {
+                self.modal = modal;
+                None
+            }
+        }
+    }
+
+    /// The prompts changed on both machines, with the choice of the version to keep
+    pub fn show_sync_conflicts_modal(
+        &mut self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::SyncConflicts(merge) = &mut self.modal {
+            modal.title(ui, "Sync conflicts");
+
+            modal.frame(ui, |ui| {
+                ui.label(format!(
+                    "{} prompt(s) were changed on this machine and on another one since the last \
+                     sync. Their histories are merged either way.",
+                    merge.conflicts.len()
+                ));
+
+                ui.add_space(6.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for conflict in &mut merge.conflicts {
+                            let mine = prompts
+                                .iter()
+                                .find(|prompt| prompt.sync_id == conflict.sync_id);
+
+                            ui.separator();
+                            ui.strong(mine.map_or(conflict.theirs.title.as_str(), |prompt| {
+                                prompt.title.as_str()
+                            }));
+
+                            ui.horizontal(|ui| {
+                                for resolution in ConflictResolution::ALL {
+                                    ui.radio_value(
+                                        &mut conflict.resolution,
+                                        resolution,
+                                        resolution.label(),
+                                    );
+                                }
+                            });
+
+                            ui.columns(2, |columns| {
+                                columns[0].label(RichText::new("Mine").small().weak());
+                                columns[0].label(mine.map_or("", |prompt| prompt.content.as_str()));
+                                columns[1].label(RichText::new("Theirs").small().weak());
+                                if conflict.theirs.title
+                                    != mine.map_or("", |prompt| prompt.title.as_str())
+                                {
+                                    columns[1].label(format!("Title: {}", conflict.theirs.title));
+                                }
+                                columns[1].label(&conflict.theirs.content);
+                            });
+                        }
+                    });
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Cancel").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+
+                if modal.suggested_button(ui, "Sync").clicked() {
+                    action = Some(AppAction::ResolveSyncConflicts);
+                }
+            });
+        }
+
+        action
+    }
+
+    pub fn show_maintenance_modal(
+        &self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        if let ViewModal::Maintenance(report) = &self.modal {
+            modal.title(ui, "Library maintenance");
+
+            modal.frame(ui, |ui| {
+                let words: usize = report.words.iter().sum();
+
+                ui.label(format!(
+                    "{} prompts, {} archived, {} responses in total, {words} words ({})",
+                    report.prompts,
+                    report.archived.len(),
+                    report.responses,
+                    history::reading_time(words)
+                ));
+
+                ui.add_space(6.0);
+
+                if !report.has_suggestions() {
+                    ui.label(RichText::new("Nothing to clean up").weak());
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(ui.ctx().available_rect().height() * 0.6)
+                    .show(ui, |ui| {
+                        let title = |idx: usize| {
+                            prompts
+                                .get(idx)
+                                .map(|prompt| prompt.title.clone())
+                                .unwrap_or_default()
+                        };
+                        let title_of = |sync_id: &str| {
+                            prompts
+                                .iter()
+                                .find(|prompt| prompt.sync_id == sync_id)
+                                .map(|prompt| prompt.title.clone())
+                                .unwrap_or_default()
+                        };
+
+                        if !report.unused.is_empty() {
+                            ui.heading("Never used");
+
+                            for &idx in &report.unused {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add(
+                                            IconButton::new(icons::Icon::Archive)
+                                                .text("Archive")
+                                                .small()
+                                                .frame(true),
+                                        )
+                                        .clicked()
+                                    {
+                                        action = Some(AppAction::ArchivePrompt {
+                                            idx,
+                                            archived: true,
+                                        });
+                                    }
+                                    ui.label(title(idx));
+                                });
+                            }
+                        }
+
+                        if !report.duplicates.is_empty() {
+                            ui.heading("Near duplicates");
+
+                            for duplicate in &report.duplicates {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add(
+                                            IconButton::new(icons::Icon::Merge)
+                                                .text("Merge")
+                                                .small()
+                                                .frame(true),
+                                        )
+                                        .on_hover_text(format!(
+                                            "Move the history of \"{}\" into \"{}\" and remove it",
+                                            title_of(&duplicate.remove),
+                                            title_of(&duplicate.keep)
+                                        ))
+                                        .clicked()
+                                    {
+                                        action = Some(AppAction::MergePrompts {
+                                            keep: duplicate.keep.clone(),
+                                            remove: duplicate.remove.clone(),
+                                        });
+                                    }
+                                    ui.label(format!(
+                                        "{} ← {} ({:.0}% similar)",
+                                        title_of(&duplicate.keep),
+                                        title_of(&duplicate.remove),
+                                        duplicate.similarity * 100.0
+                                    ));
+                                });
+                            }
+                        }
+
+                        if !report.oversized.is_empty() {
+                            ui.heading("Oversized histories");
+
+                            for &idx in &report.oversized {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add(
+                                            IconButton::new(icons::Icon::Cut)
+                                                .text("Prune")
+                                                .small()
+                                                .frame(true),
+                                        )
+                                        .on_hover_text(format!(
+                                            "Keep the {} most recent responses",
+                                            maintenance::PRUNED_HISTORY_LEN
+                                        ))
+                                        .clicked()
+                                    {
+                                        action = Some(AppAction::PrunePromptHistory(idx));
+                                    }
+                                    ui.label(format!(
+                                        "{} ({} responses)",
+                                        title(idx),
+                                        prompts.get(idx).map_or(0, Prompt::history_count)
+                                    ));
+                                });
+                            }
+                        }
+
+                        egui::CollapsingHeader::new("Words per prompt")
+                            .id_salt("maintenance_words")
+                            .show(ui, |ui| {
+                                let mut indices = (0..report.words.len()).collect::<Vec<_>>();
+                                indices.sort_by_key(|&idx| std::cmp::Reverse(report.words[idx]));
+
+                                egui::Grid::new("maintenance_words_grid")
+                                    .num_columns(4)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for idx in indices {
+                                            let words = report.words[idx];
+
+                                            ui.label(title(idx));
+                                            ui.label(format!(
+                                                "{} responses",
+                                                prompts.get(idx).map_or(0, Prompt::history_count)
+                                            ));
+                                            ui.label(format!("{words} words"));
+                                            ui.label(history::reading_time(words));
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+
+                        if !report.archived.is_empty() {
+                            ui.heading("Archived");
+
+                            for &idx in &report.archived {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add(
+                                            IconButton::new(icons::Icon::Restore)
+                                                .text("Restore")
+                                                .small()
+                                                .frame(true),
+                                        )
+                                        .clicked()
+                                    {
+                                        action = Some(AppAction::ArchivePrompt {
+                                            idx,
+                                            archived: false,
+                                        });
+                                    }
+                                    ui.label(title(idx));
+                                });
+                            }
+                        }
+                    });
+            });
+
+            modal.buttons(ui, |ui| {
+                if modal.button(ui, "Close").clicked() {
+                    action = Some(AppAction::CloseDialog);
+                }
+            });
+        }
+
+        action
+    }
+
+    fn show_extra_headers_editor(ui: &mut egui::Ui, id_salt: &str, headers: &mut Vec<ExtraHeader>) {
+        let mut remove_idx = None;
+
+        ui.push_id(id_salt, |ui| {
+            ui.vertical(|ui| {
+                for (idx, header) in headers.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        egui::TextEdit::singleline(&mut header.name)
+                            .hint_text("Authorization")
+                            .desired_width(120.0)
+                            .show(ui);
+
+                        egui::TextEdit::singleline(&mut header.value)
+                            .hint_text("Bearer token or Basic user:password")
+                            .password(true)
+                            .show(ui)
+                            .response
+                            .on_hover_text("🔒 Stored in the OS keyring");
+
+                        if ui
+                            .add(IconButton::new(icons::Icon::Remove))
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Remove header")
+                            .clicked()
+                        {
+                            remove_idx = Some(idx);
+                        }
+                    });
+                }
+
+                if ui
+                    .add(
+                        IconButton::new(icons::Icon::Add)
+                            .text("Add header")
+                            .frame(true),
+                    )
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Sent with every request, e.g. for a reverse proxy")
+                    .clicked()
+                {
+                    headers.push(ExtraHeader::default());
+                }
+            });
+        });
+
+        if let Some(idx) = remove_idx {
+            headers.remove(idx);
+        }
+    }
+
+    fn show_endpoint_tls_settings(ui: &mut egui::Ui, tls: &mut EndpointTls) {
+        ui.horizontal(|ui| {
+            if ui
+                .add(IconButton::new(icons::Icon::Folder).frame(true))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text("Pick a PEM file with the certificates to trust")
+                .clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select CA bundle")
+                    .add_filter("PEM certificates", &["pem", "crt", "cer"])
+                    .pick_file()
+            {
+                tls.ca_bundle = path.display().to_string();
+            }
+
+            egui::TextEdit::singleline(&mut tls.ca_bundle)
+                .hint_text("Custom CA bundle")
+                .show(ui);
+
+            let label_color = if tls.accept_invalid_certs {
+                ui.style().visuals.error_fg_color
+            } else {
+                ui.style().visuals.text_color()
+            };
+
+            ui.checkbox(
+                &mut tls.accept_invalid_certs,
+                RichText::new("Accept invalid certificates").color(label_color),
+            )
+            .on_hover_text(
+                "Trust self-signed and expired certificates. Anyone on the network can intercept the requests.",
+            );
+        });
+    }
+
+    /// A read-only prompt of the shared source, to duplicate into the library to use or change it
+    pub fn show_shared_prompt(
+        ui: &mut egui::Ui,
+        card: &PromptCard,
+        idx: usize,
+    ) -> Option<AppAction> {
+        let mut action = None;
+
+        ui.add_space(12.0);
+
+        ui.horizontal(|ui| {
+            if !card.appearance.icon.is_empty() {
+                ui.heading(&card.appearance.icon);
+            }
+
+            ui.heading(&card.title);
+            ui.label(RichText::new("shared, read-only").small().weak());
+        });
+
+        ui.add_space(6.0);
+
+        if ui
+            .add(
+                IconButton::new(icons::Icon::Copy)
+                    .text("Duplicate to my library")
+                    .frame(true),
+            )
+            .on_hover_text("Copy the prompt into the library, where it can be used and edited")
+            .clicked()
+        {
+            action = Some(AppAction::DuplicateSharedPrompt(idx));
+        }
+
+        ui.add_space(6.0);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut card.content.as_str())
+                    .desired_width(f32::INFINITY)
+                    .code_editor(),
+            );
+        });
+
+        action
+    }
+
+    pub fn show_quick_open_modal(
+        &mut self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+        models: &[LocalModel],
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let mut chosen = None;
+        let mut ran_command = false;
+
+        if let ViewModal::QuickOpen {
+            query,
+            selected,
+            commands,
+        } = &mut self.modal
+        {
+            let (title, hint_text) = if *commands {
+                ("Command Palette", "Type to search prompts and actions")
+            } else {
+                ("Quick Open", "Type to search prompts")
+            };
+            modal.title(ui, title);
+
+            modal.frame(ui, |ui| {
+                let response = egui::TextEdit::singleline(query)
+                    .hint_text(hint_text)
+                    .desired_width(f32::INFINITY)
+                    .show(ui)
+                    .response;
+
+                response.request_focus();
+
+                if response.changed() {
+                    *selected = 0;
+                }
+
+                let palette_commands = if *commands {
+                    PaletteCommand::all(models)
+                } else {
+                    Vec::new()
+                };
+                let mut matches = Self::quick_open_matches(prompts, palette_commands, query);
+
+                ui.input(|i| {
+                    if i.key_pressed(Key::ArrowDown) {
+                        *selected = (*selected + 1).min(matches.len().saturating_sub(1));
+                    } else if i.key_pressed(Key::ArrowUp) {
+                        *selected = selected.saturating_sub(1);
+                    }
+                });
+
+                ui.add_space(6.0);
+
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matches").weak());
+                }
+
+                for (pos, item) in matches.iter().enumerate() {
+                    let text = match item {
+                        PaletteItem::Prompt(idx) => {
+                            let prompt = &prompts[*idx];
+
+                            if prompt.appearance.icon.is_empty() {
+                                prompt.title.clone()
+                            } else {
+                                format!("{} {}", prompt.appearance.icon, prompt.title)
+                            }
+                        }
+                        PaletteItem::Command(command) => match command.shortcut() {
+                            Some(shortcut) => format!("› {}    {shortcut}", command.label()),
+                            None => format!("› {}", command.label()),
+                        },
+                    };
+
+                    if ui
+                        .selectable_label(pos == *selected, text)
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        chosen = Some(pos);
+                    }
+                }
+
+                if ui.input(|i| i.key_pressed(Key::Enter)) && *selected < matches.len() {
+                    chosen = Some(*selected);
+                }
+
+                if let Some(pos) = chosen.filter(|&pos| pos < matches.len()) {
+                    action = Some(match matches.swap_remove(pos) {
+                        PaletteItem::Prompt(idx) => AppAction::QuickOpenPrompt(idx),
+                        PaletteItem::Command(command) => {
+                            modal.close();
+                            ran_command = true;
+                            command.action()
+                        }
+                    });
+                }
+
+                if ui.input(|i| i.key_pressed(Key::Escape)) {
+                    modal.close();
+                    action = Some(AppAction::CloseDialog);
+                }
+            });
+        }
+
+        // The command may open another dialog, so the palette is closed before it runs
+        if ran_command {
+            self.close_modal();
+        }
+
+        action
+    }
+
+    pub fn show_input_search_modal(
+        &mut self,
+        ui: &mut egui::Ui,
+        modal: &Modal,
+        prompts: &[Prompt],
+    ) -> Option<AppAction> {
+        let mut action = None;
+        let mut chosen = None;
+
+        if let ViewModal::InputSearch {
+            idx,
+            query,
+            selected,
+            all_prompts,
+        } = &mut self.modal
+        {
+            modal.title(ui, "Search Inputs");
+
+            modal.frame(ui, |ui| {
+                let response = egui::TextEdit::singleline(query)
+                    .hint_text("Type to search the past inputs")
+                    .desired_width(f32::INFINITY)
+                    .show(ui)
+                    .response;
+
+                response.request_focus();
+
+                // Ctrl+R again widens the search to all prompts, like in a shell
+                let widen = ui.input(|i| i.modifiers.ctrl && i.key_pressed(Key::R));
+
+                if ui
+                    .checkbox(all_prompts, "All prompts")
+                    .on_hover_text("Search the inputs of all prompts (Ctrl+R)")
+                    .changed()
+                    || widen
+                    || response.changed()
+                {
+                    *all_prompts ^= widen;
+                    *selected = 0;
+                }
+
+                let matches = Self::input_search_matches(prompts, *idx, *all_prompts, query);
+
+                ui.input(|i| {
+                    if i.key_pressed(Key::ArrowDown) {
+                        *selected = (*selected + 1).min(matches.len().saturating_sub(1));
+                    } else if i.key_pressed(Key::ArrowUp) {
+                        *selected = selected.saturating_sub(1);
+                    }
+                });
+
+                ui.add_space(6.0);
+
+                if matches.is_empty() {
+                    ui.label(RichText::new("No matches").weak());
+                }
+
+                for (pos, &(prompt_idx, input)) in matches.iter().enumerate() {
+                    let label = prompt::input_label(input);
+                    let text = if *all_prompts {
+                        format!("{label}    · {}", prompts[prompt_idx].title)
+                    } else {
+                        label
+                    };
+
+                    if ui
+                        .selectable_label(pos == *selected, text)
+                        .on_hover_text(input)
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        chosen = Some(pos);
+                    }
+                }
+
+                if ui.input(|i| i.key_pressed(Key::Enter)) && *selected < matches.len() {
+                    chosen = Some(*selected);
+                }
+
+                if let Some(&(_, input)) = chosen.and_then(|pos| matches.get(pos)) {
+                    action = Some(AppAction::RecallInput {
+                        idx: *idx,
+                        input: input.to_string(),
+                    });
+                }
+
+                if ui.input(|i| i.key_pressed(Key::Escape)) {
+                    modal.close();
+                    action = Some(AppAction::CloseDialog);
+                }
+            });
+        }
+
+        action
+    }
+
+    /// Ranks the past inputs by their fuzzy score, the newest first for an empty query or an equal
+    /// score, along with the index of their prompt
+    fn input_search_matches<'a>(
+        prompts: &'a [Prompt],
+        idx: usize,
+        all_prompts: bool,
+        query: &str,
+    ) -> Vec<(usize, &'a str)> {
+        let mut inputs: Vec<(usize, &str, DateTime<Local>)> = prompts
+            .iter()
+            .enumerate()
+            .filter(|&(prompt_idx, _)| all_prompts || prompt_idx == idx)
+            .flat_map(|(prompt_idx, prompt)| {
+                prompt
+                    .past_inputs()
+                    .map(move |(input, created_at)| (prompt_idx, input, created_at))
+            })
+            .collect();
+
+        inputs.sort_by(|(_, _, a), (_, _, b)| b.cmp(a));
+
+        let mut seen = HashSet::new();
+        inputs.retain(|(_, input, _)| seen.insert(*input));
+
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored = inputs
+            .into_iter()
+            .filter_map(|(prompt_idx, input, _)| {
+                let score = if query.is_empty() {
+                    Some(0)
+                } else {
+                    matcher.fuzzy_match(input, query)
+                };
+
+                score.map(|score| ((prompt_idx, input), score))
+            })
+            .collect::<Vec<_>>();
+
+        // Stable, so the newest input stays first among equal scores
+        scored.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        scored
+            .into_iter()
+            .take(QUICK_OPEN_MAX_RESULTS)
+            .map(|(item, _)| item)
+            .collect()
+    }
+
+    /// Ranks prompts and commands by the fuzzy score of their title, keeping the library order
+    /// followed by the commands for an empty query
+    fn quick_open_matches(
+        prompts: &[Prompt],
+        commands: Vec<PaletteCommand>,
+        query: &str,
+    ) -> Vec<PaletteItem> {
+        let items = (0..prompts.len())
+            .filter(|&idx| !prompts[idx].disabled)
+            .map(PaletteItem::Prompt)
+            .chain(commands.into_iter().map(PaletteItem::Command));
+
+        if query.is_empty() {
+            return items.take(QUICK_OPEN_MAX_RESULTS).collect();
+        }
+
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored = items
+            .filter_map(|item| {
+                let score = match &item {
+                    // A prompt is found by its tags too, ranked by whichever matches best
+                    PaletteItem::Prompt(idx) => std::iter::once(&prompts[*idx].title)
+                        .chain(&prompts[*idx].tags)
+                        .filter_map(|text| matcher.fuzzy_match(text, query))
+                        .max(),
+                    PaletteItem::Command(command) => matcher.fuzzy_match(&command.label(), query),
+                };
+
+                score.map(|score| (item, score))
+            })
+            .collect::<Vec<_>>();
+
+        scored.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        scored
+            .into_iter()
+            .take(QUICK_OPEN_MAX_RESULTS)
+            .map(|(item, _)| item)
+            .collect()
+    }
+
     pub fn create_modify_prompt_modal(ctx: &egui::Context, id: String, width: f32) -> Modal {
         let style = ModalStyle {
             default_width: Some(width),
@@ -0,0 +1,125 @@
+use std::hash::Hash;
+
+use egui::{Color32, RichText, ScrollArea};
+
+use crate::content::ContentType;
+
+/// Shows a response in the specialized viewer of its content type, returns false for markdown
+pub fn show(ui: &mut egui::Ui, content: &ContentType, id_salt: impl Hash + Copy) -> bool {
+    match content {
+        ContentType::Markdown => return false,
+        ContentType::Json(value) => {
+            ScrollArea::horizontal()
+                .id_salt(("json_viewer", id_salt))
+                .show(ui, |ui| show_json(ui, None, value, id_salt));
+        }
+        ContentType::Csv(rows) => show_table(ui, rows, id_salt),
+        ContentType::Diff(diff) => show_diff(ui, diff),
+        ContentType::Code { language, code } => {
+            ui.label(RichText::new(language).small().weak());
+            ui.add(
+                egui::TextEdit::multiline(&mut code.as_str())
+                    .code_editor()
+                    .desired_width(f32::INFINITY),
+            );
+        }
+    }
+
+    true
+}
+
+fn show_json(
+    ui: &mut egui::Ui,
+    key: Option<&str>,
+    value: &serde_json::Value,
+    id_salt: impl Hash + Copy,
+) {
+    let prefix = key.map(|key| format!("{key}: ")).unwrap_or_default();
+
+    let children: Vec<(String, &serde_json::Value)> = match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| (idx.to_string(), value))
+            .collect(),
+        leaf => {
+            let color = match leaf {
+                serde_json::Value::String(_) => Color32::from_rgb(0x6a, 0xa8, 0x4f),
+                serde_json::Value::Number(_) => Color32::from_rgb(0x3d, 0x85, 0xc6),
+                _ => ui.style().visuals.weak_text_color(),
+            };
+
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(prefix).monospace());
+                ui.label(RichText::new(leaf.to_string()).monospace().color(color));
+            });
+
+            return;
+        }
+    };
+
+    let summary = if value.is_object() {
+        format!("{prefix}{{{}}}", children.len())
+    } else {
+        format!("{prefix}[{}]", children.len())
+    };
+
+    egui::CollapsingHeader::new(RichText::new(summary).monospace())
+        .id_salt((id_salt, ui.next_auto_id()))
+        .default_open(key.is_none())
+        .show(ui, |ui| {
+            for (key, value) in children {
+                show_json(ui, Some(&key), value, id_salt);
+            }
+        });
+}
+
+fn show_table(ui: &mut egui::Ui, rows: &[Vec<String>], id_salt: impl Hash + Copy) {
+    ScrollArea::horizontal()
+        .id_salt(("csv_viewer", id_salt))
+        .show(ui, |ui| {
+            egui::Grid::new(("csv_table", id_salt))
+                .striped(true)
+                .show(ui, |ui| {
+                    for (row_idx, row) in rows.iter().enumerate() {
+                        for field in row {
+                            if row_idx == 0 {
+                                ui.label(RichText::new(field).strong());
+                            } else {
+                                ui.label(field);
+                            }
+                        }
+
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+pub fn show_diff(ui: &mut egui::Ui, diff: &str) {
+    let visuals = &ui.style().visuals;
+    let added = Color32::from_rgb(0x6a, 0xa8, 0x4f);
+    let removed = visuals.error_fg_color;
+    let hunk = visuals.weak_text_color();
+
+    for line in diff.lines() {
+        let text = RichText::new(line).monospace();
+        let text = if line.starts_with("+++") || line.starts_with("---") {
+            text.strong()
+        } else if line.starts_with('+') {
+            text.color(added)
+        } else if line.starts_with('-') {
+            text.color(removed)
+        } else if line.starts_with("@@") {
+            text.color(hunk)
+        } else {
+            text
+        };
+
+        ui.label(text);
+    }
+}
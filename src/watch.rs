@@ -0,0 +1,101 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::config::CHECK_INTERVAL;
+
+/// Extensions of the text documents that are attached to the input or processed from a folder
+pub const DOCUMENT_EXTENSIONS: [&str; 4] = ["txt", "text", "md", "markdown"];
+
+pub fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| DOCUMENT_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// A folder whose new documents a prompt processes, e.g. meeting notes saved into it
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct FolderWatch {
+    pub dir: PathBuf,
+    /// Names of the documents that were in the folder when it started being watched or that were
+    /// processed since, so that the ones added while the app was closed are processed on startup
+    seen: BTreeSet<String>,
+    /// Size of the new documents at the last check, which are processed once it stops changing
+    /// as they may still be written
+    #[serde(skip)]
+    growing: HashMap<String, u64>,
+    #[serde(skip)]
+    checked_at: Option<Instant>,
+}
+
+impl FolderWatch {
+    /// Watches the folder for the documents added from now on
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        Ok(Self {
+            seen: documents(&dir)?.into_keys().collect(),
+            dir,
+            ..Default::default()
+        })
+    }
+
+    /// The documents added since the last call that are written completely, in the order of their
+    /// names, checked at most every few seconds
+    pub fn poll(&mut self) -> anyhow::Result<Vec<PathBuf>> {
+        if self
+            .checked_at
+            .is_some_and(|checked_at| checked_at.elapsed() < CHECK_INTERVAL)
+        {
+            return Ok(Vec::new());
+        }
+
+        self.checked_at = Some(Instant::now());
+
+        let mut added = BTreeSet::new();
+        let mut growing = HashMap::new();
+
+        for (name, size) in documents(&self.dir)? {
+            if self.seen.contains(&name) {
+                continue;
+            }
+
+            // Empty documents are usually about to be written
+            if size > 0 && self.growing.get(&name) == Some(&size) {
+                added.insert(self.dir.join(&name));
+                self.seen.insert(name);
+            } else {
+                growing.insert(name, size);
+            }
+        }
+
+        self.growing = growing;
+
+        Ok(added.into_iter().collect())
+    }
+}
+
+/// The documents of the folder by name with their size, leaving out hidden files like the ones
+/// editors write while saving
+fn documents(dir: &Path) -> anyhow::Result<HashMap<String, u64>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| anyhow::anyhow!("failed to read {}: {e}", dir.display()))?;
+    let mut documents = HashMap::new();
+
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        if metadata.is_file()
+            && is_document(&entry.path())
+            && let Some(name) = entry.file_name().to_str()
+            && !name.starts_with('.')
+        {
+            documents.insert(name.to_string(), metadata.len());
+        }
+    }
+
+    Ok(documents)
+}
@@ -0,0 +1,77 @@
+use std::{collections::HashMap, fs};
+
+use crate::{app, migration};
+
+/// Width egui gives a side panel that was never resized
+pub const DEFAULT_LEFT_PANEL_WIDTH: f32 = 200.0;
+
+/// Size and position of the window and width of the prompt list, restored at startup
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct WindowGeometry {
+    /// Inner size of the window before it was maximized, in points
+    pub size: Option<[f32; 2]>,
+    /// Outer position of the window before it was maximized, in points
+    pub position: Option<[f32; 2]>,
+    pub maximized: bool,
+    pub left_panel_width: Option<f32>,
+}
+
+impl WindowGeometry {
+    /// Reads the geometry from the stored app state, before the app and its window are created
+    pub fn load() -> Option<Self> {
+        /// The part of the app state the geometry is read from, the other fields are skipped
+        #[derive(serde::Deserialize, Default)]
+        #[serde(default)]
+        struct StoredWindow {
+            window: WindowGeometry,
+        }
+
+        let path = eframe::storage_dir(app::TITLE)?.join("app.ron");
+        let text = fs::read_to_string(path).ok()?;
+        let storage: HashMap<String, String> = ron::from_str(&text).ok()?;
+        let stored: StoredWindow = migration::parse(storage.get(eframe::APP_KEY)?)
+            .inspect_err(|e| log::warn!("Failed to read the window geometry: {e}"))
+            .ok()?;
+
+        Some(stored.window)
+    }
+
+    pub fn apply(&self, mut viewport: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        if let Some(size) = self.size {
+            viewport = viewport.with_inner_size(size);
+        }
+
+        if let Some(position) = self.position {
+            viewport = viewport.with_position(position);
+        }
+
+        viewport.with_maximized(self.maximized)
+    }
+
+    /// Follows the window as it is moved and resized, keeping the last size it had before it
+    /// was maximized or made fullscreen
+    pub fn update(&mut self, ctx: &egui::Context) {
+        ctx.input(|i| {
+            let viewport = i.viewport();
+
+            if viewport.fullscreen == Some(true) {
+                return;
+            }
+
+            self.maximized = viewport.maximized == Some(true);
+
+            if !self.maximized
+                && let Some(rect) = viewport.inner_rect
+            {
+                self.size = Some(rect.size().into());
+            }
+
+            if !self.maximized
+                && let Some(rect) = viewport.outer_rect
+            {
+                self.position = Some(rect.min.into());
+            }
+        });
+    }
+}
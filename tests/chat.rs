@@ -0,0 +1,115 @@
+use chrono::{Duration, Local};
+use reprompt::{
+    chat::{self, UNTITLED},
+    compose::Exchange,
+    history::PromptResponse,
+    prompt::Prompt,
+};
+
+fn conversation_prompt() -> Prompt {
+    let mut prompt = Prompt::new("Chat".to_string(), "You are helpful".to_string(), 0);
+    prompt.conversation = true;
+
+    prompt
+}
+
+#[test]
+fn cleans_up_generated_titles() {
+    assert_eq!(
+        chat::clean_title("\"Rust borrow checker basics.\"\n\nThis title sums it up"),
+        Some("Rust borrow checker basics".to_string())
+    );
+    assert_eq!(
+        chat::clean_title("Title: **Trip to Lisbon**"),
+        Some("Trip to Lisbon".to_string())
+    );
+    assert_eq!(chat::clean_title("  \n\"\"\n"), None);
+
+    let prompt = chat::title_prompt(&Exchange {
+        input: "How do lifetimes work?".to_string(),
+        output: "They describe how long references are valid.".to_string(),
+    });
+
+    assert!(prompt.prompt.contains("How do lifetimes work?"));
+}
+
+#[test]
+fn generates_a_title_once_unless_written() {
+    let mut prompt = conversation_prompt();
+
+    prompt.new_chat_session();
+    let generated = prompt.active_chat_session().unwrap().created_at;
+
+    prompt.set_generated_chat_title(generated, "Lifetimes".to_string());
+    prompt.set_generated_chat_title(generated, "Borrowing".to_string());
+
+    assert_eq!(prompt.active_chat_session().unwrap().title, "Lifetimes");
+
+    prompt.new_chat_session();
+    let written = prompt.active_chat_session().unwrap().created_at;
+
+    prompt.rename_chat_session(written, "Trip to Lisbon");
+    prompt.set_generated_chat_title(written, "Travel plans".to_string());
+
+    assert_eq!(
+        prompt.active_chat_session().unwrap().title,
+        "Trip to Lisbon"
+    );
+}
+
+#[test]
+fn shows_the_responses_of_the_active_conversation() {
+    let mut prompt = conversation_prompt();
+
+    prompt.new_chat_session();
+    let first = prompt.active_chat_session().unwrap().created_at;
+    prompt.new_chat_session();
+    let second = prompt.active_chat_session().unwrap().created_at;
+
+    for (n, chat_session) in [first, second].into_iter().enumerate() {
+        prompt.insert_history(PromptResponse {
+            output: format!("answer {n}"),
+            created_at: Local::now() + Duration::seconds(n as i64),
+            chat_session: Some(chat_session),
+            ..Default::default()
+        });
+    }
+
+    let shown = |prompt: &Prompt| {
+        prompt
+            .responses()
+            .filter(|response| prompt.is_in_active_chat(response))
+            .map(|response| response.output.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(shown(&prompt), ["answer 1"]);
+
+    prompt.select_chat_session(first);
+
+    assert_eq!(shown(&prompt), ["answer 0"]);
+
+    prompt.archive_chat_session(first, true);
+
+    assert!(prompt.active_chat_session().is_none());
+
+    prompt.delete_chat_session(second);
+
+    assert_eq!(prompt.chat_sessions.len(), 1);
+    assert_eq!(prompt.history_count(), 1);
+}
+
+#[test]
+fn renames_a_conversation() {
+    let mut prompt = conversation_prompt();
+
+    prompt.new_chat_session();
+    let created_at = prompt.active_chat_session().unwrap().created_at;
+
+    assert_eq!(prompt.active_chat_session().unwrap().title, UNTITLED);
+
+    prompt.rename_chat_session(created_at, "   ");
+    prompt.rename_chat_session(created_at, "  Lifetimes  ");
+
+    assert_eq!(prompt.active_chat_session().unwrap().title, "Lifetimes");
+}
@@ -0,0 +1,45 @@
+use reprompt::{
+    config::ConfigFile,
+    settings::{ExtraHeader, Settings},
+};
+
+fn settings_with_api_key() -> Settings {
+    Settings {
+        openai_api_key: "sk-stored".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn keeps_the_stored_api_key() {
+    let config = ConfigFile::parse(r#"ollama_url = "http://gpu-box:11434""#).unwrap();
+    let merged = config.apply(&settings_with_api_key()).unwrap();
+
+    assert_eq!(merged.ollama_url, "http://gpu-box:11434");
+    assert_eq!(merged.openai_api_key, "sk-stored");
+}
+
+#[test]
+fn takes_the_api_key_set_by_the_file() {
+    let config = ConfigFile::parse(r#"openai_api_key = "sk-file""#).unwrap();
+    let merged = config.apply(&settings_with_api_key()).unwrap();
+
+    assert_eq!(merged.openai_api_key, "sk-file");
+}
+
+#[test]
+fn keeps_the_stored_openai_header_values() {
+    let settings = Settings {
+        openai_headers: vec![ExtraHeader {
+            name: "X-Team".to_string(),
+            value: "stored".to_string(),
+        }],
+        ..Default::default()
+    };
+    let config = ConfigFile::parse("[openai_tls]\naccept_invalid_certs = true").unwrap();
+    let merged = config.apply(&settings).unwrap();
+
+    assert!(merged.openai_tls.accept_invalid_certs);
+    assert_eq!(merged.openai_headers[0].name, "X-Team");
+    assert_eq!(merged.openai_headers[0].value, "stored");
+}
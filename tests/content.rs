@@ -0,0 +1,39 @@
+use chrono::Local;
+use reprompt::content::{self, HighlightCache};
+
+#[test]
+fn highlights_the_matches_in_the_text() {
+    assert_eq!(
+        content::highlight_matches("The Fox and the fox", "fox"),
+        "The **Fox** and the **fox**"
+    );
+}
+
+#[test]
+fn leaves_code_and_links_untouched() {
+    let markdown = "See [the fox](https://fox.example/fox \"fox\"), <https://fox.example> \
+        and https://fox.example/fox, or `fox`\n\n[a fox][fox]\n\n[fox]: https://fox.example/fox\n";
+
+    assert_eq!(
+        content::highlight_matches(markdown, "fox"),
+        "See [the **fox**](https://fox.example/fox \"fox\"), <https://fox.example> \
+        and https://fox.example/fox, or `fox`\n\n[a **fox**][fox]\n\n[fox]: https://fox.example/fox\n"
+    );
+    assert_eq!(
+        content::highlight_matches("```\nfox\n```\n", "fox"),
+        "```\nfox\n```\n"
+    );
+}
+
+#[test]
+fn highlights_the_output_again_once_it_changes() {
+    let mut cache = HighlightCache::default();
+    let created_at = Local::now();
+
+    assert_eq!(cache.get(created_at, "a fox", "fox"), "a **fox**");
+    assert_eq!(cache.get(created_at, "a fox runs", "fox"), "a **fox** runs");
+    assert_eq!(
+        cache.get(created_at, "a fox runs", "runs"),
+        "a fox **runs**"
+    );
+}
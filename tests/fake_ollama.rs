@@ -0,0 +1,214 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reprompt::{
+    compose::ComposedPrompt,
+    fake_ollama::{FakeOllama, FakeReply},
+    history::StopReason,
+    ollama::{self, OllamaClient},
+};
+
+fn prompt(text: &str) -> ComposedPrompt {
+    ComposedPrompt {
+        system: Some("Be brief".to_string()),
+        prompt: text.to_string(),
+    }
+}
+
+async fn connect(server: &FakeOllama) -> OllamaClient {
+    OllamaClient::new(ollama::connect(&server.url(), reqwest::Client::new()).unwrap())
+}
+
+#[tokio::test]
+async fn lists_models() {
+    let server = FakeOllama::start(
+        vec![
+            FakeOllama::model("llama3:8b", 4_000),
+            FakeOllama::model("llama3:70b", 40_000),
+        ],
+        Vec::new(),
+    )
+    .await
+    .unwrap();
+
+    let models = connect(&server).await.list_models().await.unwrap();
+
+    assert_eq!(
+        models
+            .iter()
+            .map(|model| model.name.as_str())
+            .collect::<Vec<_>>(),
+        ["llama3:8b", "llama3:70b"]
+    );
+}
+
+#[tokio::test]
+async fn keeps_the_path_of_a_proxied_url() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let server = FakeOllama::start_under(
+        "/ollama",
+        vec![model.clone()],
+        vec![FakeReply::stream(&["Hello"], Duration::ZERO)],
+    )
+    .await
+    .unwrap();
+
+    // With or without the trailing slash
+    for url in [server.url(), format!("{}/", server.url())] {
+        let client = OllamaClient::new(ollama::connect(&url, reqwest::Client::new()).unwrap());
+        let models = client.list_models().await.unwrap();
+
+        assert_eq!(models[0].name, "llama3:8b");
+
+        let response = client
+            .generate_completion(prompt("Greet me"), &model, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(response, "Hello");
+    }
+}
+
+#[tokio::test]
+async fn streams_a_completion() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let server = FakeOllama::start(
+        vec![model.clone()],
+        vec![FakeReply::stream(&["Hello", ", ", "world"], Duration::ZERO)],
+    )
+    .await
+    .unwrap();
+    let partials = Arc::new(Mutex::new(Vec::new()));
+
+    let response = connect(&server)
+        .await
+        .generate_completion(prompt("Greet me"), &model, {
+            let partials = partials.clone();
+            move |partial| partials.lock().unwrap().push(partial)
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response, "Hello, world");
+    assert_eq!(partials.lock().unwrap().first().unwrap(), "Hello");
+
+    let requests = server.generate_requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["model"], "llama3:8b");
+    assert_eq!(requests[0]["prompt"], "Greet me");
+    assert_eq!(requests[0]["system"], "Be brief");
+}
+
+#[tokio::test]
+async fn reports_server_errors() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let server = FakeOllama::start(
+        vec![model.clone()],
+        vec![FakeReply::Error {
+            status: 500,
+            message: "model failed to load".to_string(),
+        }],
+    )
+    .await
+    .unwrap();
+
+    let error = connect(&server)
+        .await
+        .generate_completion(prompt("Greet me"), &model, |_| {})
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("model failed to load"));
+}
+
+#[tokio::test]
+async fn cancels_a_generation() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let server = FakeOllama::start(
+        vec![model.clone()],
+        vec![FakeReply::stream(
+            &["one ", "two ", "three ", "four "],
+            Duration::from_millis(200),
+        )],
+    )
+    .await
+    .unwrap();
+    let client = connect(&server).await;
+
+    let generation = tokio::spawn({
+        let client = client.clone();
+        let model = model.clone();
+
+        async move {
+            client
+                .generate_completion(prompt("Count"), &model, |_| {})
+                .await
+        }
+    });
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.cancel_generation();
+
+    let response = generation.await.unwrap().unwrap();
+
+    assert_eq!(response, "one ");
+}
+
+#[tokio::test]
+async fn continues_an_answer_cut_off_by_the_length_limit() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let server = FakeOllama::start(
+        vec![model.clone()],
+        vec![
+            FakeReply::stream(&["Once ", "upon "], Duration::ZERO),
+            FakeReply::stream(&["a time"], Duration::ZERO),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let answer = connect(&server)
+        .await
+        .with_output_limit(2, 3)
+        .generate_answer(prompt("Tell a story"), None, &model, |_| {}, |_, _| {})
+        .await
+        .unwrap();
+
+    assert_eq!(answer.output, "Once upon a time");
+    assert_eq!(answer.stop_reason, Some(StopReason::Stop));
+    assert_eq!(answer.metrics.unwrap().eval_count, 3);
+
+    let requests = server.generate_requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["options"]["num_predict"], 2);
+
+    let messages = requests[1]["messages"].as_array().unwrap();
+    assert_eq!(messages[1]["content"], "Tell a story");
+    assert_eq!(messages[2]["role"], "assistant");
+    assert_eq!(messages[2]["content"], "Once upon ");
+}
+
+#[tokio::test]
+async fn retries_a_request_that_fails_to_connect() {
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    // Nothing listens on the port once the server is dropped
+    let url = FakeOllama::start(Vec::new(), Vec::new())
+        .await
+        .unwrap()
+        .url();
+    let retries = Arc::new(Mutex::new(Vec::new()));
+
+    let error = OllamaClient::new(ollama::connect(&url, reqwest::Client::new()).unwrap())
+        .with_retries(1)
+        .generate_answer(prompt("Greet me"), None, &model, |_| {}, {
+            let retries = retries.clone();
+            move |retry, max_retries| retries.lock().unwrap().push((retry, max_retries))
+        })
+        .await
+        .unwrap_err();
+
+    assert!(!error.to_string().is_empty());
+    assert_eq!(*retries.lock().unwrap(), [(1, 1)]);
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Local, TimeZone};
+use reprompt::feed::{Briefing, FeedItem, FeedSubscription, parse_feed};
+
+const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+  <channel>
+    <title>Release notes</title>
+    <item>
+      <title>Version 1.0</title>
+      <link>https://example.com/1.0</link>
+      <guid>release-1.0</guid>
+      <pubDate>Mon, 13 Jan 2025 10:00:00 +0000</pubDate>
+      <description>The &lt;b&gt;first&lt;/b&gt; release</description>
+    </item>
+    <item>
+      <title>Version 1.1</title>
+      <link>https://example.com/1.1</link>
+      <pubDate>Tue, 14 Jan 2025 10:00:00 +0000</pubDate>
+      <description>Short</description>
+      <content:encoded><![CDATA[<p>Faster</p><p>Smaller</p>]]></content:encoded>
+    </item>
+  </channel>
+</rss>"#;
+
+const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Team blog</title>
+  <entry>
+    <title>Hello</title>
+    <link rel="edit" href="https://example.com/edit/1"/>
+    <link href="https://example.com/hello"/>
+    <id>urn:uuid:1</id>
+    <updated>2025-01-15T08:00:00Z</updated>
+    <summary type="html">Hi &amp;amp; welcome</summary>
+  </entry>
+</feed>"#;
+
+#[test]
+fn reads_rss_items() {
+    let feed = parse_feed(RSS).unwrap();
+
+    assert_eq!(feed.title, "Release notes");
+    assert_eq!(feed.items.len(), 2);
+    assert_eq!(feed.items[0].id, "release-1.0");
+    assert_eq!(feed.items[0].summary, "The first release");
+    // Without a guid the link identifies the item, and the full content wins
+    assert_eq!(feed.items[1].id, "https://example.com/1.1");
+    assert_eq!(feed.items[1].summary, "Faster\nSmaller");
+}
+
+#[test]
+fn reads_atom_entries() {
+    let feed = parse_feed(ATOM).unwrap();
+    let entry = &feed.items[0];
+
+    assert_eq!(feed.title, "Team blog");
+    assert_eq!(entry.id, "urn:uuid:1");
+    assert_eq!(entry.link, "https://example.com/hello");
+    assert_eq!(entry.summary, "Hi & welcome");
+    assert!(entry.published.is_some());
+}
+
+#[test]
+fn takes_only_the_new_items() {
+    let mut subscription = FeedSubscription::new("https://example.com/feed.xml".to_string());
+    let mut feed = parse_feed(RSS).unwrap();
+    let first = subscription.take_new_items(feed.clone());
+
+    assert_eq!(subscription.title, "Release notes");
+    assert_eq!(first[0].title, "Version 1.1");
+    assert_eq!(first.len(), 2);
+
+    feed.items.push(FeedItem {
+        id: "release-1.2".to_string(),
+        title: "Version 1.2".to_string(),
+        ..Default::default()
+    });
+
+    let second = subscription.take_new_items(feed);
+
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].title, "Version 1.2");
+}
+
+#[test]
+fn links_the_summaries_to_their_items() {
+    let feed = parse_feed(RSS).unwrap();
+    let items = feed
+        .items
+        .into_iter()
+        .map(|item| ("Release notes".to_string(), item))
+        .collect();
+    let mut briefing = Briefing::new(items, Vec::new());
+    let created_at: DateTime<Local> = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+
+    assert!(
+        briefing
+            .next_input()
+            .unwrap()
+            .contains("Link: https://example.com/1.0")
+    );
+    briefing.record_response(created_at);
+    briefing.next_input();
+    assert!(!briefing.is_done());
+
+    briefing.record_response(created_at + chrono::Duration::minutes(1));
+    assert!(briefing.is_done());
+
+    let document =
+        briefing.document(|response| (response == created_at).then_some("A first release."));
+
+    assert!(document.contains("\n## Release notes\n"));
+    assert!(document.contains(
+        "\n### [Version 1.0](https://example.com/1.0)\n\n*2025-01-13 10:00*\n\nA first release.\n"
+    ));
+    assert!(document.contains("[Version 1.1](https://example.com/1.1)"));
+}
@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use reprompt::{
+    fake_ollama::{FakeOllama, FakeReply},
+    ollama::{self, OllamaClient},
+    prompt::Prompt,
+};
+use tokio::runtime::Runtime;
+
+/// Polls the prompt like its panel does every frame, until the condition gives a value
+fn poll_until<T>(
+    prompt: &mut Prompt,
+    idx: usize,
+    mut condition: impl FnMut(&mut Prompt) -> Option<T>,
+) -> T {
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    loop {
+        prompt.poll_generations(idx);
+
+        if let Some(value) = condition(prompt) {
+            return value;
+        }
+
+        assert!(Instant::now() < deadline, "the generation didn't finish");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn switches_only_the_stalled_prompt_to_a_smaller_model() {
+    let rt = Runtime::new().unwrap();
+    let large = FakeOllama::model("llama3:70b", 40_000);
+    let small = FakeOllama::model("llama3:8b", 4_000);
+    let server = rt
+        .block_on(FakeOllama::start(
+            vec![large.clone(), small.clone()],
+            vec![FakeReply::stream(
+                &["A", " fox", " jumps", " over", " a", " dog."],
+                Duration::from_millis(50),
+            )],
+        ))
+        .unwrap();
+    let client = OllamaClient::new(ollama::connect(&server.url(), reqwest::Client::new()).unwrap());
+
+    let mut stalled = Prompt::new("Summarize".to_string(), "Summarize the text".to_string(), 0);
+    let mut other = Prompt::new(
+        "Translate".to_string(),
+        "Translate to French".to_string(),
+        1,
+    );
+
+    stalled.generate_response("The first text".to_string(), &large, &rt, &client);
+    other.generate_response("The second text".to_string(), &large, &rt, &client);
+
+    stalled.switch_model(small.clone());
+
+    assert!(!stalled.state.is_generating());
+    assert!(other.state.is_generating());
+
+    poll_until(&mut other, 1, |prompt| {
+        (!prompt.state.is_generating()).then_some(())
+    });
+
+    assert_eq!(other.response_output(0), Some("A fox jumps over a dog."));
+    assert!(!other.responses().next().unwrap().cancelled);
+
+    let (input, model) = poll_until(&mut stalled, 0, Prompt::take_queued_input);
+
+    assert_eq!(input, "The first text");
+    assert_eq!(model.map(|model| model.name), Some(small.name));
+    assert!(stalled.responses().next().unwrap().cancelled);
+}
+
+#[test]
+fn regenerates_an_entry_in_place() {
+    let rt = Runtime::new().unwrap();
+    let model = FakeOllama::model("llama3:8b", 4_000);
+    let delay = Duration::from_millis(10);
+    let server = rt
+        .block_on(FakeOllama::start(
+            vec![model.clone()],
+            vec![
+                FakeReply::stream(&["First", " answer."], delay),
+                FakeReply::stream(&["Second", " answer."], delay),
+                FakeReply::stream(&["Regenerated", " answer."], delay),
+            ],
+        ))
+        .unwrap();
+    let client = OllamaClient::new(ollama::connect(&server.url(), reqwest::Client::new()).unwrap());
+    let mut prompt = Prompt::new("Summarize".to_string(), "Summarize the text".to_string(), 0);
+
+    for input in ["The first text", "The second text"] {
+        prompt.generate_response(input.to_string(), &model, &rt, &client);
+        poll_until(&mut prompt, 0, |prompt| {
+            (!prompt.state.is_generating()).then_some(())
+        });
+    }
+
+    let input = prompt.regenerate_input(1).unwrap();
+    prompt.generate_response(input, &model, &rt, &client);
+    poll_until(&mut prompt, 0, |prompt| {
+        (!prompt.state.is_generating()).then_some(())
+    });
+
+    let entries: Vec<_> = prompt.responses().collect();
+
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].created_at > entries[1].created_at);
+    assert_eq!(entries[0].output, "Second answer.");
+    assert_eq!(entries[1].input, "The first text");
+    assert_eq!(entries[1].output, "Regenerated answer.");
+    assert_eq!(entries[1].versions, ["First answer."]);
+
+    // Undoing the removal of the newer entry puts it back in front
+    let removed = prompt.remove_history(0).unwrap();
+    prompt.insert_history(removed);
+
+    assert_eq!(prompt.response_output(0), Some("Second answer."));
+    assert_eq!(prompt.response_output(1), Some("Regenerated answer."));
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use reprompt::mail::{MailDigest, MailMessage, read_mailbox};
+
+const MULTIPART: &str = "From: =?UTF-8?Q?Ren=C3=A9e_Dupont?= <renee@example.com>
+Subject: =?utf-8?B?UXVhcnRlcmx5?= report
+Date: Tue, 14 Jan 2025 09:30:00 +0100
+MIME-Version: 1.0
+Content-Type: multipart/alternative; boundary=\"sep\"
+
+--sep
+Content-Type: text/html; charset=utf-8
+
+<p>The <b>html</b> part</p>
+--sep
+Content-Type: text/plain; charset=utf-8
+Content-Transfer-Encoding: quoted-printable
+
+The numbers are =
+up by 5 =E2=82=AC.
+--sep--
+";
+
+#[test]
+fn reads_the_text_part_of_a_message() {
+    let message = MailMessage::parse(MULTIPART);
+
+    assert_eq!(message.from, "Renée Dupont <renee@example.com>");
+    assert_eq!(message.subject, "Quarterly report");
+    assert_eq!(message.formatted_date(), "2025-01-14 09:30");
+    assert_eq!(message.body, "The numbers are up by 5 €.");
+}
+
+#[test]
+fn reads_an_mbox_newest_first() {
+    let path = std::env::temp_dir().join(format!("reprompt-mail-{}.mbox", std::process::id()));
+
+    std::fs::write(
+        &path,
+        "From a@example.com Mon Jan 13 10:00:00 2025
+From: a@example.com
+Subject: Older
+Date: Mon, 13 Jan 2025 10:00:00 +0000
+
+>From the archive
+
+From b@example.com Tue Jan 14 10:00:00 2025
+From: b@example.com
+Subject: Newer
+Date: Tue, 14 Jan 2025 10:00:00 +0000
+Content-Type: text/html
+
+<div>Hello&nbsp;there</div>
+",
+    )
+    .unwrap();
+
+    let messages = read_mailbox(&path).unwrap();
+
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].subject, "Newer");
+    assert_eq!(messages[0].body, "Hello there");
+    assert_eq!(messages[1].body, "From the archive");
+}
+
+#[test]
+fn puts_the_summaries_together_once_all_are_generated() {
+    let messages = ["First", "Second", "Third"]
+        .map(|subject| MailMessage {
+            subject: subject.to_string(),
+            ..Default::default()
+        })
+        .to_vec();
+    let mut digest = MailDigest::new("inbox".into(), messages);
+    let created_at: DateTime<Local> = Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+
+    digest.selected.extend([0, 2]);
+    digest.start();
+
+    assert!(digest.next_input().unwrap().contains("Subject: First"));
+    digest.record_response(created_at);
+    assert!(digest.next_input().unwrap().contains("Subject: Third"));
+    assert_eq!(digest.next_input(), None);
+
+    // The last message hasn't started generating
+    digest.finish(|_| None);
+    assert!(digest.is_running());
+
+    digest.record_response(created_at + Duration::minutes(1));
+    digest.finish(|response| (response == created_at).then_some("Summary of the first"));
+
+    assert!(!digest.is_running());
+
+    let document = digest.document.unwrap();
+
+    assert!(document.contains("## First\n\n* · *\n\nSummary of the first\n"));
+    assert!(document.contains("## Third"));
+    assert!(document.contains("*No summary was generated.*"));
+    assert!(!document.contains("Second"));
+}
@@ -0,0 +1,72 @@
+use reprompt::patch::{Patch, PatchKind};
+
+#[test]
+fn takes_lines_looking_like_headers_inside_a_hunk_as_changes() {
+    let diff = "\
+--- a/counter.lua
++++ b/counter.lua
+@@ -1,3 +1,3 @@
+ local count = 0
+--- increment the count
++++ count
+ return count
+";
+    let patch = Patch::parse(diff).unwrap();
+
+    assert_eq!(patch.target.as_deref(), Some("counter.lua"));
+    assert_eq!(patch.kind, PatchKind::Modify);
+    assert_eq!(
+        patch
+            .apply("local count = 0\n-- increment the count\nreturn count\n")
+            .unwrap(),
+        "local count = 0\n++ count\nreturn count\n"
+    );
+}
+
+#[test]
+fn rejects_a_diff_of_several_files() {
+    let diff = "\
+--- a/one.txt
++++ b/one.txt
+@@ -1 +1 @@
+-one
++1
+--- a/two.txt
++++ b/two.txt
+@@ -1 +1 @@
+-two
++2
+";
+
+    assert!(Patch::parse(diff).is_err());
+}
+
+#[test]
+fn creates_a_new_file() {
+    let diff = "\
+--- /dev/null
++++ b/notes.md
+@@ -0,0 +1,2 @@
++# Notes
++
+";
+    let patch = Patch::parse(diff).unwrap();
+
+    assert_eq!(patch.target.as_deref(), Some("notes.md"));
+    assert_eq!(patch.kind, PatchKind::Create);
+    assert_eq!(patch.apply("").unwrap(), "# Notes\n\n");
+}
+
+#[test]
+fn deletes_a_file() {
+    let diff = "\
+--- a/notes.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Notes
+";
+    let patch = Patch::parse(diff).unwrap();
+
+    assert_eq!(patch.target.as_deref(), Some("notes.md"));
+    assert_eq!(patch.kind, PatchKind::Delete);
+}
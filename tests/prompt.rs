@@ -0,0 +1,90 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use reprompt::{
+    history::PromptResponse,
+    prompt::{AccentColor, Prompt, PromptAppearance},
+    store::{HistoryPage, StoredPrompt},
+};
+
+fn created_at(n: usize) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap() + Duration::minutes(n as i64)
+}
+
+fn response(n: usize) -> PromptResponse {
+    PromptResponse {
+        input: format!("input {n}"),
+        output: format!("output {n}"),
+        created_at: created_at(n),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn keeps_the_history_newest_first() {
+    let mut prompt = Prompt::new("Summarize".to_string(), "Summarize the text".to_string(), 0);
+
+    prompt.insert_history(response(1));
+    prompt.insert_history(response(3));
+    prompt.insert_history(response(2));
+
+    assert_eq!(prompt.history_count(), 3);
+    assert_eq!(prompt.response_output(0), Some("output 3"));
+    assert_eq!(prompt.response_output(2), Some("output 1"));
+
+    let removed = prompt.remove_history(1).unwrap();
+
+    assert_eq!(removed.output, "output 2");
+    assert_eq!(prompt.history_count(), 2);
+}
+
+#[test]
+fn reads_back_a_written_record() {
+    let mut prompt = Prompt::new(
+        "Translate".to_string(),
+        "Translate to French".to_string(),
+        0,
+    );
+
+    prompt.appearance = PromptAppearance {
+        icon: "★".to_string(),
+        color: Some(AccentColor([255, 165, 0, 255])),
+    };
+    prompt.insert_history(response(1));
+    prompt.insert_history(response(2));
+
+    let record = prompt.to_record().unwrap();
+    let stored = StoredPrompt {
+        settings: record.settings,
+        history: HistoryPage {
+            responses: record
+                .history
+                .into_iter()
+                .map(|(_, response)| response)
+                .collect(),
+            unloaded: None,
+        },
+    };
+    let read = Prompt::from_stored(stored).unwrap();
+
+    assert_eq!(read.title, "Translate");
+    assert_eq!(read.content, "Translate to French");
+    assert_eq!(read.appearance, prompt.appearance);
+    assert_eq!(
+        read.responses()
+            .map(|response| response.output.as_str())
+            .collect::<Vec<_>>(),
+        ["output 2", "output 1"]
+    );
+}
+
+#[test]
+fn serializes_the_accent_color_as_its_bytes() {
+    let appearance = PromptAppearance {
+        icon: String::new(),
+        color: Some(AccentColor([255, 165, 0, 255])),
+    };
+
+    assert_eq!(
+        serde_json::to_string(&appearance).unwrap(),
+        r#"{"icon":"","color":[255,165,0,255]}"#
+    );
+}
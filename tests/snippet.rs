@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use reprompt::snippet::{Snippet, SnippetPolicy};
+
+fn policy(sandbox_command: &str) -> SnippetPolicy {
+    SnippetPolicy {
+        enabled: true,
+        languages: vec!["sh".to_string(), "python".to_string()],
+        shell_commands: vec!["echo".to_string(), "cat".to_string(), "wc".to_string()],
+        sandbox_command: sandbox_command.to_string(),
+        timeout: Duration::from_secs(10),
+    }
+}
+
+fn snippet(language: &str, code: &str) -> Snippet {
+    Snippet {
+        language: language.to_string(),
+        code: code.to_string(),
+    }
+}
+
+#[test]
+fn refuses_snippets_without_a_sandbox() {
+    let policy = policy("");
+
+    assert!(policy.check(&snippet("python", "print(1)\n")).is_err());
+    assert!(policy.check(&snippet("sh", "echo hello\n")).is_err());
+}
+
+#[test]
+fn allows_sandboxed_snippets_with_allowed_commands() {
+    let policy = policy("bwrap --ro-bind /usr /usr --unshare-all");
+
+    assert!(policy.check(&snippet("python", "print(1)\n")).is_ok());
+    assert!(policy.check(&snippet("sh", "echo hello | wc -c\n")).is_ok());
+    assert!(policy.check(&snippet("sh", "cat notes.txt\n")).is_ok());
+    assert!(policy.check(&snippet("sh", "rm -rf notes\n")).is_err());
+}
+
+#[test]
+fn refuses_paths_out_of_the_snippet_directory() {
+    let policy = policy("bwrap --ro-bind /usr /usr --unshare-all");
+
+    for code in [
+        "cat /etc/passwd\n",
+        "cat '/etc/passwd'\n",
+        "cat ~/.ssh/id_rsa\n",
+        "cat ../secrets\n",
+        "file=/etc/hosts; cat $file\n",
+    ] {
+        assert!(policy.check(&snippet("sh", code)).is_err(), "{code}");
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+use reprompt::store::{HISTORY_PAGE_LEN, PromptRecord, Store};
+
+/// A fresh database in the temporary directory
+fn database(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "reprompt-store-{}-{name}.sqlite3",
+        std::process::id()
+    ));
+
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+fn created_at(n: usize) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap() + Duration::minutes(n as i64)
+}
+
+/// A prompt with `len` responses, the newest first
+fn record(key: &str, len: usize) -> PromptRecord {
+    PromptRecord {
+        key: key.to_string(),
+        settings: format!("(title: \"{key}\")"),
+        history: (0..len)
+            .rev()
+            .map(|n| (created_at(n), format!("response {n}")))
+            .collect(),
+        unloaded: None,
+    }
+}
+
+#[test]
+fn reads_the_history_page_by_page() {
+    let path = database("pages");
+    let len = HISTORY_PAGE_LEN + 10;
+
+    Store::open(&path)
+        .unwrap()
+        .save(&[record("a", len), record("b", 1)])
+        .unwrap();
+
+    let store = Store::open(&path).unwrap();
+    let prompts = store.load().unwrap();
+
+    assert_eq!(prompts.len(), 2);
+    assert_eq!(prompts[0].settings, "(title: \"a\")");
+    assert_eq!(prompts[1].history.responses, ["response 0"]);
+    assert_eq!(prompts[1].history.unloaded, None);
+
+    let newest = &prompts[0].history;
+    let unloaded = newest.unloaded.unwrap();
+
+    assert_eq!(newest.responses.len(), HISTORY_PAGE_LEN);
+    assert_eq!(newest.responses[0], format!("response {}", len - 1));
+    assert_eq!(unloaded.len, 10);
+    assert_eq!(unloaded.before, created_at(10));
+
+    let older = store.history_page("a", Some(unloaded.before)).unwrap();
+
+    assert_eq!(older.responses.len(), 10);
+    assert_eq!(older.responses[9], "response 0");
+    assert_eq!(older.unloaded, None);
+}
+
+#[test]
+fn keeps_the_unloaded_responses_when_saving() {
+    let path = database("unloaded");
+    let len = HISTORY_PAGE_LEN + 10;
+
+    Store::open(&path)
+        .unwrap()
+        .save(&[record("a", len), record("b", 1)])
+        .unwrap();
+
+    let mut store = Store::open(&path).unwrap();
+    let page = store.history_page("a", None).unwrap();
+
+    // The newest response is removed and the prompt "b" with it
+    let mut loaded = record("a", len);
+
+    loaded.history = loaded.history[1..HISTORY_PAGE_LEN].to_vec();
+    loaded.unloaded = page.unloaded;
+
+    store.save(&[loaded]).unwrap();
+
+    let prompts = Store::open(&path).unwrap().load().unwrap();
+    let history = &prompts[0].history;
+
+    assert_eq!(prompts.len(), 1);
+    assert_eq!(history.responses[0], format!("response {}", len - 2));
+    assert_eq!(
+        history.responses.len() + history.unloaded.unwrap().len,
+        len - 1
+    );
+}
+
+#[test]
+fn keeps_the_responses_created_at_the_same_time() {
+    let path = database("same-time");
+    let mut prompt = record("a", 0);
+
+    // The page ends among the responses created at the same time
+    prompt.history = (0..HISTORY_PAGE_LEN - 1)
+        .rev()
+        .map(|n| (created_at(n + 2), format!("response {n}")))
+        .chain(["tie 0", "tie 1", "tie 2"].map(|tie| (created_at(1), tie.to_string())))
+        .chain([(created_at(0), "oldest".to_string())])
+        .collect();
+
+    Store::open(&path).unwrap().save(&[prompt]).unwrap();
+
+    let store = Store::open(&path).unwrap();
+    let page = store.history_page("a", None).unwrap();
+    let unloaded = page.unloaded.unwrap();
+
+    assert_eq!(page.responses.len(), HISTORY_PAGE_LEN + 2);
+    assert_eq!(
+        page.responses[HISTORY_PAGE_LEN - 1..],
+        ["tie 0", "tie 1", "tie 2"]
+    );
+    assert_eq!(unloaded.before, created_at(1));
+    assert_eq!(unloaded.len, 1);
+
+    let older = store.history_page("a", Some(unloaded.before)).unwrap();
+
+    assert_eq!(older.responses, ["oldest"]);
+}
+
+#[test]
+fn gives_the_responses_of_an_older_database_ids() {
+    let path = database("migration");
+    let connection = rusqlite::Connection::open(&path).unwrap();
+
+    connection
+        .execute_batch(
+            "CREATE TABLE prompts (
+                 key TEXT PRIMARY KEY,
+                 position INTEGER NOT NULL,
+                 settings TEXT NOT NULL
+             );
+             CREATE TABLE responses (
+                 prompt_key TEXT NOT NULL REFERENCES prompts (key) ON DELETE CASCADE,
+                 created_at INTEGER NOT NULL,
+                 response TEXT NOT NULL,
+                 PRIMARY KEY (prompt_key, created_at)
+             );
+             INSERT INTO prompts VALUES ('a', 0, '(title: \"a\")');",
+        )
+        .unwrap();
+
+    for n in 0..2 {
+        connection
+            .execute(
+                "INSERT INTO responses VALUES ('a', ?1, ?2)",
+                rusqlite::params![created_at(n).timestamp_micros(), format!("response {n}")],
+            )
+            .unwrap();
+    }
+
+    drop(connection);
+
+    let mut store = Store::open(&path).unwrap();
+
+    assert_eq!(
+        store.load().unwrap()[0].history.responses,
+        ["response 1", "response 0"]
+    );
+
+    let mut prompt = record("a", 2);
+
+    prompt
+        .history
+        .insert(0, (created_at(1), "response 1 again".to_string()));
+    store.save(&[prompt]).unwrap();
+
+    assert_eq!(
+        Store::open(&path).unwrap().load().unwrap()[0]
+            .history
+            .responses,
+        ["response 1 again", "response 1", "response 0"]
+    );
+}
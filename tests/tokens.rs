@@ -0,0 +1,42 @@
+use std::time::Instant;
+
+use reprompt::tokens::{self, Estimate, RECOUNT_DELAY};
+
+#[test]
+fn counts_the_first_request_at_once() {
+    let mut estimate = Estimate::default();
+    let text = "Summarize the text:\nThe quick brown fox jumps over the lazy dog";
+
+    assert_eq!(
+        estimate.tokens(1, Instant::now(), || text.to_string()),
+        tokens::estimate(text)
+    );
+    assert!(!estimate.is_pending());
+}
+
+#[test]
+fn counts_a_changed_request_once_it_stops_changing() {
+    let mut estimate = Estimate::default();
+    let start = Instant::now();
+    let first = estimate.tokens(1, start, || "Summarize".to_string());
+
+    assert_eq!(
+        estimate.tokens(2, start + RECOUNT_DELAY / 2, || unreachable!()),
+        first
+    );
+    assert!(estimate.is_pending());
+
+    // Typing on delays the count again
+    assert_eq!(
+        estimate.tokens(3, start + RECOUNT_DELAY, || unreachable!()),
+        first
+    );
+
+    let text = "Summarize the text:\nThe quick brown fox jumps over the lazy dog";
+
+    assert_eq!(
+        estimate.tokens(3, start + RECOUNT_DELAY * 2, || text.to_string()),
+        tokens::estimate(text)
+    );
+    assert!(!estimate.is_pending());
+}
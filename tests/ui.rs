@@ -0,0 +1,89 @@
+use chrono::{Local, TimeZone};
+use egui::accesskit::Role;
+use egui_kittest::{Harness, kittest::Queryable};
+use reprompt::{app::App, prompt::Prompt, script::ScriptStep};
+
+fn harness(prompts: Vec<Prompt>) -> Harness<'static, App> {
+    Harness::builder()
+        .with_size(egui::vec2(1280.0, 800.0))
+        .build_state(|ctx, app: &mut App| app.ui(ctx), App::with_prompts(prompts))
+}
+
+fn prompt_with_history(title: &str) -> Prompt {
+    let mut prompt = Prompt::new(title.to_string(), "Summarize the text".to_string(), 0);
+
+    prompt.import_steps(vec![ScriptStep {
+        input: "The quick brown fox jumps over the lazy dog".to_string(),
+        output: "A fox jumps over a dog.".to_string(),
+        local_model_name: "llama3:8b".to_string(),
+        // A fixed time, for the history to show the same date on every run
+        created_at: Local.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+    }]);
+
+    prompt
+}
+
+#[test]
+fn left_panel_lists_the_prompts() {
+    let mut harness = harness(vec![
+        Prompt::new("Summarize".to_string(), "Summarize the text".to_string(), 0),
+        Prompt::new(
+            "Translate".to_string(),
+            "Translate to French".to_string(),
+            1,
+        ),
+    ]);
+
+    harness.run();
+
+    harness.get_by_role_and_label(Role::Button, "Summarize");
+    harness.get_by_role_and_label(Role::Button, "Translate");
+    harness.get_by_label("Add Prompt");
+}
+
+#[test]
+fn add_prompt_button_opens_the_modal() {
+    let mut harness = harness(Vec::new());
+
+    harness.run();
+    harness.get_by_label("Add Prompt").click();
+    harness.run();
+
+    harness.get_by_role_and_label(Role::Button, "Create");
+    harness.get_by_label("Request layout:");
+}
+
+#[test]
+fn settings_button_opens_the_modal() {
+    let mut harness = harness(Vec::new());
+
+    harness.run();
+    harness
+        .get_by_role_and_label(Role::Button, "Settings")
+        .click();
+    harness.run();
+
+    harness.get_by_role_and_label(Role::Label, "Settings");
+    harness.get_by_label("Ollama URL");
+}
+
+#[test]
+fn selecting_a_prompt_renders_its_history() {
+    let mut harness = harness(vec![prompt_with_history("Summarize")]);
+
+    harness.run();
+    assert!(
+        harness
+            .query_by_label("The quick brown fox jumps over the lazy dog")
+            .is_none()
+    );
+
+    harness
+        .get_by_role_and_label(Role::Button, "Summarize")
+        .click();
+    harness.run();
+
+    harness.get_by_label("The quick brown fox jumps over the lazy dog");
+    harness.get_by_label_contains("llama3:8b");
+    harness.get_by_label_contains("2025-01-01 12:00:00");
+}